@@ -0,0 +1,54 @@
+//! Generates C# bindings as described by a ``csharp_binder.toml`` file. Run with ``--check`` to
+//! verify the existing bindings are up to date instead, which fails if they are not.
+
+use csharp_binder::cli::{run, DEFAULT_CONFIG_FILE};
+use std::path::PathBuf;
+use std::process::exit;
+
+const USAGE: &str = "Usage: csharp_binder [--check] [--config <path>]
+
+Options:
+    --config <path>  The configuration file to use, csharp_binder.toml by default
+    --check          Fail if the existing bindings differ from the generated bindings, instead of
+                     writing them
+    -h, --help       Print this message";
+
+fn main() {
+    let mut config_path = PathBuf::from(DEFAULT_CONFIG_FILE);
+    let mut check = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => check = true,
+            "--config" => match args.next() {
+                Some(path) => config_path = PathBuf::from(path),
+                None => usage_error("--config requires a path"),
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => usage_error(&format!("Unknown argument '{}'", arg)),
+        }
+    }
+
+    match run(&config_path, check) {
+        Ok(warnings) => {
+            for warning in warnings {
+                eprintln!(
+                    "warning: skipped '{}': {}",
+                    warning.item_name, warning.error
+                );
+            }
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            exit(1);
+        }
+    }
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    exit(2);
+}
@@ -1,17 +1,84 @@
-use crate::{CSharpBuilder, Error};
+use crate::{
+    BlittabilityWarning, BuildMode, CLongSize, CSharpBuilder, CSharpCharMapping,
+    CSharpConfiguration, CSharpType, CallingConvention, CfgOptions, ConstructorMode, Error,
+    ExtractedEnum, ExtractedField, ExtractedFunction, ExtractedStruct, ExtractedType,
+    ExtractedVariant, GeneratedFile, Indentation, LayoutTests, Mismatch, NameKind, Newline,
+    Ordering, PointerWidth, StructStyle, TypeRemarks, VerifyError, Warning,
+};
+use regex::Regex;
 use std::borrow::Borrow;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
 use std::fmt::Write;
+use syn::ext::IdentExt;
 use syn::spanned::Spanned;
 use syn::{
-    Attribute, Expr, FnArg, GenericArgument, GenericParam, Item, ItemEnum, ItemFn, ItemStruct,
-    Meta, NestedMeta, Pat, Path, PathArguments, ReturnType, Type,
+    Abi, Attribute, BinOp, Expr, ExprLit, ExprUnary, FnArg, GenericArgument, GenericParam,
+    ImplItem, Item, ItemConst, ItemEnum, ItemFn, ItemImpl, ItemMod, ItemStatic, ItemStruct,
+    ItemType, ItemUnion, LitInt, Meta, NestedMeta, Pat, PatIdent, PatType, Path, PathArguments,
+    ReturnType, Type, TypeArray, TypeBareFn, TypePtr, TypeReference, UnOp, UseTree, Visibility,
 };
 
+/// Writes the generated code to an output, using the configured indentation and newlines.
+pub(crate) struct CodeWriter<'a> {
+    out: &'a mut dyn Write,
+    indentation: String,
+    newline: Newline,
+}
+
+impl<'a> CodeWriter<'a> {
+    fn new(out: &'a mut dyn Write, configuration: &CSharpConfiguration) -> Self {
+        let indentation = match configuration.indentation {
+            Indentation::Tabs => "\t".to_string(),
+            Indentation::Spaces(width) => " ".repeat(width as usize),
+        };
+        Self {
+            out,
+            indentation,
+            newline: configuration.newline,
+        }
+    }
+
+    /// Creates a writer to a buffer, which is later written to a writer created with
+    /// [`CodeWriter::new`]. Newlines are only converted then, so they aren't converted twice.
+    fn new_buffer(out: &'a mut dyn Write, configuration: &CSharpConfiguration) -> Self {
+        Self {
+            newline: Newline::Lf,
+            ..Self::new(out, configuration)
+        }
+    }
+
+    fn write_indent(&mut self, indents: i32) -> Result<(), Error> {
+        for _ in 0..indents {
+            self.out.write_str(&self.indentation)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for CodeWriter<'_> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        match self.newline {
+            Newline::Lf => self.out.write_str(s),
+            Newline::CrLf => {
+                for (index, line) in s.split('\n').enumerate() {
+                    if index != 0 {
+                        self.out.write_str("\r\n")?;
+                    }
+                    self.out.write_str(line)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
 struct TypeNameContainer {
     csharp_name: String,
     rust_name: String,
     generics: Vec<TypeNameContainer>,
+    /// The UnmanagedType this type should be marshalled as, if it requires explicit marshalling.
+    marshal_as: Option<String>,
 }
 
 impl TypeNameContainer {
@@ -20,6 +87,21 @@ impl TypeNameContainer {
             csharp_name,
             rust_name,
             generics: Vec::new(),
+            marshal_as: None,
+        }
+    }
+
+    fn with_marshal_as(mut self, marshal_as: Option<String>) -> TypeNameContainer {
+        self.marshal_as = marshal_as;
+        self
+    }
+
+    /// Returns the MarshalAs attribute for this type with the given attribute target, or an empty
+    /// string if the type does not require explicit marshalling.
+    fn marshal_attribute(&self, target: &str) -> String {
+        match &self.marshal_as {
+            None => "".to_string(),
+            Some(marshal_as) => format!("[{}MarshalAs({})] ", target, marshal_as),
         }
     }
 
@@ -39,624 +121,6303 @@ impl TypeNameContainer {
         }
         Ok(s)
     }
+
+    /// Returns the Rust name of this type including its generic arguments, such as
+    /// ``TestStruct<u16>``, for use in documentation.
+    fn stringify_rust(&self) -> String {
+        if self.generics.is_empty() {
+            return self.rust_name.clone();
+        }
+        let generics = self
+            .generics
+            .iter()
+            .map(|generic| generic.stringify_rust())
+            .collect::<Vec<_>>();
+        format!("{}<{}>", self.rust_name, generics.join(", "))
+    }
+
+    fn extracted(&self) -> Result<ExtractedType, Error> {
+        Ok(ExtractedType {
+            rust_type: self.stringify_rust(),
+            csharp_type: self.stringify()?,
+        })
+    }
 }
 
 pub fn parse_script(script: &str) -> syn::Result<syn::File> {
     syn::parse_str(script)
 }
 
-pub fn build_csharp(builder: &mut CSharpBuilder) -> Result<String, Error> {
-    let mut script: String = "".to_string();
-    let mut indent = 0;
+/// The output of a finished command.
+#[cfg(feature = "expand")]
+pub(crate) struct CommandOutput {
+    pub(crate) success: bool,
+    pub(crate) stdout: String,
+    pub(crate) stderr: String,
+}
 
-    {
-        let generated_warning = &builder.configuration.generated_warning;
-        if !generated_warning.is_empty() {
-            for line in generated_warning.lines() {
-                write_line(&mut script, "// ".to_string() + line, indent)?;
-            }
-        }
+/// Runs a command, and waits for it to finish.
+#[cfg(feature = "expand")]
+pub(crate) fn run_command(command: &mut std::process::Command) -> std::io::Result<CommandOutput> {
+    let output = command.output()?;
+    Ok(CommandOutput {
+        success: output.status.success(),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    })
+}
+
+/// Expands the macros of the library of a crate with ``cargo expand``, or with rustc if
+/// cargo-expand is not installed, and returns the expanded script.
+#[cfg(feature = "expand")]
+pub(crate) fn expand_crate(
+    manifest_path: &std::path::Path,
+    run: impl Fn(&mut std::process::Command) -> std::io::Result<CommandOutput>,
+) -> Result<String, Error> {
+    let mut expand = std::process::Command::new("cargo");
+    expand
+        .arg("expand")
+        .arg("--lib")
+        .arg("--manifest-path")
+        .arg(manifest_path);
+    // Cargo not being found is a failure to expand the crate as well.
+    let spawn_error =
+        |e: std::io::Error| Error::ExpansionError(format!("Failed to run cargo: {}", e));
+    let mut output = run(&mut expand).map_err(spawn_error)?;
+    if !output.success && output.stderr.contains("no such command") {
+        let mut rustc = std::process::Command::new("cargo");
+        rustc
+            .arg("rustc")
+            .arg("--lib")
+            .arg("--manifest-path")
+            .arg(manifest_path)
+            .args(["--", "-Zunpretty=expanded"])
+            // Allows the unstable flag on stable compilers, like cargo-expand does.
+            .env("RUSTC_BOOTSTRAP", "1");
+        output = run(&mut rustc).map_err(spawn_error)?;
     }
-    for using in &builder.usings {
-        write_line(&mut script, format!("using {};", using), indent)?;
+    if !output.success {
+        return Err(Error::ExpansionError(output.stderr.trim().to_string()));
     }
-    writeln!(script)?;
+    Ok(strip_expansion_preamble(&output.stdout))
+}
 
-    match &builder.namespace {
-        None => {}
-        Some(ns) => {
-            write_line(&mut script, format!("namespace {}", ns), indent)?;
-            write_line(&mut script, "{".to_string(), indent)?;
-            indent += 1;
-        }
-    };
-    match &builder.type_name {
-        None => {}
-        Some(t) => {
-            write_line(&mut script, format!("internal static class {}", t), indent)?;
-            write_line(&mut script, "{".to_string(), indent)?;
-            indent += 1;
+/// Removes the lines that expansion adds to the start of a crate, such as its inner attributes
+/// and the import of the prelude, which aren't needed to generate the script.
+#[cfg(feature = "expand")]
+fn strip_expansion_preamble(script: &str) -> String {
+    let mut lines = script.lines().peekable();
+    while let Some(line) = lines.peek() {
+        let line = line.trim();
+        let is_preamble = line.is_empty()
+            || line.starts_with("#![")
+            || line == "#[prelude_import]"
+            || line == "#[macro_use]"
+            || (line.starts_with("use ") && line.contains("::prelude::"))
+            || (line.starts_with("extern crate ") && line.ends_with(';'));
+        if !is_preamble {
+            break;
         }
+        lines.next();
     }
+    lines.collect::<Vec<_>>().join("\n")
+}
+
+pub fn build_csharp(builder: &mut CSharpBuilder, out: &mut dyn Write) -> Result<(), Error> {
+    builder.staged_types.clear();
+    builder.qualified_types.clear();
+    let result = write_script(builder, out);
+    builder.finish_known_types(result)
+}
+
+fn write_script(builder: &mut CSharpBuilder, out: &mut dyn Write) -> Result<(), Error> {
+    builder.delegates.clear();
+    builder.member_names.clear();
+    builder.warnings.clear();
+    builder.enum_extensions.clear();
+    builder.blittability_warnings.clear();
+    builder.non_blittable_structs.clear();
+    builder.entry_points.clear();
+    let mut indent = 0;
+    let script = &mut CodeWriter::new(out, builder.configuration);
+
+    write_file_start(script, builder, &mut indent)?;
+    write_class_start(script, builder, &mut indent)?;
 
     let tokens = builder.tokens.items.clone();
-    for token in &tokens {
-        write_token(&mut script, token, &mut indent, builder)?;
-    }
+    register_opaque_handles(&tokens, builder)?;
+    write_ordered_items(script, &tokens, &mut indent, builder)?;
 
-    match &builder.type_name {
-        None => {}
-        Some(_) => {
-            indent -= 1;
-            write_line(&mut script, "}".to_string(), indent)?;
-        }
-    }
-    match &builder.namespace {
-        None => {}
-        Some(_) => {
-            indent -= 1;
-            write_line(&mut script, "}".to_string(), indent)?;
-        }
-    };
-    Ok(script)
+    write_class_end(script, builder, &mut indent)?;
+    write_file_end(script, builder, &mut indent)?;
+    Ok(())
 }
 
-fn write_token(
-    str: &mut String,
-    token: &Item,
-    indents: &mut i32,
-    builder: &mut CSharpBuilder<'_>,
-) -> Result<(), Error> {
-    match token {
-        Item::Const(_) => {}
-        Item::Enum(en) => write_enum(str, indents, en, builder)?,
-        Item::ExternCrate(_) => {}
-        Item::Fn(fun) => write_function(str, indents, builder, fun)?,
-        Item::ForeignMod(_) => {}
-        Item::Impl(_) => {}
-        Item::Macro(_) => {}
-        Item::Macro2(_) => {}
-        Item::Mod(module) => {
-            // We don't particularly care for the module itself (should we? Potentially make it a separate class?)
-            // But we do care for the items inside, so extract those.
-            match &module.content.as_ref() {
-                None => {}
-                Some(r) => {
-                    for item in &r.1 {
-                        write_token(str, item, indents, builder)?
-                    }
-                }
-            }
-        }
-        Item::Static(_) => {}
-        Item::Struct(strct) => write_struct(str, indents, strct, builder)?,
-        Item::Trait(_) => {}
-        Item::TraitAlias(_) => {}
-        Item::Type(typedef) => {
-            let ty: &Type = typedef.ty.borrow();
-            if let Type::Path(type_path) = ty {
-                let type_name_opt = get_path_name(&type_path.path);
-                match type_name_opt {
-                    None => {}
-                    Some(type_name) => {
-                        let t = builder.configuration.get_known_type(type_name.as_str());
-                        if t.is_none() {
-                            return Ok(());
-                        }
-                        let inner_type = t.unwrap();
-                        let namespace = inner_type.namespace.clone();
-                        let inside_type = inner_type.inside_type.clone();
-                        let mut real_type_name = inner_type.real_type_name.clone();
-
-                        if let PathArguments::AngleBracketed(generics) =
-                            &type_path.path.segments.last().unwrap().arguments
-                        {
-                            write!(real_type_name, "<")?;
-                            for (index, generic) in generics.args.iter().enumerate() {
-                                if let GenericArgument::Type(t) = generic {
-                                    if index != 0 {
-                                        write!(real_type_name, ", ")?;
-                                    }
-                                    write!(
-                                        real_type_name,
-                                        "{}",
-                                        convert_type_name(t, builder)?.csharp_name
-                                    )?;
-                                }
-                            }
-                            write!(real_type_name, ">")?;
-                        }
+/// Generates C# tests which compare the size and field offsets of the generated structs with
+/// those of the Rust structs, and the Rust functions that return the latter.
+pub fn build_layout_tests(builder: &mut CSharpBuilder, prefix: &str) -> Result<LayoutTests, Error> {
+    let extracted = builder.extracted_items()?;
+    let calling_convention = builder.configuration.default_calling_convention;
+    let mut rust = String::new();
+    for line in expand_generated_warning(builder)?.lines() {
+        writeln!(rust, "// {}", line)?;
+    }
 
-                        builder.configuration.add_known_type(
-                            typedef.ident.to_string().as_str(),
-                            namespace,
-                            inside_type,
-                            real_type_name,
-                        )
-                    }
+    // The imports of the Rust functions, and the checks of the verify method.
+    let mut imports = String::new();
+    let imports_writer = &mut CodeWriter::new_buffer(&mut imports, builder.configuration);
+    let mut checks: Vec<String> = Vec::new();
+    let mut indent = namespace_indent(builder) + 1;
+    for strct in extracted.structs.iter().filter(|s| s.generics.is_empty()) {
+        let csharp_type = match builder.get_known_type(&strct.rust_name) {
+            Some(CSharpType {
+                inside_type: Some(inside_type),
+                ..
+            }) => format!("{}.{}", inside_type, strct.csharp_name),
+            _ => strct.csharp_name.clone(),
+        };
+        let struct_fn = format!("{}{}", prefix, convert_to_snake_case(&strct.rust_name));
+
+        let size_fn = struct_fn.clone() + "_size";
+        write!(
+            rust,
+            "\n#[no_mangle]\npub extern \"C\" fn {}() -> usize {{\n    std::mem::size_of::<{}>()\n}}\n",
+            size_fn, strct.rust_name
+        )?;
+        write_layout_import(
+            imports_writer,
+            &mut indent,
+            builder,
+            &size_fn,
+            calling_convention,
+        )?;
+        checks.push(format!(
+            "Check(\"{}\", Marshal.SizeOf<{}>(), {}().ToUInt64());",
+            strct.csharp_name,
+            csharp_type,
+            convert_naming(&size_fn, false)
+        ));
+
+        for field in &strct.fields {
+            let offset_fn = format!("{}_{}_offset", struct_fn, field.rust_name);
+            // Parts of a split 128 bit integer after the low half are checked against the offset
+            // of the Rust field, which is only exported for the low half.
+            let expected = match field.offset_in_field {
+                0 => {
+                    write!(
+                        rust,
+                        "\n#[no_mangle]\npub extern \"C\" fn {}() -> usize {{\n    std::mem::offset_of!({}, {})\n}}\n",
+                        offset_fn, strct.rust_name, field.rust_name
+                    )?;
+                    write_layout_import(
+                        imports_writer,
+                        &mut indent,
+                        builder,
+                        &offset_fn,
+                        calling_convention,
+                    )?;
+                    format!("{}().ToUInt64()", convert_naming(&offset_fn, false))
                 }
-            }
+                offset => format!(
+                    "{}().ToUInt64() + {}",
+                    convert_naming(&offset_fn, false),
+                    offset
+                ),
+            };
+            // The fields of auto-properties are named after the property by the compiler.
+            let field_name = match field.is_property {
+                true => format!("<{}>k__BackingField", field.csharp_name),
+                false => field.csharp_name.clone(),
+            };
+            checks.push(format!(
+                "Check(\"{}.{}\", Marshal.OffsetOf<{}>(\"{}\").ToInt64(), {});",
+                strct.csharp_name, field.csharp_name, csharp_type, field_name, expected
+            ));
         }
-        Item::Union(_) => {}
-        Item::Use(_) => {}
-        Item::Verbatim(_) => {}
-        Item::__TestExhaustive(_) => {}
     }
-    Ok(())
-}
 
-fn get_path_name(path: &Path) -> Option<String> {
-    Some(path.segments.last()?.ident.to_string())
+    let mut csharp = String::new();
+    let writer = &mut CodeWriter::new(&mut csharp, builder.configuration);
+    let mut indent = 0;
+    write_file_start(writer, builder, &mut indent)?;
+    let class_name = match &builder.type_name {
+        Some(type_name) => type_name.to_string() + "LayoutTests",
+        None => "LayoutTests".to_string(),
+    };
+    write_line(
+        writer,
+        format!("{} class {}", class_modifiers(builder), class_name),
+        indent,
+    )?;
+    write_line(writer, "{".to_string(), indent)?;
+    write_library_name(writer, builder, indent + 1, None)?;
+    writer.write_str(&imports)?;
+    for line in [
+        "/// <summary>",
+        "/// Checks that the sizes and field offsets of the generated structs match those of the Rust",
+        "/// structs, and throws an <see cref=\"InvalidOperationException\"/> if any of them differ.",
+        "/// </summary>",
+        "public static void Verify()",
+        "{",
+    ] {
+        write_line(writer, line.to_string(), indent + 1)?;
+    }
+    // The resolver of the library is registered by the type of the builder.
+    if let (Some(_), Some(type_name)) = (&builder.dll_names, &builder.type_name) {
+        if builder.configuration.resolve_dll_names_at_runtime {
+            write_line(
+                writer,
+                format!(
+                    "System.Runtime.CompilerServices.RuntimeHelpers.RunClassConstructor(typeof({}).TypeHandle);",
+                    type_name
+                ),
+                indent + 2,
+            )?;
+        }
+    }
+    for check in checks {
+        write_line(writer, check, indent + 2)?;
+    }
+    // The lines of the end of the verify method and the check helper, with their indentation
+    // relative to the class members.
+    for (line_indent, line) in [
+        (0, "}"),
+        (0, ""),
+        (0, "private static void Check(string name, long actual, ulong expected)"),
+        (0, "{"),
+        (1, "if ((ulong)actual != expected)"),
+        (1, "{"),
+        (2, "throw new InvalidOperationException(\"The layout of \" + name + \" differs, expected \" + expected + \" but was \" + actual + \".\");"),
+        (1, "}"),
+        (0, "}"),
+    ] {
+        match line.is_empty() {
+            true => writeln!(writer)?,
+            false => write_line(writer, line.to_string(), indent + 1 + line_indent)?,
+        }
+    }
+    write_line(writer, "}".to_string(), indent)?;
+    write_file_end(writer, builder, &mut indent)?;
+    Ok(LayoutTests { csharp, rust })
 }
 
-fn write_function(
-    str: &mut String,
+fn write_layout_import(
+    str: &mut CodeWriter,
     indents: &mut i32,
     builder: &CSharpBuilder,
-    fun: &ItemFn,
+    entry_point: &str,
+    calling_convention: CallingConvention,
 ) -> Result<(), Error> {
-    if !is_extern_c(fun) {
-        return Ok(());
-    }
-
-    let return_type = match &fun.sig.output {
-        ReturnType::Default => TypeNameContainer::new("void".to_string(), "void".to_string()),
-        ReturnType::Type(_, t) => convert_type_name(t.borrow(), builder)?,
-    };
-    let mut parameters: Vec<(String, String, String)> = Vec::new();
-    for input in &fun.sig.inputs {
-        match input {
-            FnArg::Receiver(_) => {
-                return Err(Error::UnsupportedError(
-                    "Receiver parameters aren't supported".to_string(),
-                    input.span(),
-                ))
-            }
-            FnArg::Typed(t) => match t.pat.borrow() {
-                Pat::Ident(i) => {
-                    let type_name = convert_type_name(t.ty.borrow(), builder)?;
-                    parameters.push((
-                        convert_naming(&i.ident.to_string(), true),
-                        type_name.stringify()?,
-                        type_name.rust_name,
-                    ));
-                }
-                _ => {
-                    return Err(Error::UnsupportedError(
-                        "Parameters that are not identity aren't supported".to_string(),
-                        input.span(),
-                    ))
-                }
-            },
-        }
-    }
-
-    let outer_docs = extract_outer_docs(&fun.attrs)?;
-    write_summary_from_outer_docs(str, outer_docs, indents)?;
-
-    for parameter in &parameters {
-        write_line(
-            str,
-            format!(
-                "/// <param name=\"{}\">{}</param>",
-                parameter.0, parameter.2
-            ),
-            *indents,
-        )?;
-    }
-    write_line(
+    write_import_attribute(
         str,
-        format!("/// <returns>{}</returns>", return_type.rust_name),
-        *indents,
+        indents,
+        builder,
+        entry_point,
+        entry_point,
+        calling_convention,
     )?;
     write_line(
         str,
         format!(
-            "[DllImport(\"{}\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"{}\")]",
-            builder.dll_name,
-            fun.sig.ident.to_string()
+            "private static {} UIntPtr {}();",
+            import_modifier(builder),
+            convert_naming(entry_point, false)
         ),
         *indents,
     )?;
+    writeln!(str)?;
+    Ok(())
+}
 
-    for _ in 0..*indents {
-        write!(str, "    ").ok();
-    }
-    write!(
-        str,
-        "internal static extern {} {}(",
-        return_type.stringify()?,
-        convert_naming(&fun.sig.ident.to_string(), false)
-    )?;
-
-    for (i, parameter) in parameters.iter().enumerate() {
-        if i != 0 {
-            write!(str, ", ")?;
+/// Converts a Rust type name (CamelCase) into the naming scheme of Rust functions (snake_case).
+fn convert_to_snake_case(input: &str) -> String {
+    let mut output = String::new();
+    for (index, c) in input.chars().enumerate() {
+        if c.is_ascii_uppercase() && index != 0 {
+            output.push('_');
         }
-        write!(str, "{} {}", parameter.1, parameter.0)?;
+        output.push(c.to_ascii_lowercase());
     }
-    writeln!(str, ");")?;
-    writeln!(str)?;
+    output
+}
 
-    Ok(())
+/// Registers the types of the items in the type registry, without writing anything. The types are
+/// registered with the same names as when they are written.
+pub fn register_types(builder: &mut CSharpBuilder) -> Result<(), Error> {
+    builder.staged_types.clear();
+    builder.qualified_types.clear();
+    let tokens = builder.tokens.items.clone();
+    let result = register_opaque_handles(&tokens, builder)
+        .and_then(|_| register_item_types(&tokens, builder));
+    builder.finish_known_types(result)
 }
 
-fn write_enum(
-    str: &mut String,
-    indents: &mut i32,
-    en: &ItemEnum,
-    builder: &mut CSharpBuilder<'_>,
-) -> Result<(), Error> {
-    let mut size_option: Option<TypeNameContainer> = None;
-    for attr in &en.attrs {
-        let repr_attr = get_repr_attribute_value(attr)?;
-        match repr_attr {
-            None => {}
-            Some(val) => {
-                match val.get_ident() {
-                    None => {}
-                    Some(identifier) => {
-                        match identifier.to_string().as_str() {
-                            "C" => {
-                                return Err(Error::UnsupportedError(
-                                    "The size of a repr[C] enum is not specifically defined. Please use repr[u*] to define an actual size".to_string(),
-                                    identifier.span()
-                                ))
-                            }
-                            _ => size_option = Some(convert_type_path(&val, builder)?),
-                        }
-                    }
+fn register_item_types(items: &[Item], builder: &mut CSharpBuilder) -> Result<(), Error> {
+    for item in items {
+        let name = get_item_name(item);
+        if !matches!(item, Item::Mod(_)) && builder.configuration.is_ignored(&name) {
+            continue;
+        }
+        let attrs = get_item_attrs(item);
+        if is_skipped(attrs, builder) || is_cfg_disabled(attrs, builder) {
+            continue;
+        }
+        match item {
+            Item::Enum(en) => {
+                let repr = get_repr_options(&en.attrs, &name, builder)?;
+                if repr.primitive.is_some() {
+                    let type_name = convert_symbol_naming(&name, NameKind::Type, builder);
+                    builder.add_known_type(&name, &type_name, en.ident.span())?;
+                }
+            }
+            Item::Struct(strct) => {
+                let repr = get_repr_options(&strct.attrs, &name, builder)?;
+                if repr.transparent && !builder.configuration.emit_transparent_wrappers {
+                    // The type of the field might only be registered by another builder, in
+                    // which case the struct is registered when it is built.
+                    let _ = register_transparent_struct(strct, builder);
+                } else if repr.c.is_some() || repr.transparent {
+                    let type_name = convert_symbol_naming(&name, NameKind::Type, builder);
+                    builder.add_known_type(&name, &type_name, strct.ident.span())?;
                 }
             }
+            Item::Union(union) => {
+                let repr = get_repr_options(&union.attrs, &name, builder)?;
+                if repr.c.is_some() {
+                    let type_name = convert_symbol_naming(&name, NameKind::Type, builder);
+                    builder.add_known_type(&name, &type_name, union.ident.span())?;
+                }
+            }
+            Item::Type(typedef) => match get_type_alias_target(typedef) {
+                Type::BareFn(_) => {
+                    let type_name = convert_symbol_naming(&name, NameKind::Type, builder);
+                    builder.add_known_type(&name, &type_name, typedef.ident.span())?
+                }
+                Type::Path(_) | Type::Ptr(_) => {
+                    // Like transparent structs, aliased types might not be known yet.
+                    let _ = register_type_alias(typedef, builder);
+                }
+                _ => {}
+            },
+            Item::Mod(module) => {
+                if let Some(content) = &module.content {
+                    builder.module_path.push(module.ident.unraw().to_string());
+                    let result = if builder.configuration.nest_modules_as_classes {
+                        let module_type_name = get_module_type_name(builder, module);
+                        let type_name = builder.type_name.replace(module_type_name);
+                        let result = register_item_types(&content.1, builder);
+                        builder.type_name = type_name;
+                        result
+                    } else {
+                        register_item_types(&content.1, builder)
+                    };
+                    builder.module_path.pop();
+                    result?;
+                }
+            }
+            _ => {}
         }
     }
-    if size_option.is_none() {
-        return Ok(());
-    }
-    let size = size_option.expect("");
+    Ok(())
+}
 
-    let outer_docs = extract_outer_docs(&en.attrs)?;
-    write_summary_from_outer_docs(str, outer_docs, indents)?;
-    write_line(
-        str,
-        format!(
-            "public enum {} : {}",
-            en.ident.to_string(),
-            size.csharp_name
-        ),
-        *indents,
-    )?;
-    write_line(str, "{".to_string(), *indents)?;
-    *indents += 1;
+/// Builds the C# script as separate files. Enums, structs and unions are each written to their own
+/// file, outside of the type. Everything else is written to a file with the type.
+pub fn build_csharp_split(builder: &mut CSharpBuilder) -> Result<Vec<GeneratedFile>, Error> {
+    builder.staged_types.clear();
+    builder.qualified_types.clear();
+    let result = write_split_files(builder);
+    builder.finish_known_types(result)
+}
 
-    for variant in &en.variants {
-        if !variant.fields.is_empty() {
-            return Err(Error::UnsupportedError(
-                "Enum with values with fields is not supported".to_string(),
-                variant.span(),
-            ));
-        }
+fn write_split_files(builder: &mut CSharpBuilder) -> Result<Vec<GeneratedFile>, Error> {
+    builder.delegates.clear();
+    builder.member_names.clear();
+    builder.warnings.clear();
+    builder.enum_extensions.clear();
+    builder.blittability_warnings.clear();
+    builder.non_blittable_structs.clear();
+    builder.entry_points.clear();
+    builder.split_files = Some(Vec::new());
 
-        let outer_docs = extract_outer_docs(&variant.attrs)?;
-        write_summary_from_outer_docs(str, outer_docs, indents)?;
+    let mut indent = namespace_indent(builder);
+    if builder.type_name.is_some() {
+        indent += 1;
+    }
+    let tokens = builder.tokens.items.clone();
+    register_opaque_handles(&tokens, builder)?;
+    let mut body = String::new();
+    let result = write_ordered_items(
+        &mut CodeWriter::new_buffer(&mut body, builder.configuration),
+        &tokens,
+        &mut indent,
+        builder,
+    );
+    let mut files = builder.split_files.take().unwrap_or_default();
+    result?;
 
-        let name = variant.ident.to_string();
-        for _ in 0..*indents {
-            write!(str, "    ")?;
-        }
-        write!(str, "{}", name)?;
-        match &variant.discriminant {
-            Some(v) => {
-                let expr = v.1.borrow();
-                if let Expr::Lit(l) = expr {
-                    if let syn::Lit::Int(i) = &l.lit {
-                        write!(str, " = {}", i.base10_digits())?;
-                    }
-                }
-            }
-            None => {}
-        }
+    if !body.is_empty() {
+        let mut script = String::new();
+        let mut indent = 0;
+        let writer = &mut CodeWriter::new(&mut script, builder.configuration);
+        write_file_start(writer, builder, &mut indent)?;
+        write_class_start(writer, builder, &mut indent)?;
+        writer.write_str(&body)?;
+        write_class_end(writer, builder, &mut indent)?;
+        write_file_end(writer, builder, &mut indent)?;
+        let name = match &builder.type_name {
+            Some(type_name) => type_name.to_string(),
+            None => builder.dll_name.to_string(),
+        };
+        files.push(GeneratedFile {
+            suggested_name: name + ".cs",
+            contents: script,
+        });
+    }
+    Ok(files)
+}
 
-        write!(str, ",")?;
-        writeln!(str)?;
+/// Writes an enum, struct or union to its own file. As it is written outside of the type, it is
+/// also registered as being outside of the type.
+fn write_separate_file(token: &Item, builder: &mut CSharpBuilder<'_>) -> Result<(), Error> {
+    let type_name = builder.type_name.take();
+    let mut indent = namespace_indent(builder);
+    let mut content = String::new();
+    let writer = &mut CodeWriter::new_buffer(&mut content, builder.configuration);
+    let result = match token {
+        Item::Enum(en) => write_enum(writer, &mut indent, en, builder),
+        Item::Struct(strct) => write_struct(writer, &mut indent, strct, builder),
+        Item::Union(union) => write_union(writer, &mut indent, union, builder),
+        _ => Ok(()),
+    };
+    builder.type_name = type_name;
+    result?;
+    // Items such as enums without a repr are not written at all.
+    if content.is_empty() {
+        return Ok(());
     }
-    *indents -= 1;
-    write_line(str, "}".to_string(), *indents)?;
-    writeln!(str)?;
 
-    builder.add_known_type(en.ident.to_string().as_str(), en.ident.to_string().as_str());
+    let mut script = String::new();
+    let mut indent = 0;
+    let writer = &mut CodeWriter::new(&mut script, builder.configuration);
+    write_file_start(writer, builder, &mut indent)?;
+    // Items end with an empty line to separate them from the next one, which isn't needed here.
+    writer.write_str(content.strip_suffix('\n').unwrap_or(&content))?;
+    write_file_end(writer, builder, &mut indent)?;
+    let file_name = convert_symbol_naming(&get_item_name(token), NameKind::Type, builder);
+    if let Some(files) = &mut builder.split_files {
+        files.push(GeneratedFile {
+            suggested_name: file_name + ".cs",
+            contents: script,
+        });
+    }
     Ok(())
 }
 
-fn write_struct(
-    str: &mut String,
-    indents: &mut i32,
-    strct: &ItemStruct,
-    builder: &mut CSharpBuilder<'_>,
+/// Writes the generated warning and usings, and opens the namespace.
+fn write_file_start(
+    script: &mut CodeWriter,
+    builder: &CSharpBuilder,
+    indent: &mut i32,
 ) -> Result<(), Error> {
-    let mut found_c_repr = false;
-    for attr in &strct.attrs {
-        let repr_attr = get_repr_attribute_value(attr)?;
-        match repr_attr {
-            None => {}
-            Some(val) => match &val.get_ident() {
-                None => {}
-                Some(attr_identifier) => {
-                    if let "C" = attr_identifier.to_string().as_str() {
-                        found_c_repr = true
-                    }
-                }
-            },
+    if builder.configuration.emit_auto_generated_marker {
+        write_line(script, "// <auto-generated/>".to_string(), *indent)?;
+    }
+    {
+        let generated_warning = expand_generated_warning(builder)?;
+        if !generated_warning.is_empty() {
+            for line in generated_warning.lines() {
+                write_line(script, "// ".to_string() + line, *indent)?;
+            }
         }
     }
-    if !found_c_repr {
-        return Ok(());
+    for using in &builder.usings {
+        write_line(script, format!("using {};", using), *indent)?;
+    }
+    if builder.configuration.uses_library_import()
+        && !builder
+            .usings
+            .iter()
+            .any(|u| u == "System.Runtime.InteropServices.Marshalling")
+    {
+        write_line(
+            script,
+            "using System.Runtime.InteropServices.Marshalling;".to_string(),
+            *indent,
+        )?;
+    }
+    if !builder.configuration.pragma_suppressions.is_empty() {
+        write_line(
+            script,
+            format!(
+                "#pragma warning disable {}",
+                builder.configuration.pragma_suppressions.join(", ")
+            ),
+            *indent,
+        )?;
     }
+    if builder.configuration.uses_nullable_directive() {
+        write_line(script, "#nullable enable".to_string(), *indent)?;
+    } else if builder.configuration.uses_nullable_annotations() {
+        write_line(script, "#nullable enable annotations".to_string(), *indent)?;
+    }
+    writeln!(script)?;
 
-    let outer_docs = extract_outer_docs(&strct.attrs)?;
-    write_summary_from_outer_docs(str, outer_docs, indents)?;
+    match &builder.namespace {
+        None => {}
+        Some(ns) if builder.configuration.uses_file_scoped_namespace() => {
+            write_line(script, format!("namespace {};", ns), *indent)?;
+            writeln!(script)?;
+        }
+        Some(ns) => {
+            write_line(script, format!("namespace {}", ns), *indent)?;
+            write_line(script, "{".to_string(), *indent)?;
+            *indent += 1;
+        }
+    };
+    Ok(())
+}
+
+/// Builds the script, and compares it with an existing script.
+pub fn verify_script(builder: &mut CSharpBuilder, existing: &str) -> Result<(), VerifyError> {
+    let generated = builder.build()?;
+    compare_scripts(builder.configuration, existing, &generated).map_err(VerifyError::Mismatch)
+}
+
+/// Compares an existing script with a generated script, regardless of their newlines and the
+/// timestamp of the generated warning.
+pub(crate) fn compare_scripts(
+    configuration: &CSharpConfiguration,
+    existing: &str,
+    generated: &str,
+) -> Result<(), Mismatch> {
+    let existing = existing.replace("\r\n", "\n");
+    let generated = generated.replace("\r\n", "\n");
+    // A header with a timestamp differs between every build, so it's left out.
+    let mut header_lines = 0;
+    if configuration.generated_warning.contains("{timestamp}") {
+        header_lines = configuration.generated_warning.lines().count();
+        if configuration.emit_auto_generated_marker {
+            header_lines += 1;
+        }
+    }
+    let existing_lines = existing.lines().skip(header_lines).collect::<Vec<_>>();
+    let generated_lines = generated.lines().skip(header_lines).collect::<Vec<_>>();
+    if existing_lines == generated_lines {
+        return Ok(());
+    }
+
+    let first_difference = existing_lines
+        .iter()
+        .zip(&generated_lines)
+        .take_while(|(existing, generated)| existing == generated)
+        .count();
+    Err(Mismatch {
+        line: header_lines + first_difference + 1,
+        existing_line: existing_lines
+            .get(first_difference)
+            .map(|line| line.to_string()),
+        generated_line: generated_lines
+            .get(first_difference)
+            .map(|line| line.to_string()),
+        diff: unified_diff(&existing_lines, &generated_lines, header_lines),
+    })
+}
+
+/// The number of unchanged lines shown around the changes of a diff.
+const DIFF_CONTEXT: usize = 3;
+
+/// Creates a unified diff between two lists of lines. The line numbers in the hunk headers are
+/// offset by the given number of lines.
+fn unified_diff(existing: &[&str], generated: &[&str], line_offset: usize) -> String {
+    // The lines both have in common at the start and end are unchanged, which leaves only the
+    // changed part of the scripts to compare line by line.
+    let prefix = existing
+        .iter()
+        .zip(generated)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = existing[prefix..]
+        .iter()
+        .rev()
+        .zip(generated[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let existing_changed = &existing[prefix..existing.len() - suffix];
+    let generated_changed = &generated[prefix..generated.len() - suffix];
+
+    let mut lines: Vec<(char, &str)> = existing[..prefix].iter().map(|l| (' ', *l)).collect();
+    lines.extend(diff_lines(existing_changed, generated_changed));
+    lines.extend(
+        existing[existing.len() - suffix..]
+            .iter()
+            .map(|l| (' ', *l)),
+    );
+
+    let mut diff = "--- existing\n+++ generated\n".to_string();
+    let mut index = 0;
+    while index < lines.len() {
+        if lines[index].0 == ' ' {
+            index += 1;
+            continue;
+        }
+        // Changes that are close enough to share their context are put in the same hunk.
+        let start = index.saturating_sub(DIFF_CONTEXT);
+        let mut last_change = index;
+        for (next, line) in lines.iter().enumerate().skip(index + 1) {
+            if next - last_change > DIFF_CONTEXT * 2 {
+                break;
+            }
+            if line.0 != ' ' {
+                last_change = next;
+            }
+        }
+        let end = (last_change + DIFF_CONTEXT + 1).min(lines.len());
+
+        let count_before = |kind: char| {
+            lines[..start]
+                .iter()
+                .filter(|line| line.0 == ' ' || line.0 == kind)
+                .count()
+        };
+        let count_in_hunk = |kind: char| {
+            lines[start..end]
+                .iter()
+                .filter(|line| line.0 == ' ' || line.0 == kind)
+                .count()
+        };
+        diff += &format!(
+            "@@ -{},{} +{},{} @@\n",
+            line_offset + count_before('-') + 1,
+            count_in_hunk('-'),
+            line_offset + count_before('+') + 1,
+            count_in_hunk('+')
+        );
+        for (kind, line) in &lines[start..end] {
+            diff.push(*kind);
+            diff.push_str(line);
+            diff.push('\n');
+        }
+        index = end;
+    }
+    diff
+}
+
+/// Compares two lists of lines using their longest common subsequence. Returns each line with
+/// ``' '`` if it is in both lists, ``'-'`` if it's only in the first, and ``'+'`` if it's only in
+/// the second.
+fn diff_lines<'a>(existing: &[&'a str], generated: &[&'a str]) -> Vec<(char, &'a str)> {
+    // The table grows with the product of the lengths, so very large changes are shown as
+    // replacing all lines instead.
+    if existing.len().saturating_mul(generated.len()) > 4_000_000 {
+        let mut lines: Vec<(char, &str)> = existing.iter().map(|l| ('-', *l)).collect();
+        lines.extend(generated.iter().map(|l| ('+', *l)));
+        return lines;
+    }
+    // The length of the longest common subsequence of the remaining lines, from each position.
+    let width = generated.len() + 1;
+    let mut lengths = vec![0usize; (existing.len() + 1) * width];
+    for i in (0..existing.len()).rev() {
+        for j in (0..generated.len()).rev() {
+            lengths[i * width + j] = match existing[i] == generated[j] {
+                true => lengths[(i + 1) * width + j + 1] + 1,
+                false => lengths[(i + 1) * width + j].max(lengths[i * width + j + 1]),
+            };
+        }
+    }
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < existing.len() && j < generated.len() {
+        if existing[i] == generated[j] {
+            lines.push((' ', existing[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            lines.push(('-', existing[i]));
+            i += 1;
+        } else {
+            lines.push(('+', generated[j]));
+            j += 1;
+        }
+    }
+    lines.extend(existing[i..].iter().map(|l| ('-', *l)));
+    lines.extend(generated[j..].iter().map(|l| ('+', *l)));
+    lines
+}
+
+/// Replaces the placeholders in the generated warning. Unknown placeholders are left as is.
+fn expand_generated_warning(builder: &CSharpBuilder) -> Result<String, Error> {
+    let generated_warning = &builder.configuration.generated_warning;
+    if !generated_warning.contains('{') {
+        return Ok(generated_warning.to_string());
+    }
+    let placeholder = Regex::new(r"\{(\w+)\}")?;
+    Ok(placeholder
+        .replace_all(
+            generated_warning,
+            |captures: &regex::Captures| match &captures[1] {
+                "timestamp" => match &builder.timestamp {
+                    Some(timestamp) => timestamp.to_string(),
+                    None => format_timestamp(std::time::SystemTime::now()),
+                },
+                "source" => match &builder.source_name {
+                    Some(source_name) => source_name.to_string(),
+                    None => captures[0].to_string(),
+                },
+                "dll_name" => builder.dll_name.to_string(),
+                "tool_version" => env!("CARGO_PKG_VERSION").to_string(),
+                _ => captures[0].to_string(),
+            },
+        )
+        .into_owned())
+}
+
+/// Formats a time as an ISO 8601 timestamp in UTC, such as ``2024-01-31T12:00:00Z``.
+fn format_timestamp(time: std::time::SystemTime) -> String {
+    let seconds = time
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs()) as i64;
+    let (days, seconds) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+    // Converts the days since the epoch into a date in the proleptic Gregorian calendar, with
+    // years starting in March so the leap day is at the end.
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// The indentation of items directly inside the namespace.
+fn namespace_indent(builder: &CSharpBuilder) -> i32 {
+    if builder.namespace.is_some() && !builder.configuration.uses_file_scoped_namespace() {
+        1
+    } else {
+        0
+    }
+}
 
+fn write_class_start(
+    script: &mut CodeWriter,
+    builder: &CSharpBuilder,
+    indent: &mut i32,
+) -> Result<(), Error> {
+    match &builder.type_name {
+        None => {}
+        Some(t) => {
+            let crate_docs = extract_module_docs(&builder.tokens.attrs);
+            write_summary_from_outer_docs(script, crate_docs, indent, builder)?;
+            if builder.configuration.emit_generated_code_attribute {
+                write_line(
+                    script,
+                    format!(
+                        "[System.CodeDom.Compiler.GeneratedCode(\"csharp_binder\", \"{}\")]",
+                        builder.configuration.generated_code_version
+                    ),
+                    *indent,
+                )?;
+            }
+            write_line(
+                script,
+                format!("{} class {}", class_modifiers(builder), t),
+                *indent,
+            )?;
+            write_line(script, "{".to_string(), *indent)?;
+            *indent += 1;
+            write_library_name(script, builder, *indent, Some(t))?;
+            for code in &builder.class_preamble {
+                write_verbatim(script, code, *indent)?;
+                writeln!(script)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn class_modifiers(builder: &CSharpBuilder) -> String {
+    let mut modifiers = builder
+        .configuration
+        .accessibility
+        .class
+        .csharp_name()
+        .to_string();
+    modifiers += " static";
+    if builder.configuration.use_unsafe_pointers {
+        modifiers += " unsafe";
+    }
+    // Source generated imports need to be able to add their implementation to the class.
+    if builder.configuration.uses_library_import() || builder.configuration.emit_partial_class {
+        modifiers += " partial";
+    }
+    modifiers
+}
+
+/// Gets the full name of the class the items of a module are written in, when modules are nested
+/// as classes.
+fn get_module_type_name(builder: &CSharpBuilder, module: &ItemMod) -> String {
+    let class_name = convert_naming(&module.ident.unraw().to_string(), false);
+    match &builder.type_name {
+        Some(type_name) => format!("{}.{}", type_name, class_name),
+        None => class_name,
+    }
+}
+
+/// Writes the items of a module inside a nested static class. Members are only required to be
+/// unique within the class, and types are registered as being inside it.
+fn write_module_class(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    module: &ItemMod,
+    items: &[Item],
+    builder: &mut CSharpBuilder,
+) -> Result<(), Error> {
+    let class_name = convert_naming(&module.ident.unraw().to_string(), false);
+    if !builder.member_names.insert(class_name.clone()) {
+        return Err(Error::DuplicateSymbol(class_name, module.ident.span()));
+    }
+    let module_type_name = get_module_type_name(builder, module);
+    let type_name = builder.type_name.replace(module_type_name);
+    let member_names = std::mem::take(&mut builder.member_names);
+    let delegates = std::mem::take(&mut builder.delegates);
+    let mut body = String::new();
+    let mut body_indents = *indents + 1;
+    let result = write_items(
+        &mut CodeWriter::new_buffer(&mut body, builder.configuration),
+        items,
+        &mut body_indents,
+        builder,
+    );
+    builder.type_name = type_name;
+    builder.member_names = member_names;
+    builder.delegates = delegates;
+    result?;
+    // Modules without any generated items are left out entirely.
+    if body.is_empty() {
+        return Ok(());
+    }
+
+    write_summary_from_outer_docs(str, extract_module_docs(&module.attrs), indents, builder)?;
     write_line(
         str,
-        "[StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]".to_string(),
+        format!("{} class {}", class_modifiers(builder), class_name),
         *indents,
     )?;
-
-    for _ in 0..*indents {
-        write!(str, "    ")?;
+    write_line(str, "{".to_string(), *indents)?;
+    // The resolver of the library is registered by the outermost class, which has to be
+    // initialized before the imports of nested classes are used.
+    if let (Some(_), Some(type_name)) = (&builder.dll_names, &builder.type_name) {
+        if builder.configuration.resolve_dll_names_at_runtime {
+            write_line(str, format!("static {}()", class_name), *indents + 1)?;
+            write_line(str, "{".to_string(), *indents + 1)?;
+            write_line(
+                str,
+                format!(
+                    "System.Runtime.CompilerServices.RuntimeHelpers.RunClassConstructor(typeof({}).TypeHandle);",
+                    type_name.split('.').next().unwrap_or(type_name)
+                ),
+                *indents + 2,
+            )?;
+            write_line(str, "}".to_string(), *indents + 1)?;
+            writeln!(str)?;
+        }
     }
-    write!(str, "public struct {}", strct.ident.to_string())?;
+    str.write_str(&body)?;
+    write_line(str, "}".to_string(), *indents)?;
+    writeln!(str)?;
+    Ok(())
+}
 
-    let mut generics: HashSet<String> = HashSet::new();
-    for param in &strct.generics.params {
-        match param {
-            GenericParam::Type(type_param) => {
-                generics.insert(type_param.ident.to_string());
+fn write_class_end(
+    script: &mut CodeWriter,
+    builder: &CSharpBuilder,
+    indent: &mut i32,
+) -> Result<(), Error> {
+    match &builder.type_name {
+        None => {}
+        Some(type_name) => {
+            // Extension methods can only be declared in a top level class, so those of the enums
+            // in nested classes are written here too.
+            let prefix = format!("{}.", type_name);
+            for extensions in &builder.enum_extensions {
+                let enum_name = extensions
+                    .name
+                    .strip_prefix(&prefix)
+                    .unwrap_or(&extensions.name);
+                write_enum_extensions(script, *indent, enum_name, extensions, builder)?;
+                writeln!(script)?;
             }
-            GenericParam::Lifetime(_) => {}
-            GenericParam::Const(_) => {}
+            for code in &builder.class_epilogue {
+                write_verbatim(script, code, *indent)?;
+                writeln!(script)?;
+            }
+            *indent -= 1;
+            write_line(script, "}".to_string(), *indent)?;
         }
     }
+    Ok(())
+}
+
+fn write_file_end(
+    script: &mut CodeWriter,
+    builder: &CSharpBuilder,
+    indent: &mut i32,
+) -> Result<(), Error> {
+    match &builder.namespace {
+        None => {}
+        Some(_) if builder.configuration.uses_file_scoped_namespace() => {}
+        Some(_) => {
+            *indent -= 1;
+            write_line(script, "}".to_string(), *indent)?;
+        }
+    };
+    Ok(())
+}
 
-    if !generics.is_empty() {
-        write!(str, "<")?;
+/// The groups items are sorted into with [`Ordering::Grouped`], in the order they are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum ItemGroup {
+    Enums,
+    Structs,
+    Functions,
+    Other,
+}
 
-        for (index, generic) in generics.iter().enumerate() {
-            if index != 0 {
-                write!(str, ", ")?;
-            }
-            write!(str, "{}", generic)?;
+impl ItemGroup {
+    fn of(item: &Item) -> Self {
+        match item {
+            Item::Enum(_) => ItemGroup::Enums,
+            Item::Struct(_) | Item::Union(_) => ItemGroup::Structs,
+            Item::Fn(_) => ItemGroup::Functions,
+            _ => ItemGroup::Other,
         }
+    }
 
-        write!(str, ">")?;
+    fn region_name(&self) -> &'static str {
+        match self {
+            ItemGroup::Enums => "Enums",
+            ItemGroup::Structs => "Structs",
+            ItemGroup::Functions => "Functions",
+            ItemGroup::Other => "Other",
+        }
     }
+}
 
-    writeln!(str)?;
-    write_line(str, "{".to_string(), *indents)?;
+/// An item written with [`Ordering::Grouped`], which is sorted before it is added to the script.
+pub(crate) struct GroupedItem {
+    group: ItemGroup,
+    name: String,
+    code: String,
+}
 
-    *indents += 1;
-    let mut converted_fields: Vec<(String, String)> = Vec::new();
+/// Writes the items in the configured order. With [`Ordering::Grouped`], the items are still
+/// converted in the order they are declared, so types are registered the same way, and only
+/// written once they are sorted.
+fn write_ordered_items(
+    str: &mut CodeWriter,
+    items: &[Item],
+    indents: &mut i32,
+    builder: &mut CSharpBuilder<'_>,
+) -> Result<(), Error> {
+    if builder.configuration.output_ordering == Ordering::SourceOrder {
+        return write_items(str, items, indents, builder);
+    }
+    builder.grouped_items = Some(Vec::new());
+    let result = write_items(str, items, indents, builder);
+    let mut grouped_items = builder.grouped_items.take().unwrap_or_default();
+    result?;
+    grouped_items.sort_by(|a, b| (a.group, &a.name).cmp(&(b.group, &b.name)));
+    let mut index = 0;
+    while index < grouped_items.len() {
+        let group = grouped_items[index].group;
+        if builder.configuration.emit_regions {
+            write_line(str, format!("#region {}", group.region_name()), *indents)?;
+            writeln!(str)?;
+        }
+        while index < grouped_items.len() && grouped_items[index].group == group {
+            str.write_str(&grouped_items[index].code)?;
+            index += 1;
+        }
+        if builder.configuration.emit_regions {
+            write_line(str, "#endregion".to_string(), *indents)?;
+            writeln!(str)?;
+        }
+    }
+    Ok(())
+}
 
-    for field in &strct.fields {
-        let mut generic_t = None;
-        if let Type::Path(p) = &field.ty {
-            match p.path.get_ident() {
-                None => {}
-                Some(ident) => {
-                    if generics.contains(ident.to_string().as_str()) {
-                        generic_t = Some(ident.to_string())
-                    }
+/// Writes an item separately, and adds it to the grouped items, to be sorted with the other
+/// items.
+fn write_grouped_item(
+    token: &Item,
+    indents: &mut i32,
+    builder: &mut CSharpBuilder<'_>,
+) -> Result<(), Error> {
+    let grouped_items = builder.grouped_items.take();
+    let mut code = String::new();
+    let result = write_token(
+        &mut CodeWriter::new_buffer(&mut code, builder.configuration),
+        token,
+        indents,
+        builder,
+    );
+    builder.grouped_items = grouped_items;
+    result?;
+    // Items that aren't generated, such as ignored items, are left out entirely.
+    if code.is_empty() {
+        return Ok(());
+    }
+    let name = match token {
+        Item::Fn(fun) => convert_symbol_naming(
+            &fun.sig.ident.unraw().to_string(),
+            NameKind::Function,
+            builder,
+        ),
+        Item::Mod(module) => convert_naming(&module.ident.unraw().to_string(), false),
+        _ => get_item_name(token),
+    };
+    if let Some(grouped_items) = &mut builder.grouped_items {
+        grouped_items.push(GroupedItem {
+            group: ItemGroup::of(token),
+            name,
+            code,
+        });
+    }
+    Ok(())
+}
+
+/// Writes a list of items. In lenient builds, an item that fails to convert is omitted, and a
+/// warning is raised for it instead. In builds collecting errors, the item is omitted as well, and
+/// its errors are collected.
+fn write_items(
+    str: &mut CodeWriter,
+    items: &[Item],
+    indents: &mut i32,
+    builder: &mut CSharpBuilder<'_>,
+) -> Result<(), Error> {
+    for item in items {
+        if builder.mode == BuildMode::Strict {
+            write_token(str, item, indents, builder)?;
+            continue;
+        }
+        // Write the item separately, so nothing of it ends up in the script if it fails halfway.
+        let mut item_str = String::new();
+        let mut item_indents = *indents;
+        let delegates = builder.delegates.clone();
+        let member_names = builder.member_names.clone();
+        let recorded_errors = builder.errors.len();
+        let item_writer = &mut CodeWriter::new_buffer(&mut item_str, builder.configuration);
+        match write_token(item_writer, item, &mut item_indents, builder) {
+            Ok(()) if builder.errors.len() == recorded_errors => str.write_str(&item_str)?,
+            Ok(()) => {
+                builder.delegates = delegates;
+                builder.member_names = member_names;
+            }
+            Err(e) => {
+                builder.delegates = delegates;
+                builder.member_names = member_names;
+                let e = builder.locate_error(e);
+                match builder.mode {
+                    BuildMode::CollectErrors => builder.errors.push(e),
+                    _ => match &mut builder.extraction {
+                        Some(extraction) => extraction.unsupported.push(Warning {
+                            item_name: get_item_name(item),
+                            error: e,
+                        }),
+                        None => builder.add_warning(&get_item_name(item), e),
+                    },
                 }
             }
         }
+    }
+    Ok(())
+}
 
-        let t = match generic_t {
-            None => convert_type_name(&field.ty, builder)?,
-            Some(v) => TypeNameContainer::new(v.to_string(), v),
+/// Gets the extern functions declared inside an impl block as free functions, so they are written
+/// the same way. If enabled, ``&self`` and ``&mut self`` receivers are replaced with a pointer
+/// parameter of the implemented type.
+fn get_impl_functions(imp: &ItemImpl, builder: &CSharpBuilder) -> Vec<Item> {
+    let mut functions = Vec::new();
+    for item in &imp.items {
+        let method = match item {
+            ImplItem::Method(method) if method.sig.abi.is_some() => method,
+            _ => continue,
         };
-        let outer_docs = extract_outer_docs(&field.attrs)?;
-        write_summary_from_outer_docs(str, outer_docs, indents)?;
-
-        write_line(
-            str,
-            format!("/// <remarks>{}</remarks>", t.rust_name),
-            *indents,
-        )?;
-
-        match &field.ident {
-            None => {}
-            Some(field_identifier) => {
-                let csharp_field_name =
-                    convert_naming(field_identifier.to_string().as_str(), false);
-                // If C# version is 9 or newer, we make all fields { get; init; }, so they can be
-                // initialised, but are readonly afterwards. Otherwise we just make them readonly.
-                if builder.configuration.csharp_version >= 9 {
-                    write_line(
-                        str,
-                        format!(
-                            "public {} {} {{ get; init; }}",
-                            t.stringify()?,
-                            csharp_field_name
-                        ),
-                        *indents,
-                    )?;
-                } else {
-                    write_line(
-                        str,
-                        format!("public readonly {} {};", t.stringify()?, csharp_field_name),
-                        *indents,
-                    )?;
+        let mut sig = method.sig.clone();
+        if builder.configuration.map_impl_receivers {
+            if let Some(FnArg::Receiver(receiver)) = sig.inputs.first() {
+                if receiver.reference.is_some() {
+                    let span = receiver.self_token.span;
+                    let pointer = TypePtr {
+                        star_token: Default::default(),
+                        const_token: match receiver.mutability {
+                            Some(_) => None,
+                            None => Some(Default::default()),
+                        },
+                        mutability: receiver.mutability,
+                        elem: imp.self_ty.clone(),
+                    };
+                    sig.inputs[0] = FnArg::Typed(PatType {
+                        attrs: Vec::new(),
+                        pat: Box::new(Pat::Ident(PatIdent {
+                            attrs: Vec::new(),
+                            by_ref: None,
+                            mutability: None,
+                            ident: syn::Ident::new("self", span),
+                            subpat: None,
+                        })),
+                        colon_token: Default::default(),
+                        ty: Box::new(Type::Ptr(pointer)),
+                    });
                 }
-                converted_fields.push((t.stringify()?, csharp_field_name));
             }
         }
+        functions.push(Item::Fn(ItemFn {
+            attrs: method.attrs.clone(),
+            vis: method.vis.clone(),
+            sig,
+            block: Box::new(method.block.clone()),
+        }));
     }
+    functions
+}
 
-    writeln!(str)?;
+fn get_item_name(item: &Item) -> String {
+    match item {
+        Item::Const(constant) => constant.ident.unraw().to_string(),
+        Item::Enum(en) => en.ident.unraw().to_string(),
+        Item::Fn(fun) => fun.sig.ident.unraw().to_string(),
+        Item::Mod(module) => module.ident.unraw().to_string(),
+        Item::Static(stat) => stat.ident.unraw().to_string(),
+        Item::Struct(strct) => strct.ident.unraw().to_string(),
+        Item::Type(typedef) => typedef.ident.unraw().to_string(),
+        Item::Union(union) => union.ident.unraw().to_string(),
+        _ => String::new(),
+    }
+}
 
-    for _ in 0..*indents {
-        write!(str, "    ")?;
+fn get_item_attrs(item: &Item) -> &[Attribute] {
+    match item {
+        Item::Const(constant) => &constant.attrs,
+        Item::Enum(en) => &en.attrs,
+        Item::Fn(fun) => &fun.attrs,
+        Item::Impl(imp) => &imp.attrs,
+        Item::Mod(module) => &module.attrs,
+        Item::Static(stat) => &stat.attrs,
+        Item::Struct(strct) => &strct.attrs,
+        Item::Type(typedef) => &typedef.attrs,
+        Item::Union(union) => &union.attrs,
+        _ => &[],
     }
-    write!(str, "public {}(", strct.ident.to_string())?;
-    for (index, converted_field) in converted_fields.iter().enumerate() {
-        if index != 0 {
-            write!(str, ", ")?;
-        }
+}
 
-        let mut parameter_name = converted_field.1.to_string();
-        if let Some(r) = parameter_name.get_mut(0..1) {
-            r.make_ascii_lowercase();
+fn write_token(
+    str: &mut CodeWriter,
+    token: &Item,
+    indents: &mut i32,
+    builder: &mut CSharpBuilder<'_>,
+) -> Result<(), Error> {
+    // Modules and impl blocks aren't generated themselves, so only their items are matched.
+    if !matches!(token, Item::Mod(_) | Item::Impl(_))
+        && builder.configuration.is_ignored(&get_item_name(token))
+    {
+        return Ok(());
+    }
+    if is_skipped(get_item_attrs(token), builder) {
+        return Ok(());
+    }
+    if !is_cfg_enabled(get_item_attrs(token), &get_item_name(token), builder) {
+        return Ok(());
+    }
+    if builder.split_files.is_some()
+        && matches!(token, Item::Enum(_) | Item::Struct(_) | Item::Union(_))
+    {
+        return write_separate_file(token, builder);
+    }
+    // Impl blocks, and modules that aren't nested as classes, only contain the items to sort.
+    let is_container = match token {
+        Item::Impl(_) => true,
+        Item::Mod(_) => !builder.configuration.nest_modules_as_classes,
+        _ => false,
+    };
+    if builder.grouped_items.is_some() && !is_container {
+        return write_grouped_item(token, indents, builder);
+    }
+    match token {
+        Item::Const(constant) => write_const(str, indents, constant, builder)?,
+        Item::Enum(en) => write_enum(str, indents, en, builder)?,
+        Item::ExternCrate(_) => {}
+        Item::Fn(fun) => {
+            builder.in_function_signature = true;
+            let result = write_function(str, indents, builder, fun);
+            builder.in_function_signature = false;
+            result?
+        }
+        Item::ForeignMod(_) => {}
+        Item::Impl(imp) => write_items(str, &get_impl_functions(imp, builder), indents, builder)?,
+        Item::Macro(_) => {}
+        Item::Macro2(_) => {}
+        Item::Mod(module) => {
+            // Unless modules are nested as classes, we don't care for the module itself, only for
+            // the items inside, so extract those.
+            builder.module_path.push(module.ident.unraw().to_string());
+            let result = match &module.content.as_ref() {
+                None => Ok(()),
+                Some(r) if builder.configuration.nest_modules_as_classes => {
+                    write_module_class(str, indents, module, &r.1, builder)
+                }
+                Some(r) => write_items(str, &r.1, indents, builder),
+            };
+            builder.module_path.pop();
+            result?
         }
+        Item::Static(stat) => write_static(str, indents, stat, builder)?,
+        Item::Struct(strct) => write_struct(str, indents, strct, builder)?,
+        Item::Trait(_) => {}
+        Item::TraitAlias(_) => {}
+        Item::Type(typedef) => match get_type_alias_target(typedef) {
+            Type::BareFn(bare_fn) => {
+                let delegate_name = convert_symbol_naming(
+                    &typedef.ident.unraw().to_string(),
+                    NameKind::Type,
+                    builder,
+                );
+                let outer_docs = extract_outer_docs(&typedef.attrs)?;
+                let delegate = write_delegate(
+                    str,
+                    indents,
+                    bare_fn,
+                    builder,
+                    &delegate_name,
+                    outer_docs,
+                    false,
+                )?;
+                builder.add_known_type(
+                    &typedef.ident.unraw().to_string(),
+                    &delegate.csharp_name,
+                    typedef.ident.span(),
+                )?;
+            }
+            Type::Path(_) | Type::Ptr(_) => register_type_alias(typedef, builder)?,
+            _ => {}
+        },
+        Item::Union(union) => write_union(str, indents, union, builder)?,
+        Item::Use(_) => {}
+        Item::Verbatim(_) => {}
+        _ => {}
+    }
+    Ok(())
+}
 
-        write!(str, "{} {}", converted_field.0, parameter_name)?;
+/// Gets the type a type alias refers to. Optional function pointers are unwrapped, as they are
+/// written as a delegate as well.
+fn get_type_alias_target(typedef: &ItemType) -> &Type {
+    match get_option_inner(typedef.ty.borrow()) {
+        Some(inner @ Type::BareFn(_)) => inner,
+        _ => typedef.ty.borrow(),
     }
-    writeln!(str, ")")?;
-    write_line(str, "{".to_string(), *indents)?;
-    *indents += 1;
+}
 
-    for converted_field in converted_fields {
-        let mut parameter_name = converted_field.1.to_string();
-        if let Some(r) = parameter_name.get_mut(0..1) {
-            r.make_ascii_lowercase();
-        }
-        write_line(
-            str,
-            format!("{} = {};", converted_field.1, parameter_name),
-            *indents,
+/// Registers a type alias as the type it refers to. Aliases of known types are registered as that
+/// type, with the same generic arguments, and aliases of other types, such as primitives and
+/// pointers, as the C# type they are converted into. Aliases of types that aren't known are
+/// ignored.
+fn register_type_alias(typedef: &ItemType, builder: &mut CSharpBuilder) -> Result<(), Error> {
+    if let Some(t) = resolve_type_alias(typedef, builder, &mut Vec::new())? {
+        builder.register_known_type(
+            typedef.ident.unraw().to_string().as_str(),
+            t,
+            typedef.ident.span(),
         )?;
     }
-    *indents -= 1;
+    Ok(())
+}
+
+/// Resolves the C# type of a type alias. Aliases of other aliases in the script are resolved
+/// regardless of the order they are declared in. The chain holds the aliases that are being
+/// resolved, to detect aliases that refer to themselves.
+fn resolve_type_alias(
+    typedef: &ItemType,
+    builder: &CSharpBuilder,
+    chain: &mut Vec<String>,
+) -> Result<Option<CSharpType>, Error> {
+    let name = typedef.ident.unraw().to_string();
+    if chain.contains(&name) {
+        chain.push(name.clone());
+        return Err(Error::UnsupportedError(
+            format!(
+                "Type alias '{}' refers to itself: {}",
+                name,
+                chain.join(" -> ")
+            ),
+            typedef.ident.span(),
+        ));
+    }
+    chain.push(name);
+
+    if let Type::Path(type_path) = typedef.ty.borrow() {
+        let type_name = match get_path_name(&type_path.path) {
+            Some(type_name) => type_name,
+            None => return Ok(None),
+        };
+        let inner_type = match builder.get_known_type(type_name.as_str()) {
+            Some(t) => Some(t),
+            None => match find_type_alias(&builder.tokens.items, &type_name) {
+                Some(target) => resolve_type_alias(target, builder, chain)?,
+                None => None,
+            },
+        };
+        if let Some(inner_type) = inner_type {
+            let mut real_type_name = inner_type.real_type_name.clone();
+            if let PathArguments::AngleBracketed(generics) =
+                &type_path.path.segments.last().unwrap().arguments
+            {
+                write!(real_type_name, "<")?;
+                for (index, generic) in generics.args.iter().enumerate() {
+                    if let GenericArgument::Type(t) = generic {
+                        if index != 0 {
+                            write!(real_type_name, ", ")?;
+                        }
+                        write!(
+                            real_type_name,
+                            "{}",
+                            convert_type_name(t, builder)?.csharp_name
+                        )?;
+                    }
+                }
+                write!(real_type_name, ">")?;
+            }
+            return Ok(Some(CSharpType {
+                namespace: inner_type.namespace,
+                inside_type: inner_type.inside_type,
+                real_type_name,
+                marshal_as: inner_type.marshal_as,
+            }));
+        }
+    }
+
+    // Types that are converted with a marshalling attribute or a modifier can't be registered.
+    match convert_type_name(typedef.ty.borrow(), builder) {
+        Ok(t) if t.marshal_as.is_none() && !t.csharp_name.contains(' ') => Ok(Some(CSharpType {
+            namespace: None,
+            inside_type: None,
+            real_type_name: t.stringify()?,
+            marshal_as: None,
+        })),
+        _ => Ok(None),
+    }
+}
+
+/// Finds a type alias by its name in the items of the script, including those in modules.
+fn find_type_alias<'a>(items: &'a [Item], name: &str) -> Option<&'a ItemType> {
+    items.iter().find_map(|item| match item {
+        Item::Type(typedef) if typedef.ident == name => Some(typedef),
+        Item::Mod(m) => find_type_alias(&m.content.as_ref()?.1, name),
+        _ => None,
+    })
+}
+
+/// Warns about, or denies, a function that returns a struct by value that is larger than the
+/// configured size.
+fn check_return_struct_size(
+    fun: &ItemFn,
+    return_type: &Type,
+    builder: &mut CSharpBuilder,
+) -> Result<(), Error> {
+    let max_size = match builder.configuration.max_return_struct_size {
+        Some(max_size) => max_size,
+        None => return Ok(()),
+    };
+    let struct_name = match ungroup_type(return_type) {
+        Type::Path(p) => match get_path_name(&p.path) {
+            Some(name) if find_struct(&builder.tokens.items, &name).is_some() => name,
+            _ => return Ok(()),
+        },
+        _ => return Ok(()),
+    };
+    let size = match estimate_layout(return_type, builder, 0) {
+        Some((size, _)) if size > max_size => size,
+        _ => return Ok(()),
+    };
+    let error = Error::UnsupportedError(
+        format!(
+            "Function '{}' returns the struct '{}' of an estimated {} bytes by value, which is larger than {} bytes. ABIs differ in how large structs are returned, return it through a pointer instead",
+            fun.sig.ident, struct_name, size, max_size
+        ),
+        return_type.span(),
+    );
+    if builder.configuration.deny_large_return_structs {
+        builder.record_error(error)
+    } else {
+        builder.add_warning(&fun.sig.ident.unraw().to_string(), error);
+        Ok(())
+    }
+}
+
+/// Finds a struct by its name in the items of the script, including those in modules.
+fn find_struct<'a>(items: &'a [Item], name: &str) -> Option<&'a ItemStruct> {
+    items.iter().find_map(|item| match item {
+        Item::Struct(s) if s.ident == name => Some(s),
+        Item::Mod(m) => find_struct(&m.content.as_ref()?.1, name),
+        _ => None,
+    })
+}
+
+/// Estimates the size and alignment of a Rust type in bytes, assuming structs use the C layout.
+/// Returns None if the type isn't known, such as types from other crates.
+fn estimate_layout(t: &Type, builder: &CSharpBuilder, depth: usize) -> Option<(usize, usize)> {
+    // Guards against types that contain themselves, which can't be sized anyway.
+    if depth > 32 {
+        return None;
+    }
+    let pointer_size = match builder.configuration.get_pointer_width() {
+        PointerWidth::W32 => 4,
+        PointerWidth::W64 | PointerWidth::Native => 8,
+    };
+    let path = match ungroup_type(t) {
+        Type::Ptr(_) | Type::Reference(_) | Type::BareFn(_) => {
+            return Some((pointer_size, pointer_size))
+        }
+        Type::Array(array) => {
+            let (size, align) = estimate_layout(&array.elem, builder, depth + 1)?;
+            let length = match &array.len {
+                Expr::Lit(ExprLit {
+                    lit: syn::Lit::Int(i),
+                    ..
+                }) => i.base10_parse::<usize>().ok()?,
+                _ => return None,
+            };
+            return Some((size * length, align));
+        }
+        Type::Tuple(tuple) if tuple.elems.is_empty() => return Some((0, 1)),
+        Type::Path(p) => &p.path,
+        _ => return None,
+    };
+    if let Some(inner) = get_option_inner(t) {
+        // Only optional pointers have the size of their inner type.
+        return match ungroup_type(inner) {
+            Type::Ptr(_) | Type::Reference(_) | Type::BareFn(_) => {
+                Some((pointer_size, pointer_size))
+            }
+            _ => None,
+        };
+    }
+    let name = get_path_name(path)?;
+    // Non-zero integers have the size of the integer they wrap, such as NonZeroU32.
+    let primitive = match name.strip_prefix("NonZero") {
+        Some(
+            integer @ ("U8" | "I8" | "U16" | "I16" | "U32" | "I32" | "U64" | "I64" | "U128"
+            | "I128" | "Usize" | "Isize"),
+        ) => integer.to_ascii_lowercase(),
+        _ => name.clone(),
+    };
+    let size = match primitive.as_str() {
+        "u8" | "i8" | "bool" | "c_char" | "c_schar" | "c_uchar" => 1,
+        "u16" | "i16" | "c_short" | "c_ushort" => 2,
+        "u32" | "i32" | "f32" | "char" | "c_int" | "c_uint" | "c_float" => 4,
+        "u64" | "i64" | "f64" | "c_longlong" | "c_ulonglong" | "c_double" => 8,
+        "u128" | "i128" => 16,
+        "c_long" | "c_ulong" => match builder.configuration.c_long_size {
+            CLongSize::Bits32 => 4,
+            CLongSize::Bits64 => 8,
+        },
+        "usize" | "isize" => pointer_size,
+        // Structs of the script with the same name shadow these.
+        "Box" | "NonNull" if find_struct(&builder.tokens.items, &name).is_none() => pointer_size,
+        _ => {
+            let strct = find_struct(&builder.tokens.items, &name)?;
+            return estimate_struct_layout(strct, builder, depth);
+        }
+    };
+    Some((size, size))
+}
+
+/// Estimates the size and alignment of a struct with the C layout, including its padding.
+fn estimate_struct_layout(
+    strct: &ItemStruct,
+    builder: &CSharpBuilder,
+    depth: usize,
+) -> Option<(usize, usize)> {
+    let pack = strct.attrs.iter().find_map(|attr| match attr.parse_meta() {
+        Ok(Meta::List(list)) if list.path.is_ident("repr") => {
+            list.nested.iter().find_map(|nested| match nested {
+                NestedMeta::Meta(Meta::Path(p)) if p.is_ident("packed") => Some(1),
+                NestedMeta::Meta(Meta::List(l)) if l.path.is_ident("packed") => {
+                    match l.nested.first() {
+                        Some(NestedMeta::Lit(syn::Lit::Int(i))) => i.base10_parse().ok(),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            })
+        }
+        _ => None,
+    });
+    let mut size = 0;
+    let mut struct_align = 1;
+    for field in &strct.fields {
+        let (field_size, field_align) = estimate_layout(&field.ty, builder, depth + 1)?;
+        let field_align = pack.map_or(field_align, |pack: usize| field_align.min(pack));
+        size = align_up(size, field_align) + field_size;
+        struct_align = struct_align.max(field_align);
+    }
+    Some((align_up(size, struct_align), struct_align))
+}
+
+fn align_up(value: usize, align: usize) -> usize {
+    value.div_ceil(align) * align
+}
+
+fn get_path_name(path: &Path) -> Option<String> {
+    Some(path.segments.last()?.ident.unraw().to_string())
+}
+
+fn write_function(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    builder: &mut CSharpBuilder,
+    fun: &ItemFn,
+) -> Result<(), Error> {
+    let calling_convention = match &fun.sig.abi {
+        Some(abi) => get_calling_convention(abi, builder)?,
+        None => return Ok(()),
+    };
+    if has_type_generics(&fun.sig.generics) {
+        return write_monomorphized_functions(str, indents, builder, fun);
+    }
+    if !is_exported(fun, builder) {
+        builder.add_warning(
+            &fun.sig.ident.unraw().to_string(),
+            Error::UnsupportedError(
+                "Functions without a #[no_mangle] or #[export_name] attribute are not exported."
+                    .to_string(),
+                fun.sig.ident.span(),
+            ),
+        );
+        return Ok(());
+    }
+    if let Some(handle) =
+        get_opaque_handle_for_destructor(builder, &fun.sig.ident.unraw().to_string())
+    {
+        return write_opaque_handle(str, indents, builder, &handle, fun, calling_convention);
+    }
+    if let Some(variadic) = &fun.sig.variadic {
+        if !builder.configuration.allow_arglist_variadics {
+            return Err(Error::UnsupportedError(
+                format!(
+                    "Variadic function '{}' is not supported, as C# can only call it with __arglist, \
+                     or through native shims with a fixed number of arguments. __arglist can be \
+                     enabled with allow_arglist_variadics",
+                    fun.sig.ident
+                ),
+                variadic.span(),
+            ));
+        }
+        if calling_convention != CallingConvention::Cdecl {
+            return Err(Error::UnsupportedError(
+                format!(
+                    "Variadic function '{}' can only be called with __arglist if it uses the Cdecl \
+                     calling convention",
+                    fun.sig.ident
+                ),
+                variadic.span(),
+            ));
+        }
+    }
+    let function_name = builder.claim_member_name(
+        &convert_symbol_naming(
+            &fun.sig.ident.unraw().to_string(),
+            NameKind::Function,
+            builder,
+        ),
+        fun.sig.ident.span(),
+    )?;
+
+    let return_type = match &fun.sig.output {
+        ReturnType::Default => TypeNameContainer::new("void".to_string(), "void".to_string()),
+        ReturnType::Type(_, t) if convert_utf8_string(t, builder).is_some() => {
+            let mut string_type = convert_utf8_string(t, builder).unwrap();
+            if builder.configuration.uses_library_import() {
+                // Returned strings are null if the function returns a null pointer.
+                if builder.configuration.uses_nullable_annotations() {
+                    string_type.csharp_name.push('?');
+                }
+                string_type
+            } else {
+                write_string_helper(str, indents, builder)?;
+                TypeNameContainer::new("IntPtr".to_string(), string_type.rust_name)
+            }
+        }
+        ReturnType::Type(_, t) => match match ungroup_type(t) {
+            Type::Reference(reference) => convert_returned_reference(reference, builder),
+            t => convert_signature_type_name(
+                str,
+                indents,
+                t,
+                builder,
+                &(function_name.to_string() + "Return"),
+            ),
+        } {
+            Ok(return_type) => return_type,
+            Err(e) => {
+                // Keep converting the parameters, to find any further errors.
+                builder.record_error(with_type_context(e, || {
+                    format!("the return type of function '{}'", fun.sig.ident)
+                }))?;
+                TypeNameContainer::new("void".to_string(), "void".to_string())
+            }
+        },
+    };
+    if let ReturnType::Type(_, t) = &fun.sig.output {
+        check_return_struct_size(fun, t, builder)?;
+    }
+    // The parameters, with their name, declaration and type.
+    let mut parameters: Vec<(String, String, TypeNameContainer)> = Vec::new();
+    // The names the parameters are documented with, which don't depend on the naming converter.
+    let mut documented_names: Vec<String> = Vec::new();
+    for input in &fun.sig.inputs {
+        match input {
+            FnArg::Receiver(_) => {
+                return Err(Error::UnsupportedError(
+                    "Receiver parameters aren't supported".to_string(),
+                    input.span(),
+                ))
+            }
+            FnArg::Typed(t) => match t.pat.borrow() {
+                Pat::Ident(i) => {
+                    if let Some(string_type) = convert_utf8_string(&t.ty, builder) {
+                        documented_names.push(convert_naming(&i.ident.unraw().to_string(), true));
+                        parameters.push((
+                            convert_symbol_naming(
+                                &i.ident.unraw().to_string(),
+                                NameKind::Parameter,
+                                builder,
+                            ),
+                            string_type.marshal_attribute("") + string_type.stringify()?.as_str(),
+                            string_type,
+                        ));
+                        continue;
+                    }
+                    if let Some(out_type) = convert_named_out_parameter(&i.ident, &t.ty, builder)? {
+                        documented_names.push(convert_naming(&i.ident.unraw().to_string(), true));
+                        parameters.push((
+                            convert_symbol_naming(
+                                &i.ident.unraw().to_string(),
+                                NameKind::Parameter,
+                                builder,
+                            ),
+                            out_type.marshal_attribute("") + out_type.stringify()?.as_str(),
+                            out_type,
+                        ));
+                        continue;
+                    }
+                    let type_name = match convert_signature_type_name(
+                        str,
+                        indents,
+                        t.ty.borrow(),
+                        builder,
+                        &(function_name.to_string()
+                            + convert_naming(&i.ident.unraw().to_string(), false).as_str()),
+                    ) {
+                        Ok(type_name) => type_name,
+                        Err(e) => {
+                            builder.record_error(with_type_context(e, || {
+                                format!("parameter '{}' of function '{}'", i.ident, fun.sig.ident)
+                            }))?;
+                            continue;
+                        }
+                    };
+                    documented_names.push(convert_naming(&i.ident.unraw().to_string(), true));
+                    parameters.push((
+                        convert_symbol_naming(
+                            &i.ident.unraw().to_string(),
+                            NameKind::Parameter,
+                            builder,
+                        ),
+                        type_name.marshal_attribute("") + type_name.stringify()?.as_str(),
+                        type_name,
+                    ));
+                }
+                _ => {
+                    return Err(Error::UnsupportedError(
+                        "Parameters that are not identity aren't supported".to_string(),
+                        input.span(),
+                    ))
+                }
+            },
+        }
+    }
+
+    let mut doc_comment = DocComment::parse(extract_outer_docs(&fun.attrs)?, true);
+    let parameter_docs: Vec<Option<String>> = documented_names
+        .iter()
+        .map(|name| doc_comment.take_argument(name))
+        .collect();
+    // Documented arguments that don't match a parameter are kept in the summary.
+    for argument in doc_comment.arguments.drain(..) {
+        doc_comment.summary.extend(argument.2);
+    }
+    let returns = doc_comment.returns.join(" ");
+
+    let variadic = fun.sig.variadic.is_some();
+    // The arguments of __arglist can't be passed on by a wrapper.
+    let wrapped = builder.configuration.generate_safe_wrappers && !variadic;
+    let slices = match variadic {
+        true => Vec::new(),
+        false => find_slice_parameters(fun, &parameters, builder)?,
+    };
+    // The raw extern is hidden behind the wrapper or the span overload.
+    let hidden = wrapped || !slices.is_empty();
+    let extern_name = match hidden {
+        true => {
+            builder.claim_member_name(&(function_name.clone() + "Native"), fun.sig.ident.span())?
+        }
+        false => function_name.clone(),
+    };
+    if !hidden {
+        let parameter_docs = parameters
+            .iter()
+            .zip(&parameter_docs)
+            .map(|(p, description)| (p.0.as_str(), description.as_deref(), p.2.stringify_rust()))
+            .collect::<Vec<_>>();
+        write_function_docs(
+            str,
+            indents,
+            builder,
+            &doc_comment,
+            &parameter_docs,
+            (Some(&returns), &return_type.stringify_rust()),
+        )?;
+    }
+    write_obsolete_attribute(str, &fun.attrs, indents)?;
+    if hidden {
+        write_line(
+            str,
+            "[System.ComponentModel.EditorBrowsable(System.ComponentModel.EditorBrowsableState.Never)]"
+                .to_string(),
+            *indents,
+        )?;
+    }
+    if let Some(attributes) = builder
+        .configuration
+        .extra_attributes
+        .get(&fun.sig.ident.unraw().to_string())
+    {
+        for attribute in attributes {
+            write_verbatim(str, attribute, *indents)?;
+        }
+    }
+    let entry_point = get_symbol_name(&fun.attrs, &fun.sig.ident);
+    let rust_name = fun.sig.ident.unraw().to_string();
+    // Source generated imports don't support __arglist.
+    if variadic {
+        write_dll_import_attribute(
+            str,
+            indents,
+            builder,
+            &rust_name,
+            &entry_point,
+            calling_convention,
+        )?;
+    } else {
+        write_import_attribute(
+            str,
+            indents,
+            builder,
+            &rust_name,
+            &entry_point,
+            calling_convention,
+        )?;
+    }
+    builder.entry_points.push(entry_point);
+    let return_marshal_attribute = return_type.marshal_attribute("return: ");
+    if !return_marshal_attribute.is_empty() {
+        write_line(
+            str,
+            return_marshal_attribute.trim_end().to_string(),
+            *indents,
+        )?;
+    }
+
+    str.write_indent(*indents)?;
+    write!(
+        str,
+        "{} static {} {} {}(",
+        methods_access(builder),
+        if variadic {
+            "extern"
+        } else {
+            import_modifier(builder)
+        },
+        return_type.stringify()?,
+        extern_name
+    )?;
+
+    for (i, parameter) in parameters.iter().enumerate() {
+        if i != 0 {
+            write!(str, ", ")?;
+        }
+        write!(str, "{} {}", parameter.1, parameter.0)?;
+    }
+    if variadic {
+        if !parameters.is_empty() {
+            write!(str, ", ")?;
+        }
+        write!(str, "__arglist")?;
+    }
+    writeln!(str, ");")?;
+    writeln!(str)?;
+
+    if wrapped {
+        let wrapper = SafeWrapper {
+            function: fun,
+            name: function_name.clone(),
+            extern_name: extern_name.clone(),
+            parameters: &parameters,
+            parameter_docs: &parameter_docs,
+            return_type: &return_type,
+            returns: &returns,
+        };
+        write_safe_wrapper(str, indents, builder, wrapper, &doc_comment)?;
+    }
+    if !slices.is_empty() {
+        let wrapper = SafeWrapper {
+            function: fun,
+            name: function_name.clone(),
+            extern_name,
+            parameters: &parameters,
+            parameter_docs: &parameter_docs,
+            return_type: &return_type,
+            returns: &returns,
+        };
+        write_span_overload(str, indents, builder, wrapper, &slices, &doc_comment)?;
+    }
+
+    if let Some(extraction) = &mut builder.extraction {
+        extraction.functions.push(ExtractedFunction {
+            rust_name: fun.sig.ident.unraw().to_string(),
+            csharp_name: function_name.clone(),
+            entry_point: get_symbol_name(&fun.attrs, &fun.sig.ident),
+            params: parameters
+                .iter()
+                .map(|p| Ok((p.0.clone(), p.2.extracted()?)))
+                .collect::<Result<_, Error>>()?,
+            return_type: return_type.extracted()?,
+        });
+    }
+    Ok(())
+}
+
+/// Writes the XML documentation of a function, from its parameters with their description and
+/// Rust type, and the description and Rust type of its return value.
+fn write_function_docs(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    builder: &CSharpBuilder,
+    doc_comment: &DocComment,
+    parameters: &[(&str, Option<&str>, String)],
+    returns: (Option<&str>, &str),
+) -> Result<(), Error> {
+    write_doc_element(str, "summary", &doc_comment.summary, indents, builder)?;
+    for (name, description, rust_type) in parameters {
+        if let Some(description) = describe_rust_type(*description, rust_type, builder) {
+            write_line(
+                str,
+                format!(
+                    "/// <param name=\"{}\">{}</param>",
+                    name.trim_start_matches('@'),
+                    description
+                ),
+                *indents,
+            )?;
+        }
+    }
+    if let Some(returns) = describe_rust_type(returns.0, returns.1, builder) {
+        write_line(str, format!("/// <returns>{}</returns>", returns), *indents)?;
+    }
+    write_doc_element(str, "remarks", &doc_comment.remarks, indents, builder)
+}
+
+/// A function for which a safe wrapper is written around its raw extern.
+struct SafeWrapper<'a> {
+    function: &'a ItemFn,
+    name: String,
+    extern_name: String,
+    parameters: &'a [(String, String, TypeNameContainer)],
+    parameter_docs: &'a [Option<String>],
+    return_type: &'a TypeNameContainer,
+    returns: &'a str,
+}
+
+/// Writes a public method that calls the raw extern of a function. Out parameters are returned
+/// instead, either as the single return value or in a tuple, and returned C strings are converted
+/// to a managed string. A function returning a bool with a single out parameter is written as a
+/// ``TryXxx`` method instead, which keeps its out parameter.
+fn write_safe_wrapper(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    builder: &mut CSharpBuilder,
+    wrapper: SafeWrapper,
+    doc_comment: &DocComment,
+) -> Result<(), Error> {
+    let span = wrapper.function.sig.ident.span();
+    let out_parameters: Vec<usize> = (0..wrapper.parameters.len())
+        .filter(|&i| wrapper.parameters[i].2.csharp_name.starts_with("out "))
+        .collect();
+    let is_void = wrapper.return_type.csharp_name == "void";
+    let returns_ref = wrapper.return_type.csharp_name.starts_with("ref ");
+    let returns_string = !wrapper.return_type.csharp_name.starts_with("string")
+        && wrapper.return_type.rust_name.starts_with("*const c_char");
+    let is_try = wrapper.return_type.csharp_name == "bool" && out_parameters.len() == 1;
+    // The out parameters that are returned instead. Returning multiple values requires tuples.
+    let returned_outs = if is_try
+        || returns_ref
+        || (out_parameters.len() + usize::from(!is_void) > 1
+            && !builder.configuration.supports_version(7, 0))
+    {
+        Vec::new()
+    } else {
+        out_parameters
+    };
+
+    let name = match is_try && !wrapper.name.starts_with("Try") {
+        true => builder.claim_member_name(&format!("Try{}", wrapper.name), span)?,
+        false => wrapper.name.clone(),
+    };
+    let mut result_local = "result".to_string();
+    while wrapper.parameters.iter().any(|p| p.0 == result_local) {
+        result_local.push('_');
+    }
+    // The name of the returned value in a tuple, which can not be the name of an out parameter.
+    let tuple_element_name = |name: &str| convert_naming(name.trim_start_matches('@'), false);
+    let mut result_element = tuple_element_name(&result_local);
+    while wrapper
+        .parameters
+        .iter()
+        .any(|p| tuple_element_name(&p.0) == result_element)
+    {
+        result_element.push('_');
+    }
+
+    // The returned values, as their C# type, their name in the wrapper, and their Rust type.
+    let mut returned_values: Vec<(String, String, String)> = Vec::new();
+    if !is_void {
+        let csharp_type = match returns_string {
+            true => nullable_string(builder),
+            false => wrapper.return_type.stringify()?,
+        };
+        returned_values.push((
+            csharp_type,
+            result_local.clone(),
+            wrapper.return_type.stringify_rust(),
+        ));
+    }
+    for &i in &returned_outs {
+        let parameter = &wrapper.parameters[i];
+        returned_values.push((
+            parameter.2.stringify()?["out ".len()..].to_string(),
+            parameter.0.clone(),
+            parameter.2.stringify_rust(),
+        ));
+    }
+
+    let return_csharp_type = match returned_values.as_slice() {
+        [] => "void".to_string(),
+        [value] => value.0.clone(),
+        values => {
+            let elements = values
+                .iter()
+                .map(|v| match v.1 == result_local && !is_void {
+                    true => format!("{} {}", v.0, result_element),
+                    false => format!("{} {}", v.0, tuple_element_name(&v.1)),
+                })
+                .collect::<Vec<_>>();
+            format!("({})", elements.join(", "))
+        }
+    };
+    let return_rust_type = match returned_values.as_slice() {
+        [] => "void".to_string(),
+        [value] => value.2.clone(),
+        values => values
+            .iter()
+            .map(|v| v.2.as_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+    };
+    let mut returns = Some(wrapper.returns.to_string()).filter(|r| !r.is_empty());
+    if let ([i], true) = (returned_outs.as_slice(), is_void) {
+        returns = returns.or_else(|| wrapper.parameter_docs[*i].clone());
+    }
+    let parameter_docs = wrapper
+        .parameters
+        .iter()
+        .zip(wrapper.parameter_docs)
+        .enumerate()
+        .filter(|(i, _)| !returned_outs.contains(i))
+        .map(|(_, (p, description))| (p.0.as_str(), description.as_deref(), p.2.stringify_rust()))
+        .collect::<Vec<_>>();
+    write_function_docs(
+        str,
+        indents,
+        builder,
+        doc_comment,
+        &parameter_docs,
+        (returns.as_deref(), &return_rust_type),
+    )?;
+    write_obsolete_attribute(str, &wrapper.function.attrs, indents)?;
+
+    str.write_indent(*indents)?;
+    write!(
+        str,
+        "{} static {} {}(",
+        methods_access(builder),
+        return_csharp_type,
+        name
+    )?;
+    let mut first = true;
+    for (i, parameter) in wrapper.parameters.iter().enumerate() {
+        if returned_outs.contains(&i) {
+            continue;
+        }
+        if !first {
+            write!(str, ", ")?;
+        }
+        first = false;
+        write!(str, "{} {}", parameter.1, parameter.0)?;
+    }
+    writeln!(str, ")")?;
+    write_line(str, "{".to_string(), *indents)?;
+    *indents += 1;
+    for &i in &returned_outs {
+        let parameter = &wrapper.parameters[i];
+        write_line(
+            str,
+            format!(
+                "{} {};",
+                &parameter.2.stringify()?["out ".len()..],
+                parameter.0
+            ),
+            *indents,
+        )?;
+    }
+    let arguments = wrapper
+        .parameters
+        .iter()
+        .map(|p| match p.2.csharp_name.split_once(' ') {
+            Some((modifier @ ("out" | "ref" | "in"), _)) => format!("{} {}", modifier, p.0),
+            _ => p.0.clone(),
+        })
+        .collect::<Vec<_>>();
+    let call = format!("{}({})", wrapper.extern_name, arguments.join(", "));
+    let convert_result = |result: &str| match returns_string {
+        true if wrapper.return_type.csharp_name == "IntPtr" => {
+            format!("Marshal.PtrToStringUTF8({})", result)
+        }
+        true => format!("Marshal.PtrToStringUTF8((IntPtr){})", result),
+        false => result.to_string(),
+    };
+    if returns_ref {
+        write_line(str, format!("return ref {};", call), *indents)?;
+    } else if !is_void && returned_outs.is_empty() {
+        write_line(str, format!("return {};", convert_result(&call)), *indents)?;
+    } else {
+        match is_void {
+            true => write_line(str, format!("{};", call), *indents)?,
+            false => write_line(str, format!("var {} = {};", result_local, call), *indents)?,
+        }
+        let values = returned_values
+            .iter()
+            .map(|v| match v.1 == result_local && !is_void {
+                true => convert_result(&v.1),
+                false => v.1.clone(),
+            })
+            .collect::<Vec<_>>();
+        match values.as_slice() {
+            [] => {}
+            [value] => write_line(str, format!("return {};", value), *indents)?,
+            values => write_line(str, format!("return ({});", values.join(", ")), *indents)?,
+        }
+    }
+    *indents -= 1;
+    write_line(str, "}".to_string(), *indents)?;
+    writeln!(str)?;
+    Ok(())
+}
+
+/// A pointer and length parameter of a function, which are passed as a span by its span overload.
+struct SliceParameter {
+    /// The index of the pointer parameter.
+    pointer: usize,
+    /// The index of the length parameter.
+    length: usize,
+    /// The C# type of the elements.
+    element_type: String,
+    mutable: bool,
+}
+
+/// Finds the pointer and length parameters of a function that are marked as a slice, or detected
+/// as one. Slices need C# 7.3 to be pinned with ``fixed``.
+fn find_slice_parameters(
+    fun: &ItemFn,
+    parameters: &[(String, String, TypeNameContainer)],
+    builder: &CSharpBuilder,
+) -> Result<Vec<SliceParameter>, Error> {
+    let configuration = builder.configuration;
+    if !configuration.supports_version(7, 3) {
+        return Ok(Vec::new());
+    }
+    let inputs = fun
+        .sig
+        .inputs
+        .iter()
+        .filter_map(|input| match input {
+            FnArg::Typed(PatType { pat, ty, .. }) => match pat.borrow() {
+                Pat::Ident(i) => Some((i.ident.unraw().to_string(), ty.borrow())),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<(String, &Type)>>();
+    // Parameters that failed to convert are missing.
+    if inputs.len() != parameters.len() {
+        return Ok(Vec::new());
+    }
+    let find = |name: &str| {
+        inputs.iter().position(|(n, _)| n == name).ok_or_else(|| {
+            Error::UnsupportedError(
+                format!(
+                    "Function '{}' has no parameter '{}' to pass as a slice",
+                    fun.sig.ident, name
+                ),
+                fun.sig.ident.span(),
+            )
+        })
+    };
+
+    let mut pairs = Vec::new();
+    if let Some(marked) = configuration
+        .slice_parameters
+        .get(&fun.sig.ident.unraw().to_string())
+    {
+        for (pointer, length) in marked {
+            pairs.push((find(pointer)?, find(length)?, true));
+        }
+    }
+    if configuration.detect_slice_parameters {
+        for (index, window) in inputs.windows(2).enumerate() {
+            let is_length =
+                matches!(ungroup_type(window[1].1), Type::Path(p) if p.path.is_ident("usize"));
+            if window[1].0 == format!("{}_len", window[0].0)
+                && is_length
+                && !pairs.iter().any(|pair| pair.0 == index)
+            {
+                pairs.push((index, index + 1, false));
+            }
+        }
+    }
+
+    let mut slices = Vec::new();
+    for (pointer, length, marked) in pairs {
+        let csharp_type = &parameters[pointer].2.csharp_name;
+        let element = match ungroup_type(inputs[pointer].1) {
+            Type::Ptr(ptr) if csharp_type == "IntPtr" || csharp_type.ends_with('*') => {
+                match ungroup_type(&ptr.elem) {
+                    Type::Path(p) if get_path_name(&p.path).as_deref() == Some("c_void") => None,
+                    elem => Some((convert_type_name(elem, builder)?, ptr.mutability.is_some())),
+                }
+            }
+            _ => None,
+        };
+        match element {
+            Some((element, mutable)) => slices.push(SliceParameter {
+                pointer,
+                length,
+                element_type: element.stringify()?,
+                mutable,
+            }),
+            None if marked => {
+                return Err(Error::UnsupportedError(
+                    format!(
+                        "Parameter '{}' of function '{}' can't be passed as a slice, as it isn't a \
+                         typed pointer",
+                        inputs[pointer].0, fun.sig.ident
+                    ),
+                    inputs[pointer].1.span(),
+                ))
+            }
+            None => {}
+        }
+    }
+    Ok(slices)
+}
+
+/// Writes an overload of a function that takes a span for each of its slices, instead of their
+/// pointer and length. The spans are pinned with ``fixed``, and passed to the raw extern.
+fn write_span_overload(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    builder: &CSharpBuilder,
+    wrapper: SafeWrapper,
+    slices: &[SliceParameter],
+    doc_comment: &DocComment,
+) -> Result<(), Error> {
+    let lengths = slices.iter().map(|s| s.length).collect::<Vec<_>>();
+    let parameter_docs = wrapper
+        .parameters
+        .iter()
+        .zip(wrapper.parameter_docs)
+        .enumerate()
+        .filter(|(i, _)| !lengths.contains(i))
+        .map(|(_, (p, description))| (p.0.as_str(), description.as_deref(), p.2.stringify_rust()))
+        .collect::<Vec<_>>();
+    write_function_docs(
+        str,
+        indents,
+        builder,
+        doc_comment,
+        &parameter_docs,
+        (
+            Some(wrapper.returns).filter(|r| !r.is_empty()),
+            &wrapper.return_type.stringify_rust(),
+        ),
+    )?;
+    write_obsolete_attribute(str, &wrapper.function.attrs, indents)?;
+
+    let mut declarations = Vec::new();
+    let mut arguments = Vec::new();
+    for (i, parameter) in wrapper.parameters.iter().enumerate() {
+        if let Some(slice) = slices.iter().find(|s| s.pointer == i) {
+            let span_type = match slice.mutable {
+                true => "Span",
+                false => "ReadOnlySpan",
+            };
+            declarations.push(format!(
+                "{}<{}> {}",
+                span_type, slice.element_type, parameter.0
+            ));
+            let pointer = format!("{}Pointer", parameter.0);
+            arguments.push(match parameter.2.csharp_name.as_str() {
+                "IntPtr" => format!("(IntPtr){}", pointer),
+                _ => pointer,
+            });
+        } else if let Some(slice) = slices.iter().find(|s| s.length == i) {
+            arguments.push(format!(
+                "({}){}.Length",
+                parameter.2.stringify()?,
+                wrapper.parameters[slice.pointer].0
+            ));
+        } else {
+            declarations.push(format!("{} {}", parameter.1, parameter.0));
+            arguments.push(match parameter.2.csharp_name.split_once(' ') {
+                Some((modifier @ ("out" | "ref" | "in"), _)) => {
+                    format!("{} {}", modifier, parameter.0)
+                }
+                _ => parameter.0.clone(),
+            });
+        }
+    }
+
+    // Safe code only uses pointers in the overload, which has to be unsafe.
+    let modifier = match builder.configuration.use_unsafe_pointers {
+        true => "",
+        false => " unsafe",
+    };
+    write_line(
+        str,
+        format!(
+            "{} static{} {} {}({})",
+            methods_access(builder),
+            modifier,
+            wrapper.return_type.stringify()?,
+            wrapper.name,
+            declarations.join(", ")
+        ),
+        *indents,
+    )?;
+    write_line(str, "{".to_string(), *indents)?;
+    for slice in slices {
+        let name = &wrapper.parameters[slice.pointer].0;
+        write_line(
+            str,
+            format!("fixed ({}* {}Pointer = {})", slice.element_type, name, name),
+            *indents + 1,
+        )?;
+    }
+    write_line(str, "{".to_string(), *indents + 1)?;
+    let call = format!("{}({})", wrapper.extern_name, arguments.join(", "));
+    match wrapper.return_type.csharp_name.as_str() {
+        "void" => write_line(str, format!("{};", call), *indents + 2)?,
+        _ => write_line(str, format!("return {};", call), *indents + 2)?,
+    }
+    write_line(str, "}".to_string(), *indents + 1)?;
+    write_line(str, "}".to_string(), *indents)?;
+    writeln!(str)?;
+    Ok(())
+}
+
+/// Whether generics have type or const parameters, which unlike lifetimes have to be known to
+/// import a function.
+fn has_type_generics(generics: &syn::Generics) -> bool {
+    generics
+        .params
+        .iter()
+        .any(|param| !matches!(param, GenericParam::Lifetime(_)))
+}
+
+/// Writes an overload for each registered instantiation of a generic function, which imports the
+/// concrete function the Rust library exports for it.
+fn write_monomorphized_functions(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    builder: &mut CSharpBuilder,
+    fun: &ItemFn,
+) -> Result<(), Error> {
+    let name = fun.sig.ident.unraw().to_string();
+    let configuration = builder.configuration;
+    let instantiations = match configuration.monomorphizations.get(&name) {
+        Some(instantiations) => instantiations,
+        None => {
+            return Err(Error::UnsupportedError(
+                format!(
+                    "Generic function '{}' can't be imported, as only concrete instantiations of it \
+                     can be exported. Export a function for each type it is used with, and register \
+                     them with monomorphize_function",
+                    name
+                ),
+                fun.sig.generics.span(),
+            ))
+        }
+    };
+    let entry_point_pattern = configuration
+        .monomorphized_entry_points
+        .get(&name)
+        .cloned()
+        .unwrap_or_else(|| {
+            let mut pattern = "{function}".to_string();
+            for param in fun.sig.generics.type_params() {
+                pattern += &format!("_{{{}}}", param.ident);
+            }
+            pattern
+        });
+    // The overloads share their names, which are claimed again for each overload.
+    let member_names = builder.member_names.clone();
+    let mut overload_names = Vec::new();
+    for instantiation in instantiations {
+        let mut substitutions = HashMap::new();
+        let mut entry_point = entry_point_pattern.replace("{function}", &name);
+        for param in &fun.sig.generics.params {
+            let ident = match param {
+                GenericParam::Type(type_param) => &type_param.ident,
+                GenericParam::Const(const_param) => &const_param.ident,
+                GenericParam::Lifetime(_) => continue,
+            };
+            let type_name = match instantiation.iter().find(|(p, _)| ident == p) {
+                Some((_, type_name)) => type_name,
+                None => {
+                    return Err(Error::UnsupportedError(
+                        format!(
+                            "An instantiation of generic function '{}' has no type for generic \
+                             parameter '{}'",
+                            name, ident
+                        ),
+                        ident.span(),
+                    ))
+                }
+            };
+            entry_point = entry_point.replace(
+                &format!("{{{}}}", ident),
+                &convert_to_symbol_name(type_name),
+            );
+            substitutions.insert(
+                ident.unraw().to_string(),
+                syn::parse_str::<Type>(type_name)?,
+            );
+        }
+
+        let mut instance = fun.clone();
+        instance.sig.generics.params = fun
+            .sig
+            .generics
+            .params
+            .iter()
+            .filter(|param| matches!(param, GenericParam::Lifetime(_)))
+            .cloned()
+            .collect();
+        instance.sig.generics.where_clause = None;
+        for input in instance.sig.inputs.iter_mut() {
+            if let FnArg::Typed(pat_type) = input {
+                substitute_generic_types(&mut pat_type.ty, &substitutions);
+            }
+        }
+        if let ReturnType::Type(_, t) = &mut instance.sig.output {
+            substitute_generic_types(t, &substitutions);
+        }
+        instance
+            .attrs
+            .retain(|attr| !attr.path.is_ident("no_mangle") && !attr.path.is_ident("export_name"));
+        instance
+            .attrs
+            .push(syn::parse_quote!(#[export_name = #entry_point]));
+
+        for overload_name in &overload_names {
+            builder.member_names.remove(overload_name);
+        }
+        write_function(str, indents, builder, &instance)?;
+        if overload_names.is_empty() {
+            overload_names = builder
+                .member_names
+                .difference(&member_names)
+                .cloned()
+                .collect();
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the generic parameters in a type with their concrete types.
+fn substitute_generic_types(t: &mut Type, substitutions: &HashMap<String, Type>) {
+    match t {
+        Type::Path(type_path) => {
+            if type_path.qself.is_none() {
+                if let Some(ident) = type_path.path.get_ident() {
+                    if let Some(substitution) = substitutions.get(&ident.unraw().to_string()) {
+                        *t = substitution.clone();
+                        return;
+                    }
+                }
+            }
+            for segment in type_path.path.segments.iter_mut() {
+                if let PathArguments::AngleBracketed(arguments) = &mut segment.arguments {
+                    for argument in arguments.args.iter_mut() {
+                        if let GenericArgument::Type(t) = argument {
+                            substitute_generic_types(t, substitutions);
+                        }
+                    }
+                }
+            }
+        }
+        Type::Ptr(ptr) => substitute_generic_types(&mut ptr.elem, substitutions),
+        Type::Reference(reference) => substitute_generic_types(&mut reference.elem, substitutions),
+        Type::Group(group) => substitute_generic_types(&mut group.elem, substitutions),
+        Type::Paren(paren) => substitute_generic_types(&mut paren.elem, substitutions),
+        Type::Array(array) => substitute_generic_types(&mut array.elem, substitutions),
+        Type::Slice(slice) => substitute_generic_types(&mut slice.elem, substitutions),
+        Type::Tuple(tuple) => {
+            for elem in tuple.elems.iter_mut() {
+                substitute_generic_types(elem, substitutions);
+            }
+        }
+        Type::BareFn(bare_fn) => {
+            for input in bare_fn.inputs.iter_mut() {
+                substitute_generic_types(&mut input.ty, substitutions);
+            }
+            if let ReturnType::Type(_, t) = &mut bare_fn.output {
+                substitute_generic_types(t, substitutions);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Converts a type into a name that can be used in a symbol, such as ``*const u8`` into
+/// ``const_u8``.
+fn convert_to_symbol_name(type_name: &str) -> String {
+    type_name
+        .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Strips the configured prefix from the name of a function, unless that leaves an invalid name.
+fn strip_function_prefix<'n>(name: &'n str, builder: &CSharpBuilder) -> &'n str {
+    if let Some(prefix) = &builder.configuration.function_prefix_strip {
+        if let Some(stripped) = name.strip_prefix(prefix.as_str()) {
+            if stripped.chars().next().is_some_and(|c| !c.is_ascii_digit()) {
+                return stripped;
+            }
+        }
+    }
+    name
+}
+
+/// Writes the attribute that imports a function from the native library.
+fn write_import_attribute(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    builder: &CSharpBuilder,
+    rust_name: &str,
+    entry_point: &str,
+    calling_convention: CallingConvention,
+) -> Result<(), Error> {
+    if builder.configuration.uses_library_import() {
+        let options = builder.configuration.get_dllimport_options(rust_name);
+        let set_last_error = match options.set_last_error {
+            Some(set_last_error) => format!(", SetLastError = {}", set_last_error),
+            None => String::new(),
+        };
+        write_line(
+            str,
+            format!(
+                "[LibraryImport({}, EntryPoint = \"{}\"{})]",
+                library_name(builder),
+                entry_point,
+                set_last_error
+            ),
+            *indents,
+        )?;
+        if let Some(call_conv_type) = calling_convention.call_conv_type() {
+            write_line(
+                str,
+                format!(
+                    "[UnmanagedCallConv(CallConvs = new[] {{ typeof(System.Runtime.CompilerServices.{}) }})]",
+                    call_conv_type
+                ),
+                *indents,
+            )?;
+        }
+        Ok(())
+    } else {
+        write_dll_import_attribute(
+            str,
+            indents,
+            builder,
+            rust_name,
+            entry_point,
+            calling_convention,
+        )
+    }
+}
+
+/// Writes the ``DllImport`` attribute that imports a function from the native library.
+fn write_dll_import_attribute(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    builder: &CSharpBuilder,
+    rust_name: &str,
+    entry_point: &str,
+    calling_convention: CallingConvention,
+) -> Result<(), Error> {
+    let options = builder.configuration.get_dllimport_options(rust_name);
+    let mut extra_options = String::new();
+    if let Some(set_last_error) = options.set_last_error {
+        write!(extra_options, ", SetLastError = {}", set_last_error)?;
+    }
+    if let Some(exact_spelling) = options.exact_spelling {
+        write!(extra_options, ", ExactSpelling = {}", exact_spelling)?;
+    }
+    if let Some(best_fit_mapping) = options.best_fit_mapping {
+        write!(extra_options, ", BestFitMapping = {}", best_fit_mapping)?;
+    }
+    if let Some(charset) = options.charset {
+        write!(
+            extra_options,
+            ", CharSet = CharSet.{}",
+            charset.csharp_name()
+        )?;
+    }
+    write_line(
+        str,
+        format!(
+            "[DllImport({}, CallingConvention = CallingConvention.{}, EntryPoint=\"{}\"{})]",
+            library_name(builder),
+            calling_convention.csharp_name(),
+            entry_point,
+            extra_options
+        ),
+        *indents,
+    )
+}
+
+/// The library name used by imports, which is either the name of the library, or the constant
+/// with the name of the library for the current platform.
+fn library_name(builder: &CSharpBuilder) -> String {
+    match &builder.dll_names {
+        Some(_) => "LibraryName".to_string(),
+        None => format!("\"{}\"", builder.dll_name),
+    }
+}
+
+/// Writes the ``LibraryName`` constant of per-platform library names. When the library is
+/// resolved at runtime, the constant is the name of the library, and a static constructor of the
+/// class registers the resolver, if a class name is given.
+fn write_library_name(
+    script: &mut CodeWriter,
+    builder: &CSharpBuilder,
+    indent: i32,
+    class_name: Option<&str>,
+) -> Result<(), Error> {
+    let dll_names = match &builder.dll_names {
+        Some(dll_names) => dll_names,
+        None => return Ok(()),
+    };
+    if !builder.configuration.resolve_dll_names_at_runtime {
+        // Preprocessor directives are conventionally not indented.
+        writeln!(
+            script,
+            "#if UNITY_STANDALONE_WIN || UNITY_EDITOR_WIN || WINDOWS"
+        )?;
+        write_library_name_constant(script, &dll_names.windows, indent)?;
+        writeln!(
+            script,
+            "#elif UNITY_STANDALONE_OSX || UNITY_EDITOR_OSX || OSX || MACOS"
+        )?;
+        write_library_name_constant(script, &dll_names.macos, indent)?;
+        writeln!(script, "#else")?;
+        write_library_name_constant(script, &dll_names.linux, indent)?;
+        writeln!(script, "#endif")?;
+        writeln!(script)?;
+        return Ok(());
+    }
+
+    write_library_name_constant(script, &builder.dll_name, indent)?;
+    writeln!(script)?;
+    let class_name = match class_name {
+        Some(class_name) => class_name,
+        None => return Ok(()),
+    };
+    write_line(script, format!("static {}()", class_name), indent)?;
+    write_line(script, "{".to_string(), indent)?;
+    write_line(
+        script,
+        format!(
+            "NativeLibrary.SetDllImportResolver(typeof({}).Assembly, (name, assembly, searchPath) =>",
+            class_name
+        ),
+        indent + 1,
+    )?;
+    write_line(script, "{".to_string(), indent + 1)?;
+    write_line(script, "if (name != LibraryName)".to_string(), indent + 2)?;
+    write_line(script, "{".to_string(), indent + 2)?;
+    write_line(script, "return IntPtr.Zero;".to_string(), indent + 3)?;
+    write_line(script, "}".to_string(), indent + 2)?;
+    for (platform, file_name) in [("Windows", &dll_names.windows), ("OSX", &dll_names.macos)] {
+        write_line(
+            script,
+            format!(
+                "if (RuntimeInformation.IsOSPlatform(OSPlatform.{}))",
+                platform
+            ),
+            indent + 2,
+        )?;
+        write_line(script, "{".to_string(), indent + 2)?;
+        write_line(
+            script,
+            format!(
+                "return NativeLibrary.Load(\"{}\", assembly, searchPath);",
+                file_name
+            ),
+            indent + 3,
+        )?;
+        write_line(script, "}".to_string(), indent + 2)?;
+    }
+    write_line(
+        script,
+        format!(
+            "return NativeLibrary.Load(\"{}\", assembly, searchPath);",
+            dll_names.linux
+        ),
+        indent + 2,
+    )?;
+    write_line(script, "});".to_string(), indent + 1)?;
+    write_line(script, "}".to_string(), indent)?;
+    writeln!(script)?;
+    Ok(())
+}
+
+fn write_library_name_constant(
+    script: &mut CodeWriter,
+    name: &str,
+    indent: i32,
+) -> Result<(), Error> {
+    write_line(
+        script,
+        format!("internal const string LibraryName = \"{}\";", name),
+        indent,
+    )
+}
+
+/// The expression loading the library, for statics. Libraries chosen at runtime are loaded
+/// through the resolver.
+fn load_library(builder: &CSharpBuilder) -> String {
+    match (&builder.dll_names, &builder.type_name) {
+        (Some(_), Some(type_name)) if builder.configuration.resolve_dll_names_at_runtime => {
+            format!(
+                "NativeLibrary.Load(LibraryName, typeof({}).Assembly, null)",
+                type_name
+            )
+        }
+        _ => format!("NativeLibrary.Load({})", library_name(builder)),
+    }
+}
+
+fn types_access(builder: &CSharpBuilder) -> &'static str {
+    builder.configuration.accessibility.types.csharp_name()
+}
+
+/// The access modifier of imported functions, and the methods that wrap them.
+fn methods_access(builder: &CSharpBuilder) -> &'static str {
+    builder.configuration.accessibility.methods.csharp_name()
+}
+
+/// Imported functions are either extern, or partial when their implementation is source generated.
+fn import_modifier(builder: &CSharpBuilder) -> &'static str {
+    if builder.configuration.uses_library_import() {
+        "partial"
+    } else {
+        "extern"
+    }
+}
+
+/// Converts the type of a function parameter or return value. Function pointers are converted into
+/// a delegate, which gets written before the function that uses it. Delegates with an identical
+/// signature are only written once.
+fn convert_signature_type_name(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    t: &Type,
+    builder: &mut CSharpBuilder,
+    name_prefix: &str,
+) -> Result<TypeNameContainer, Error> {
+    let t = ungroup_type(t);
+    // A function pointer in an Option is nullable, but has the same layout.
+    let (t, optional) = match get_option_inner(t) {
+        Some(inner @ Type::BareFn(_)) => (inner, true),
+        _ => (t, false),
+    };
+    match t {
+        Type::BareFn(bare_fn) => {
+            let mut delegate = write_delegate(
+                str,
+                indents,
+                bare_fn,
+                builder,
+                &(name_prefix.to_string() + "Delegate"),
+                Vec::new(),
+                true,
+            )?;
+            if optional {
+                delegate.rust_name = format!("Option<{}>, nullable", delegate.rust_name);
+                if builder.configuration.uses_nullable_annotations() {
+                    delegate.csharp_name.push('?');
+                }
+            }
+            Ok(delegate)
+        }
+        _ => convert_type_name(t, builder),
+    }
+}
+
+/// Whether a type is a function pointer, or an optional function pointer.
+fn is_function_pointer(t: &Type) -> bool {
+    let t = ungroup_type(t);
+    matches!(get_option_inner(t).unwrap_or(t), Type::BareFn(_))
+}
+
+/// Converts the return type and the named parameters of a function pointer.
+fn convert_delegate_signature(
+    bare_fn: &TypeBareFn,
+    builder: &CSharpBuilder,
+) -> Result<(TypeNameContainer, Vec<(String, TypeNameContainer)>), Error> {
+    let return_type = match &bare_fn.output {
+        ReturnType::Default => TypeNameContainer::new("void".to_string(), "void".to_string()),
+        ReturnType::Type(_, t) => match ungroup_type(t) {
+            Type::Reference(reference) => convert_returned_reference(reference, builder)?,
+            t => convert_type_name(t, builder)?,
+        },
+    };
+    let mut parameters: Vec<(String, TypeNameContainer)> = Vec::new();
+    for (index, input) in bare_fn.inputs.iter().enumerate() {
+        let parameter_name = match &input.name {
+            Some((name, _)) => {
+                convert_symbol_naming(&name.unraw().to_string(), NameKind::Parameter, builder)
+            }
+            None => format!("arg{}", index),
+        };
+        parameters.push((parameter_name, convert_type_name(&input.ty, builder)?));
+    }
+    Ok((return_type, parameters))
+}
+
+/// Writes a delegate for a function pointer. If `reuse_existing` is set, and a delegate with the
+/// same signature was already written, that delegate is used instead.
+fn write_delegate(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    bare_fn: &TypeBareFn,
+    builder: &mut CSharpBuilder,
+    delegate_name: &str,
+    outer_docs: Vec<String>,
+    reuse_existing: bool,
+) -> Result<TypeNameContainer, Error> {
+    let calling_convention = match &bare_fn.abi {
+        Some(abi) => get_calling_convention(abi, builder)?,
+        None => {
+            return Err(Error::UnsupportedError(
+                "Function pointers without an extern ABI are not supported.".to_string(),
+                bare_fn.span(),
+            ))
+        }
+    };
+    if bare_fn.variadic.is_some() {
+        return Err(Error::UnsupportedError(
+            "Variadic function pointers are not supported.".to_string(),
+            bare_fn.span(),
+        ));
+    }
+
+    // Native code can't pass a SafeHandle to a callback, so delegates keep plain pointers.
+    let in_function_signature = std::mem::replace(&mut builder.in_function_signature, false);
+    let signature = convert_delegate_signature(bare_fn, builder);
+    builder.in_function_signature = in_function_signature;
+    let (return_type, parameters) = signature?;
+
+    let mut rust_name = "fn(".to_string();
+    let mut signature =
+        return_type.marshal_attribute("return: ") + return_type.stringify()?.as_str();
+    signature += "(";
+    for (index, parameter) in parameters.iter().enumerate() {
+        if index != 0 {
+            rust_name += ", ";
+            signature += ", ";
+        }
+        rust_name += parameter.1.stringify_rust().as_str();
+        write!(
+            signature,
+            "{}{} {}",
+            parameter.1.marshal_attribute(""),
+            parameter.1.stringify()?,
+            parameter.0
+        )?;
+    }
+    rust_name += ")";
+    signature += ")";
+    if let ReturnType::Type(..) = &bare_fn.output {
+        write!(rust_name, " -> {}", return_type.stringify_rust())?;
+    }
+
+    if reuse_existing {
+        if let Some(existing) = builder.delegates.get(&signature) {
+            return Ok(TypeNameContainer::new(existing.to_string(), rust_name));
+        }
+    }
+
+    write_summary_from_outer_docs(str, outer_docs, indents, builder)?;
+    if builder.configuration.is_unity() {
+        write_callback_example(str, indents, delegate_name, &return_type, &parameters)?;
+    }
+    write_line(
+        str,
+        format!(
+            "[UnmanagedFunctionPointer(CallingConvention.{})]",
+            calling_convention.csharp_name()
+        ),
+        *indents,
+    )?;
+    let return_marshal_attribute = return_type.marshal_attribute("return: ");
+    if !return_marshal_attribute.is_empty() {
+        write_line(
+            str,
+            return_marshal_attribute.trim_end().to_string(),
+            *indents,
+        )?;
+    }
+    str.write_indent(*indents)?;
+    write!(
+        str,
+        "{} delegate {} {}(",
+        types_access(builder),
+        return_type.stringify()?,
+        delegate_name
+    )?;
+    for (index, parameter) in parameters.iter().enumerate() {
+        if index != 0 {
+            write!(str, ", ")?;
+        }
+        write!(
+            str,
+            "{}{} {}",
+            parameter.1.marshal_attribute(""),
+            parameter.1.stringify()?,
+            parameter.0
+        )?;
+    }
+    writeln!(str, ");")?;
+    writeln!(str)?;
+
+    builder
+        .delegates
+        .entry(signature)
+        .or_insert_with(|| delegate_name.to_string());
+    Ok(TypeNameContainer::new(delegate_name.to_string(), rust_name))
+}
+
+/// IL2CPP can only call static methods marked with ``MonoPInvokeCallback`` from native code, which
+/// is easily forgotten, so an example of such a method is added to the documentation of delegates.
+fn write_callback_example(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    delegate_name: &str,
+    return_type: &TypeNameContainer,
+    parameters: &[(String, TypeNameContainer)],
+) -> Result<(), Error> {
+    let mut signature = String::new();
+    for (index, parameter) in parameters.iter().enumerate() {
+        if index != 0 {
+            signature += ", ";
+        }
+        write!(signature, "{} {}", parameter.1.stringify()?, parameter.0)?;
+    }
+    let return_type = return_type.stringify()?;
+    let body = match return_type.as_str() {
+        "void" => "{ }",
+        _ => "{ return default; }",
+    };
+    for line in [
+        "/// <example>".to_string(),
+        "/// IL2CPP requires methods passed as this delegate to be static, and marked with"
+            .to_string(),
+        "/// <c>MonoPInvokeCallback</c>:".to_string(),
+        "/// <code>".to_string(),
+        format!("/// [AOT.MonoPInvokeCallback(typeof({}))]", delegate_name),
+        escape_xml(&format!(
+            "/// private static {} On{}({}) {}",
+            return_type,
+            delegate_name
+                .strip_suffix("Delegate")
+                .unwrap_or(delegate_name),
+            signature,
+            body
+        )),
+        "/// </code>".to_string(),
+        "/// </example>".to_string(),
+    ] {
+        write_line(str, line, *indents)?;
+    }
+    Ok(())
+}
+
+fn write_const(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    constant: &ItemConst,
+    builder: &mut CSharpBuilder<'_>,
+) -> Result<(), Error> {
+    if !matches!(constant.vis, Visibility::Public(_)) {
+        return Ok(());
+    }
+    let name = constant.ident.unraw().to_string();
+    if builder.type_name.is_none() {
+        builder.add_warning(
+            &name,
+            Error::UnsupportedError(
+                "Constants can only be generated when a type is set.".to_string(),
+                constant.span(),
+            ),
+        );
+        return Ok(());
+    }
+
+    let t = match convert_type_name(constant.ty.borrow(), builder) {
+        Ok(t) if is_const_type(&t) => t,
+        Ok(_) => {
+            builder.add_warning(
+                &name,
+                Error::UnsupportedError(
+                    "Only constants of primitive types are supported.".to_string(),
+                    constant.ty.span(),
+                ),
+            );
+            return Ok(());
+        }
+        Err(e) => {
+            builder.add_warning(&name, e);
+            return Ok(());
+        }
+    };
+    let value = match convert_const_value(constant.expr.borrow(), &t, builder) {
+        Some(value) => value,
+        None => {
+            builder.add_warning(
+                &name,
+                Error::UnsupportedError(
+                    "Only constants with a literal value are supported.".to_string(),
+                    constant.expr.span(),
+                ),
+            );
+            return Ok(());
+        }
+    };
+
+    let constant_name =
+        builder.claim_member_name(&convert_constant_naming(&name), constant.ident.span())?;
+    let outer_docs = extract_outer_docs(&constant.attrs)?;
+    write_summary_from_outer_docs(str, outer_docs, indents, builder)?;
+    write_obsolete_attribute(str, &constant.attrs, indents)?;
+    write_line(
+        str,
+        format!(
+            "public const {} {} = {};",
+            t.csharp_name, constant_name, value
+        ),
+        *indents,
+    )?;
+    writeln!(str)?;
+    Ok(())
+}
+
+/// Writes an exported static. Immutable statics with a literal value are written as a constant.
+/// Other statics are written as a property which reads the static from the library, if enabled.
+fn write_static(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    stat: &ItemStatic,
+    builder: &mut CSharpBuilder<'_>,
+) -> Result<(), Error> {
+    if !matches!(stat.vis, Visibility::Public(_)) {
+        return Ok(());
+    }
+    let name = stat.ident.unraw().to_string();
+    if !has_export_attribute(&stat.attrs) {
+        builder.add_warning(
+            &name,
+            Error::UnsupportedError(
+                "Statics without a #[no_mangle] or #[export_name] attribute are not exported."
+                    .to_string(),
+                stat.ident.span(),
+            ),
+        );
+        return Ok(());
+    }
+    if builder.type_name.is_none() {
+        builder.add_warning(
+            &name,
+            Error::UnsupportedError(
+                "Statics can only be generated when a type is set.".to_string(),
+                stat.span(),
+            ),
+        );
+        return Ok(());
+    }
+
+    let t = match convert_type_name(stat.ty.borrow(), builder) {
+        Ok(t) => t,
+        Err(e) => {
+            builder.add_warning(&name, e);
+            return Ok(());
+        }
+    };
+    let value = match stat.mutability {
+        None if is_const_type(&t) => convert_const_value(stat.expr.borrow(), &t, builder),
+        _ => None,
+    };
+    if value.is_none() && !builder.configuration.read_statics_from_library {
+        builder.add_warning(
+            &name,
+            Error::UnsupportedError(
+                "Only immutable statics of primitive types with a literal value are supported, unless statics are read from the library.".to_string(),
+                stat.span(),
+            ),
+        );
+        return Ok(());
+    }
+    if value.is_none() && t.marshal_as.is_some() {
+        builder.add_warning(
+            &name,
+            Error::UnsupportedError(
+                "Statics of types that require marshalling can not be read from the library."
+                    .to_string(),
+                stat.ty.span(),
+            ),
+        );
+        return Ok(());
+    }
+
+    let static_name =
+        builder.claim_member_name(&convert_constant_naming(&name), stat.ident.span())?;
+    let outer_docs = extract_outer_docs(&stat.attrs)?;
+    let csharp_type = match value {
+        Some(value) => {
+            write_summary_from_outer_docs(str, outer_docs, indents, builder)?;
+            write_obsolete_attribute(str, &stat.attrs, indents)?;
+            write_line(
+                str,
+                format!(
+                    "public const {} {} = {};",
+                    t.csharp_name, static_name, value
+                ),
+                *indents,
+            )?;
+            writeln!(str)?;
+            return Ok(());
+        }
+        None => t.stringify()?,
+    };
+
+    // The address of the static is looked up once, but the value is read on every access, as
+    // mutable statics can change.
+    let address_name =
+        builder.claim_member_name(&(static_name.clone() + "Address"), stat.ident.span())?;
+    write_line(
+        str,
+        format!("private static IntPtr {};", address_name),
+        *indents,
+    )?;
+    writeln!(str)?;
+    write_summary_from_outer_docs(str, outer_docs, indents, builder)?;
+    write_obsolete_attribute(str, &stat.attrs, indents)?;
+    write_line(
+        str,
+        format!("public static {} {}", csharp_type, static_name),
+        *indents,
+    )?;
+    write_line(str, "{".to_string(), *indents)?;
+    write_line(str, "get".to_string(), *indents + 1)?;
+    write_line(str, "{".to_string(), *indents + 1)?;
+    write_line(
+        str,
+        format!("if ({} == IntPtr.Zero)", address_name),
+        *indents + 2,
+    )?;
+    write_line(str, "{".to_string(), *indents + 2)?;
+    write_line(
+        str,
+        format!(
+            "{} = NativeLibrary.GetExport({}, \"{}\");",
+            address_name,
+            load_library(builder),
+            get_symbol_name(&stat.attrs, &stat.ident)
+        ),
+        *indents + 3,
+    )?;
+    write_line(str, "}".to_string(), *indents + 2)?;
+    write_line(
+        str,
+        format!(
+            "return Marshal.PtrToStructure<{}>({});",
+            csharp_type, address_name
+        ),
+        *indents + 2,
+    )?;
+    write_line(str, "}".to_string(), *indents + 1)?;
+    write_line(str, "}".to_string(), *indents)?;
+    writeln!(str)?;
+    Ok(())
+}
+
+/// C# only allows constants of a limited set of primitive types.
+fn is_const_type(t: &TypeNameContainer) -> bool {
+    t.generics.is_empty()
+        && matches!(
+            t.csharp_name.as_str(),
+            "byte"
+                | "sbyte"
+                | "short"
+                | "ushort"
+                | "int"
+                | "uint"
+                | "long"
+                | "ulong"
+                | "nint"
+                | "nuint"
+                | "float"
+                | "double"
+                | "bool"
+        )
+}
+
+/// Converts the literal value of a constant into its C# representation. Returns None if the value
+/// is not a (negated) literal.
+fn convert_const_value(
+    expr: &Expr,
+    t: &TypeNameContainer,
+    builder: &CSharpBuilder,
+) -> Option<String> {
+    match expr {
+        Expr::Lit(l) => match &l.lit {
+            syn::Lit::Int(i) => Some(convert_int_literal(i, builder)),
+            syn::Lit::Float(f) => {
+                if t.csharp_name == "float" {
+                    Some(f.base10_digits().to_string() + "f")
+                } else {
+                    Some(f.base10_digits().to_string())
+                }
+            }
+            syn::Lit::Bool(b) => Some(b.value.to_string()),
+            _ => None,
+        },
+        Expr::Unary(unary) => match unary.op {
+            UnOp::Neg(_) if !t.csharp_name.starts_with('u') && t.csharp_name != "byte" => Some(
+                "-".to_string() + convert_const_value(unary.expr.borrow(), t, builder)?.as_str(),
+            ),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Converts an integer literal into C#, keeping its radix and digit separators where the C#
+/// version supports them. Octal literals don't exist in C#, so these are written in decimal.
+fn convert_int_literal(i: &LitInt, builder: &CSharpBuilder) -> String {
+    let token = i.to_string();
+    let token = &token[..token.len() - i.suffix().len()];
+    let (prefix, digits) = if let Some(digits) = token.strip_prefix("0x") {
+        ("0x", digits)
+    } else if let Some(digits) = token.strip_prefix("0b") {
+        ("0b", digits)
+    } else if token.starts_with("0o") {
+        return i.base10_digits().to_string();
+    } else {
+        ("", token)
+    };
+    // Binary literals and digit separators were both introduced in C# 7.
+    if !builder.configuration.supports_version(7, 0) {
+        if prefix == "0b" {
+            return i.base10_digits().to_string();
+        }
+        return format!("{}{}", prefix, digits.replace('_', ""));
+    }
+    // Rust allows separators at the start and end of the digits, C# only between them.
+    format!("{}{}", prefix, digits.trim_matches('_'))
+}
+
+fn write_enum(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    en: &ItemEnum,
+    builder: &mut CSharpBuilder<'_>,
+) -> Result<(), Error> {
+    let repr = get_repr_options(&en.attrs, &en.ident.unraw().to_string(), builder)?;
+    let size = match &repr.primitive {
+        Some(primitive) => convert_enum_size(primitive, builder)?,
+        None => {
+            if let Some(span) = repr.c {
+                return Err(Error::UnsupportedError(
+                    "The size of a repr[C] enum is not specifically defined. Please use repr[u*] to define an actual size".to_string(),
+                    span,
+                ));
+            }
+            return Ok(());
+        }
+    };
+
+    if en.variants.iter().any(|v| !v.fields.is_empty()) {
+        return write_tagged_union(str, indents, en, &size, repr.c.is_some(), builder);
+    }
+
+    let enum_name = builder.claim_member_name(
+        &convert_symbol_naming(&en.ident.unraw().to_string(), NameKind::Type, builder),
+        en.ident.span(),
+    )?;
+    let doc_comment = DocComment::parse(extract_outer_docs(&en.attrs)?, false);
+    write_doc_element(str, "summary", &doc_comment.summary, indents, builder)?;
+    let mut remarks = format_doc_lines(&doc_comment.remarks, builder);
+    if builder.configuration.generate_enum_helpers
+        && en.attrs.iter().any(|a| a.path.is_ident("non_exhaustive"))
+    {
+        remarks.push(
+            "This enum is non-exhaustive, native code may return values that are not declared \
+             here. Use IsDefinedFast to check for these."
+                .to_string(),
+        );
+    }
+    if let Some(primitive) = &repr.primitive {
+        if builder.configuration.emit_enum_remarks {
+            remarks.extend(enum_repr_remarks(en, primitive, &size, builder)?);
+        }
+        remarks.extend(pointer_sized_repr_remark(primitive, &size));
+    }
+    write_xml_element(str, "remarks", remarks, indents)?;
+    write_obsolete_attribute(str, &en.attrs, indents)?;
+    let flags = is_flags_enum(en, &size, builder)?;
+    if flags {
+        write_line(str, "[Flags]".to_string(), *indents)?;
+    }
+    write_line(
+        str,
+        format!(
+            "{} enum {} : {}",
+            types_access(builder),
+            enum_name,
+            size.csharp_name
+        ),
+        *indents,
+    )?;
+    let variants = write_enum_variants(str, indents, en, &size, builder)?;
+    writeln!(str)?;
+
+    if builder.configuration.generate_to_string || builder.configuration.generate_enum_helpers {
+        let extensions = EnumExtensions {
+            name: enum_name.clone(),
+            underlying_type: size.csharp_name.clone(),
+            variants: variants.iter().map(|v| v.csharp_name.clone()).collect(),
+            flags,
+        };
+        match &builder.type_name {
+            Some(type_name) => builder.enum_extensions.push(EnumExtensions {
+                name: format!("{}.{}", type_name, enum_name),
+                ..extensions
+            }),
+            None => {
+                write_line(
+                    str,
+                    format!(
+                        "{} static class {}Extensions",
+                        types_access(builder),
+                        enum_name
+                    ),
+                    *indents,
+                )?;
+                write_line(str, "{".to_string(), *indents)?;
+                write_enum_extensions(str, *indents + 1, &enum_name, &extensions, builder)?;
+                write_line(str, "}".to_string(), *indents)?;
+                writeln!(str)?;
+            }
+        }
+    }
+
+    builder.add_known_type(&en.ident.unraw().to_string(), &enum_name, en.ident.span())?;
+    record_extracted_enum(en, &enum_name, &size, variants, builder)
+}
+
+/// The remarks of an enum with its Rust representation, and a table with the values of the
+/// variants without an explicit discriminant. Once a variant is skipped, the values of the
+/// variants after it are written explicitly, so these are left out.
+fn enum_repr_remarks(
+    en: &ItemEnum,
+    primitive: &Path,
+    size: &TypeNameContainer,
+    builder: &CSharpBuilder,
+) -> Result<Vec<String>, Error> {
+    let repr = primitive
+        .get_ident()
+        .map_or_else(|| size.rust_name.to_string(), |i| i.to_string());
+    let mut remarks = vec![format!("Rust representation: <c>repr({})</c>.", repr)];
+    let mut implied = Vec::new();
+    let mut implicit_value: i128 = 0;
+    let mut has_skipped = false;
+    for variant in &en.variants {
+        let value = match &variant.discriminant {
+            Some(v) => {
+                implicit_value = convert_discriminant(v.1.borrow(), &size.csharp_name)?
+                    .parse::<i128>()
+                    .unwrap_or_default()
+                    + 1;
+                continue;
+            }
+            None => implicit_value,
+        };
+        implicit_value = value + 1;
+        if is_skipped(&variant.attrs, builder) {
+            has_skipped = true;
+            continue;
+        }
+        if has_skipped {
+            continue;
+        }
+        implied.push(format!(
+            "<item><term>{}</term><description>{}</description></item>",
+            convert_variant_naming(en, &variant.ident.unraw().to_string(), builder),
+            value
+        ));
+    }
+    if !implied.is_empty() {
+        remarks.push("<list type=\"table\">".to_string());
+        remarks.push(
+            "<listheader><term>Variant</term><description>Value</description></listheader>"
+                .to_string(),
+        );
+        remarks.extend(implied);
+        remarks.push("</list>".to_string());
+    }
+    Ok(remarks)
+}
+
+/// An enum that gets extension methods, with the C# names of its variants.
+pub(crate) struct EnumExtensions {
+    name: String,
+    underlying_type: String,
+    variants: Vec<String>,
+    flags: bool,
+}
+
+/// Writes the extension methods of an enum that are enabled in the configuration, separated by
+/// empty lines.
+fn write_enum_extensions(
+    str: &mut CodeWriter,
+    indents: i32,
+    enum_name: &str,
+    extensions: &EnumExtensions,
+    builder: &CSharpBuilder,
+) -> Result<(), Error> {
+    let mut first = true;
+    if builder.configuration.generate_to_string {
+        write_enum_to_string(str, indents, enum_name, &extensions.underlying_type)?;
+        first = false;
+    }
+    if builder.configuration.generate_enum_helpers {
+        if !first {
+            writeln!(str)?;
+        }
+        write_enum_is_defined(str, indents, enum_name, extensions)?;
+        if builder.configuration.generate_enum_try_from_raw {
+            writeln!(str)?;
+            write_enum_try_from_raw(str, indents, enum_name, &extensions.underlying_type)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes an extension method that checks whether the value of an enum is declared, without
+/// reflection. Values of flags enums are declared if only declared flags are set.
+fn write_enum_is_defined(
+    str: &mut CodeWriter,
+    indents: i32,
+    enum_name: &str,
+    extensions: &EnumExtensions,
+) -> Result<(), Error> {
+    write_line(
+        str,
+        format!("public static bool IsDefinedFast(this {} value)", enum_name),
+        indents,
+    )?;
+    write_line(str, "{".to_string(), indents)?;
+    if extensions.flags {
+        let flags = extensions
+            .variants
+            .iter()
+            .map(|v| format!("{}.{}", enum_name, v))
+            .collect::<Vec<_>>()
+            .join(" | ");
+        write_line(
+            str,
+            format!("return (value & ~({})) == 0;", flags),
+            indents + 1,
+        )?;
+    } else {
+        write_line(str, "switch (value)".to_string(), indents + 1)?;
+        write_line(str, "{".to_string(), indents + 1)?;
+        for variant in &extensions.variants {
+            write_line(str, format!("case {}.{}:", enum_name, variant), indents + 2)?;
+        }
+        if !extensions.variants.is_empty() {
+            write_line(str, "return true;".to_string(), indents + 3)?;
+        }
+        write_line(str, "default:".to_string(), indents + 2)?;
+        write_line(str, "return false;".to_string(), indents + 3)?;
+        write_line(str, "}".to_string(), indents + 1)?;
+    }
+    write_line(str, "}".to_string(), indents)?;
+    Ok(())
+}
+
+/// Writes a method that converts a raw value into an enum, and returns whether the value is
+/// declared.
+fn write_enum_try_from_raw(
+    str: &mut CodeWriter,
+    indents: i32,
+    enum_name: &str,
+    underlying_type: &str,
+) -> Result<(), Error> {
+    write_line(
+        str,
+        format!(
+            "public static bool TryFromRaw({} raw, out {} value)",
+            underlying_type, enum_name
+        ),
+        indents,
+    )?;
+    write_line(str, "{".to_string(), indents)?;
+    write_line(str, format!("value = ({})raw;", enum_name), indents + 1)?;
+    write_line(
+        str,
+        "return value.IsDefinedFast();".to_string(),
+        indents + 1,
+    )?;
+    write_line(str, "}".to_string(), indents)?;
+    Ok(())
+}
+
+/// Writes an extension method that returns the name of the value of an enum, followed by its
+/// numeric value.
+fn write_enum_to_string(
+    str: &mut CodeWriter,
+    indents: i32,
+    enum_name: &str,
+    underlying_type: &str,
+) -> Result<(), Error> {
+    write_line(
+        str,
+        format!(
+            "public static string ToFriendlyString(this {} value)",
+            enum_name
+        ),
+        indents,
+    )?;
+    write_line(str, "{".to_string(), indents)?;
+    write_line(
+        str,
+        format!("return $\"{{value}} ({{({})value}})\";", underlying_type),
+        indents + 1,
+    )?;
+    write_line(str, "}".to_string(), indents)?;
+    Ok(())
+}
+
+/// Writes the body of a C# enum with the variants of a Rust enum.
+fn write_enum_variants(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    en: &ItemEnum,
+    size: &TypeNameContainer,
+    builder: &CSharpBuilder<'_>,
+) -> Result<Vec<ExtractedVariant>, Error> {
+    write_line(str, "{".to_string(), *indents)?;
+    *indents += 1;
+
+    let mut variants = Vec::new();
+    let mut implicit_value: i128 = 0;
+    let mut has_skipped = false;
+    for variant in &en.variants {
+        let value = match &variant.discriminant {
+            Some(v) => Some(convert_discriminant(v.1.borrow(), &size.csharp_name)?),
+            None => None,
+        };
+        let current_value = match &value {
+            Some(v) => v.parse().unwrap_or_default(),
+            None => implicit_value,
+        };
+        implicit_value = current_value + 1;
+        if is_skipped(&variant.attrs, builder) {
+            has_skipped = true;
+            continue;
+        }
+
+        let variant_name = convert_variant_naming(en, &variant.ident.unraw().to_string(), builder);
+        let doc_comment = DocComment::parse(extract_outer_docs(&variant.attrs)?, false);
+        let mut summary = format_doc_lines(&doc_comment.summary, builder);
+        if variant.ident != variant_name {
+            summary.push(format!(
+                "Rust name: {}",
+                escape_xml(&variant.ident.unraw().to_string())
+            ));
+        }
+        write_xml_element(str, "summary", summary, indents)?;
+        write_doc_element(str, "remarks", &doc_comment.remarks, indents, builder)?;
+        write_obsolete_attribute(str, &variant.attrs, indents)?;
+
+        str.write_indent(*indents)?;
+        write!(str, "{}", variant_name)?;
+        variants.push(ExtractedVariant {
+            rust_name: variant.ident.unraw().to_string(),
+            csharp_name: variant_name,
+            discriminant: value.clone().unwrap_or_else(|| current_value.to_string()),
+        });
+        match value {
+            Some(v) => {
+                let literal = variant
+                    .discriminant
+                    .as_ref()
+                    .and_then(|d| format_discriminant(&d.1, builder));
+                write!(str, " = {}", literal.unwrap_or(v))?
+            }
+            // Once a variant is skipped, the implicit values of the variants after it would
+            // shift, so these are written explicitly.
+            None if has_skipped => write!(str, " = {}", current_value)?,
+            None => {}
+        }
+
+        write!(str, ",")?;
+        writeln!(str)?;
+    }
+    *indents -= 1;
+    write_line(str, "}".to_string(), *indents)?;
+    Ok(variants)
+}
+
+fn record_extracted_enum(
+    en: &ItemEnum,
+    enum_name: &str,
+    size: &TypeNameContainer,
+    variants: Vec<ExtractedVariant>,
+    builder: &mut CSharpBuilder<'_>,
+) -> Result<(), Error> {
+    if let Some(extraction) = &mut builder.extraction {
+        extraction.enums.push(ExtractedEnum {
+            rust_name: en.ident.unraw().to_string(),
+            csharp_name: enum_name.to_string(),
+            underlying_type: size.extracted()?,
+            variants,
+        });
+    }
+    Ok(())
+}
+
+/// Writes an enum with fields as a tagged union. For repr(C, u*) enums, the layout is a struct of
+/// the tag followed by a union of the fields of each variant. For repr(u*) enums, the layout is a
+/// union of a struct for each variant, which starts with the tag.
+fn write_tagged_union(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    en: &ItemEnum,
+    size: &TypeNameContainer,
+    is_repr_c: bool,
+    builder: &mut CSharpBuilder<'_>,
+) -> Result<(), Error> {
+    if !en.generics.params.is_empty() {
+        return Err(Error::UnsupportedError(
+            "Generic enums with fields are not supported.".to_string(),
+            en.generics.span(),
+        ));
+    }
+
+    let enum_name = builder.claim_member_name(
+        &convert_symbol_naming(&en.ident.unraw().to_string(), NameKind::Type, builder),
+        en.ident.span(),
+    )?;
+    let outer_docs = extract_outer_docs(&en.attrs)?;
+    write_summary_from_outer_docs(str, outer_docs, indents, builder)?;
+    write_obsolete_attribute(str, &en.attrs, indents)?;
+    let layout = if is_repr_c { "Sequential" } else { "Explicit" };
+    write_line(
+        str,
+        format!("[StructLayout(LayoutKind.{})]", layout),
+        *indents,
+    )?;
+    write_line(
+        str,
+        format!("{} struct {}", types_access(builder), enum_name),
+        *indents,
+    )?;
+    write_line(str, "{".to_string(), *indents)?;
+    *indents += 1;
+
+    write_line(
+        str,
+        format!("public enum Tag : {}", size.csharp_name),
+        *indents,
+    )?;
+    let tags = write_enum_variants(str, indents, en, size, builder)?;
+    writeln!(str)?;
+
+    let mut variants = Vec::new();
+    for variant in &en.variants {
+        if variant.fields.is_empty() || is_skipped(&variant.attrs, builder) {
+            continue;
+        }
+        write_line(
+            str,
+            format!(
+                "[StructLayout(LayoutKind.Sequential, CharSet = CharSet.{})]",
+                builder.configuration.struct_charset().csharp_name()
+            ),
+            *indents,
+        )?;
+        let variant_name = convert_variant_naming(en, &variant.ident.unraw().to_string(), builder);
+        write_line(str, format!("public struct {}Data", variant_name), *indents)?;
+        write_line(str, "{".to_string(), *indents)?;
+        *indents += 1;
+        if !is_repr_c {
+            write_line(str, "public Tag Kind;".to_string(), *indents)?;
+        }
+        for (index, field) in variant.fields.iter().enumerate() {
+            if is_zero_sized_marker(&field.ty) {
+                continue;
+            }
+            let t = convert_type_name(&field.ty, builder)?;
+            // The variants overlap, so the same restrictions as for union fields apply.
+            ensure_blittable(&t, &field.ty, builder)?;
+            let field_name = match &field.ident {
+                Some(field_identifier) => {
+                    convert_symbol_naming(&field_identifier.to_string(), NameKind::Field, builder)
+                }
+                None => format!(
+                    "{}{}",
+                    builder.configuration.tuple_field_prefix,
+                    tuple_field_index(&variant.fields, index)
+                ),
+            };
+            write_field_docs(
+                str,
+                &field.attrs,
+                &t.stringify_rust(),
+                None,
+                indents,
+                builder,
+            )?;
+            write_line(
+                str,
+                format!("public {} {};", t.stringify()?, field_name),
+                *indents,
+            )?;
+        }
+        *indents -= 1;
+        write_line(str, "}".to_string(), *indents)?;
+        writeln!(str)?;
+        variants.push(variant_name);
+    }
+
+    if is_repr_c {
+        write_line(
+            str,
+            "[StructLayout(LayoutKind.Explicit)]".to_string(),
+            *indents,
+        )?;
+        write_line(str, "public struct Variants".to_string(), *indents)?;
+        write_line(str, "{".to_string(), *indents)?;
+        *indents += 1;
+        for variant in &variants {
+            write_line(str, "[FieldOffset(0)]".to_string(), *indents)?;
+            write_line(
+                str,
+                format!("public {}Data {};", variant, variant),
+                *indents,
+            )?;
+        }
+        *indents -= 1;
+        write_line(str, "}".to_string(), *indents)?;
+        writeln!(str)?;
+        write_line(str, "public Tag Kind;".to_string(), *indents)?;
+        write_line(str, "public Variants Data;".to_string(), *indents)?;
+    } else {
+        write_line(str, "[FieldOffset(0)]".to_string(), *indents)?;
+        write_line(str, "public Tag Kind;".to_string(), *indents)?;
+        for variant in &variants {
+            write_line(str, "[FieldOffset(0)]".to_string(), *indents)?;
+            write_line(
+                str,
+                format!("public {}Data {};", variant, variant),
+                *indents,
+            )?;
+        }
+    }
+
+    *indents -= 1;
+    write_line(str, "}".to_string(), *indents)?;
+    writeln!(str)?;
+
+    builder.add_known_type(&en.ident.unraw().to_string(), &enum_name, en.ident.span())?;
+    record_extracted_enum(en, &enum_name, size, tags, builder)
+}
+
+/// Converts the primitive of an enum repr into the underlying type of the C# enum. C# enums can't
+/// use native integers, so sized integers are used for these instead.
+fn convert_enum_size(
+    primitive: &Path,
+    builder: &CSharpBuilder,
+) -> Result<TypeNameContainer, Error> {
+    if let Some(pointer_sized) = get_pointer_sized_repr(primitive) {
+        let signed = pointer_sized == "isize";
+        let (csharp_name, width) = match builder.configuration.pointer_width {
+            Some(PointerWidth::W32) if signed => ("int", 32),
+            Some(PointerWidth::W32) => ("uint", 32),
+            // Without a configured pointer width, 64 bit is assumed.
+            Some(PointerWidth::W64) | None if signed => ("long", 64),
+            Some(PointerWidth::W64) | None => ("ulong", 64),
+            Some(PointerWidth::Native) => {
+                return Err(Error::UnsupportedError(
+                    format!(
+                        "C# enums can not use native sized integers, so repr({}) is not supported. \
+                         Use set_pointer_width to use a fixed width, or use a fixed size repr such as repr({}64)",
+                        pointer_sized,
+                        if signed { "i" } else { "u" }
+                    ),
+                    primitive.span(),
+                ))
+            }
+        };
+        return Ok(TypeNameContainer::new(
+            csharp_name.to_string(),
+            format!("{} ({} bit)", pointer_sized, width),
+        ));
+    }
+    let size = convert_type_path(primitive, builder)?;
+    match size.csharp_name.as_str() {
+        "byte" | "sbyte" | "short" | "ushort" | "int" | "uint" | "long" | "ulong" => {}
+        _ => {
+            return Err(Error::UnsupportedError(
+                format!(
+                    "{} can not be used as the underlying type of a C# enum",
+                    size.rust_name
+                ),
+                primitive.span(),
+            ))
+        }
+    }
+    Ok(size)
+}
+
+/// Gets ``usize`` or ``isize`` if they are used as the repr of an enum.
+fn get_pointer_sized_repr(primitive: &Path) -> Option<&'static str> {
+    if primitive.is_ident("usize") {
+        Some("usize")
+    } else if primitive.is_ident("isize") {
+        Some("isize")
+    } else {
+        None
+    }
+}
+
+/// The remark of an enum with a pointer sized repr, which explains the width it is written with.
+fn pointer_sized_repr_remark(primitive: &Path, size: &TypeNameContainer) -> Option<String> {
+    let pointer_sized = get_pointer_sized_repr(primitive)?;
+    let width = if size.csharp_name.ends_with("int") {
+        32
+    } else {
+        64
+    };
+    Some(format!(
+        "The Rust representation <c>repr({})</c> is pointer sized, which C# enums can not use. It is \
+         written as <c>{}</c>, which assumes a {} bit process.",
+        pointer_sized, size.csharp_name, width
+    ))
+}
+
+/// Whether an enum should be generated as a bit flags enum. Enums marked in the configuration use
+/// that, otherwise it is detected from their values if enabled.
+fn is_flags_enum(
+    en: &ItemEnum,
+    size: &TypeNameContainer,
+    builder: &CSharpBuilder,
+) -> Result<bool, Error> {
+    if let Some(flags) = builder
+        .configuration
+        .flags_enums
+        .get(&en.ident.unraw().to_string())
+    {
+        return Ok(*flags);
+    }
+    if !builder.configuration.detect_flags_enums {
+        return Ok(false);
+    }
+    let mut flags = HashSet::new();
+    for variant in &en.variants {
+        let value = match &variant.discriminant {
+            Some(v) => convert_discriminant(v.1.borrow(), &size.csharp_name)?,
+            None => return Ok(false),
+        };
+        match value.parse::<u64>() {
+            Ok(0) => {}
+            Ok(v) if v.is_power_of_two() && flags.insert(v) => {}
+            _ => return Ok(false),
+        }
+    }
+    // Require at least three flags, so enums such as `One = 1, Two = 2` aren't detected.
+    Ok(flags.len() >= 3)
+}
+
+/// Converts the discriminant of an enum variant into C# the way it was written, like
+/// [`convert_int_literal`]. Returns None for discriminants that aren't (negated) integer literals.
+fn format_discriminant(expr: &Expr, builder: &CSharpBuilder) -> Option<String> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: syn::Lit::Int(i),
+            ..
+        }) => Some(convert_int_literal(i, builder)),
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) => Some("-".to_string() + &format_discriminant(expr, builder)?),
+        Expr::Paren(paren) => format_discriminant(&paren.expr, builder),
+        _ => None,
+    }
+}
+
+/// Converts the discriminant of an enum variant into a C# value. Only (negated) integer literals
+/// are supported, as other expressions would need to be evaluated.
+fn convert_discriminant(expr: &Expr, size: &str) -> Result<String, Error> {
+    match expr {
+        Expr::Lit(l) => {
+            if let syn::Lit::Int(i) = &l.lit {
+                return Ok(i.base10_digits().to_string());
+            }
+        }
+        Expr::Unary(unary) => {
+            if let UnOp::Neg(_) = unary.op {
+                if matches!(size, "byte" | "ushort" | "uint" | "ulong") {
+                    return Err(Error::UnsupportedError(
+                        format!("Negative values are not valid for an enum of type {}", size),
+                        expr.span(),
+                    ));
+                }
+                return Ok("-".to_string() + &convert_discriminant(unary.expr.borrow(), size)?);
+            }
+        }
+        Expr::Paren(paren) => return convert_discriminant(paren.expr.borrow(), size),
+        // Flags are commonly written as shifts, and combinations of flags as bitwise ors, which
+        // are evaluated so the values can still be checked.
+        Expr::Binary(binary) => {
+            let left = convert_discriminant(binary.left.borrow(), size)?.parse::<i128>();
+            let right = convert_discriminant(binary.right.borrow(), size)?.parse::<i128>();
+            if let (Ok(left), Ok(right)) = (left, right) {
+                let value = match binary.op {
+                    BinOp::Shl(_) => u32::try_from(right)
+                        .ok()
+                        .and_then(|right| left.checked_shl(right)),
+                    BinOp::BitOr(_) => Some(left | right),
+                    _ => None,
+                };
+                if let Some(value) = value {
+                    return Ok(value.to_string());
+                }
+            }
+        }
+        _ => {}
+    }
+    Err(Error::UnsupportedError(
+        "Only integer literals, and shifts and bitwise ors of these, are supported as enum values"
+            .to_string(),
+        expr.span(),
+    ))
+}
+
+fn write_struct(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    strct: &ItemStruct,
+    builder: &mut CSharpBuilder<'_>,
+) -> Result<(), Error> {
+    let repr = get_repr_options(&strct.attrs, &strct.ident.unraw().to_string(), builder)?;
+    if repr.transparent && !builder.configuration.emit_transparent_wrappers {
+        return register_transparent_struct(strct, builder);
+    }
+    if repr.c.is_none() && !repr.transparent {
+        return Ok(());
+    }
+    if repr.align.is_some() {
+        builder.add_warning(
+            strct.ident.unraw().to_string().as_str(),
+            Error::UnsupportedError(
+                "C# has no equivalent of repr(align), the struct might be aligned differently."
+                    .to_string(),
+                strct.span(),
+            ),
+        );
+    }
+
+    let struct_name = builder.claim_member_name(
+        &convert_symbol_naming(&strct.ident.unraw().to_string(), NameKind::Type, builder),
+        strct.ident.span(),
+    )?;
+    // The delegates of function pointer fields are written before the struct, mapped from the
+    // index of their field.
+    let mut delegates = HashMap::new();
+    for (index, field) in strct.fields.iter().enumerate() {
+        if is_function_pointer(&field.ty) {
+            let prefix = struct_name.clone() + &convert_naming(&field_name(field, index), false);
+            let delegate = convert_signature_type_name(str, indents, &field.ty, builder, &prefix)
+                .map(|mut t| {
+                    // Fields of a default struct are null, even if the function pointer isn't
+                    // optional.
+                    if builder.configuration.uses_nullable_directive()
+                        && !t.csharp_name.ends_with('?')
+                    {
+                        t.csharp_name.push('?');
+                    }
+                    t.with_marshal_as(Some("UnmanagedType.FunctionPtr".to_string()))
+                });
+            delegates.insert(index, delegate);
+        }
+    }
+    let outer_docs = extract_outer_docs(&strct.attrs)?;
+    write_summary_from_outer_docs(str, outer_docs, indents, builder)?;
+
+    let configuration = builder.configuration;
+    // Skipped fields and fixed buffers can't be written as parameters of a record, so such structs
+    // are written as readonly structs instead.
+    let record = configuration.struct_style == StructStyle::RecordStruct
+        && configuration.supports_version(10, 0)
+        && strct.fields.iter().enumerate().all(|(index, field)| {
+            let fixed_buffer =
+                configuration.use_fixed_buffers && matches!(field.ty, Type::Array(_));
+            !is_hidden_field(strct, field, index, builder) && !fixed_buffer
+        });
+    let readonly =
+        configuration.struct_style != StructStyle::Classic && configuration.supports_version(7, 2);
+
+    // The documentation of the parameters of a record goes before the attributes, so these are
+    // written once the fields are converted.
+    let mut attributes = String::new();
+    let attributes_writer = &mut CodeWriter::new_buffer(&mut attributes, configuration);
+    write_obsolete_attribute(attributes_writer, &strct.attrs, indents)?;
+    let charset = configuration.struct_charset().csharp_name();
+    match repr.packed {
+        None => write_line(
+            attributes_writer,
+            format!(
+                "[StructLayout(LayoutKind.Sequential, CharSet = CharSet.{})]",
+                charset
+            ),
+            *indents,
+        )?,
+        Some(pack) => write_line(
+            attributes_writer,
+            format!(
+                "[StructLayout(LayoutKind.Sequential, CharSet = CharSet.{}, Pack = {})]",
+                charset, pack
+            ),
+            *indents,
+        )?,
+    }
+
+    // The generic type parameters, in declaration order.
+    let mut generics: Vec<String> = Vec::new();
+    for param in &strct.generics.params {
+        match param {
+            GenericParam::Type(type_param) => {
+                generics.push(type_param.ident.unraw().to_string());
+            }
+            GenericParam::Lifetime(_) => {}
+            GenericParam::Const(_) => {}
+        }
+    }
+
+    let full_name = match generics.is_empty() {
+        true => struct_name.clone(),
+        false => format!("{}<{}>", struct_name, generics.join(", ")),
+    };
+    let mut constraints = Vec::new();
+    if configuration.emit_unmanaged_constraints && configuration.supports_version(7, 3) {
+        for generic in &generics {
+            constraints.push(format!("where {} : unmanaged", generic));
+        }
+    }
+    // Records already implement equality.
+    let equality = configuration.generate_struct_equality && !record;
+
+    if !record {
+        str.write_str(&attributes)?;
+        str.write_indent(*indents)?;
+        write!(
+            str,
+            "{} {}struct {}",
+            types_access(builder),
+            if readonly { "readonly " } else { "" },
+            full_name
+        )?;
+        if equality {
+            write!(str, " : IEquatable<{}>", full_name)?;
+        }
+        writeln!(str)?;
+        for constraint in &constraints {
+            write_line(str, constraint.to_string(), *indents + 1)?;
+        }
+        write_line(str, "{".to_string(), *indents)?;
+    }
+
+    *indents += 1;
+    let mut converted_fields: Vec<ConstructorParameter> = Vec::new();
+    // The hidden fields, which the constructor initializes to their default.
+    let mut hidden_fields: Vec<String> = Vec::new();
+    let mut equality_fields: Vec<EqualityField> = Vec::new();
+    // The fields shown by the generated ToString, with their C# type.
+    let mut displayed_fields: Vec<(String, String)> = Vec::new();
+    let mut record_parameters: Vec<RecordParameter> = Vec::new();
+    let mut extracted_fields = Vec::new();
+
+    // The struct is registered before its fields are converted, so these can point to it.
+    builder.add_known_type(
+        &strct.ident.unraw().to_string(),
+        &struct_name,
+        strct.ident.span(),
+    )?;
+    for (index, field) in strct.fields.iter().enumerate() {
+        if is_zero_sized_marker(&field.ty) {
+            continue;
+        }
+        let generic_t = match &field.ty {
+            Type::Path(p) => match p.path.get_ident() {
+                Some(ident) if generics.contains(&ident.unraw().to_string()) => Some(
+                    TypeNameContainer::new(ident.unraw().to_string(), ident.unraw().to_string()),
+                ),
+                _ => None,
+            },
+            t => convert_generic_pointer(t, &generics, builder),
+        };
+
+        let split = get_split_128_bit_field(&field.ty, builder);
+        let converted = match (generic_t, &field.ty) {
+            (Some(t), _) => Ok(t),
+            // The low half is converted here, the high half is written after it.
+            (None, _) if split.is_some() => Ok(TypeNameContainer::new(
+                "ulong".to_string(),
+                format!(
+                    "{}, low 64 bits",
+                    if split == Some(true) { "i128" } else { "u128" }
+                ),
+            )),
+            (None, Type::Array(array)) => convert_array_type(array, builder),
+            (None, _) => delegates
+                .remove(&index)
+                .unwrap_or_else(|| convert_type_name(&field.ty, builder)),
+        };
+        let t = match converted {
+            Ok(t) => t,
+            Err(e) => {
+                // Keep converting the other fields, to find any further errors.
+                builder.record_error(with_type_context(e, || {
+                    format!(
+                        "field '{}' of struct '{}'",
+                        field_name(field, index),
+                        strct.ident
+                    )
+                }))?;
+                continue;
+            }
+        };
+        // Skipped fields are still needed for the layout of the struct, so they are made private
+        // instead, and left out of the constructor.
+        let hidden = is_hidden_field(strct, field, index, builder);
+        let access = if hidden { "private" } else { "public" };
+
+        let csharp_field_name = match &field.ident {
+            Some(field_identifier) => {
+                convert_symbol_naming(&field_identifier.to_string(), NameKind::Field, builder)
+            }
+            // Fields of tuple structs have no name, so we name them by their index.
+            None => format!(
+                "{}{}",
+                builder.configuration.tuple_field_prefix,
+                tuple_field_index(&strct.fields, index)
+            ),
+        };
+        let mut parts = vec![t];
+        if let Some(signed) = split {
+            let (csharp_name, rust_name) = match signed {
+                true => ("long", "i128, high 64 bits"),
+                false => ("ulong", "u128, high 64 bits"),
+            };
+            parts.push(TypeNameContainer::new(
+                csharp_name.to_string(),
+                rust_name.to_string(),
+            ));
+        }
+        for (part_index, t) in parts.into_iter().enumerate() {
+            let csharp_field_name = match (split, part_index) {
+                (None, _) => csharp_field_name.clone(),
+                (Some(_), 0) => format!("{}Low", csharp_field_name),
+                (Some(_), _) => format!("{}High", csharp_field_name),
+            };
+            // Arrays of primitives can be written as fixed buffers if the user allows unsafe
+            // code. These can't be assigned, so they are left out of the constructor.
+            let fixed_buffer = match &field.ty {
+                Type::Array(array) if builder.configuration.use_fixed_buffers => {
+                    let element = convert_type_name(array.elem.borrow(), builder)?;
+                    match is_fixed_buffer_type(&element) {
+                        true => Some((element, get_array_length(array)?)),
+                        false => None,
+                    }
+                }
+                _ => None,
+            };
+            extracted_fields.push(ExtractedField {
+                rust_name: match &field.ident {
+                    Some(field_identifier) => field_identifier.to_string(),
+                    None => index.to_string(),
+                },
+                csharp_name: csharp_field_name.clone(),
+                field_type: t.extracted()?,
+                is_property: record
+                    || (fixed_buffer.is_none() && builder.configuration.uses_init_accessors()),
+                // The high half of a split integer follows the low half.
+                offset_in_field: part_index * 8,
+            });
+            // Fixed buffers are blittable, unlike the arrays they replace.
+            let non_blittable = match fixed_buffer {
+                Some(_) => None,
+                None => non_blittable_reason(&t, builder)?,
+            };
+            if let Some(reason) = &non_blittable {
+                builder.blittability_warnings.push(BlittabilityWarning {
+                    struct_name: strct.ident.unraw().to_string(),
+                    field_name: field_name(field, index),
+                    reason: reason.clone(),
+                });
+            }
+            if record {
+                let doc_comment = DocComment::parse(extract_outer_docs(&field.attrs)?, false);
+                record_parameters.push(RecordParameter {
+                    declaration: format!(
+                        "{}{} {}",
+                        t.marshal_attribute("field: "),
+                        t.stringify()?,
+                        csharp_field_name
+                    ),
+                    description: describe_rust_type(
+                        Some(&doc_comment.summary.join(" ")),
+                        &t.stringify_rust(),
+                        builder,
+                    ),
+                    name: csharp_field_name,
+                });
+                continue;
+            }
+            if !hidden {
+                let warning = non_blittable.filter(|_| builder.configuration.warn_non_blittable);
+                write_field_docs(
+                    str,
+                    &field.attrs,
+                    &t.stringify_rust(),
+                    warning.as_deref(),
+                    indents,
+                    builder,
+                )?;
+            }
+            if let Some((element, length)) = fixed_buffer {
+                write_line(
+                    str,
+                    format!(
+                        "{} unsafe fixed {} {}[{}];",
+                        access, element.csharp_name, csharp_field_name, length
+                    ),
+                    *indents,
+                )?;
+                continue;
+            }
+            // If C# version is 9 or newer, we make all fields { get; init; }, so they can be
+            // initialised, but are readonly afterwards. Otherwise we just make them readonly.
+            if builder.configuration.uses_init_accessors() {
+                let marshal_attribute = t.marshal_attribute("field: ");
+                if !marshal_attribute.is_empty() {
+                    write_line(str, marshal_attribute.trim_end().to_string(), *indents)?;
+                }
+                write_line(
+                    str,
+                    format!(
+                        "{} {} {} {{ get; init; }}",
+                        access,
+                        t.stringify()?,
+                        csharp_field_name
+                    ),
+                    *indents,
+                )?;
+            } else {
+                let marshal_attribute = t.marshal_attribute("");
+                if !marshal_attribute.is_empty() {
+                    write_line(str, marshal_attribute.trim_end().to_string(), *indents)?;
+                }
+                write_line(
+                    str,
+                    format!(
+                        "{} readonly {} {};",
+                        access,
+                        t.stringify()?,
+                        csharp_field_name
+                    ),
+                    *indents,
+                )?;
+            }
+            let csharp_type = t.stringify()?;
+            equality_fields.push(EqualityField {
+                comparison: match csharp_type.as_str() {
+                    generic if generics.contains(&csharp_type) => {
+                        FieldComparison::Generic(generic.to_string())
+                    }
+                    pointer if pointer.ends_with('*') => FieldComparison::Pointer,
+                    "IntPtr" | "UIntPtr" => FieldComparison::Operator,
+                    _ if t.marshal_as.as_deref() == Some("UnmanagedType.FunctionPtr") => {
+                        FieldComparison::Operator
+                    }
+                    _ => FieldComparison::Equals,
+                },
+                name: csharp_field_name.clone(),
+            });
+            if hidden {
+                hidden_fields.push(csharp_field_name);
+            } else {
+                displayed_fields.push((csharp_type.clone(), csharp_field_name.clone()));
+                let doc_comment = DocComment::parse(extract_outer_docs(&field.attrs)?, false);
+                converted_fields.push(ConstructorParameter {
+                    csharp_type,
+                    field_name: csharp_field_name,
+                    summary: doc_comment
+                        .summary
+                        .first()
+                        .map(|line| line.trim())
+                        .filter(|line| !line.is_empty())
+                        .map(|line| format_doc_inline(line, builder)),
+                    rust_type: t.stringify_rust(),
+                });
+            }
+        }
+    }
+
+    let write_constructor = match builder.configuration.struct_constructor {
+        _ if record => false,
+        ConstructorMode::Always => true,
+        ConstructorMode::Never => false,
+        ConstructorMode::NonEmptyOnly => !converted_fields.is_empty(),
+    };
+    if write_constructor {
+        write_struct_constructor(
+            str,
+            indents,
+            &struct_name,
+            &converted_fields,
+            &hidden_fields,
+            builder,
+        )?;
+    }
+    let has_members =
+        write_constructor || strct.fields.iter().any(|f| !is_zero_sized_marker(&f.ty));
+    if equality {
+        write_struct_equality(
+            str,
+            indents,
+            &full_name,
+            &equality_fields,
+            has_members,
+            builder,
+        )?;
+    }
+    // Records already override ToString.
+    if builder.configuration.generate_to_string && !record {
+        if has_members || equality {
+            writeln!(str)?;
+        }
+        write_struct_to_string(str, *indents, &struct_name, &displayed_fields)?;
+    }
+
+    *indents -= 1;
+    if record {
+        write_record_declaration(
+            str,
+            indents,
+            &full_name,
+            &record_parameters,
+            &attributes,
+            &constraints,
+            builder,
+        )?;
+    } else {
+        write_line(str, "}".to_string(), *indents)?;
+    }
+    writeln!(str)?;
+
+    let rust_name = strct.ident.unraw().to_string();
+    if builder
+        .blittability_warnings
+        .iter()
+        .any(|warning| warning.struct_name == rust_name)
+    {
+        builder
+            .non_blittable_structs
+            .insert(rust_name, struct_name.clone());
+    }
+    if let Some(extraction) = &mut builder.extraction {
+        extraction.structs.push(ExtractedStruct {
+            rust_name: strct.ident.unraw().to_string(),
+            csharp_name: struct_name,
+            generics,
+            fields: extracted_fields,
+        });
+    }
+    Ok(())
+}
+
+/// A field of a struct that is written as a parameter of a positional record.
+struct RecordParameter {
+    name: String,
+    /// The parameter as it is declared, with its type and marshalling attribute.
+    declaration: String,
+    description: Option<String>,
+}
+
+/// Writes a struct as a ``readonly record struct`` with a parameter for each field. The fields
+/// are documented as parameters of the record.
+fn write_record_declaration(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    full_name: &str,
+    parameters: &[RecordParameter],
+    attributes: &str,
+    constraints: &[String],
+    builder: &CSharpBuilder,
+) -> Result<(), Error> {
+    for parameter in parameters {
+        if let Some(description) = &parameter.description {
+            write_line(
+                str,
+                format!(
+                    "/// <param name=\"{}\">{}</param>",
+                    parameter.name, description
+                ),
+                *indents,
+            )?;
+        }
+    }
+    str.write_str(attributes)?;
+    str.write_indent(*indents)?;
+    write!(
+        str,
+        "{} readonly record struct {}",
+        types_access(builder),
+        full_name
+    )?;
+    if !parameters.is_empty() {
+        let declarations = parameters
+            .iter()
+            .map(|p| p.declaration.as_str())
+            .collect::<Vec<_>>();
+        write!(str, "({})", declarations.join(", "))?;
+    }
+    match constraints.split_last() {
+        None => writeln!(str, ";")?,
+        Some((last, rest)) => {
+            writeln!(str)?;
+            for constraint in rest {
+                write_line(str, constraint.to_string(), *indents + 1)?;
+            }
+            write_line(str, format!("{};", last), *indents + 1)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns why a field of a struct with the given type isn't blittable, if it isn't.
+fn non_blittable_reason(
+    t: &TypeNameContainer,
+    builder: &CSharpBuilder,
+) -> Result<Option<String>, Error> {
+    let reason = match t.stringify()?.as_str() {
+        "bool" => "bool fields are converted when marshalled".to_string(),
+        "char" => "char fields are converted according to the CharSet".to_string(),
+        "string" => "strings are copied when marshalled".to_string(),
+        "System.Numerics.BigInteger" => "BigInteger is a managed type".to_string(),
+        _ if t.marshal_as.as_deref() == Some("UnmanagedType.FunctionPtr") => {
+            "delegates are converted into function pointers".to_string()
+        }
+        array if array.ends_with("[]") => "arrays are copied when marshalled".to_string(),
+        _ => match builder.non_blittable_structs.get(&t.stringify_rust()) {
+            Some(csharp_name) => format!("{} contains non-blittable fields", csharp_name),
+            None => return Ok(None),
+        },
+    };
+    Ok(Some(reason))
+}
+
+/// Converts a pointer or reference to a generic type parameter of a struct, which isn't a known
+/// type. These are written as ``IntPtr``, or as a pointer to the type parameter if unsafe pointers
+/// are used and the type parameter is constrained to unmanaged types.
+fn convert_generic_pointer(
+    t: &Type,
+    generics: &[String],
+    builder: &CSharpBuilder,
+) -> Option<TypeNameContainer> {
+    let (elem, rust_prefix) = match ungroup_type(t) {
+        Type::Ptr(ptr) => (
+            ptr.elem.borrow(),
+            if ptr.mutability.is_some() {
+                "*mut "
+            } else {
+                "*const "
+            },
+        ),
+        Type::Reference(r) => (
+            r.elem.borrow(),
+            if r.mutability.is_some() { "&mut " } else { "&" },
+        ),
+        _ => return None,
+    };
+    let underlying = match ungroup_type(elem) {
+        Type::Path(p) => match p.path.get_ident() {
+            Some(ident) if generics.contains(&ident.unraw().to_string()) => {
+                TypeNameContainer::new(ident.unraw().to_string(), ident.unraw().to_string())
+            }
+            _ => return None,
+        },
+        elem => convert_generic_pointer(elem, generics, builder)?,
+    };
+    let rust_name = rust_prefix.to_string() + underlying.stringify_rust().as_str();
+    let configuration = builder.configuration;
+    if configuration.use_unsafe_pointers
+        && configuration.emit_unmanaged_constraints
+        && configuration.supports_version(7, 3)
+    {
+        Some(TypeNameContainer::new(
+            underlying.stringify().ok()? + "*",
+            rust_name,
+        ))
+    } else {
+        Some(TypeNameContainer::new("IntPtr".to_string(), rust_name))
+    }
+}
+
+/// Writes an override of ``ToString`` that returns the name of a struct with its fields, like
+/// ``Foo { FieldA = 1, FieldB = 2 }``. Pointers are written in hexadecimal.
+fn write_struct_to_string(
+    str: &mut CodeWriter,
+    indents: i32,
+    struct_name: &str,
+    fields: &[(String, String)],
+) -> Result<(), Error> {
+    write_line(
+        str,
+        "public override string ToString()".to_string(),
+        indents,
+    )?;
+    write_line(str, "{".to_string(), indents)?;
+    let values = fields
+        .iter()
+        .map(|(csharp_type, name)| match csharp_type.as_str() {
+            pointer if pointer.ends_with('*') => format!("{} = 0x{{(ulong){}:X}}", name, name),
+            "IntPtr" => format!("{} = 0x{{(long){}:X}}", name, name),
+            "UIntPtr" => format!("{} = 0x{{(ulong){}:X}}", name, name),
+            _ => format!("{0} = {{{0}}}", name),
+        })
+        .collect::<Vec<_>>();
+    let line = match values.is_empty() {
+        true => format!("return \"{} {{ }}\";", struct_name),
+        false => format!(
+            "return $\"{} {{{{ {} }}}}\";",
+            struct_name,
+            values.join(", ")
+        ),
+    };
+    write_line(str, line, indents + 1)?;
+    write_line(str, "}".to_string(), indents)?;
+    Ok(())
+}
+
+/// How a field is compared by the generated equality of a struct.
+enum FieldComparison {
+    /// Compared with its ``Equals`` method, which doesn't box for primitives and structs.
+    Equals,
+    /// Compared with ``==``, for types without an ``Equals(T)`` method on older runtimes, and for
+    /// delegates, which can be null.
+    Operator,
+    /// A pointer, which is compared with ``==``, and hashed as an ``IntPtr``.
+    Pointer,
+    /// A generic type parameter, which is compared with the default equality comparer.
+    Generic(String),
+}
+
+/// A field that is compared by the generated equality of a struct.
+struct EqualityField {
+    name: String,
+    comparison: FieldComparison,
+}
+
+/// Writes the members that implement ``IEquatable<T>`` for a struct, and the equality operators.
+fn write_struct_equality(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    full_name: &str,
+    fields: &[EqualityField],
+    has_members: bool,
+    builder: &CSharpBuilder,
+) -> Result<(), Error> {
+    if has_members {
+        writeln!(str)?;
+    }
+    write_line(
+        str,
+        format!("public bool Equals({} other)", full_name),
+        *indents,
+    )?;
+    write_line(str, "{".to_string(), *indents)?;
+    let comparisons = fields
+        .iter()
+        .map(|field| match field.comparison {
+            FieldComparison::Equals => format!("{0}.Equals(other.{0})", field.name),
+            FieldComparison::Operator | FieldComparison::Pointer => {
+                format!("{0} == other.{0}", field.name)
+            }
+            FieldComparison::Generic(ref generic) => format!(
+                "System.Collections.Generic.EqualityComparer<{}>.Default.Equals({1}, other.{1})",
+                generic, field.name
+            ),
+        })
+        .collect::<Vec<_>>();
+    match comparisons.split_first() {
+        None => write_line(str, "return true;".to_string(), *indents + 1)?,
+        Some((first, rest)) => {
+            let mut line = format!("return {}", first);
+            let mut line_indents = *indents + 1;
+            for comparison in rest {
+                write_line(str, line, line_indents)?;
+                // The other comparisons are continuations of the return statement.
+                line = format!("&& {}", comparison);
+                line_indents = *indents + 2;
+            }
+            write_line(str, line + ";", line_indents)?;
+        }
+    }
+    write_line(str, "}".to_string(), *indents)?;
+    writeln!(str)?;
+
+    write_line(
+        str,
+        match builder.configuration.uses_nullable_annotations() {
+            true => "public override bool Equals(object? obj)".to_string(),
+            false => "public override bool Equals(object obj)".to_string(),
+        },
+        *indents,
+    )?;
+    write_line(str, "{".to_string(), *indents)?;
+    match builder.configuration.supports_version(7, 0) {
+        true => write_line(
+            str,
+            format!("return obj is {} other && Equals(other);", full_name),
+            *indents + 1,
+        )?,
+        false => write_line(
+            str,
+            format!("return obj is {0} && Equals(({0})obj);", full_name),
+            *indents + 1,
+        )?,
+    }
+    write_line(str, "}".to_string(), *indents)?;
+    writeln!(str)?;
+
+    write_line(
+        str,
+        "public override int GetHashCode()".to_string(),
+        *indents,
+    )?;
+    write_line(str, "{".to_string(), *indents)?;
+    let hashed = fields
+        .iter()
+        .map(|field| match field.comparison {
+            // Pointers can't be used as generic arguments.
+            FieldComparison::Pointer => format!("(IntPtr){}", field.name),
+            _ => field.name.clone(),
+        })
+        .collect::<Vec<_>>();
+    write_line(
+        str,
+        format!("return {};", combine_hash_codes(hashed)),
+        *indents + 1,
+    )?;
+    write_line(str, "}".to_string(), *indents)?;
+    writeln!(str)?;
+
+    for (operator, negation) in [("==", ""), ("!=", "!")] {
+        write_line(
+            str,
+            format!(
+                "public static bool operator {}({1} left, {1} right)",
+                operator, full_name
+            ),
+            *indents,
+        )?;
+        write_line(str, "{".to_string(), *indents)?;
+        write_line(
+            str,
+            format!("return {}left.Equals(right);", negation),
+            *indents + 1,
+        )?;
+        write_line(str, "}".to_string(), *indents)?;
+        if operator == "==" {
+            writeln!(str)?;
+        }
+    }
+    Ok(())
+}
+
+/// Combines hash codes with ``HashCode.Combine``, which takes up to 8 values. More values are
+/// combined in chunks, whose hash codes are then combined.
+fn combine_hash_codes(values: Vec<String>) -> String {
+    match values.len() {
+        0 => "0".to_string(),
+        1..=8 => format!("HashCode.Combine({})", values.join(", ")),
+        _ => combine_hash_codes(
+            values
+                .chunks(8)
+                .map(|chunk| combine_hash_codes(chunk.to_vec()))
+                .collect(),
+        ),
+    }
+}
+
+/// A parameter of the constructor of a struct, which assigns one of its fields.
+struct ConstructorParameter {
+    csharp_type: String,
+    field_name: String,
+    /// The first line of the summary of the field, if it is documented.
+    summary: Option<String>,
+    rust_type: String,
+}
+
+impl ConstructorParameter {
+    /// The name of the parameter, which is the name of the field starting with a lowercase letter.
+    fn parameter_name(&self) -> String {
+        let mut parameter_name = self.field_name.to_string();
+        if let Some(r) = parameter_name.get_mut(0..1) {
+            r.make_ascii_lowercase();
+        }
+        escape_csharp_keyword(parameter_name)
+    }
+}
+
+/// Writes the constructor of a struct, which has a parameter for each of the given fields.
+fn write_struct_constructor(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    struct_name: &str,
+    converted_fields: &[ConstructorParameter],
+    hidden_fields: &[String],
+    builder: &CSharpBuilder,
+) -> Result<(), Error> {
+    let default_parameters = builder.configuration.default_constructor_parameters
+        && builder.configuration.supports_version(10, 0);
+    writeln!(str)?;
+
+    // The parameters are only documented if any of the fields are, otherwise they would only
+    // repeat the types of the fields.
+    if converted_fields.iter().any(|f| f.summary.is_some()) {
+        for converted_field in converted_fields {
+            let description = match &converted_field.summary {
+                Some(summary) => summary.to_string(),
+                None => escape_xml(&converted_field.rust_type),
+            };
+            write_line(
+                str,
+                format!(
+                    "/// <param name=\"{}\">{}</param>",
+                    converted_field.parameter_name().trim_start_matches('@'),
+                    description
+                ),
+                *indents,
+            )?;
+        }
+    }
+    str.write_indent(*indents)?;
+    write!(str, "public {}(", struct_name)?;
+    for (index, converted_field) in converted_fields.iter().enumerate() {
+        if index != 0 {
+            write!(str, ", ")?;
+        }
+        write!(
+            str,
+            "{} {}",
+            converted_field.csharp_type,
+            converted_field.parameter_name()
+        )?;
+        if default_parameters {
+            write!(str, " = default")?;
+        }
+    }
+    writeln!(str, ")")?;
+    write_line(str, "{".to_string(), *indents)?;
+    *indents += 1;
+
+    for converted_field in converted_fields {
+        let parameter_name = converted_field.parameter_name();
+        // A naming converter can give the parameter the same name as the field, in which case the
+        // parameter hides the field.
+        let field_name = match converted_field.field_name == parameter_name {
+            true => format!("this.{}", converted_field.field_name),
+            false => converted_field.field_name.to_string(),
+        };
+        write_line(
+            str,
+            format!("{} = {};", field_name, parameter_name),
+            *indents,
+        )?;
+    }
+    // All fields of a struct have to be assigned by its constructor.
+    for hidden_field in hidden_fields {
+        write_line(str, format!("{} = default;", hidden_field), *indents)?;
+    }
+    *indents -= 1;
+
+    write_line(str, "}".to_string(), *indents)
+}
+
+fn write_union(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    union: &ItemUnion,
+    builder: &mut CSharpBuilder<'_>,
+) -> Result<(), Error> {
+    if get_repr_options(&union.attrs, &union.ident.unraw().to_string(), builder)?
+        .c
+        .is_none()
+    {
+        return Ok(());
+    }
+    if !union.generics.params.is_empty() {
+        return Err(Error::UnsupportedError(
+            "Generic unions are not supported.".to_string(),
+            union.generics.span(),
+        ));
+    }
+
+    let union_name = builder.claim_member_name(
+        &convert_symbol_naming(&union.ident.unraw().to_string(), NameKind::Type, builder),
+        union.ident.span(),
+    )?;
+    let outer_docs = extract_outer_docs(&union.attrs)?;
+    write_summary_from_outer_docs(str, outer_docs, indents, builder)?;
+    write_obsolete_attribute(str, &union.attrs, indents)?;
+    write_line(
+        str,
+        "[StructLayout(LayoutKind.Explicit)]".to_string(),
+        *indents,
+    )?;
+    write_line(
+        str,
+        format!("{} struct {}", types_access(builder), union_name),
+        *indents,
+    )?;
+    write_line(str, "{".to_string(), *indents)?;
+    *indents += 1;
+
+    for field in &union.fields.named {
+        let t = convert_type_name(&field.ty, builder).map_err(|e| {
+            with_type_context(e, || {
+                format!(
+                    "field '{}' of union '{}'",
+                    field_name(field, 0),
+                    union.ident
+                )
+            })
+        })?;
+        ensure_blittable(&t, &field.ty, builder)?;
+        write_field_docs(
+            str,
+            &field.attrs,
+            &t.stringify_rust(),
+            None,
+            indents,
+            builder,
+        )?;
+        write_line(str, "[FieldOffset(0)]".to_string(), *indents)?;
+        if let Some(field_identifier) = &field.ident {
+            write_line(
+                str,
+                format!(
+                    "public {} {};",
+                    t.stringify()?,
+                    convert_symbol_naming(&field_identifier.to_string(), NameKind::Field, builder)
+                ),
+                *indents,
+            )?;
+        }
+    }
+
+    *indents -= 1;
+    write_line(str, "}".to_string(), *indents)?;
+    writeln!(str)?;
+
+    builder.add_known_type(
+        &union.ident.unraw().to_string(),
+        &union_name,
+        union.ident.span(),
+    )?;
+    Ok(())
+}
+
+/// Overlapping fields can only be used for types that have the same representation in managed and
+/// unmanaged memory.
+fn ensure_blittable(
+    t: &TypeNameContainer,
+    ty: &Type,
+    builder: &CSharpBuilder,
+) -> Result<(), Error> {
+    if t.marshal_as.is_some() || builder.delegates.values().any(|d| d == &t.csharp_name) {
+        return Err(Error::UnsupportedError(
+            format!(
+                "Union fields of type '{}' are not supported, as they are not blittable.",
+                t.rust_name
+            ),
+            ty.span(),
+        ));
+    }
+    Ok(())
+}
+
+/// Marker types such as PhantomData are zero sized, so they do not exist in the struct layout.
+fn is_zero_sized_marker(t: &Type) -> bool {
+    match t {
+        Type::Path(p) => matches!(
+            get_path_name(&p.path).as_deref(),
+            Some("PhantomData") | Some("PhantomPinned")
+        ),
+        _ => false,
+    }
+}
+
+/// A transparent struct has the same layout as its single field, so we can simply use the type of
+/// that field wherever the struct is used.
+fn register_transparent_struct(
+    strct: &ItemStruct,
+    builder: &mut CSharpBuilder<'_>,
+) -> Result<(), Error> {
+    // Zero sized marker fields don't change the layout, so they can sit next to the single field.
+    let fields = strct
+        .fields
+        .iter()
+        .filter(|f| !is_zero_sized_marker(&f.ty))
+        .collect::<Vec<_>>();
+    let field = match fields.as_slice() {
+        [field] => field,
+        _ => {
+            return Err(Error::UnsupportedError(
+                "Transparent structs are only supported with a single field.".to_string(),
+                strct.span(),
+            ))
+        }
+    };
+    let t = convert_type_name(&field.ty, builder)?;
+    builder.register_known_type(
+        strct.ident.unraw().to_string().as_str(),
+        CSharpType {
+            namespace: None,
+            inside_type: None,
+            real_type_name: t.stringify()?,
+            // Types like bool keep their marshalling when wrapped in a transparent struct.
+            marshal_as: t.marshal_as,
+        },
+        strct.ident.span(),
+    )?;
+    Ok(())
+}
+
+fn extract_outer_docs(attrs: &[Attribute]) -> Result<Vec<String>, Error> {
+    let mut outer_docs: Vec<String> = Vec::new();
+    for attr in attrs {
+        let parsed = attr.parse_meta()?;
+        match parsed {
+            Meta::Path(_) => {}
+            Meta::List(_) => {}
+            Meta::NameValue(nv) => match nv.path.get_ident() {
+                None => {}
+                Some(identifier) => {
+                    if let "doc" = identifier.to_string().as_str() {
+                        if let syn::Lit::Str(v) = nv.lit {
+                            outer_docs.push(v.value());
+                        }
+                    }
+                }
+            },
+        }
+    }
+    Ok(outer_docs)
+}
+
+/// Extracts the docs of a crate or module, from both its inner ``//!`` and outer ``///`` docs. Docs
+/// that aren't a string literal, such as ``#![doc = include_str!("../README.md")]``, are skipped,
+/// as macros aren't expanded.
+fn extract_module_docs(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(nv)) if nv.path.is_ident("doc") => match nv.lit {
+                syn::Lit::Str(v) => Some(v.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether an item, enum variant or field is marked to be skipped, either with ``#[doc(hidden)]`` or
+/// with the configured skip attribute.
+fn is_skipped(attrs: &[Attribute], builder: &CSharpBuilder) -> bool {
+    let skip_attribute = builder.configuration.skip_attribute.as_deref();
+    let is_skip_attribute = |path: &Path| match skip_attribute {
+        Some(skip_attribute) => path.is_ident(skip_attribute),
+        None => false,
+    };
+    attrs.iter().any(|attr| match attr.parse_meta() {
+        Ok(Meta::List(list)) if list.path.is_ident("doc") => list.nested.iter().any(
+            |nested| matches!(nested, NestedMeta::Meta(Meta::Path(p)) if p.is_ident("hidden")),
+        ),
+        // The first argument of cfg_attr is the condition, the rest are the attributes.
+        Ok(Meta::List(list)) if list.path.is_ident("cfg_attr") => list.nested.iter().skip(1).any(
+            |nested| matches!(nested, NestedMeta::Meta(Meta::Path(p)) if is_skip_attribute(p)),
+        ),
+        Ok(meta) => is_skip_attribute(meta.path()),
+        Err(_) => false,
+    })
+}
+
+/// Checks whether an item is enabled by its ``#[cfg(...)]`` attributes. Items are always enabled if
+/// cfg evaluation isn't configured. Items with a predicate that can't be evaluated are enabled as
+/// well, with a warning.
+fn is_cfg_enabled(attrs: &[Attribute], item_name: &str, builder: &mut CSharpBuilder) -> bool {
+    let options = match &builder.configuration.cfg_options {
+        Some(options) => options,
+        None => return true,
+    };
+    match evaluate_cfg_attributes(attrs, options) {
+        Ok(enabled) => enabled,
+        Err(span) => {
+            builder.add_warning(item_name, unknown_cfg_predicate(span));
+            true
+        }
+    }
+}
+
+/// Checks whether an item is disabled by its ``#[cfg(...)]`` attributes, without raising warnings.
+fn is_cfg_disabled(attrs: &[Attribute], builder: &CSharpBuilder) -> bool {
+    match &builder.configuration.cfg_options {
+        Some(options) => matches!(evaluate_cfg_attributes(attrs, options), Ok(false)),
+        None => false,
+    }
+}
+
+fn unknown_cfg_predicate(span: proc_macro2::Span) -> Error {
+    Error::UnsupportedError(
+        "The cfg predicate could not be evaluated, so the item is generated.".to_string(),
+        span,
+    )
+}
+
+/// Evaluates the ``#[cfg(...)]`` attributes of an item, which all need to hold for it to be
+/// enabled. If a predicate can't be evaluated, and none of the others disable the item, the span of
+/// the predicate is returned as error.
+fn evaluate_cfg_attributes(
+    attrs: &[Attribute],
+    options: &CfgOptions,
+) -> Result<bool, proc_macro2::Span> {
+    let mut unknown = None;
+    for attr in attrs {
+        let list = match attr.parse_meta() {
+            Ok(Meta::List(list)) if list.path.is_ident("cfg") => list,
+            _ => continue,
+        };
+        let predicate = list.nested.first().filter(|_| list.nested.len() == 1);
+        match predicate.and_then(|predicate| evaluate_cfg(predicate, options)) {
+            Some(true) => {}
+            Some(false) => return Ok(false),
+            None => unknown = unknown.or_else(|| Some(list.span())),
+        }
+    }
+    match unknown {
+        Some(span) => Err(span),
+        None => Ok(true),
+    }
+}
+
+/// Evaluates a cfg predicate, or returns None if it can't be evaluated with the options.
+fn evaluate_cfg(predicate: &NestedMeta, options: &CfgOptions) -> Option<bool> {
+    let meta = match predicate {
+        NestedMeta::Meta(meta) => meta,
+        NestedMeta::Lit(_) => return None,
+    };
+    let name = meta.path().get_ident()?.to_string();
+    match meta {
+        Meta::Path(_) => match name.as_str() {
+            "test" => Some(options.test),
+            "windows" => Some(options.target_os.as_deref()? == "windows"),
+            "unix" => Some(matches!(
+                options.target_os.as_deref()?,
+                "linux"
+                    | "macos"
+                    | "ios"
+                    | "android"
+                    | "freebsd"
+                    | "netbsd"
+                    | "openbsd"
+                    | "dragonfly"
+                    | "solaris"
+                    | "illumos"
+            )),
+            _ => None,
+        },
+        Meta::NameValue(nv) => {
+            let value = match &nv.lit {
+                syn::Lit::Str(value) => value.value(),
+                _ => return None,
+            };
+            match name.as_str() {
+                "feature" => Some(options.features.contains(&value)),
+                "target_os" => Some(options.target_os.as_ref()? == &value),
+                _ => None,
+            }
+        }
+        Meta::List(list) => {
+            let mut results = list
+                .nested
+                .iter()
+                .map(|predicate| evaluate_cfg(predicate, options));
+            // A predicate that can't be evaluated only matters if the others don't decide it.
+            let (decisive, default) = match name.as_str() {
+                "all" => (false, true),
+                "any" => (true, false),
+                "not" if list.nested.len() == 1 => return results.next()?.map(|result| !result),
+                _ => return None,
+            };
+            let mut evaluated = Some(default);
+            for result in results {
+                match result {
+                    Some(result) if result == decisive => return Some(decisive),
+                    Some(_) => {}
+                    None => evaluated = None,
+                }
+            }
+            evaluated
+        }
+    }
+}
+
+/// Gets the metas of attributes. If cfg evaluation is configured, the attributes of
+/// ``#[cfg_attr(...)]`` attributes are used in their place when the condition holds.
+fn get_active_metas(
+    attrs: &[Attribute],
+    item_name: &str,
+    builder: &mut CSharpBuilder,
+) -> Result<Vec<Meta>, Error> {
+    let mut metas = Vec::new();
+    for attr in attrs {
+        expand_cfg_attr(attr.parse_meta()?, item_name, builder, &mut metas);
+    }
+    Ok(metas)
+}
+
+fn expand_cfg_attr(
+    meta: Meta,
+    item_name: &str,
+    builder: &mut CSharpBuilder,
+    metas: &mut Vec<Meta>,
+) {
+    let options = match &builder.configuration.cfg_options {
+        Some(options) => options,
+        None => {
+            metas.push(meta);
+            return;
+        }
+    };
+    let list = match meta {
+        Meta::List(list) if list.path.is_ident("cfg_attr") => list,
+        meta => {
+            metas.push(meta);
+            return;
+        }
+    };
+    let span = list.span();
+    let mut nested = list.nested.into_iter();
+    let enabled = match nested
+        .next()
+        .and_then(|predicate| evaluate_cfg(&predicate, options))
+    {
+        Some(enabled) => enabled,
+        None => {
+            builder.add_warning(item_name, unknown_cfg_predicate(span));
+            true
+        }
+    };
+    if enabled {
+        for nested in nested {
+            if let NestedMeta::Meta(meta) = nested {
+                expand_cfg_attr(meta, item_name, builder, metas);
+            }
+        }
+    }
+}
+
+/// Gets the name of the symbol a function is exported as, which is the value of its
+/// ``#[export_name]`` attribute if it has one, and its identifier otherwise.
+fn get_symbol_name(attrs: &[Attribute], ident: &syn::Ident) -> String {
+    for attr in attrs {
+        if let Ok(Meta::NameValue(nv)) = attr.parse_meta() {
+            if nv.path.is_ident("export_name") {
+                if let syn::Lit::Str(v) = nv.lit {
+                    return v.value();
+                }
+            }
+        }
+    }
+    ident.unraw().to_string()
+}
+
+/// Whether a function is exported under a known symbol name. This is only checked when required
+/// by the configuration.
+fn is_exported(fun: &ItemFn, builder: &CSharpBuilder) -> bool {
+    if !builder.configuration.require_no_mangle {
+        return true;
+    }
+    has_export_attribute(&fun.attrs)
+}
+
+fn has_export_attribute(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .any(|attr| attr.path.is_ident("no_mangle") || attr.path.is_ident("export_name"))
+}
+
+/// Writes an ``[Obsolete]`` attribute if the attributes contain ``#[deprecated]``, with its note as
+/// message if it has one.
+fn write_obsolete_attribute(
+    str: &mut CodeWriter,
+    attrs: &[Attribute],
+    indents: &mut i32,
+) -> Result<(), Error> {
+    for attr in attrs {
+        if !attr.path.is_ident("deprecated") {
+            continue;
+        }
+        let note = match attr.parse_meta()? {
+            Meta::NameValue(nv) => match nv.lit {
+                syn::Lit::Str(v) => Some(v.value()),
+                _ => None,
+            },
+            Meta::List(list) => list.nested.iter().find_map(|nested| match nested {
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("note") => {
+                    match &nv.lit {
+                        syn::Lit::Str(v) => Some(v.value()),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            }),
+            Meta::Path(_) => None,
+        };
+        match note {
+            Some(note) => write_line(
+                str,
+                format!("[Obsolete(\"{}\")]", escape_string(&note)),
+                *indents,
+            )?,
+            None => write_line(str, "[Obsolete]".to_string(), *indents)?,
+        }
+    }
+    Ok(())
+}
+
+/// Escapes a string to be used inside a C# string literal.
+fn escape_string(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn write_summary_from_outer_docs(
+    str: &mut CodeWriter,
+    outer_docs: Vec<String>,
+    indents: &mut i32,
+    builder: &CSharpBuilder,
+) -> Result<(), Error> {
+    let doc_comment = DocComment::parse(outer_docs, false);
+    write_doc_element(str, "summary", &doc_comment.summary, indents, builder)?;
+    write_doc_element(str, "remarks", &doc_comment.remarks, indents, builder)
+}
+
+/// Writes the documentation of a field, with its Rust type where the configuration places it.
+fn write_field_docs(
+    str: &mut CodeWriter,
+    attrs: &[Attribute],
+    rust_type: &str,
+    non_blittable: Option<&str>,
+    indents: &mut i32,
+    builder: &CSharpBuilder,
+) -> Result<(), Error> {
+    let doc_comment = DocComment::parse(extract_outer_docs(attrs)?, false);
+    let mut summary = format_doc_lines(&doc_comment.summary, builder);
+    let mut remarks = format_doc_lines(&doc_comment.remarks, builder);
+    match builder.configuration.type_remarks {
+        TypeRemarks::Off => {}
+        TypeRemarks::Remarks if remarks.is_empty() && non_blittable.is_none() => {
+            write_xml_element(str, "summary", summary, indents)?;
+            return write_line(
+                str,
+                format!("/// <remarks>{}</remarks>", escape_xml(rust_type)),
+                *indents,
+            );
+        }
+        TypeRemarks::Remarks => remarks.push(escape_xml(rust_type)),
+        TypeRemarks::AppendToSummary if summary.is_empty() => summary.push(escape_xml(rust_type)),
+        TypeRemarks::AppendToSummary => summary.push(format!("({})", escape_xml(rust_type))),
+    }
+    if let Some(reason) = non_blittable {
+        remarks.push(format!("WARNING: non-blittable, {}.", escape_xml(reason)));
+    }
+    write_xml_element(str, "summary", summary, indents)?;
+    write_xml_element(str, "remarks", remarks, indents)
+}
+
+fn write_doc_element(
+    str: &mut CodeWriter,
+    element: &str,
+    lines: &[String],
+    indents: &mut i32,
+    builder: &CSharpBuilder,
+) -> Result<(), Error> {
+    write_xml_element(str, element, format_doc_lines(lines, builder), indents)
+}
+
+/// Writes an XML documentation element, if it has any content.
+fn write_xml_element(
+    str: &mut CodeWriter,
+    element: &str,
+    lines: Vec<String>,
+    indents: &mut i32,
+) -> Result<(), Error> {
+    if !lines.is_empty() {
+        write_line(str, format!("/// <{}>", element), *indents)?;
+        for line in lines {
+            write_line(str, format!("/// {}", line), *indents)?;
+        }
+        write_line(str, format!("/// </{}>", element), *indents)?;
+    }
+    Ok(())
+}
+
+/// A doc comment split into its rustdoc sections. ``# Safety`` and ``# Panics`` sections are
+/// written as remarks, and for functions the ``# Arguments`` and ``# Returns`` sections are used
+/// for the parameter and return value documentation.
+#[derive(Default)]
+struct DocComment {
+    summary: Vec<String>,
+    /// The documented arguments, as their C# parameter name, and the lines they were parsed from.
+    arguments: Vec<(String, String, Vec<String>)>,
+    returns: Vec<String>,
+    remarks: Vec<String>,
+}
+
+#[derive(PartialEq)]
+enum DocSection {
+    Summary,
+    Arguments,
+    Returns,
+    Remarks,
+}
+
+impl DocComment {
+    fn parse(outer_docs: Vec<String>, is_function: bool) -> DocComment {
+        let mut doc_comment = DocComment::default();
+        let mut section = DocSection::Summary;
+        let mut in_code_block = false;
+        let mut remarks_heading: Option<String> = None;
+        for outer_doc in &outer_docs {
+            for line in outer_doc.split('\n') {
+                // Code blocks keep their indentation.
+                let raw = line.strip_prefix(' ').unwrap_or(line).trim_end();
+                let line = match in_code_block && !raw.trim_start().starts_with("```") {
+                    true => raw,
+                    false => raw.trim(),
+                };
+                if line.starts_with("```") {
+                    in_code_block = !in_code_block;
+                } else if !in_code_block && line.starts_with('#') {
+                    let heading = line.trim_start_matches('#').trim();
+                    section = match heading.to_ascii_lowercase().as_str() {
+                        "arguments" | "parameters" if is_function => DocSection::Arguments,
+                        "returns" if is_function => DocSection::Returns,
+                        "safety" | "panics" => {
+                            trim_empty_lines(&mut doc_comment.remarks);
+                            let label = format!("{}:", heading);
+                            doc_comment.remarks.push(label.clone());
+                            remarks_heading = Some(label);
+                            DocSection::Remarks
+                        }
+                        _ => DocSection::Summary,
+                    };
+                    if section != DocSection::Summary {
+                        continue;
+                    }
+                }
+                match section {
+                    DocSection::Summary => doc_comment.summary.push(line.to_string()),
+                    DocSection::Arguments => doc_comment.push_argument_line(line),
+                    DocSection::Returns => doc_comment.returns.push(line.to_string()),
+                    // Skip the empty line between a heading and its content.
+                    DocSection::Remarks
+                        if line.is_empty()
+                            && doc_comment.remarks.last() == remarks_heading.as_ref() => {}
+                    DocSection::Remarks => doc_comment.remarks.push(line.to_string()),
+                }
+            }
+        }
+        trim_empty_lines(&mut doc_comment.summary);
+        trim_empty_lines(&mut doc_comment.returns);
+        trim_empty_lines(&mut doc_comment.remarks);
+        doc_comment
+    }
+
+    /// Parses a line of an arguments section, which is either a bullet like ``* `name` - text``,
+    /// or the continuation of the previous one.
+    fn push_argument_line(&mut self, line: &str) {
+        if line.is_empty() {
+            return;
+        }
+        let bullet = line.strip_prefix("* ").or_else(|| line.strip_prefix("- "));
+        match bullet {
+            Some(bullet) => {
+                let bullet = bullet.trim_start();
+                let (name, rest) = match bullet.strip_prefix('`') {
+                    Some(quoted) => quoted.split_once('`').unwrap_or((quoted, "")),
+                    None => bullet.split_at(
+                        bullet
+                            .find(|c: char| !c.is_alphanumeric() && c != '_')
+                            .unwrap_or(bullet.len()),
+                    ),
+                };
+                let description = rest
+                    .trim_start()
+                    .trim_start_matches(['-', ':', '\u{2013}'])
+                    .trim_start();
+                self.arguments.push((
+                    convert_naming(name, true),
+                    description.to_string(),
+                    vec![line.to_string()],
+                ));
+            }
+            None => match self.arguments.last_mut() {
+                Some(argument) => {
+                    argument.1 = format!("{} {}", argument.1, line);
+                    argument.2.push(line.to_string());
+                }
+                None => self.summary.push(line.to_string()),
+            },
+        }
+    }
+
+    /// Takes the description of a parameter out of the arguments section.
+    fn take_argument(&mut self, parameter: &str) -> Option<String> {
+        let index = self.arguments.iter().position(|a| a.0 == parameter)?;
+        Some(self.arguments.remove(index).1)
+    }
+}
+
+fn trim_empty_lines(lines: &mut Vec<String>) {
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    let leading = lines.iter().take_while(|l| l.is_empty()).count();
+    lines.drain(..leading);
+}
+
+/// Combines the documentation of a parameter or return value with its Rust type. Returns `None` if
+/// there is nothing to document.
+fn describe_rust_type(
+    description: Option<&str>,
+    rust_type: &str,
+    builder: &CSharpBuilder,
+) -> Option<String> {
+    let description = description
+        .filter(|d| !d.is_empty())
+        .map(|d| format_doc_inline(d, builder));
+    match (description, builder.configuration.type_remarks) {
+        (Some(description), TypeRemarks::Off) => Some(description),
+        (None, TypeRemarks::Off) => None,
+        (Some(description), _) => Some(format!("{} ({})", description, escape_xml(rust_type))),
+        (None, _) => Some(escape_xml(rust_type)),
+    }
+}
+
+/// Converts the lines of a doc comment into XML documentation. Fenced code blocks are written as
+/// ``<code>`` elements, and if there are multiple paragraphs, each is written as a ``<para>``.
+fn format_doc_lines(lines: &[String], builder: &CSharpBuilder) -> Vec<String> {
+    // The blocks of the doc comment, and whether they are code blocks.
+    let mut blocks: Vec<(bool, Vec<String>)> = Vec::new();
+    // Whether the current code block is Rust code, where lines starting with `#` are hidden.
+    let mut code_block: Option<bool> = None;
+    let mut new_paragraph = true;
+    for line in lines {
+        if line.starts_with("```") {
+            match code_block {
+                None => {
+                    let language = line.trim_start_matches('`').trim();
+                    code_block = Some(
+                        language.is_empty() || language.split(',').any(|l| l.trim() == "rust"),
+                    );
+                    blocks.push((true, Vec::new()));
+                }
+                Some(_) => {
+                    code_block = None;
+                    new_paragraph = true;
+                }
+            }
+            continue;
+        }
+        match code_block {
+            Some(true) if line == "#" || line.starts_with("# ") => {}
+            Some(_) => blocks.last_mut().unwrap().1.push(escape_xml(line)),
+            None if line.is_empty() => new_paragraph = true,
+            None => {
+                if new_paragraph {
+                    blocks.push((false, Vec::new()));
+                    new_paragraph = false;
+                }
+                blocks
+                    .last_mut()
+                    .unwrap()
+                    .1
+                    .push(format_doc_inline(line, builder));
+            }
+        }
+    }
+    if let [(false, paragraph)] = blocks.as_slice() {
+        return paragraph.clone();
+    }
+    let mut result = Vec::new();
+    for (is_code, block) in blocks {
+        if block.is_empty() {
+            continue;
+        }
+        let element = if is_code { "code" } else { "para" };
+        result.push(format!("<{}>", element));
+        result.extend(block);
+        result.push(format!("</{}>", element));
+    }
+    result
+}
+
+/// Converts the markdown in a line of doc comment into XML. Code spans are written as ``<c>``, and
+/// intra-doc links as ``<see cref>`` if they link to a known type.
+fn format_doc_inline(text: &str, builder: &CSharpBuilder) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '`' {
+            if let Some(end) = rest[1..].find('`') {
+                result += &format!("<c>{}</c>", escape_xml(&rest[1..end + 1]));
+                rest = &rest[end + 2..];
+                continue;
+            }
+        } else if c == '[' {
+            if let Some(end) = rest.find(']') {
+                let link = &rest[1..end];
+                let after = &rest[end + 1..];
+                // Markdown links with an url or reference are not intra-doc links.
+                if !link.is_empty()
+                    && !link.contains(char::is_whitespace)
+                    && !after.starts_with('(')
+                    && !after.starts_with('[')
+                {
+                    result += &format_doc_link(link, builder);
+                    rest = after;
+                    continue;
+                }
+            }
+        }
+        result += &escape_xml(&rest[..c.len_utf8()]);
+        rest = &rest[c.len_utf8()..];
+    }
+    result
+}
+
+fn format_doc_link(link: &str, builder: &CSharpBuilder) -> String {
+    let target = link.trim_matches('`');
+    // Strip disambiguators like `struct@Foo`, and the path to the item.
+    let name = target.split_once('@').map_or(target, |(_, name)| name);
+    let name = name.rsplit("::").next().unwrap_or(name);
+    let known_type = syn::parse_str::<syn::Ident>(name)
+        .ok()
+        .and_then(|ident| resolve_known_type_name(builder, &ident).ok());
+    match known_type {
+        Some(t) => format!("<see cref=\"{}\"/>", escape_xml(&t.csharp_name)),
+        None => format!("<c>{}</c>", escape_xml(target)),
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Gets the C# calling convention for an extern ABI. The C ABI uses the configured default calling
+/// convention.
+fn get_calling_convention(abi: &Abi, builder: &CSharpBuilder) -> Result<CallingConvention, Error> {
+    match &abi.name {
+        // A bare `extern fn` defaults to the C ABI.
+        None => Ok(builder.configuration.default_calling_convention),
+        Some(name) => match name.value().as_str() {
+            "C" | "C-unwind" | "cdecl" | "cdecl-unwind" => {
+                Ok(builder.configuration.default_calling_convention)
+            }
+            "stdcall" | "stdcall-unwind" => Ok(CallingConvention::StdCall),
+            "thiscall" | "thiscall-unwind" => Ok(CallingConvention::ThisCall),
+            "system" | "system-unwind" | "win64" | "win64-unwind" => Ok(CallingConvention::Winapi),
+            other => Err(Error::UnsupportedError(
+                format!("The extern \"{}\" ABI is not supported", other),
+                abi.span(),
+            )),
+        },
+    }
+}
+
+/// Gets the type inside invisible groups and parentheses, such as those left by macro expansion.
+fn ungroup_type(t: &Type) -> &Type {
+    match t {
+        Type::Group(group) => ungroup_type(&group.elem),
+        Type::Paren(paren) => ungroup_type(&paren.elem),
+        t => t,
+    }
+}
+
+fn convert_type_name(t: &syn::Type, builder: &CSharpBuilder) -> Result<TypeNameContainer, Error> {
+    match t {
+        Type::Array(_) => Err(Error::UnsupportedError(
+            "Using rust arrays from ffi is not supported.".to_string(),
+            t.span()
+        )),
+        Type::BareFn(_) => Err(Error::UnsupportedError(
+            "Using bare functions from ffi is not supported.".to_string(),
+            t.span()
+        )),
+        Type::Group(group) => convert_type_name(&group.elem, builder),
+        Type::ImplTrait(_) => Err(Error::UnsupportedError(
+            "Using rust impl traits from ffi is not supported.".to_string(),
+            t.span()
+        )),
+        Type::Infer(_) => Err(Error::UnsupportedError(
+            "Using type infers is not supported. We can't generate a binding if we do not know the type.".to_string(),
+            t.span()
+        )),
+        Type::Macro(_) => Err(Error::UnsupportedError(
+            "Using rust macros from ffi is not supported.".to_string(),
+            t.span()
+        )),
+        Type::Never(_) => Err(Error::UnsupportedError(
+            "Using rust never type from ffi is not supported.".to_string(),
+            t.span()
+        )),
+        Type::Paren(paren) => convert_type_name(&paren.elem, builder),
+        Type::Path(p) => convert_type_path(&p.path, builder),
+        Type::Ptr(ptr) => convert_pointer_type(ptr.elem.borrow(), ptr.mutability.is_some(), builder),
+        Type::Reference(r) => {
+            let underlying = convert_type_name(r.elem.borrow(), builder)?;
+            let (modifier, rust_name) = match r.mutability {
+                Some(_) => ("ref ", "&mut ".to_string() + underlying.stringify_rust().as_str()),
+                // Immutable references can be passed as readonly references since C# 7.2.
+                None if builder.configuration.supports_version(7, 2) => {
+                    ("in ", "&".to_string() + underlying.stringify_rust().as_str())
+                }
+                None => ("ref ", "&".to_string() + underlying.stringify_rust().as_str()),
+            };
+            Ok(TypeNameContainer::new(
+                modifier.to_string() + underlying.stringify()?.as_str(),
+                rust_name,
+            )
+            .with_marshal_as(underlying.marshal_as))
+        }
+        Type::Slice(_) => Err(Error::UnsupportedError(
+            "Using rust slices from ffi is not supported.".to_string(),            
+            t.span()
+        )),
+        Type::TraitObject(_) => Err(Error::UnsupportedError(
+            "Using rust traits from ffi is not supported.".to_string(),
+            t.span()
+        )),
+        Type::Tuple(_) => Err(Error::UnsupportedError(
+            "Using rust tuples from ffi is not supported.".to_string(),
+            t.span()
+
+        )),
+        Type::Verbatim(_) => Err(Error::UnsupportedError(
+            "Using rust verbatim from ffi is not supported.".to_string(),
+            t.span()
+
+        )),
+        _ => Err(Error::UnsupportedError(
+            "Unknown type kind is not supported.".to_string(),
+            t.span()
+        )),
+    }
+}
+
+fn convert_pointer_type(
+    elem: &Type,
+    mutable: bool,
+    builder: &CSharpBuilder,
+) -> Result<TypeNameContainer, Error> {
+    let elem = ungroup_type(elem);
+    if let Some(handle) = resolve_opaque_handle(elem, mutable, builder) {
+        return handle;
+    }
+    let underlying = match elem {
+        // A void pointer is an untyped pointer.
+        Type::Path(p) if get_path_name(&p.path).as_deref() == Some("c_void") => {
+            TypeNameContainer::new("void".to_string(), "c_void".to_string())
+        }
+        elem => convert_type_name(elem, builder)?,
+    };
+    let rust_name = match mutable {
+        true => "*mut ".to_string() + underlying.stringify_rust().as_str(),
+        false => "*const ".to_string() + underlying.stringify_rust().as_str(),
+    };
+    if builder.configuration.use_unsafe_pointers {
+        Ok(TypeNameContainer::new(
+            underlying.stringify()? + "*",
+            rust_name,
+        ))
+    } else {
+        Ok(TypeNameContainer::new("IntPtr".to_string(), rust_name))
+    }
+}
+
+/// Converts a reference returned by a function. References can't be returned by imported
+/// functions and delegates, so these are returned as a pointer instead, with the reference as the
+/// Rust name.
+fn convert_returned_reference(
+    reference: &TypeReference,
+    builder: &CSharpBuilder,
+) -> Result<TypeNameContainer, Error> {
+    let mut pointer =
+        convert_pointer_type(&reference.elem, reference.mutability.is_some(), builder)?;
+    let referenced = pointer
+        .rust_name
+        .strip_prefix("*mut ")
+        .or_else(|| pointer.rust_name.strip_prefix("*const "));
+    if let Some(referenced) = referenced {
+        pointer.rust_name = match reference.mutability {
+            Some(_) => format!("&mut {}", referenced),
+            None => format!("&{}", referenced),
+        };
+    }
+    Ok(pointer)
+}
+
+/// Gets the type argument of a type with a single generic type, such as ``NonNull<T>``. Lifetime
+/// arguments are ignored.
+fn get_single_generic(v: &syn::PathSegment) -> Option<&Type> {
+    match &v.arguments {
+        PathArguments::AngleBracketed(a) => {
+            let mut arguments = a
+                .args
+                .iter()
+                .filter(|argument| !matches!(argument, GenericArgument::Lifetime(_)));
+            match (arguments.next(), arguments.next()) {
+                (Some(GenericArgument::Type(t)), None) => Some(t),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// 128 bit integers have no blittable C# equivalent, so they can only be used if the user added a
+/// known type for them, or explicitly opted into BigInteger.
+fn convert_128_bit_integer(
+    v: &syn::PathSegment,
+    builder: &CSharpBuilder,
+) -> Result<TypeNameContainer, Error> {
+    if builder
+        .get_known_type(&v.ident.unraw().to_string())
+        .is_some()
+    {
+        return resolve_known_type_name(builder, &v.ident);
+    }
+    if builder.configuration.map_u128_as_big_integer {
+        return Ok(TypeNameContainer::new(
+            "System.Numerics.BigInteger".to_string(),
+            v.ident.unraw().to_string(),
+        ));
+    }
+    Err(Error::UnsupportedError(
+        format!(
+            "Found a {} type. 128 bit integers have no C# equivalent that can be marshalled. Use map_u128_as_pair to split struct fields into two 64 bit fields, or add a known type for it.",
+            v.ident
+        ),
+        v.ident.span(),
+    ))
+}
+
+/// Gets whether a struct field should be split into two 64 bit fields, and whether it is signed.
+fn get_split_128_bit_field(ty: &Type, builder: &CSharpBuilder) -> Option<bool> {
+    if !builder.configuration.map_u128_as_pair {
+        return None;
+    }
+    let ident = match ty {
+        Type::Path(p) => p.path.get_ident()?.to_string(),
+        _ => return None,
+    };
+    if builder.get_known_type(&ident).is_some() {
+        return None;
+    }
+    match ident.as_str() {
+        "u128" => Some(false),
+        "i128" => Some(true),
+        _ => None,
+    }
+}
+
+/// Converts ``usize`` or ``isize`` with the configured pointer width. For fixed widths, the width
+/// is added to the Rust name, so the assumption is visible in the documentation.
+fn convert_pointer_sized(
+    signed: bool,
+    v: &syn::PathSegment,
+    builder: &CSharpBuilder,
+) -> Result<TypeNameContainer, Error> {
+    let rust_name = v.ident.unraw().to_string();
+    let (csharp_name, rust_name) = match (builder.configuration.get_pointer_width(), signed) {
+        (PointerWidth::W32, false) => ("uint", rust_name + " (32 bit)"),
+        (PointerWidth::W32, true) => ("int", rust_name + " (32 bit)"),
+        (PointerWidth::W64, false) => ("ulong", rust_name + " (64 bit)"),
+        (PointerWidth::W64, true) => ("long", rust_name + " (64 bit)"),
+        (PointerWidth::Native, false) if builder.configuration.is_unity() => ("UIntPtr", rust_name),
+        (PointerWidth::Native, true) if builder.configuration.is_unity() => ("IntPtr", rust_name),
+        (PointerWidth::Native, _) if builder.configuration.csharp_version < 9 => {
+            return Err(Error::UnsupportedError(
+                "Native sized integers require C# 9. Use set_pointer_width to use a fixed width."
+                    .to_string(),
+                v.span(),
+            ))
+        }
+        (PointerWidth::Native, false) => ("nuint", rust_name),
+        (PointerWidth::Native, true) => ("nint", rust_name),
+    };
+    Ok(TypeNameContainer::new(csharp_name.to_string(), rust_name))
+}
+
+/// Gets the underlying integer of a non-zero integer type, such as ``u32`` for ``NonZeroU32``.
+fn get_non_zero_integer(name: &str) -> Option<&'static str> {
+    match name {
+        "NonZeroU8" => Some("u8"),
+        "NonZeroU16" => Some("u16"),
+        "NonZeroU32" => Some("u32"),
+        "NonZeroU64" => Some("u64"),
+        "NonZeroU128" => Some("u128"),
+        "NonZeroUsize" => Some("usize"),
+        "NonZeroI8" => Some("i8"),
+        "NonZeroI16" => Some("i16"),
+        "NonZeroI32" => Some("i32"),
+        "NonZeroI64" => Some("i64"),
+        "NonZeroI128" => Some("i128"),
+        "NonZeroIsize" => Some("isize"),
+        _ => None,
+    }
+}
+
+/// Gets the inner type of an ``Option<T>``.
+fn get_option_inner(t: &Type) -> Option<&Type> {
+    match t {
+        Type::Path(p) => {
+            let segment = p.path.segments.last()?;
+            match segment.ident == "Option" {
+                true => get_single_generic(segment),
+                false => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Converts an ``Option`` of a pointer-like type, which has the same layout as a nullable pointer.
+fn convert_option_type(
+    v: &syn::PathSegment,
+    builder: &CSharpBuilder,
+) -> Result<TypeNameContainer, Error> {
+    let unsupported = || {
+        Error::UnsupportedError(
+            "Only an Option of a pointer-like type, such as NonNull<T>, a reference, Box<T> or a function pointer, or of a non-zero integer, has a guaranteed layout that can be used over FFI.".to_string(),
+            v.span(),
+        )
+    };
+    let inner = get_single_generic(v).ok_or_else(unsupported)?;
+    let (mut pointer, wrapper) = match inner {
+        Type::Reference(r) => {
+            let pointer = convert_pointer_type(r.elem.borrow(), r.mutability.is_some(), builder)?;
+            let wrapper = match r.mutability {
+                Some(_) => "&mut {}",
+                None => "&{}",
+            };
+            (pointer, wrapper)
+        }
+        Type::Path(p) => {
+            let segment = p.path.segments.last().ok_or_else(unsupported)?;
+            let name = segment.ident.unraw().to_string();
+            // Non-zero integers use zero for None.
+            if name == "NonZero" || get_non_zero_integer(&name).is_some() {
+                let mut integer = convert_type_name(inner, builder)?;
+                integer.rust_name = format!("Option<{}>", integer.rust_name);
+                return Ok(integer);
+            }
+            let wrapper = match name.as_str() {
+                "NonNull" => "NonNull<{}>",
+                "Box" => "Box<{}>",
+                _ => return Err(unsupported()),
+            };
+            let elem = get_single_generic(segment).ok_or_else(unsupported)?;
+            (convert_pointer_type(elem, true, builder)?, wrapper)
+        }
+        // Function pointers are only supported in function signatures, where they become delegates.
+        Type::BareFn(_) => return convert_type_name(inner, builder),
+        _ => return Err(unsupported()),
+    };
+    let pointee = pointer
+        .rust_name
+        .trim_start_matches("*mut ")
+        .trim_start_matches("*const ");
+    pointer.rust_name = format!("Option<{}>", wrapper.replace("{}", pointee));
+    Ok(pointer)
+}
+
+/// Converts a fixed size array into a managed array, marshalled by value.
+fn convert_array_type(
+    array: &TypeArray,
+    builder: &CSharpBuilder,
+) -> Result<TypeNameContainer, Error> {
+    let length = get_array_length(array)?;
+    let element = convert_type_name(array.elem.borrow(), builder)?;
+    Ok(TypeNameContainer::new(
+        element.stringify()? + "[]",
+        format!("[{}; {}]", element.stringify_rust(), length),
+    )
+    .with_marshal_as(Some(format!(
+        "UnmanagedType.ByValArray, SizeConst = {}",
+        length
+    ))))
+}
+
+fn get_array_length(array: &TypeArray) -> Result<String, Error> {
+    if let Expr::Lit(l) = &array.len {
+        if let syn::Lit::Int(i) = &l.lit {
+            return Ok(i.base10_digits().to_string());
+        }
+    }
+    Err(Error::UnsupportedError(
+        "Arrays are only supported with an integer literal as length.".to_string(),
+        array.len.span(),
+    ))
+}
+
+/// C# only allows fixed size buffers of a limited set of primitive types.
+fn is_fixed_buffer_type(t: &TypeNameContainer) -> bool {
+    t.generics.is_empty()
+        && t.marshal_as.is_none()
+        && matches!(
+            t.csharp_name.as_str(),
+            "byte"
+                | "sbyte"
+                | "char"
+                | "short"
+                | "ushort"
+                | "int"
+                | "uint"
+                | "long"
+                | "ulong"
+                | "float"
+                | "double"
+        )
+}
+
+/// Registers the handle types of opaque handles whose destructor is in the script, so they can be
+/// used before the destructor is encountered.
+fn register_opaque_handles(items: &[Item], builder: &mut CSharpBuilder) -> Result<(), Error> {
+    for item in items {
+        match item {
+            Item::Fn(fun)
+                if fun.sig.abi.is_some()
+                    && is_exported(fun, builder)
+                    && !builder
+                        .configuration
+                        .is_ignored(&fun.sig.ident.unraw().to_string())
+                    && !is_skipped(&fun.attrs, builder)
+                    && !is_cfg_disabled(&fun.attrs, builder) =>
+            {
+                if let Some(handle) =
+                    get_opaque_handle_for_destructor(builder, &fun.sig.ident.unraw().to_string())
+                {
+                    builder.add_known_type(
+                        &handle,
+                        &(handle.to_string() + "Handle"),
+                        fun.sig.ident.span(),
+                    )?;
+                }
+            }
+            Item::Impl(imp)
+                if !is_skipped(&imp.attrs, builder) && !is_cfg_disabled(&imp.attrs, builder) =>
+            {
+                register_opaque_handles(&get_impl_functions(imp, builder), builder)?
+            }
+            Item::Mod(module) if !is_cfg_disabled(&module.attrs, builder) => {
+                if let Some(content) = &module.content {
+                    builder.module_path.push(module.ident.unraw().to_string());
+                    let result = if builder.configuration.nest_modules_as_classes {
+                        let module_type_name = get_module_type_name(builder, module);
+                        let type_name = builder.type_name.replace(module_type_name);
+                        let result = register_opaque_handles(&content.1, builder);
+                        builder.type_name = type_name;
+                        result
+                    } else {
+                        register_opaque_handles(&content.1, builder)
+                    };
+                    builder.module_path.pop();
+                    result?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn get_opaque_handle_for_destructor(builder: &CSharpBuilder, function: &str) -> Option<String> {
+    builder
+        .configuration
+        .opaque_handles
+        .iter()
+        .find(|(_, destructor)| destructor.as_str() == function)
+        .map(|(handle, _)| handle.to_string())
+}
+
+/// Converts a ``*const c_char`` of a function signature into a UTF-8 marshalled string, if that is
+/// enabled.
+fn convert_utf8_string(t: &Type, builder: &CSharpBuilder) -> Option<TypeNameContainer> {
+    let configuration = &builder.configuration;
+    if !configuration.marshal_utf8_strings || !configuration.supports_version(7, 1) {
+        return None;
+    }
+    let element = match t {
+        Type::Ptr(ptr) if ptr.mutability.is_none() => match ptr.elem.borrow() {
+            Type::Path(p) => get_path_name(&p.path)?,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    if !configuration.utf8_string_types.contains(&element) {
+        return None;
+    }
+    Some(
+        TypeNameContainer::new(
+            "string".to_string(),
+            format!("*const {} (UTF-8 string)", element),
+        )
+        .with_marshal_as(Some("UnmanagedType.LPUTF8Str".to_string())),
+    )
+}
+
+/// Writes the helper to convert returned UTF-8 strings, if it was not written yet.
+fn write_string_helper(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    builder: &mut CSharpBuilder,
+) -> Result<(), Error> {
+    if !builder.member_names.insert("PtrToString".to_string()) {
+        return Ok(());
+    }
+    write_line(str, "/// <summary>".to_string(), *indents)?;
+    write_line(
+        str,
+        "/// Converts a UTF-8 string returned by a native function into a managed string."
+            .to_string(),
+        *indents,
+    )?;
+    write_line(str, "/// </summary>".to_string(), *indents)?;
+    write_line(
+        str,
+        format!(
+            "internal static {} PtrToString(IntPtr ptr)",
+            nullable_string(builder)
+        ),
+        *indents,
+    )?;
+    write_line(str, "{".to_string(), *indents)?;
+    write_line(
+        str,
+        "return Marshal.PtrToStringUTF8(ptr);".to_string(),
+        *indents + 1,
+    )?;
+    write_line(str, "}".to_string(), *indents)?;
+    writeln!(str)?;
+    Ok(())
+}
+
+/// The type of strings converted from returned pointers, which are null for null pointers.
+fn nullable_string(builder: &CSharpBuilder) -> String {
+    match builder.configuration.uses_nullable_annotations() {
+        true => "string?".to_string(),
+        false => "string".to_string(),
+    }
+}
+
+/// Pointers to a registered opaque handle type are converted into their SafeHandle class.
+fn resolve_opaque_handle(
+    elem: &Type,
+    mutable: bool,
+    builder: &CSharpBuilder,
+) -> Option<Result<TypeNameContainer, Error>> {
+    let path = match elem {
+        Type::Path(p) => &p.path,
+        _ => return None,
+    };
+    if !builder.in_function_signature {
+        return None;
+    }
+    let ident = &path.segments.last()?.ident;
+    if !builder
+        .configuration
+        .opaque_handles
+        .contains_key(&ident.unraw().to_string())
+    {
+        return None;
+    }
+    let rust_name = match mutable {
+        true => format!("*mut {}", ident.unraw()),
+        false => format!("*const {}", ident.unraw()),
+    };
+    Some(resolve_known_type_name(builder, ident).map(|mut t| {
+        t.rust_name = rust_name;
+        t
+    }))
+}
+
+/// Writes the SafeHandle class for an opaque handle, which calls its destructor when released. The
+/// destructor itself is only available through the handle.
+fn write_opaque_handle(
+    str: &mut CodeWriter,
+    indents: &mut i32,
+    builder: &mut CSharpBuilder,
+    handle: &str,
+    destructor: &ItemFn,
+    calling_convention: CallingConvention,
+) -> Result<(), Error> {
+    let is_valid_destructor = destructor.sig.inputs.len() == 1
+        && matches!(destructor.sig.output, ReturnType::Default)
+        && matches!(destructor.sig.inputs.first(), Some(FnArg::Typed(t)) if matches!(t.ty.borrow(), Type::Ptr(_)));
+    if !is_valid_destructor {
+        return Err(Error::UnsupportedError(
+            "The destructor of an opaque handle should take a single pointer parameter, and return nothing."
+                .to_string(),
+            destructor.sig.span(),
+        ));
+    }
+    let class_name = handle.to_string() + "Handle";
+    // The handle type was registered before writing, so its name can not be disambiguated.
+    if !builder.member_names.insert(class_name.clone()) {
+        return Err(Error::DuplicateSymbol(
+            class_name,
+            destructor.sig.ident.span(),
+        ));
+    }
 
+    let outer_docs = extract_outer_docs(&destructor.attrs)?;
+    write_summary_from_outer_docs(str, outer_docs, indents, builder)?;
+    write_line(
+        str,
+        format!(
+            "{} {}class {} : Microsoft.Win32.SafeHandles.SafeHandleZeroOrMinusOneIsInvalid",
+            types_access(builder),
+            if builder.configuration.uses_library_import() {
+                "partial "
+            } else {
+                ""
+            },
+            class_name
+        ),
+        *indents,
+    )?;
+    write_line(str, "{".to_string(), *indents)?;
+    *indents += 1;
+    write_line(
+        str,
+        format!("public {}() : base(true)", class_name),
+        *indents,
+    )?;
+    write_line(str, "{".to_string(), *indents)?;
     write_line(str, "}".to_string(), *indents)?;
-
+    writeln!(str)?;
+    write_line(
+        str,
+        "protected override bool ReleaseHandle()".to_string(),
+        *indents,
+    )?;
+    write_line(str, "{".to_string(), *indents)?;
+    write_line(str, "Destroy(handle);".to_string(), *indents + 1)?;
+    write_line(str, "return true;".to_string(), *indents + 1)?;
+    write_line(str, "}".to_string(), *indents)?;
+    writeln!(str)?;
+    let entry_point = get_symbol_name(&destructor.attrs, &destructor.sig.ident);
+    write_import_attribute(
+        str,
+        indents,
+        builder,
+        &destructor.sig.ident.unraw().to_string(),
+        &entry_point,
+        calling_convention,
+    )?;
+    builder.entry_points.push(entry_point);
+    write_line(
+        str,
+        format!(
+            "private static {} void Destroy(IntPtr handle);",
+            import_modifier(builder)
+        ),
+        *indents,
+    )?;
     *indents -= 1;
     write_line(str, "}".to_string(), *indents)?;
     writeln!(str)?;
-
-    builder.add_known_type(
-        strct.ident.to_string().as_str(),
-        strct.ident.to_string().as_str(),
-    );
     Ok(())
 }
 
-fn extract_outer_docs(attrs: &[Attribute]) -> Result<Vec<String>, Error> {
-    let mut outer_docs: Vec<String> = Vec::new();
-    for attr in attrs {
-        let parsed = attr.parse_meta()?;
-        match parsed {
-            Meta::Path(_) => {}
-            Meta::List(_) => {}
-            Meta::NameValue(nv) => match nv.path.get_ident() {
-                None => {}
-                Some(identifier) => {
-                    if let "doc" = identifier.to_string().as_str() {
-                        if let syn::Lit::Str(v) = nv.lit {
-                            outer_docs.push(v.value());
-                        }
-                    }
-                }
-            },
-        }
-    }
-    Ok(outer_docs)
+/// Convert Rust constant naming scheme (SCREAMING_SNAKE_CASE) to C# naming scheme (CamelCase)
+fn convert_constant_naming(input: &str) -> String {
+    input
+        .split('_')
+        .map(|s| {
+            let mut s = s.to_ascii_lowercase();
+            if let Some(r) = s.get_mut(0..1) {
+                r.make_ascii_uppercase();
+            }
+            s
+        })
+        .collect()
 }
 
-fn write_summary_from_outer_docs(
-    str: &mut String,
-    outer_docs: Vec<String>,
-    indents: &mut i32,
-) -> Result<(), Error> {
-    if !outer_docs.is_empty() {
-        write_line(str, "/// <summary>".to_string(), *indents)?;
-        for outer_doc in outer_docs {
-            write_line(str, format!("/// {}", outer_doc.trim()), *indents)?;
-        }
-        write_line(str, "/// </summary>".to_string(), *indents)?;
+/// Converts the Rust name of a symbol into its C# name. Renamed symbols take precedence over the
+/// naming converter of the configuration, which takes precedence over the default conversion.
+fn convert_symbol_naming(name: &str, kind: NameKind, builder: &CSharpBuilder) -> String {
+    let configuration = &builder.configuration;
+    if let Some(renamed) = configuration.renamed_symbols.get(name) {
+        return escape_csharp_keyword(renamed.to_string());
     }
-    Ok(())
-}
-
-fn is_extern_c(func: &ItemFn) -> bool {
-    match &func.sig.abi {
-        None => false,
-        Some(abi) => match &abi.name {
-            None => false,
-            Some(name) => name.value() == "C",
-        },
+    let name = match kind {
+        NameKind::Function => strip_function_prefix(name, builder),
+        _ => name,
+    };
+    match (&configuration.naming_converter, kind) {
+        (Some(converter), _) => escape_csharp_keyword(converter(name, kind)),
+        (None, NameKind::Type) => escape_csharp_keyword(name.to_string()),
+        (None, _) => convert_naming(name, kind == NameKind::Parameter),
     }
 }
 
-fn convert_type_name(t: &syn::Type, builder: &CSharpBuilder) -> Result<TypeNameContainer, Error> {
-    match t {
-        Type::Array(_) => Err(Error::UnsupportedError(
-            "Using rust arrays from ffi is not supported.".to_string(),
-            t.span()
-        )),
-        Type::BareFn(_) => Err(Error::UnsupportedError(
-            "Using bare functions from ffi is not supported.".to_string(),
-            t.span()
-        )),
-        Type::Group(_) => Err(Error::UnsupportedError(
-            "Using type group from ffi is not supported.".to_string(),           
-            t.span()
-        )),
-        Type::ImplTrait(_) => Err(Error::UnsupportedError(
-            "Using rust impl traits from ffi is not supported.".to_string(),
-            t.span()
-        )),
-        Type::Infer(_) => Err(Error::UnsupportedError(
-            "Using type infers is not supported. We can't generate a binding if we do not know the type.".to_string(),
-            t.span()
-        )),
-        Type::Macro(_) => Err(Error::UnsupportedError(
-            "Using rust macros from ffi is not supported.".to_string(),
-            t.span()
-        )),
-        Type::Never(_) => Err(Error::UnsupportedError(
-            "Using rust never type from ffi is not supported.".to_string(),
-            t.span()
-        )),
-        Type::Paren(_) => Err(Error::UnsupportedError(
-            "Using rust parenthesis from ffi is not supported.".to_string(),
-            t.span()
-        )),
-        Type::Path(p) => convert_type_path(&p.path, builder),
-        Type::Ptr(ptr) => {
-            let underlying = convert_type_name(ptr.elem.borrow(), builder)?;
-            Ok(TypeNameContainer::new("IntPtr".to_string(), underlying.rust_name + "*"))
-        }
-        Type::Reference(r) => {
-            let underlying = convert_type_name(r.elem.borrow(), builder)?;
-            Ok(TypeNameContainer::new(
-                "ref ".to_string() + underlying.stringify()?.as_str(),
-                underlying.rust_name + "&",
-            ))
-        }
-        Type::Slice(_) => Err(Error::UnsupportedError(
-            "Using rust slices from ffi is not supported.".to_string(),            
-            t.span()
-        )),
-        Type::TraitObject(_) => Err(Error::UnsupportedError(
-            "Using rust traits from ffi is not supported.".to_string(),
-            t.span()
-        )),
-        Type::Tuple(_) => Err(Error::UnsupportedError(
-            "Using rust tuples from ffi is not supported.".to_string(),
-            t.span()
-
-        )),
-        Type::Verbatim(_) => Err(Error::UnsupportedError(
-            "Using rust verbatim from ffi is not supported.".to_string(),
-            t.span()
-
-        )),
-        Type::__TestExhaustive(_) => unreachable!(),
+/// Converts the name of an enum variant into C#, using its configured rename, or the C# naming
+/// scheme if enabled. Variants in SCREAMING_SNAKE_CASE are converted like constants.
+fn convert_variant_naming(en: &ItemEnum, variant_name: &str, builder: &CSharpBuilder) -> String {
+    let enum_name = en.ident.unraw().to_string();
+    if let Some(name) = builder
+        .configuration
+        .get_enum_variant_rename(&enum_name, variant_name)
+    {
+        return name.to_string();
+    }
+    if !builder.configuration.convert_enum_variant_names {
+        return variant_name.to_string();
+    }
+    let name = if variant_name.chars().any(|c| c.is_ascii_lowercase()) {
+        convert_naming(variant_name, false)
+    } else {
+        convert_constant_naming(variant_name)
+    };
+    match name.chars().next() {
+        Some(c) if c.is_ascii_digit() => format!("_{}", name),
+        Some(_) => name,
+        // Variants such as `__` have no name left after the conversion.
+        None => enum_name,
     }
 }
 
-/// Convert Rust naming scheme (underscore snake_case) to C# naming scheme (CamelCase)
+/// Convert Rust naming scheme (underscore snake_case) to C# naming scheme (CamelCase). Raw
+/// identifiers lose their ``r#`` prefix, and names that are C# keywords are prefixed with ``@``.
 fn convert_naming(input: &str, is_parameter: bool) -> String {
+    let input = input.strip_prefix("r#").unwrap_or(input);
     let mut split: Vec<String> = input.split('_').map(|x| x.to_string()).collect();
     for s in &mut split {
         if let Some(r) = s.get_mut(0..1) {
@@ -670,97 +6431,249 @@ fn convert_naming(input: &str, is_parameter: bool) -> String {
         }
     }
 
-    f
+    escape_csharp_keyword(f)
 }
 
-fn get_repr_attribute_value(attr: &Attribute) -> Result<Option<syn::Path>, Error> {
-    let parsed = attr.parse_meta()?;
-    match parsed {
-        Meta::Path(_) => Ok(None),
-        Meta::List(ls) => match ls.path.get_ident() {
-            None => Ok(None),
-            Some(identifier) => {
-                if let "repr" = identifier.to_string().as_str() {
-                    let value_option = ls.nested.first();
-                    match value_option {
-                        None => Ok(None),
-                        Some(value) => match value {
-                            NestedMeta::Meta(val) => {
-                                if let Meta::Path(path) = val {
-                                    return Ok(Some(path.clone()));
-                                }
-                                Ok(None)
-                            }
-                            NestedMeta::Lit(_) => Ok(None),
-                        },
+/// The reserved keywords of C#, which can only be used as names when prefixed with ``@``.
+const CSHARP_KEYWORDS: &[&str] = &[
+    "abstract",
+    "as",
+    "base",
+    "bool",
+    "break",
+    "byte",
+    "case",
+    "catch",
+    "char",
+    "checked",
+    "class",
+    "const",
+    "continue",
+    "decimal",
+    "default",
+    "delegate",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "event",
+    "explicit",
+    "extern",
+    "false",
+    "finally",
+    "fixed",
+    "float",
+    "for",
+    "foreach",
+    "goto",
+    "if",
+    "implicit",
+    "in",
+    "int",
+    "interface",
+    "internal",
+    "is",
+    "lock",
+    "long",
+    "namespace",
+    "new",
+    "null",
+    "object",
+    "operator",
+    "out",
+    "override",
+    "params",
+    "private",
+    "protected",
+    "public",
+    "readonly",
+    "ref",
+    "return",
+    "sbyte",
+    "sealed",
+    "short",
+    "sizeof",
+    "stackalloc",
+    "static",
+    "string",
+    "struct",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "uint",
+    "ulong",
+    "unchecked",
+    "unsafe",
+    "ushort",
+    "using",
+    "virtual",
+    "void",
+    "volatile",
+    "while",
+];
+
+/// Prefixes a name with ``@`` if it is a C# keyword, so it can be used as an identifier.
+fn escape_csharp_keyword(name: String) -> String {
+    if CSHARP_KEYWORDS.contains(&name.as_str()) {
+        format!("@{}", name)
+    } else {
+        name
+    }
+}
+
+/// The options set in the ``#[repr(...)]`` attributes of an item.
+#[derive(Default)]
+struct ReprOptions {
+    /// The span of the C representation, if it was set.
+    c: Option<proc_macro2::Span>,
+    transparent: bool,
+    /// The primitive representation of an enum, e.g. ``u8``.
+    primitive: Option<syn::Path>,
+    packed: Option<String>,
+    align: Option<String>,
+}
+
+fn get_repr_options(
+    attrs: &[Attribute],
+    item_name: &str,
+    builder: &mut CSharpBuilder,
+) -> Result<ReprOptions, Error> {
+    let mut repr = ReprOptions::default();
+    for meta in get_active_metas(attrs, item_name, builder)? {
+        let list = match meta {
+            Meta::List(ls) if ls.path.is_ident("repr") => ls,
+            _ => continue,
+        };
+        for nested in &list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::Path(path)) => match path.get_ident() {
+                    Some(ident) if ident == "C" => repr.c = Some(ident.span()),
+                    Some(ident) if ident == "transparent" => repr.transparent = true,
+                    Some(ident) if ident == "packed" => repr.packed = Some("1".to_string()),
+                    _ => repr.primitive = Some(path.clone()),
+                },
+                NestedMeta::Meta(Meta::List(ls)) => {
+                    let value = match ls.nested.first() {
+                        Some(NestedMeta::Lit(syn::Lit::Int(i))) => i.base10_digits().to_string(),
+                        _ => continue,
+                    };
+                    if ls.path.is_ident("packed") {
+                        repr.packed = Some(value);
+                    } else if ls.path.is_ident("align") {
+                        repr.align = Some(value);
                     }
-                } else {
-                    Ok(None)
                 }
+                _ => {}
             }
-        },
-        Meta::NameValue(_) => Ok(None),
+        }
     }
+    Ok(repr)
 }
 
 fn convert_type_path(
     path: &syn::Path,
     builder: &CSharpBuilder,
 ) -> Result<TypeNameContainer, Error> {
-    return match path.segments.last() {
+    match path.segments.last() {
         Some(v) => {
-            match v.ident.to_string().as_str() {
+            match v.ident.unraw().to_string().as_str() {
                 // First attempt to resolve the primitive types
                 "u8" => Ok(TypeNameContainer::new("byte".to_string(), "u8".to_string())),
                 "u16" => Ok(TypeNameContainer::new("ushort".to_string(), "u16".to_string())),
                 "u32" => Ok(TypeNameContainer::new("uint".to_string(), "u32".to_string())),
                 "u64" => Ok(TypeNameContainer::new("ulong".to_string(), "u64".to_string())),
-                "u128" => Ok(TypeNameContainer::new("System.Numerics.BigInteger".to_string(), "u128".to_string())),
-                "usize" => {
-                    if builder.configuration.csharp_version >= 9 {
-                        // Use new C# 9 native integer type for size, as it should be the same.
-                        Ok(TypeNameContainer::new("nuint".to_string(), "usize".to_string()))
-                    }
-                    else{
-                        // FIXME: Not strictly correct on 32 bit computers. 
-                        Ok(TypeNameContainer::new("ulong".to_string(), "usize".to_string()))
-                    }
-                },
+                "u128" => convert_128_bit_integer(v, builder),
+                "usize" => convert_pointer_sized(false, v, builder),
 
                 "i8" => Ok(TypeNameContainer::new("sbyte".to_string(), "i8".to_string())),
                 "i16" => Ok(TypeNameContainer::new("short".to_string(), "i16".to_string())),
                 "i32" => Ok(TypeNameContainer::new("int".to_string(), "i32".to_string())),
                 "i64" => Ok(TypeNameContainer::new("long".to_string(), "i64".to_string())),
-                "i128" => Ok(TypeNameContainer::new("System.Numerics.BigInteger".to_string(), "i128".to_string())),
-                "isize" => {
-                    if builder.configuration.csharp_version >= 9 {
-                        // Use new C# 9 native integer type for size, as it should be the same.
-                        Ok(TypeNameContainer::new("nint".to_string(), "isize".to_string()))
-                    }
-                    else{
-                        // FIXME: Not strictly correct on 32 bit computers. 
-                        Ok(TypeNameContainer::new("long".to_string(), "isize".to_string()))
-                    }
-                },
+                "i128" => convert_128_bit_integer(v, builder),
+                "isize" => convert_pointer_sized(true, v, builder),
 
                 "f32" => Ok(TypeNameContainer::new("float".to_string(), "f32".to_string())),
                 "f64" => Ok(TypeNameContainer::new("double".to_string(), "f64".to_string())),
 
-                "char" => Ok(TypeNameContainer::new("char".to_string(), "char".to_string())),
-                "c_char" => Ok(TypeNameContainer::new("char".to_string(), "c_char".to_string())),
+                // The C types of ``std::os::raw`` and ``core::ffi``.
+                "c_schar" => Ok(TypeNameContainer::new("sbyte".to_string(), "c_schar".to_string())),
+                "c_uchar" => Ok(TypeNameContainer::new("byte".to_string(), "c_uchar".to_string())),
+                "c_short" => Ok(TypeNameContainer::new("short".to_string(), "c_short".to_string())),
+                "c_ushort" => Ok(TypeNameContainer::new("ushort".to_string(), "c_ushort".to_string())),
+                "c_int" => Ok(TypeNameContainer::new("int".to_string(), "c_int".to_string())),
+                "c_uint" => Ok(TypeNameContainer::new("uint".to_string(), "c_uint".to_string())),
+                "c_long" => match builder.configuration.c_long_size {
+                    CLongSize::Bits32 => Ok(TypeNameContainer::new("int".to_string(), "c_long".to_string())),
+                    CLongSize::Bits64 => Ok(TypeNameContainer::new("long".to_string(), "c_long".to_string())),
+                },
+                "c_ulong" => match builder.configuration.c_long_size {
+                    CLongSize::Bits32 => Ok(TypeNameContainer::new("uint".to_string(), "c_ulong".to_string())),
+                    CLongSize::Bits64 => Ok(TypeNameContainer::new("ulong".to_string(), "c_ulong".to_string())),
+                },
+                "c_longlong" => Ok(TypeNameContainer::new("long".to_string(), "c_longlong".to_string())),
+                "c_ulonglong" => Ok(TypeNameContainer::new("ulong".to_string(), "c_ulonglong".to_string())),
+                "c_float" => Ok(TypeNameContainer::new("float".to_string(), "c_float".to_string())),
+                "c_double" => Ok(TypeNameContainer::new("double".to_string(), "c_double".to_string())),
+                "c_void" => Err(Error::UnsupportedError("Found a c_void type that is not behind a pointer. Use a pointer to c_void, or the unit type for functions that return nothing.".to_string(), v.ident.span())),
+
+                // A Rust char is a 4 byte Unicode scalar value, unlike the 2 byte C# char.
+                "char" => match builder.configuration.rust_char_mapping {
+                    CSharpCharMapping::UInt => Ok(TypeNameContainer::new("uint".to_string(), "char".to_string())),
+                    CSharpCharMapping::Error => Err(Error::UnsupportedError("Found a char type. A Rust char is 4 bytes, and can not be converted into a C# char. Use map_rust_char_to to convert it into a uint.".to_string(), v.ident.span())),
+                },
+                "c_char" => match builder.configuration.c_char_signed {
+                    true => Ok(TypeNameContainer::new("sbyte".to_string(), "c_char".to_string())),
+                    false => Ok(TypeNameContainer::new("byte".to_string(), "c_char".to_string())),
+                },
 
-                "bool" => Err(Error::UnsupportedError("Found a boolean type. Due to differing sizes on different operating systems this is not supported for extern C functions.".to_string(),             v.ident.span()
-                )),
+                "bool" => match builder.configuration.allow_bool_marshaling {
+                    // A Rust bool is a single byte, so marshal it as a 1 byte boolean.
+                    true => Ok(TypeNameContainer::new("bool".to_string(), "bool".to_string()).with_marshal_as(Some("UnmanagedType.I1".to_string()))),
+                    false => Err(Error::UnsupportedError("Found a boolean type. Due to differing sizes on different operating systems this is not supported for extern C functions. Use allow_bool_marshaling to marshal it as a single byte.".to_string(), v.ident.span())),
+                },
                 "str" => Err(Error::UnsupportedError("Found a str type. This is not supported, please use a char pointer instead.".to_string(), v.ident.span())),
 
+                "NonNull" => {
+                    let elem = get_single_generic(v).ok_or_else(|| {
+                        Error::UnsupportedError(
+                            "NonNull requires a type argument.".to_string(),
+                            v.span(),
+                        )
+                    })?;
+                    let mut pointer = convert_pointer_type(elem, true, builder)?;
+                    pointer.rust_name =
+                        format!("NonNull<{}>", pointer.rust_name.trim_start_matches("*mut "));
+                    Ok(pointer)
+                }
+                "Option" => convert_option_type(v, builder),
+                // Non-zero integers have the same layout as their underlying integer.
+                "NonZero" => {
+                    let inner = get_single_generic(v).ok_or_else(|| {
+                        Error::UnsupportedError(
+                            "NonZero requires a type argument.".to_string(),
+                            v.span(),
+                        )
+                    })?;
+                    let mut underlying = convert_type_name(inner, builder)?;
+                    underlying.rust_name = format!("NonZero<{}>", underlying.rust_name);
+                    Ok(underlying)
+                }
+                name if get_non_zero_integer(name).is_some() => {
+                    let integer = syn::Ident::new(get_non_zero_integer(name).unwrap(), v.span());
+                    let mut underlying = convert_type_path(&integer.into(), builder)?;
+                    underlying.rust_name = name.to_string();
+                    Ok(underlying)
+                }
+
                 // If the type is not a primitive type, attempt to resolve the type from our type database.
                 _ => {
-                    let out_type = &builder.configuration.out_type;
-                    if out_type.is_some() &&
-                        &v.ident.to_string() == out_type.as_ref().unwrap() {
+                    if builder.configuration.out_types.contains(&v.ident.unraw().to_string()) {
                         return extract_out_parameter_type(v, builder);
                     }
-                    let mut base = resolve_known_type_name(&builder, &v.ident)?;
+                    let mut base = resolve_known_type_path(builder, path, &v.ident)?;
                     if let PathArguments::AngleBracketed(generics) = &v.arguments {
                         for generic in &generics.args {
                             if let GenericArgument::Type(gen) = generic {
@@ -777,21 +6690,76 @@ fn convert_type_path(
             "Types without a path are not supported".to_string(),
             path.span(),
         )),
+    }
+}
+
+/// Converts a mutable pointer or reference to a primitive into an out parameter, if the name of
+/// the parameter starts with one of the configured out parameter prefixes.
+fn convert_named_out_parameter(
+    name: &syn::Ident,
+    ty: &Type,
+    builder: &CSharpBuilder,
+) -> Result<Option<TypeNameContainer>, Error> {
+    let name = name.unraw().to_string();
+    let prefixes = &builder.configuration.out_parameter_prefixes;
+    if !prefixes
+        .iter()
+        .any(|prefix| name.starts_with(prefix.as_str()))
+    {
+        return Ok(None);
+    }
+    let (elem, rust_name) = match ty {
+        Type::Ptr(ptr) if ptr.mutability.is_some() => (ptr.elem.borrow(), "*mut "),
+        Type::Reference(reference) if reference.mutability.is_some() => {
+            (reference.elem.borrow(), "&mut ")
+        }
+        _ => return Ok(None),
     };
+    let inner_type = convert_type_name(elem, builder)?;
+    let is_primitive = inner_type.generics.is_empty()
+        && matches!(
+            inner_type.csharp_name.as_str(),
+            "byte"
+                | "sbyte"
+                | "short"
+                | "ushort"
+                | "int"
+                | "uint"
+                | "long"
+                | "ulong"
+                | "nint"
+                | "nuint"
+                | "IntPtr"
+                | "UIntPtr"
+                | "float"
+                | "double"
+                | "bool"
+        );
+    if !is_primitive {
+        return Ok(None);
+    }
+    Ok(Some(
+        TypeNameContainer::new(
+            "out ".to_string() + inner_type.stringify()?.as_str(),
+            rust_name.to_string() + inner_type.stringify_rust().as_str(),
+        )
+        .with_marshal_as(inner_type.marshal_as),
+    ))
 }
 
 fn extract_out_parameter_type(
     v: &syn::PathSegment,
     builder: &CSharpBuilder,
 ) -> Result<TypeNameContainer, Error> {
-    return match &v.arguments {
+    match &v.arguments {
         PathArguments::AngleBracketed(a) => match a.args.last() {
             Some(GenericArgument::Type(t)) => {
                 let inner_type = convert_type_name(t, builder)?;
                 Ok(TypeNameContainer::new(
                     "out ".to_string() + inner_type.stringify()?.as_str(),
-                    v.ident.to_string(),
-                ))
+                    format!("{}<{}>", v.ident.unraw(), inner_type.stringify_rust()),
+                )
+                .with_marshal_as(inner_type.marshal_as))
             }
             _ => Err(Error::UnsupportedError(
                 "Out type requires the real type to be angle bracketed.".to_string(),
@@ -802,75 +6770,347 @@ fn extract_out_parameter_type(
             "Out type requires the real type to be angle bracketed.".to_string(),
             v.ident.span(),
         )),
-    };
+    }
+}
+
+/// Adds the item that references an unknown type to its error, such as ``parameter 'cfg' of
+/// function 'engine_start'``.
+fn with_type_context(error: Error, context: impl FnOnce() -> String) -> Error {
+    match error {
+        Error::UnknownType(message, span) => {
+            Error::UnknownType(format!("{}, in {}", message, context()), span)
+        }
+        e => e,
+    }
+}
+
+/// The name of a field, which is its index for tuple structs.
+fn field_name(field: &syn::Field, index: usize) -> String {
+    match &field.ident {
+        Some(ident) => ident.unraw().to_string(),
+        None => index.to_string(),
+    }
+}
+
+/// The index of a tuple field among the fields that are written, as zero sized markers are left
+/// out.
+fn tuple_field_index(fields: &syn::Fields, index: usize) -> usize {
+    fields
+        .iter()
+        .take(index)
+        .filter(|f| !is_zero_sized_marker(&f.ty))
+        .count()
+}
+
+/// Whether a field of a struct is hidden from the C# API, either by being skipped or with
+/// [`CSharpConfiguration::hide_struct_field`].
+fn is_hidden_field(
+    strct: &ItemStruct,
+    field: &syn::Field,
+    index: usize,
+    builder: &CSharpBuilder,
+) -> bool {
+    is_skipped(&field.attrs, builder)
+        || builder
+            .configuration
+            .is_hidden_struct_field(&strct.ident.unraw().to_string(), &field_name(field, index))
+}
+
+/// The primitive types that are suggested for unknown types with a similar name.
+const PRIMITIVE_TYPES: &[&str] = &[
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "u128",
+    "usize",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "i128",
+    "isize",
+    "f32",
+    "f64",
+    "bool",
+    "char",
+    "c_char",
+    "c_schar",
+    "c_uchar",
+    "c_short",
+    "c_ushort",
+    "c_int",
+    "c_uint",
+    "c_long",
+    "c_ulong",
+    "c_longlong",
+    "c_ulonglong",
+    "c_float",
+    "c_double",
+    "c_void",
+];
+
+/// Finds the known type or primitive with the name most similar to an unknown type name. Names
+/// are similar if at most a third of their characters differ.
+fn find_similar_type_name(builder: &CSharpBuilder, name: &str) -> Option<String> {
+    let max_distance = (name.chars().count() / 3).max(1);
+    let known_types = builder.known_type_names();
+    PRIMITIVE_TYPES
+        .iter()
+        .map(|primitive| primitive.to_string())
+        .chain(known_types)
+        .map(|candidate| (edit_distance(name, &candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// The Levenshtein distance between two strings: the number of characters that have to be
+/// inserted, removed or replaced to turn one into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let replace = previous[j] + usize::from(a_char != *b_char);
+            current.push(replace.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Resolves a known type by its path. Like Rust, the path is resolved relative to the current
+/// module, and through the ``use`` declarations of the module, to the types registered with their
+/// path in the current build. Otherwise, the type is resolved by its name, unless types with that
+/// name were registered in multiple modules.
+fn resolve_known_type_path(
+    builder: &CSharpBuilder,
+    path: &Path,
+    v: &syn::Ident,
+) -> Result<TypeNameContainer, Error> {
+    let segments = path
+        .segments
+        .iter()
+        .map(|s| s.ident.unraw().to_string())
+        .collect::<Vec<_>>();
+    for candidate in type_path_candidates(builder, &segments) {
+        if let Some(t) = builder.qualified_types.get(&candidate) {
+            return Ok(known_type_name(builder, t.clone(), v));
+        }
+    }
+    let name = v.unraw().to_string();
+    let mut candidates = builder
+        .qualified_types
+        .keys()
+        .filter(|k| k.rsplit("::").next() == Some(name.as_str()))
+        .cloned()
+        .collect::<Vec<_>>();
+    if candidates.len() > 1 {
+        candidates.sort();
+        return Err(Error::AmbiguousType(name, candidates, v.span()));
+    }
+    resolve_known_type_name(builder, v)
+}
+
+/// The paths in the crate a type path could refer to from the current module, in order of
+/// precedence. Names imported with ``use`` come before the path relative to the module.
+fn type_path_candidates(builder: &CSharpBuilder, segments: &[String]) -> Vec<String> {
+    let module = &builder.module_path;
+    let mut candidates = Vec::new();
+    if let ([name], Some(items)) = (segments, module_items(&builder.tokens.items, module)) {
+        for (imported_name, mut path) in use_declarations(items) {
+            match imported_name {
+                Some(imported_name) if imported_name == *name => {}
+                // Glob imports could contain any name.
+                None => path.push(name.clone()),
+                Some(_) => continue,
+            }
+            candidates.extend(canonicalize_path(&path, module));
+        }
+    }
+    candidates.extend(canonicalize_path(segments, module));
+    candidates
+}
+
+/// Converts a path used in a module into the paths in the crate it could refer to. Paths starting
+/// with ``crate``, ``self`` or ``super`` are unambiguous, other paths are either relative to the
+/// module, or to the crate root.
+fn canonicalize_path(segments: &[String], module: &[String]) -> Vec<String> {
+    match segments.first().map(|s| s.as_str()) {
+        Some("crate") => vec![segments[1..].join("::")],
+        Some("self") | Some("super") => {
+            let mut path = module.to_vec();
+            let mut rest = segments;
+            while let Some((first, remaining)) = rest.split_first() {
+                match first.as_str() {
+                    "self" => {}
+                    "super" => {
+                        path.pop();
+                    }
+                    _ => break,
+                }
+                rest = remaining;
+            }
+            path.extend(rest.iter().cloned());
+            vec![path.join("::")]
+        }
+        _ => {
+            let mut relative = module.to_vec();
+            relative.extend(segments.iter().cloned());
+            let mut paths = vec![relative.join("::")];
+            if !module.is_empty() {
+                paths.push(segments.join("::"));
+            }
+            paths
+        }
+    }
+}
+
+/// Gets the items of a module by its path.
+fn module_items<'a>(items: &'a [Item], module: &[String]) -> Option<&'a [Item]> {
+    match module.split_first() {
+        None => Some(items),
+        Some((name, rest)) => items.iter().find_map(|item| match item {
+            Item::Mod(m) if m.ident == name => module_items(&m.content.as_ref()?.1, rest),
+            _ => None,
+        }),
+    }
+}
+
+/// Gets the names imported by the ``use`` declarations in a module, with the path they refer to.
+/// Glob imports have no name, and the path of the module they import from.
+fn use_declarations(items: &[Item]) -> Vec<(Option<String>, Vec<String>)> {
+    fn collect(
+        tree: &UseTree,
+        prefix: &mut Vec<String>,
+        out: &mut Vec<(Option<String>, Vec<String>)>,
+    ) {
+        match tree {
+            UseTree::Path(p) => {
+                prefix.push(p.ident.unraw().to_string());
+                collect(&p.tree, prefix, out);
+                prefix.pop();
+            }
+            // `use foo::{self}` imports the module itself.
+            UseTree::Name(n) if n.ident == "self" => {
+                if let Some(last) = prefix.last() {
+                    out.push((Some(last.clone()), prefix.clone()));
+                }
+            }
+            UseTree::Name(n) => {
+                let mut path = prefix.clone();
+                path.push(n.ident.unraw().to_string());
+                out.push((Some(n.ident.unraw().to_string()), path));
+            }
+            UseTree::Rename(r) => {
+                let mut path = prefix.clone();
+                path.push(r.ident.unraw().to_string());
+                out.push((Some(r.rename.unraw().to_string()), path));
+            }
+            UseTree::Glob(_) => out.push((None, prefix.clone())),
+            UseTree::Group(g) => {
+                for tree in &g.items {
+                    collect(tree, prefix, out);
+                }
+            }
+        }
+    }
+    let mut declarations = Vec::new();
+    for item in items {
+        if let Item::Use(u) = item {
+            collect(&u.tree, &mut Vec::new(), &mut declarations);
+        }
+    }
+    declarations
 }
 
 fn resolve_known_type_name(
     builder: &CSharpBuilder,
     v: &syn::Ident,
 ) -> Result<TypeNameContainer, Error> {
-    let conf = &builder.configuration;
-    let t = conf.get_known_type(v.to_string().as_str());
+    let t = builder.get_known_type(v.unraw().to_string().as_str());
     match t {
         None => Err(Error::UnknownType(
-            format!("Type with name '{}' was not found", v.to_string()),
+            match find_similar_type_name(builder, &v.unraw().to_string()) {
+                Some(similar) => format!(
+                    "Type with name '{}' was not found (did you mean '{}'?)",
+                    v, similar
+                ),
+                None => format!("Type with name '{}' was not found", v),
+            },
             v.span(),
         )),
-        Some(t) => {
-            let inside_type = &builder.type_name;
-            if builder.namespace == t.namespace
-                && (*inside_type == t.inside_type || t.inside_type.is_none())
-            {
-                Ok(TypeNameContainer::new(
-                    t.real_type_name.to_string(),
-                    v.to_string(),
-                ))
-            } else if builder.namespace == t.namespace {
-                Ok(TypeNameContainer::new(
-                    t.inside_type.as_ref().unwrap().to_string()
-                        + "."
-                        + &*t.real_type_name.to_string(),
-                    v.to_string(),
-                ))
-            } else if t.inside_type.is_none() {
-                if t.namespace.is_none() {
-                    Ok(TypeNameContainer::new(
-                        t.real_type_name.to_string(),
-                        v.to_string(),
-                    ))
-                } else {
-                    Ok(TypeNameContainer::new(
-                        t.namespace.as_ref().unwrap().to_string()
-                            + "."
-                            + &*t.real_type_name.to_string(),
-                        v.to_string(),
-                    ))
-                }
-            } else if t.namespace.is_none() {
-                Ok(TypeNameContainer::new(
-                    t.inside_type.as_ref().unwrap().to_string()
-                        + "."
-                        + t.real_type_name.to_string().as_str(),
-                    v.to_string(),
-                ))
-            } else {
-                Ok(TypeNameContainer::new(
-                    t.namespace.as_ref().unwrap().to_string()
-                        + "."
-                        + t.inside_type.as_ref().unwrap().to_string().as_str()
-                        + "."
-                        + t.real_type_name.to_string().as_str(),
-                    v.to_string(),
-                ))
+        Some(t) => Ok(known_type_name(builder, t, v)),
+    }
+}
+
+/// Converts a known type into its C# name, qualified with its namespace and type where needed.
+fn known_type_name(builder: &CSharpBuilder, t: CSharpType, v: &syn::Ident) -> TypeNameContainer {
+    let inside_type = &builder.type_name;
+    let type_name = if builder.namespace == t.namespace
+        && (*inside_type == t.inside_type || t.inside_type.is_none())
+    {
+        TypeNameContainer::new(t.real_type_name.to_string(), v.unraw().to_string())
+    } else if builder.namespace == t.namespace {
+        TypeNameContainer::new(
+            t.inside_type.as_ref().unwrap().to_string() + "." + &*t.real_type_name.to_string(),
+            v.unraw().to_string(),
+        )
+    } else {
+        match (&t.namespace, &t.inside_type) {
+            (None, None) => {
+                TypeNameContainer::new(t.real_type_name.to_string(), v.unraw().to_string())
             }
+            (Some(namespace), None) => TypeNameContainer::new(
+                namespace.to_string() + "." + &*t.real_type_name.to_string(),
+                v.unraw().to_string(),
+            ),
+            (None, Some(inside_type)) => TypeNameContainer::new(
+                inside_type.to_string() + "." + t.real_type_name.to_string().as_str(),
+                v.unraw().to_string(),
+            ),
+            (Some(namespace), Some(inside_type)) => TypeNameContainer::new(
+                namespace.to_string()
+                    + "."
+                    + inside_type.to_string().as_str()
+                    + "."
+                    + t.real_type_name.to_string().as_str(),
+                v.unraw().to_string(),
+            ),
         }
-    }
+    };
+    type_name.with_marshal_as(t.marshal_as)
 }
 
-fn write_line(str: &mut String, content: String, indents: i32) -> Result<(), Error> {
-    for _ in 0..indents {
-        write!(str, "    ")?;
+/// Writes user provided code at the given indentation. The indentation the lines have in common is
+/// removed first, so the code keeps its own relative indentation.
+fn write_verbatim(str: &mut CodeWriter, code: &str, indents: i32) -> Result<(), Error> {
+    let mut lines = code.lines().map(|line| line.trim_end()).collect::<Vec<_>>();
+    // Leading and trailing empty lines are left out, so raw strings can start on a new line.
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    let start = lines.iter().take_while(|line| line.is_empty()).count();
+    let lines = &lines[start..];
+    let common_indent = lines
+        .iter()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    for line in lines {
+        match line.is_empty() {
+            true => writeln!(str)?,
+            false => write_line(str, line[common_indent..].to_string(), indents)?,
+        }
     }
+    Ok(())
+}
+
+fn write_line(str: &mut CodeWriter, content: String, indents: i32) -> Result<(), Error> {
+    str.write_indent(indents)?;
     str.write_str(&content)?;
     writeln!(str)?;
     Ok(())
@@ -0,0 +1,253 @@
+//! Generates C# bindings as described by a ``csharp_binder.toml`` file. This drives the
+//! ``csharp_binder`` binary, for projects that don't want to generate bindings from a build script.
+//!
+//! A configuration looks like this, where relative paths are relative to the configuration file:
+//! ```toml
+//! inputs = ["src/ffi.rs", "src/ffi/**/*.rs"]
+//! dll_name = "foo"
+//! namespace = "Foo"
+//! class_name = "NativeMethods"
+//! csharp_version = 9
+//! out_types = ["Out"]
+//! ignore = ["__test_*"]
+//! output = "bindings/NativeMethods.cs"
+//!
+//! [[known_types]]
+//! rust_name = "Handle"
+//! namespace = "Foo.Interop"
+//! csharp_name = "SafeFooHandle"
+//! ```
+//!
+//! Instead of ``output``, ``output_directory`` can be set to write a file for each enum, struct
+//! and union, as [`CSharpBuilder::build_split_to_directory`] does.
+
+use crate::builder::compare_scripts;
+use crate::{CSharpBuilder, CSharpConfiguration, Error, KnownTypeEntry, VerifyError, Warning};
+use serde::Deserialize;
+use std::fmt::Formatter;
+use std::path::{Path, PathBuf};
+
+/// The configuration file the binary reads if no other file is given.
+pub const DEFAULT_CONFIG_FILE: &str = "csharp_binder.toml";
+
+/// The contents of a ``csharp_binder.toml`` file.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The Rust files to generate bindings for, or glob patterns matching them, such as
+    /// ``src/ffi/**/*.rs``. The items of all files are generated into the same script.
+    pub inputs: Vec<String>,
+    /// The name of the library C# is going to make calls to.
+    pub dll_name: String,
+    /// The namespace to generate the script in.
+    pub namespace: Option<String>,
+    /// The type that is wrapped around the generated script.
+    pub class_name: Option<String>,
+    /// The C# version to generate code for.
+    pub csharp_version: u8,
+    /// Types the converter should know about, see [`CSharpConfiguration::add_known_type`].
+    #[serde(default)]
+    pub known_types: Vec<KnownTypeEntry>,
+    /// Rust types that represent out parameters, see [`CSharpConfiguration::add_out_type`].
+    #[serde(default)]
+    pub out_types: Vec<String>,
+    /// Patterns of item names to ignore, see [`CSharpConfiguration::add_ignore_pattern`].
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// The file to write the script to.
+    pub output: Option<PathBuf>,
+    /// The directory to write a file for each enum, struct and union to, along with a file for
+    /// the type with everything else.
+    pub output_directory: Option<PathBuf>,
+}
+
+impl Config {
+    /// Parses a configuration from the contents of a ``csharp_binder.toml`` file.
+    pub fn parse(contents: &str) -> Result<Config, CliError> {
+        let config: Config =
+            toml::from_str(contents).map_err(|e| CliError::Config(e.to_string()))?;
+        match (&config.output, &config.output_directory) {
+            (None, None) => Err(CliError::Config(
+                "Either 'output' or 'output_directory' has to be set".to_string(),
+            )),
+            (Some(_), Some(_)) => Err(CliError::Config(
+                "Only one of 'output' and 'output_directory' can be set".to_string(),
+            )),
+            _ if config.inputs.is_empty() => {
+                Err(CliError::Config("No inputs are given".to_string()))
+            }
+            _ => Ok(config),
+        }
+    }
+
+    /// Reads and parses a configuration file.
+    pub fn load(path: &Path) -> Result<Config, CliError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| CliError::Config(format!("Failed to read {}: {}", path.display(), e)))?;
+        Config::parse(&contents)
+            .map_err(|e| CliError::Config(format!("In {}: {}", path.display(), e.message())))
+    }
+}
+
+/// An error of the ``csharp_binder`` binary.
+#[derive(Debug)]
+pub enum CliError {
+    /// The configuration is invalid, or could not be read.
+    Config(String),
+    /// An input file could not be read or parsed, or converting it failed.
+    Build(Error),
+    /// When checking, the existing bindings differ from the generated bindings.
+    Mismatch(String),
+}
+
+impl CliError {
+    fn message(&self) -> String {
+        match self {
+            CliError::Config(message) | CliError::Mismatch(message) => message.to_string(),
+            CliError::Build(e) => e.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message())
+    }
+}
+
+impl std::error::Error for CliError {}
+
+impl From<Error> for CliError {
+    fn from(error: Error) -> Self {
+        CliError::Build(error)
+    }
+}
+
+/// Generates the bindings described by a configuration file. If ``check`` is set, nothing is
+/// written, and the existing bindings are compared with the generated bindings instead, like
+/// [`CSharpBuilder::verify_against`]. Returns the warnings raised during the build.
+pub fn run(config_path: &Path, check: bool) -> Result<Vec<Warning>, CliError> {
+    let config = Config::load(config_path)?;
+    let base_directory = config_path.parent().unwrap_or_else(|| Path::new(""));
+    generate(&config, base_directory, check)
+}
+
+/// Generates the bindings described by a configuration, with paths relative to a base directory,
+/// like [`run`].
+pub fn generate(
+    config: &Config,
+    base_directory: &Path,
+    check: bool,
+) -> Result<Vec<Warning>, CliError> {
+    let mut configuration = CSharpConfiguration::new(config.csharp_version);
+    configuration.add_known_types(config.known_types.iter().cloned())?;
+    for out_type in &config.out_types {
+        configuration.add_out_type(out_type);
+    }
+    for pattern in &config.ignore {
+        configuration.add_ignore_pattern(pattern).map_err(|e| {
+            CliError::Config(format!("Invalid ignore pattern '{}': {}", pattern, e))
+        })?;
+    }
+
+    let inputs = resolve_inputs(&config.inputs, base_directory)?;
+    let mut builder = CSharpBuilder::new("", &config.dll_name, &configuration)?;
+    for input in &inputs {
+        let name = input.display().to_string();
+        let script = std::fs::read_to_string(input)
+            .map_err(|e| CliError::Config(format!("Failed to read {}: {}", name, e)))?;
+        builder.add_named_script(&name, &script)?;
+    }
+    // The name of a single input is used in the generated warning.
+    if let [input] = inputs.as_slice() {
+        builder.set_source_name(&input.display().to_string());
+    }
+    if let Some(namespace) = &config.namespace {
+        builder.set_namespace(namespace);
+    }
+    if let Some(class_name) = &config.class_name {
+        builder.set_type(class_name);
+    }
+
+    match (&config.output, &config.output_directory) {
+        (Some(output), _) => {
+            let output = base_directory.join(output);
+            if check {
+                match builder.verify_against_path(&output) {
+                    Ok(()) => {}
+                    Err(VerifyError::Error(e)) => return Err(CliError::Build(e)),
+                    Err(e @ VerifyError::Mismatch(_)) => {
+                        return Err(CliError::Mismatch(format!("{}: {}", output.display(), e)))
+                    }
+                }
+            } else {
+                let script = builder.build()?;
+                if let Some(parent) = output.parent() {
+                    std::fs::create_dir_all(parent).map_err(Error::from)?;
+                }
+                std::fs::write(&output, script).map_err(Error::from)?;
+            }
+        }
+        (None, Some(directory)) => {
+            let directory = base_directory.join(directory);
+            if check {
+                let outdated = builder
+                    .build_split()?
+                    .into_iter()
+                    .filter(|file| {
+                        std::fs::read_to_string(directory.join(&file.suggested_name)).map_or(
+                            true,
+                            |existing| {
+                                compare_scripts(&configuration, &existing, &file.contents).is_err()
+                            },
+                        )
+                    })
+                    .map(|file| file.suggested_name)
+                    .collect::<Vec<_>>();
+                if !outdated.is_empty() {
+                    return Err(CliError::Mismatch(format!(
+                        "The generated files differ from the files in {}: {}",
+                        directory.display(),
+                        outdated.join(", ")
+                    )));
+                }
+            } else {
+                builder.build_split_to_directory(&directory)?;
+            }
+        }
+        (None, None) => {
+            return Err(CliError::Config(
+                "Either 'output' or 'output_directory' has to be set".to_string(),
+            ))
+        }
+    }
+    Ok(std::mem::take(&mut builder.warnings))
+}
+
+/// Resolves the input paths and glob patterns of a configuration into the files they refer to.
+/// The files matching a pattern are sorted, so the output doesn't depend on the file system.
+fn resolve_inputs(inputs: &[String], base_directory: &Path) -> Result<Vec<PathBuf>, CliError> {
+    let mut paths = Vec::new();
+    for input in inputs {
+        let path = base_directory.join(input);
+        if !input.contains(['*', '?', '[']) {
+            paths.push(path);
+            continue;
+        }
+        let pattern = path.to_string_lossy();
+        let mut matches = glob::glob(&pattern)
+            .map_err(|e| CliError::Config(format!("Invalid input pattern '{}': {}", input, e)))?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.is_file())
+            .collect::<Vec<_>>();
+        if matches.is_empty() {
+            return Err(CliError::Config(format!(
+                "The input pattern '{}' matches no files",
+                input
+            )));
+        }
+        matches.sort();
+        paths.extend(matches);
+    }
+    Ok(paths)
+}
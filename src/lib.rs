@@ -5,15 +5,21 @@
 //! without having to write the extern C# functions yourself.
 //!
 //! CSharp_Binder will when given a Rust script, parse this script, and extract any functions marked as
-//! extern "C", enums with a ``[repr(u*)]`` attribute, and structs with a ``#[repr(C)]`` attribute. It
-//! will then convert these into appropriate representations in C#.
+//! extern "C", enums with a ``[repr(u*)]`` attribute, structs with a ``#[repr(C)]`` attribute, and
+//! public constants of primitive types. It will then convert these into appropriate representations
+//! in C#.
 //!
 //! CSharp_Binder will also extract Rust documentation on functions, enums and their variants, and
 //! on structs and their fields, and convert it into XML Documentation on the generated C# code.
 //!
 //! Note that CSharp_Binder uses syn to parse Rust scripts, so macros will not be expanded! If you
 //! have functions, structs, or enums that need to be extracted inside macros, make sure to run them
-//! to something like cargo-expand first.
+//! to something like cargo-expand first. With the ``expand`` feature, a crate can be expanded and
+//! loaded with ``CSharpBuilder::from_expanded_crate``.
+//!
+//! With the ``cli`` feature, the ``csharp_binder`` binary generates bindings as described by a
+//! ``csharp_binder.toml`` file, without a build script. See the ``cli`` module for its format.
+//! Running it with ``--check`` fails if the existing bindings are outdated, which is useful in CI.
 //!
 //! # Examples
 //!
@@ -21,35 +27,33 @@
 //! ```
 //! use csharp_binder::{CSharpConfiguration, CSharpBuilder};
 //!
-//! fn main(){
-//!     // Create C# configuration with C# target version 9.
-//!     let mut configuration = CSharpConfiguration::new(9);
-//!     let rust_file = r#"
-//!     /// Just a random return enum
-//!     #[repr(u8)]
-//!     enum ReturnEnum {
-//!         Val1,
-//!         Val2,
-//!     }
-//!     
-//!     /// An input struct we expect
-//!     #[repr(C)]
-//!     struct InputStruct {
-//!         field_a: u16,
-//!         /// This field is used for floats!
-//!         field_b: f64,
-//!     }
-//!     
-//!     pub extern "C" fn foo(a: InputStruct) -> ReturnEnum {
-//!     }
-//!     "#;
-//!     let mut builder = CSharpBuilder::new(rust_file, "foo", &mut configuration)
-//!                         .expect("Failed to parse file");
-//!     builder.set_namespace("MainNamespace");
-//!     builder.set_type("InsideClass");
-//!     let script = builder.build().expect("Failed to build");
+//! // Create C# configuration with C# target version 9.
+//! let configuration = CSharpConfiguration::new(9);
+//! let rust_file = r#"
+//! /// Just a random return enum
+//! #[repr(u8)]
+//! enum ReturnEnum {
+//!     Val1,
+//!     Val2,
+//! }
+//!
+//! /// An input struct we expect
+//! #[repr(C)]
+//! struct InputStruct {
+//!     field_a: u16,
+//!     /// This field is used for floats!
+//!     field_b: f64,
+//! }
+//!
+//! pub extern "C" fn foo(a: InputStruct) -> ReturnEnum {
 //! }
-//!```
+//! "#;
+//! let mut builder = CSharpBuilder::new(rust_file, "foo", &configuration)
+//!                     .expect("Failed to parse file");
+//! builder.set_namespace("MainNamespace");
+//! builder.set_type("InsideClass");
+//! let script = builder.build().expect("Failed to build");
+//! ```
 //!
 //! This would return the following C# code:
 //!
@@ -85,6 +89,8 @@
 //!             /// <remarks>f64</remarks>
 //!             public double FieldB { get; init; }
 //!
+//!             /// <param name="fieldA">u16</param>
+//!             /// <param name="fieldB">This field is used for floats!</param>
 //!             public InputStruct(ushort fieldA, double fieldB)
 //!             {
 //!                 FieldA = fieldA;
@@ -101,91 +107,1162 @@
 //! }
 //! ```
 //!
-use crate::builder::{build_csharp, parse_script};
-use std::collections::HashMap;
+use crate::builder::{
+    build_csharp, build_csharp_split, build_layout_tests, parse_script, register_types,
+    verify_script, EnumExtensions, GroupedItem,
+};
+#[cfg(feature = "expand")]
+use crate::builder::{expand_crate, run_command};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Formatter;
+use std::path::{Path, PathBuf};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use syn::spanned::Spanned;
 
 mod builder;
+#[cfg(feature = "cli")]
+pub mod cli;
 
 #[cfg(test)]
 mod tests;
 
+/// The first line of exported known types, which identifies the format.
+const KNOWN_TYPES_HEADER: &str = "csharp_binder known types 1";
+
+#[derive(Clone, PartialEq, Eq)]
 pub(crate) struct CSharpType {
     pub namespace: Option<String>,
     pub inside_type: Option<String>,
     pub real_type_name: String,
+    /// The ``MarshalAs`` value of the type, for types that are registered as another type that
+    /// needs it, such as transparent structs around a bool.
+    pub marshal_as: Option<String>,
 }
 
-/// This struct holds the generic data used between multiple builds. Currently this only holds the
-/// type registry, but further features such as ignore patterns will likely be added here.
+/// This struct holds the generic data used between multiple builds, such as the type registry and
+/// the patterns of items to ignore.
+///
+/// The configuration can be shared between builders running on different threads, as the type
+/// registry is behind a lock.
 pub struct CSharpConfiguration {
-    known_types: HashMap<String, CSharpType>,
+    known_types: RwLock<HashMap<String, CSharpType>>,
     csharp_version: u8,
-    out_type: Option<String>,
+    csharp_minor_version: u8,
+    out_types: HashSet<String>,
+    out_parameter_prefixes: Vec<String>,
     generated_warning: String,
+    allow_bool_marshaling: bool,
+    use_fixed_buffers: bool,
+    tuple_field_prefix: String,
+    emit_transparent_wrappers: bool,
+    use_unsafe_pointers: bool,
+    opaque_handles: HashMap<String, String>,
+    use_library_import: Option<bool>,
+    default_calling_convention: CallingConvention,
+    require_no_mangle: bool,
+    ignore_patterns: Vec<Regex>,
+    include_patterns: Vec<Regex>,
+    skip_attribute: Option<String>,
+    flags_enums: HashMap<String, bool>,
+    detect_flags_enums: bool,
+    use_file_scoped_namespace: Option<bool>,
+    indentation: Indentation,
+    newline: Newline,
+    function_prefix_strip: Option<String>,
+    naming_converter: Option<NamingConverter>,
+    renamed_symbols: HashMap<String, String>,
+    disambiguate_duplicate_names: bool,
+    type_remarks: TypeRemarks,
+    generate_safe_wrappers: bool,
+    marshal_utf8_strings: bool,
+    utf8_string_types: HashSet<String>,
+    c_long_size: CLongSize,
+    rust_char_mapping: CSharpCharMapping,
+    c_char_signed: bool,
+    pointer_width: Option<PointerWidth>,
+    map_u128_as_pair: bool,
+    map_u128_as_big_integer: bool,
+    emit_unmanaged_constraints: bool,
+    map_impl_receivers: bool,
+    nest_modules_as_classes: bool,
+    cfg_options: Option<CfgOptions>,
+    read_statics_from_library: bool,
+    resolve_dll_names_at_runtime: bool,
+    target_runtime: TargetRuntime,
+    accessibility: AccessibilityOptions,
+    emit_partial_class: bool,
+    extra_attributes: HashMap<String, Vec<String>>,
+    dllimport_defaults: DllImportOptions,
+    dllimport_options: HashMap<String, DllImportOptions>,
+    max_return_struct_size: Option<usize>,
+    deny_large_return_structs: bool,
+    emit_auto_generated_marker: bool,
+    emit_generated_code_attribute: bool,
+    generated_code_version: String,
+    pragma_suppressions: Vec<String>,
+    deny_type_conflicts: bool,
+    output_ordering: Ordering,
+    emit_regions: bool,
+    allow_arglist_variadics: bool,
+    monomorphizations: HashMap<String, Vec<Vec<(String, String)>>>,
+    monomorphized_entry_points: HashMap<String, String>,
+    struct_constructor: ConstructorMode,
+    default_constructor_parameters: bool,
+    generate_struct_equality: bool,
+    struct_style: StructStyle,
+    generate_to_string: bool,
+    generate_enum_helpers: bool,
+    generate_enum_try_from_raw: bool,
+    emit_enum_remarks: bool,
+    hidden_struct_fields: HashMap<String, HashSet<String>>,
+    convert_enum_variant_names: bool,
+    renamed_enum_variants: HashMap<String, HashMap<String, String>>,
+    warn_non_blittable: bool,
+    slice_parameters: HashMap<String, Vec<(String, String)>>,
+    detect_slice_parameters: bool,
+    emit_nullable_annotations: bool,
+    emit_nullable_directive: bool,
 }
 
 impl CSharpConfiguration {
     /// Create a new C# configuration. Input parameter is the target version of C#, i.e. C# 7, 8, 9, etc.
     pub fn new(csharp_version: u8) -> Self {
         Self {
-            known_types: HashMap::new(),
+            known_types: RwLock::new(HashMap::new()),
             csharp_version,
-            out_type: None,
+            csharp_minor_version: 0,
+            out_types: HashSet::new(),
+            out_parameter_prefixes: Vec::new(),
             generated_warning: "Automatically generated, do not edit!".to_string(),
+            allow_bool_marshaling: false,
+            use_fixed_buffers: false,
+            tuple_field_prefix: "Item".to_string(),
+            emit_transparent_wrappers: false,
+            use_unsafe_pointers: false,
+            opaque_handles: HashMap::new(),
+            use_library_import: None,
+            default_calling_convention: CallingConvention::Cdecl,
+            require_no_mangle: false,
+            ignore_patterns: Vec::new(),
+            include_patterns: Vec::new(),
+            skip_attribute: None,
+            flags_enums: HashMap::new(),
+            detect_flags_enums: false,
+            use_file_scoped_namespace: None,
+            indentation: Indentation::Spaces(4),
+            newline: Newline::Lf,
+            function_prefix_strip: None,
+            naming_converter: None,
+            renamed_symbols: HashMap::new(),
+            disambiguate_duplicate_names: false,
+            type_remarks: TypeRemarks::Remarks,
+            generate_safe_wrappers: false,
+            marshal_utf8_strings: false,
+            utf8_string_types: HashSet::from(["c_char".to_string()]),
+            c_long_size: CLongSize::Bits64,
+            rust_char_mapping: CSharpCharMapping::UInt,
+            c_char_signed: true,
+            pointer_width: None,
+            map_u128_as_pair: false,
+            map_u128_as_big_integer: false,
+            emit_unmanaged_constraints: true,
+            map_impl_receivers: false,
+            nest_modules_as_classes: false,
+            cfg_options: None,
+            read_statics_from_library: false,
+            resolve_dll_names_at_runtime: false,
+            target_runtime: TargetRuntime::DotNet,
+            accessibility: AccessibilityOptions::default(),
+            emit_partial_class: false,
+            extra_attributes: HashMap::new(),
+            dllimport_defaults: DllImportOptions::default(),
+            dllimport_options: HashMap::new(),
+            max_return_struct_size: None,
+            deny_large_return_structs: false,
+            emit_auto_generated_marker: false,
+            emit_generated_code_attribute: false,
+            generated_code_version: env!("CARGO_PKG_VERSION").to_string(),
+            pragma_suppressions: Vec::new(),
+            deny_type_conflicts: false,
+            output_ordering: Ordering::SourceOrder,
+            emit_regions: false,
+            allow_arglist_variadics: false,
+            monomorphizations: HashMap::new(),
+            monomorphized_entry_points: HashMap::new(),
+            struct_constructor: ConstructorMode::NonEmptyOnly,
+            default_constructor_parameters: false,
+            generate_struct_equality: false,
+            struct_style: StructStyle::Classic,
+            generate_to_string: false,
+            generate_enum_helpers: false,
+            generate_enum_try_from_raw: false,
+            emit_enum_remarks: false,
+            hidden_struct_fields: HashMap::new(),
+            convert_enum_variant_names: false,
+            renamed_enum_variants: HashMap::new(),
+            warn_non_blittable: false,
+            slice_parameters: HashMap::new(),
+            detect_slice_parameters: false,
+            emit_nullable_annotations: false,
+            emit_nullable_directive: false,
         }
     }
 
+    /// Sets the minor target version of C#, i.e. 2 for C# 7.2. Defaults to 0.
+    pub fn set_csharp_minor_version(&mut self, csharp_minor_version: u8) {
+        self.csharp_minor_version = csharp_minor_version;
+    }
+
     /// Register a type the converter should know about.
     ///
     /// Useful if you use a type on the Rust side that you know has a C# representation without first
     /// passing it through the C#builder. This function takes the Rust type name, along with an optional
     /// C# namespace, optional containing type, and the actual C# type name.
+    ///
+    /// Returns whether the type was new, or replaced an existing type. If
+    /// [`CSharpConfiguration::deny_type_conflicts`] is set, replacing a type with a different C#
+    /// type fails with an [`Error::DuplicateSymbol`] instead, and the existing type is kept.
     pub fn add_known_type(
-        &mut self,
+        &self,
         rust_type_name: &str,
         csharp_namespace: Option<String>,
         csharp_inside_type: Option<String>,
         csharp_type_name: String,
-    ) {
-        self.known_types.insert(
-            rust_type_name.to_string(),
-            CSharpType {
-                namespace: csharp_namespace,
-                inside_type: csharp_inside_type,
-                real_type_name: csharp_type_name,
-            },
-        );
+    ) -> Result<KnownTypeUpdate, Error> {
+        let t = CSharpType {
+            namespace: csharp_namespace,
+            inside_type: csharp_inside_type,
+            real_type_name: csharp_type_name,
+            marshal_as: None,
+        };
+        let mut known_types = self.write_known_types();
+        let update = match known_types.get(rust_type_name) {
+            None => KnownTypeUpdate::Inserted,
+            Some(existing) if *existing == t => KnownTypeUpdate::ReplacedIdentical,
+            Some(_) if self.deny_type_conflicts => {
+                return Err(Error::DuplicateSymbol(
+                    rust_type_name.to_string(),
+                    proc_macro2::Span::call_site(),
+                ))
+            }
+            Some(_) => KnownTypeUpdate::ReplacedDifferent,
+        };
+        known_types.insert(rust_type_name.to_string(), t);
+        Ok(update)
+    }
+
+    /// Registers multiple types the converter should know about, like
+    /// [`CSharpConfiguration::add_known_type`]. Stops at the first type that fails to register.
+    pub fn add_known_types(
+        &self,
+        entries: impl IntoIterator<Item = KnownTypeEntry>,
+    ) -> Result<(), Error> {
+        for entry in entries {
+            self.add_known_type(
+                &entry.rust_name,
+                entry.namespace,
+                entry.inside_type,
+                entry.csharp_name,
+            )?;
+        }
+        Ok(())
     }
+
+    /// Registers the handle types commonly used in FFI crates as ``IntPtr`` or ``UIntPtr``:
+    /// - The Windows handles of the ``winapi`` and ``windows-sys`` crates: ``HANDLE``, ``HWND``,
+    ///   ``HINSTANCE``, ``HMODULE``, ``HDC``, ``HKEY``, ``HMENU`` and ``HICON``.
+    /// - The Windows pointer aliases ``PVOID``, ``LPVOID`` and ``LPCVOID``.
+    /// - The Windows message parameters ``WPARAM`` as ``UIntPtr``, and ``LPARAM`` and ``LRESULT``.
+    /// - ``GCHandle``, for ``usize`` aliases holding the result of ``GCHandle.ToIntPtr``.
+    ///
+    /// Pointers to ``c_void`` are converted into ``IntPtr`` without registering them.
+    pub fn register_dotnet_interop_defaults(&self) -> Result<(), Error> {
+        self.add_known_types(
+            DOTNET_INTEROP_DEFAULTS
+                .iter()
+                .map(|(rust_name, csharp_name)| KnownTypeEntry {
+                    rust_name: rust_name.to_string(),
+                    namespace: None,
+                    inside_type: None,
+                    csharp_name: csharp_name.to_string(),
+                }),
+        )
+    }
+
+    /// Removes a type from the type registry. Returns whether the type was known.
+    pub fn remove_known_type(&self, rust_type_name: &str) -> bool {
+        self.write_known_types().remove(rust_type_name).is_some()
+    }
+
+    /// Returns the Rust names of the types in the type registry, in alphabetical order. As the
+    /// registry can be changed by builders on other threads, the names are copied.
+    pub fn known_type_names(&self) -> impl Iterator<Item = String> {
+        let mut names = self.read_known_types().keys().cloned().collect::<Vec<_>>();
+        names.sort();
+        names.into_iter()
+    }
+
+    /// Returns the C# namespace, containing type and C# name of a type in the type registry.
+    pub fn get_known_type_info(
+        &self,
+        rust_type_name: &str,
+    ) -> Option<(Option<String>, Option<String>, String)> {
+        self.get_known_type(rust_type_name)
+            .map(|t| (t.namespace, t.inside_type, t.real_type_name))
+    }
+
+    /// Fails [`CSharpConfiguration::add_known_type`] when it would replace a type with a different
+    /// C# type, instead of replacing it. Defaults to false.
+    pub fn deny_type_conflicts(&mut self, deny: bool) {
+        self.deny_type_conflicts = deny;
+    }
+
+    /// Exports the type registry, so it can be loaded into the configuration of another build with
+    /// [`CSharpConfiguration::import_known_types`]. This allows builds in different processes, such
+    /// as the build scripts of multiple crates, to use each other's types. Each type is written on
+    /// its own line, with the Rust name, namespace, type and C# name separated by tabs, sorted by
+    /// the Rust name.
+    pub fn export_known_types(&self) -> String {
+        let known_types = self.read_known_types();
+        let mut names = known_types.keys().collect::<Vec<_>>();
+        names.sort();
+        let mut data = KNOWN_TYPES_HEADER.to_string() + "\n";
+        for name in names {
+            let t = &known_types[name];
+            data += &format!(
+                "{}\t{}\t{}\t{}",
+                name,
+                t.namespace.as_deref().unwrap_or_default(),
+                t.inside_type.as_deref().unwrap_or_default(),
+                t.real_type_name
+            );
+            // The marshalling is only written when set, so other types keep four columns.
+            if let Some(marshal_as) = &t.marshal_as {
+                data += &format!("\t{}", marshal_as);
+            }
+            data += "\n";
+        }
+        data
+    }
+
+    /// Loads types exported with [`CSharpConfiguration::export_known_types`] into the type
+    /// registry. Types that are already known with a different C# type fail with an
+    /// [`Error::DuplicateSymbol`], in which case no types are loaded. Data that wasn't exported
+    /// by this function fails with an [`Error::IOError`].
+    pub fn import_known_types(&self, data: &str) -> Result<(), Error> {
+        let invalid_data = |message: String| {
+            Error::IOError(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                message,
+            ))
+        };
+        let mut lines = data.lines();
+        if lines.next() != Some(KNOWN_TYPES_HEADER) {
+            return Err(invalid_data(
+                "The data does not start with the header of exported known types".to_string(),
+            ));
+        }
+        let mut imported = Vec::new();
+        for (index, line) in lines.enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let fields = line.split('\t').collect::<Vec<_>>();
+            if !(4..=5).contains(&fields.len()) || fields[0].is_empty() || fields[3].is_empty() {
+                return Err(invalid_data(format!(
+                    "Line {} of the exported known types is not a known type",
+                    index + 2
+                )));
+            }
+            let optional = |field: &str| match field.is_empty() {
+                true => None,
+                false => Some(field.to_string()),
+            };
+            imported.push((
+                fields[0].to_string(),
+                CSharpType {
+                    namespace: optional(fields[1]),
+                    inside_type: optional(fields[2]),
+                    real_type_name: fields[3].to_string(),
+                    marshal_as: fields.get(4).and_then(|field| optional(field)),
+                },
+            ));
+        }
+
+        let mut known_types = self.write_known_types();
+        for (name, t) in &imported {
+            match known_types.get(name) {
+                Some(existing) if existing != t => {
+                    return Err(Error::DuplicateSymbol(
+                        name.to_string(),
+                        proc_macro2::Span::call_site(),
+                    ))
+                }
+                _ => {}
+            }
+        }
+        known_types.extend(imported);
+        Ok(())
+    }
+
     /// Sets a rust type to represent an out parameter in C#.
     ///
     /// This allows converting a parameter like ``foo: Out<u8>`` into ``out byte foo``.
     /// Useful for following patterns such as: <https://github.com/KodrAus/rust-csharp-ffi>
+    ///
+    /// This is the same as [`CSharpConfiguration::add_out_type`], and does not remove out types
+    /// that were set before.
     pub fn set_out_type(&mut self, rust_type_name: &str) {
-        self.out_type = Some(rust_type_name.to_string());
+        self.add_out_type(rust_type_name);
+    }
+
+    /// Adds a rust type to represent an out parameter in C#, like
+    /// [`CSharpConfiguration::set_out_type`]. Multiple out types can be used, such as ``Out<T>``
+    /// and ``OutPtr<T>``.
+    pub fn add_out_type(&mut self, rust_type_name: &str) {
+        self.out_types.insert(rust_type_name.to_string());
+    }
+
+    /// Converts parameters that are a mutable pointer or reference to a primitive into ``out``
+    /// parameters, if their name starts with one of the given prefixes. This allows converting a
+    /// parameter like ``out_count: *mut u32`` into ``out uint outCount``, for functions that don't
+    /// use an out type. The documentation keeps the Rust type of the parameter.
+    pub fn treat_mut_pointer_params_named_as_out(&mut self, prefixes: &[&str]) {
+        self.out_parameter_prefixes = prefixes.iter().map(|p| p.to_string()).collect();
     }
 
     /// By default we add a warning on top of each generated C# script, which defaults to
     /// ``// Automatically generated, do not edit!``. This functions allows you to modify this
     /// warning. Can be multiline, and can be removed entirely by setting with an empty string.
+    ///
+    /// The warning can contain placeholders, which are replaced when building:
+    /// - ``{timestamp}``: the time of the build in UTC, such as ``2024-01-31T12:00:00Z``, or the
+    ///   timestamp set with [`CSharpBuilder::set_timestamp`].
+    /// - ``{source}``: the name set with [`CSharpBuilder::set_source_name`].
+    /// - ``{dll_name}``: the name of the library.
+    /// - ``{tool_version}``: the version of this crate.
+    ///
+    /// Unknown placeholders, and ``{source}`` without a source name, are left as is.
     pub fn set_generated_warning(&mut self, generated_warning: &str) {
         self.generated_warning = generated_warning.to_string();
     }
 
-    pub(crate) fn get_known_type(&self, rust_type_name: &str) -> Option<&CSharpType> {
-        self.known_types.get(rust_type_name)
+    /// By default boolean types are rejected, as their size differs between C implementations.
+    /// Rust guarantees its bool to be a single byte however, so this function allows you to marshal
+    /// them as ``[MarshalAs(UnmanagedType.I1)] bool`` instead.
+    pub fn allow_bool_marshaling(&mut self, allow: bool) {
+        self.allow_bool_marshaling = allow;
+    }
+
+    /// By default variadic functions are rejected, as P/Invoke can't pass a variable number of
+    /// arguments. This function allows you to import them with a trailing ``__arglist``
+    /// parameter instead, which the .NET runtime supports on Windows for functions with the Cdecl
+    /// calling convention. These are always imported with ``DllImport``, and don't get a safe
+    /// wrapper.
+    pub fn allow_arglist_variadics(&mut self, allow: bool) {
+        self.allow_arglist_variadics = allow;
+    }
+
+    /// Fixed size arrays in structs are by default marshalled by value as managed arrays. This
+    /// function allows you to instead write arrays of primitives as ``unsafe fixed`` buffers, which
+    /// requires the C# project to allow unsafe code.
+    pub fn use_fixed_buffers(&mut self, use_fixed_buffers: bool) {
+        self.use_fixed_buffers = use_fixed_buffers;
+    }
+
+    /// Fields of tuple structs have no name, so are named by their index, prefixed by a string.
+    /// This defaults to ``Item``, giving fields named ``Item0``, ``Item1``, etc. This function allows
+    /// you to modify this prefix.
+    pub fn set_tuple_field_prefix(&mut self, prefix: &str) {
+        self.tuple_field_prefix = prefix.to_string();
+    }
+
+    /// Structs with a ``#[repr(transparent)]`` attribute are by default not generated, and are
+    /// instead replaced by the type of their single field wherever they are used. This function
+    /// allows you to instead generate them as a struct wrapping that single field.
+    pub fn emit_transparent_wrappers(&mut self, emit: bool) {
+        self.emit_transparent_wrappers = emit;
+    }
+
+    /// Pointers are by default converted into ``IntPtr``. This function allows you to instead use
+    /// typed pointers (i.e. ``byte*``), which marks the generated type as ``unsafe``.
+    pub fn use_unsafe_pointers(&mut self, use_unsafe_pointers: bool) {
+        self.use_unsafe_pointers = use_unsafe_pointers;
+    }
+
+    /// Registers an opaque Rust type that is only passed around by pointer, along with the name of
+    /// the extern function that destroys it.
+    ///
+    /// When a script containing the destructor is built, a ``FooHandle`` class deriving from
+    /// ``SafeHandleZeroOrMinusOneIsInvalid`` is generated in its place, which calls the destructor
+    /// when released. Any pointer to the opaque type is then converted into this handle class.
+    pub fn add_opaque_handle(&mut self, rust_type_name: &str, destructor: &str) {
+        self.opaque_handles
+            .insert(rust_type_name.to_string(), destructor.to_string());
+    }
+
+    /// Sets whether functions should be imported with the source generated ``LibraryImport``
+    /// attribute, instead of ``DllImport``. This requires .NET 7 or newer, and defaults to true when
+    /// targeting C# 11 or newer.
+    pub fn use_library_import(&mut self, use_library_import: bool) {
+        self.use_library_import = Some(use_library_import);
+    }
+
+    /// Sets the calling convention used on the C# side for functions and function pointers with an
+    /// ``extern "C"`` ABI. Defaults to [`CallingConvention::Cdecl`].
+    pub fn set_default_calling_convention(&mut self, calling_convention: CallingConvention) {
+        self.default_calling_convention = calling_convention;
+    }
+
+    /// Extern functions without a ``#[no_mangle]`` or ``#[export_name]`` attribute are not exported
+    /// under their own name. By default bindings are still generated for them. If this is set, they
+    /// are skipped instead, and a warning is raised.
+    pub fn require_no_mangle(&mut self, require: bool) {
+        self.require_no_mangle = require;
+    }
+
+    /// Adds a pattern of item names to ignore. Functions, enums, structs and other items with a
+    /// matching name are not generated, and are not added to the type registry.
+    ///
+    /// Patterns consisting of only identifier characters, ``*`` and ``?`` are treated as globs, such
+    /// as ``__test_*``. Any other pattern is treated as a regular expression, such as
+    /// ``internal_.*``. Both have to match the entire name.
+    pub fn add_ignore_pattern(&mut self, pattern: &str) -> Result<(), Error> {
+        self.ignore_patterns.push(compile_pattern(pattern)?);
+        Ok(())
+    }
+
+    /// Adds a pattern of item names to include. If any include patterns are added, only items with
+    /// a name matching one of them are generated. Patterns are handled the same as in
+    /// [`CSharpConfiguration::add_ignore_pattern`].
+    pub fn add_include_pattern(&mut self, pattern: &str) -> Result<(), Error> {
+        self.include_patterns.push(compile_pattern(pattern)?);
+        Ok(())
+    }
+
+    /// Sets the name of an attribute that marks items to skip, such as ``binder_skip``. Items
+    /// marked with it are handled the same as items marked with ``#[doc(hidden)]``. The attribute
+    /// can also be applied through ``cfg_attr``, such as
+    /// ``#[cfg_attr(feature = "binder", binder_skip)]``.
+    pub fn set_skip_attribute(&mut self, attribute: &str) {
+        self.skip_attribute = Some(attribute.to_string());
+    }
+
+    /// Marks an enum as a bit flags enum, so it is generated with a ``[Flags]`` attribute. This
+    /// takes precedence over the detection of flags enums.
+    pub fn mark_enum_as_flags(&mut self, rust_enum_name: &str) {
+        self.flags_enums.insert(rust_enum_name.to_string(), true);
+    }
+
+    /// Marks an enum as not being a bit flags enum, so it is never generated with a ``[Flags]``
+    /// attribute, even if it is detected as one.
+    pub fn mark_enum_as_not_flags(&mut self, rust_enum_name: &str) {
+        self.flags_enums.insert(rust_enum_name.to_string(), false);
+    }
+
+    /// Sets whether enums are detected as bit flags enums. An enum is detected as one if all its
+    /// variants have an explicit value, and its non-zero values are at least three distinct powers
+    /// of two. Defaults to false.
+    pub fn detect_flags_enums(&mut self, detect: bool) {
+        self.detect_flags_enums = detect;
+    }
+
+    pub(crate) fn is_ignored(&self, item_name: &str) -> bool {
+        self.ignore_patterns.iter().any(|p| p.is_match(item_name))
+            || (!self.include_patterns.is_empty()
+                && !self.include_patterns.iter().any(|p| p.is_match(item_name)))
+    }
+
+    /// Sets whether the namespace should be declared as a file scoped namespace, such as
+    /// ``namespace Foo;``, instead of wrapping everything in braces. This requires C# 10 or newer,
+    /// and defaults to true when targeting C# 10 or newer.
+    pub fn use_file_scoped_namespace(&mut self, use_file_scoped_namespace: bool) {
+        self.use_file_scoped_namespace = Some(use_file_scoped_namespace);
+    }
+
+    /// Sets the indentation of the generated C# script. Defaults to four spaces.
+    pub fn set_indentation(&mut self, indentation: Indentation) {
+        self.indentation = indentation;
+    }
+
+    /// Sets the newlines of the generated C# script. Defaults to ``\n``.
+    pub fn set_newline(&mut self, newline: Newline) {
+        self.newline = newline;
+    }
+
+    /// Sets a prefix to strip from the names of functions, such as ``mylib_``. This turns a function
+    /// ``mylib_do_thing`` into ``DoThing``, while still importing it from ``mylib_do_thing``. The
+    /// prefix is kept if stripping it would leave an empty name, or a name starting with a digit.
+    pub fn set_function_prefix_strip(&mut self, prefix: &str) {
+        self.function_prefix_strip = Some(prefix.to_string());
+    }
+
+    /// Sets the function that converts the Rust names of functions, parameters, fields and types
+    /// into C# names, instead of the default conversion. By default, function names are converted
+    /// into ``PascalCase``, parameter names into ``camelCase``, field names into ``PascalCase``, and
+    /// type names are kept as they are. Function names are passed after stripping the prefix of
+    /// [`CSharpConfiguration::set_function_prefix_strip`]. Names that are C# keywords are prefixed
+    /// with ``@``.
+    pub fn set_naming_converter(&mut self, converter: NamingConverter) {
+        self.naming_converter = Some(converter);
+    }
+
+    /// Sets the C# name of every function, parameter, field or type with the given Rust name. This
+    /// takes precedence over the naming converter and the function prefix strip. Functions are still
+    /// imported from their Rust name.
+    pub fn rename_symbol(&mut self, rust_name: &str, csharp_name: &str) {
+        self.renamed_symbols
+            .insert(rust_name.to_string(), csharp_name.to_string());
+    }
+
+    /// By default, a build fails with [`Error::DuplicateSymbol`] if two items result in the same C#
+    /// name. If this is set, a numeric suffix is added to the name of the second item instead, and
+    /// a warning is raised.
+    pub fn disambiguate_duplicate_names(&mut self, disambiguate: bool) {
+        self.disambiguate_duplicate_names = disambiguate;
+    }
+
+    /// Sets whether and where the Rust type of fields, parameters and return values is written in
+    /// the XML documentation. Defaults to [`TypeRemarks::Remarks`].
+    pub fn set_type_remarks(&mut self, type_remarks: TypeRemarks) {
+        self.type_remarks = type_remarks;
+    }
+
+    /// Generates a wrapper method for each function, which is easier to use than the raw extern,
+    /// with the access modifier of the methods of [`CSharpConfiguration::set_accessibility`]. Out parameters are returned instead, returned C strings are converted into a
+    /// ``string``, and functions returning a bool with a single out parameter are wrapped as a
+    /// ``TryXxx`` method. The raw externs are renamed with a ``Native`` suffix, and hidden from
+    /// IntelliSense. Defaults to false.
+    pub fn generate_safe_wrappers(&mut self, generate: bool) {
+        self.generate_safe_wrappers = generate;
+    }
+
+    /// Sets when structs get a constructor that initializes their fields. Defaults to
+    /// [`ConstructorMode::NonEmptyOnly`], as constructors without parameters require C# 10.
+    pub fn set_struct_constructor(&mut self, mode: ConstructorMode) {
+        self.struct_constructor = mode;
+    }
+
+    /// Gives the parameters of struct constructors a ``= default`` value, so callers can pass only
+    /// some of the fields with named arguments. Only used for C# 10 and newer. Defaults to false.
+    pub fn emit_default_constructor_parameters(&mut self, emit: bool) {
+        self.default_constructor_parameters = emit;
+    }
+
+    /// Implements ``IEquatable<T>`` on structs, with a field-wise ``Equals``, an override of
+    /// ``Equals(object)`` and ``GetHashCode``, and the ``==`` and ``!=`` operators. This avoids the
+    /// boxing of the default equality of structs. Pointers are compared by address, and fixed
+    /// buffers are left out of the comparison. ``GetHashCode`` uses ``System.HashCode``, which
+    /// requires .NET Core 2.1 or .NET Standard 2.1. Defaults to false.
+    pub fn generate_struct_equality(&mut self, generate: bool) {
+        self.generate_struct_equality = generate;
+    }
+
+    /// Sets how structs are declared. Defaults to [`StructStyle::Classic`].
+    pub fn set_struct_style(&mut self, style: StructStyle) {
+        self.struct_style = style;
+    }
+
+    /// Overrides ``ToString`` on structs, which then return their fields like
+    /// ``Foo { FieldA = 1, FieldB = 2 }``, with pointers in hexadecimal. Enums get a
+    /// ``ToFriendlyString`` extension method, which returns the name with the numeric value, like
+    /// ``Read (1)``. The extension methods of enums inside the type are written in the type, and
+    /// those outside of it in a ``FooExtensions`` class next to the enum. Defaults to false.
+    pub fn generate_to_string(&mut self, generate: bool) {
+        self.generate_to_string = generate;
+    }
+
+    /// Gives enums an ``IsDefinedFast`` extension method, which checks whether a value is one of
+    /// the declared variants with a switch instead of reflection. For flags enums, it checks
+    /// whether only declared flags are set. Enums marked ``#[non_exhaustive]`` get a remark that
+    /// native code may return undeclared values. The methods are written like those of
+    /// [`CSharpConfiguration::generate_to_string`]. Defaults to false.
+    pub fn generate_enum_helpers(&mut self, generate: bool) {
+        self.generate_enum_helpers = generate;
+    }
+
+    /// Together with [`CSharpConfiguration::generate_enum_helpers`], adds a
+    /// ``TryFromRaw(byte raw, out Foo value)`` method to enums, which converts a raw value into the
+    /// enum, and returns whether it is a declared value. Defaults to false.
+    pub fn generate_enum_try_from_raw(&mut self, generate: bool) {
+        self.generate_enum_try_from_raw = generate;
+    }
+
+    /// Adds remarks to enums with their Rust representation, such as ``repr(u8)``. For variants
+    /// without an explicit discriminant, the remarks also list the value they are implied to
+    /// have. Defaults to false.
+    pub fn emit_enum_remarks(&mut self, emit: bool) {
+        self.emit_enum_remarks = emit;
+    }
+
+    /// Hides a field of a struct from the C# API, like marking it with ``#[doc(hidden)]``. The
+    /// field is still written, as it is needed for the layout of the struct, but it is made
+    /// private, its documentation is left out, and the constructor initializes it to its default
+    /// instead of taking a parameter for it. Fields of tuple structs are named by their index.
+    pub fn hide_struct_field(&mut self, struct_name: &str, field_name: &str) {
+        self.hidden_struct_fields
+            .entry(struct_name.to_string())
+            .or_default()
+            .insert(field_name.to_string());
+    }
+
+    /// Annotates the fields of structs that aren't blittable with a warning in their remarks, such
+    /// as ``bool``, ``char`` and array fields, and fields of structs that contain these. Such
+    /// structs are copied and converted when they are marshalled, instead of being pinned. The
+    /// non-blittable fields are found by every build, and returned by
+    /// [`CSharpBuilder::blittability_warnings`]. Defaults to false.
+    pub fn warn_non_blittable(&mut self, warn: bool) {
+        self.warn_non_blittable = warn;
+    }
+
+    pub(crate) fn is_hidden_struct_field(&self, struct_name: &str, field_name: &str) -> bool {
+        self.hidden_struct_fields
+            .get(struct_name)
+            .is_some_and(|fields| fields.contains(field_name))
+    }
+
+    /// Sets whether the names of enum variants are converted into the C# naming scheme, such as
+    /// ``STATUS_OK`` into ``StatusOk``. Names that would start with a digit are prefixed with an
+    /// underscore. Variants that are renamed keep their Rust name in their summary. Defaults to
+    /// false.
+    pub fn convert_enum_variant_names(&mut self, convert: bool) {
+        self.convert_enum_variant_names = convert;
+    }
+
+    /// Sets the C# name of a variant of an enum. This takes precedence over the conversion of
+    /// [`CSharpConfiguration::convert_enum_variant_names`].
+    pub fn rename_enum_variant(&mut self, enum_name: &str, variant_name: &str, csharp_name: &str) {
+        self.renamed_enum_variants
+            .entry(enum_name.to_string())
+            .or_default()
+            .insert(variant_name.to_string(), csharp_name.to_string());
+    }
+
+    pub(crate) fn get_enum_variant_rename(
+        &self,
+        enum_name: &str,
+        variant_name: &str,
+    ) -> Option<&str> {
+        self.renamed_enum_variants
+            .get(enum_name)
+            .and_then(|variants| variants.get(variant_name))
+            .map(|name| name.as_str())
+    }
+
+    /// Marshals ``*const c_char`` parameters of functions as UTF-8 strings, which requires C# 7.1 or
+    /// newer. Returned strings are marshalled as well when using ``LibraryImport``, otherwise they
+    /// stay an ``IntPtr``, which can be converted with a generated ``PtrToString`` helper. Defaults
+    /// to false.
+    pub fn marshal_utf8_strings(&mut self, marshal: bool) {
+        self.marshal_utf8_strings = marshal;
+    }
+
+    /// Marshals pointers to the given Rust type as UTF-8 strings as well, such as ``u8``, when
+    /// [`CSharpConfiguration::marshal_utf8_strings`] is set.
+    pub fn add_utf8_string_type(&mut self, rust_type_name: &str) {
+        self.utf8_string_types.insert(rust_type_name.to_string());
+    }
+
+    /// Sets the size of ``c_long`` and ``c_ulong``, which differs per platform. Defaults to
+    /// [`CLongSize::Bits64`], which matches 64 bit Linux and macOS.
+    pub fn set_c_long_size(&mut self, size: CLongSize) {
+        self.c_long_size = size;
+    }
+
+    /// Sets how the Rust ``char`` type is converted. A Rust ``char`` is a 4 byte Unicode scalar
+    /// value, so it can not be converted into a C# ``char``. Defaults to
+    /// [`CSharpCharMapping::UInt`].
+    pub fn map_rust_char_to(&mut self, mapping: CSharpCharMapping) {
+        self.rust_char_mapping = mapping;
+    }
+
+    /// Sets whether ``c_char`` is signed, and thus converted into ``sbyte``, or unsigned, and
+    /// converted into ``byte``. This differs per platform. Defaults to signed.
+    pub fn set_c_char_signed(&mut self, signed: bool) {
+        self.c_char_signed = signed;
+    }
+
+    /// Sets the pointer width of the process that loads the library, which is used for ``usize`` and
+    /// ``isize``. Defaults to [`PointerWidth::Native`] for C# 9 and newer, and to
+    /// [`PointerWidth::W64`] for older versions.
+    pub fn set_pointer_width(&mut self, pointer_width: PointerWidth) {
+        self.pointer_width = Some(pointer_width);
+    }
+
+    /// 128 bit integers can not be marshalled, so by default they fail with an
+    /// [`Error::UnsupportedError`], unless a known type was added for ``u128`` or ``i128``. If this
+    /// is set, 128 bit struct fields are instead split into two 64 bit fields, ``FooLow`` and
+    /// ``FooHigh``. Note that Rust may align 128 bit integers to 16 bytes, while the split fields
+    /// are aligned to 8 bytes. Defaults to false.
+    pub fn map_u128_as_pair(&mut self, map: bool) {
+        self.map_u128_as_pair = map;
+    }
+
+    /// Converts 128 bit integers into ``System.Numerics.BigInteger``. This is not a blittable
+    /// type, so the generated code is only usable after post-processing. Defaults to false.
+    pub fn map_u128_as_big_integer(&mut self, map: bool) {
+        self.map_u128_as_big_integer = map;
+    }
+
+    /// Adds a ``where T : unmanaged`` constraint to each generic parameter of a struct, so it can
+    /// only be used with types that can be passed to native code. Only used for C# 7.3 and newer.
+    /// Defaults to true.
+    pub fn emit_unmanaged_constraints(&mut self, emit: bool) {
+        self.emit_unmanaged_constraints = emit;
+    }
+
+    /// Converts ``&self`` and ``&mut self`` receivers of extern functions inside ``impl`` blocks into
+    /// a leading ``self`` pointer parameter of the implemented type. Otherwise, functions with a
+    /// receiver are not supported. Defaults to false.
+    pub fn map_impl_receivers(&mut self, map: bool) {
+        self.map_impl_receivers = map;
+    }
+
+    /// Writes the items of each Rust module inside a nested static class named after the module,
+    /// so items with the same name in different modules don't collide. Otherwise, the items of
+    /// modules are all written into the same type. Defaults to false.
+    pub fn nest_modules_as_classes(&mut self, nest: bool) {
+        self.nest_modules_as_classes = nest;
+    }
+
+    /// Evaluates ``#[cfg(...)]`` attributes of items with the given options, and leaves out the
+    /// items that are disabled. ``#[cfg_attr(...)]`` attributes are resolved as well, so a
+    /// conditional ``repr`` is used when its condition holds. Items with predicates that can't be
+    /// evaluated are generated, with a warning. By default, cfg attributes are not evaluated, and
+    /// all items are generated.
+    pub fn set_cfg_evaluation(&mut self, options: CfgOptions) {
+        self.cfg_options = Some(options);
+    }
+
+    /// Generates a property for exported statics that are mutable, or don't have a literal value,
+    /// which reads the static from the library using ``NativeLibrary``. This requires .NET Core 3.0
+    /// or newer. Otherwise, only immutable statics with a literal value are generated, as a
+    /// constant. Defaults to false.
+    pub fn read_statics_from_library(&mut self, read: bool) {
+        self.read_statics_from_library = read;
+    }
+
+    /// Chooses the library of the per-platform names set with [`CSharpBuilder::set_dll_names`] at
+    /// runtime, by registering a resolver with ``NativeLibrary.SetDllImportResolver`` in the static
+    /// constructor of the type. This requires .NET Core 3.0 or newer, and only one resolver can be
+    /// registered for an assembly. Otherwise, the library is chosen at compile time with
+    /// preprocessor symbols. Defaults to false.
+    pub fn resolve_dll_names_at_runtime(&mut self, resolve: bool) {
+        self.resolve_dll_names_at_runtime = resolve;
+    }
+
+    /// Sets the runtime the generated script is used with, which overrides the features that are
+    /// otherwise chosen by the C# version. Defaults to [`TargetRuntime::DotNet`].
+    pub fn set_target_runtime(&mut self, target_runtime: TargetRuntime) {
+        self.target_runtime = target_runtime;
+    }
+
+    /// Sets the access modifiers of the generated class, imported functions, and types such as
+    /// structs, enums and delegates. Note that C# requires the types used by a public function to
+    /// be public as well. Defaults to an internal class and functions, with public types.
+    pub fn set_accessibility(&mut self, accessibility: AccessibilityOptions) {
+        self.accessibility = accessibility;
+    }
+
+    /// Marks the generated class as ``partial``, so that it can be extended in another file.
+    /// Defaults to false.
+    pub fn emit_partial_class(&mut self, emit: bool) {
+        self.emit_partial_class = emit;
+    }
+
+    /// Adds an attribute, such as ``[SuppressGCTransition]``, to the import of the Rust function
+    /// with the given name. The attribute is written verbatim above the ``DllImport`` attribute.
+    pub fn add_extra_attribute(&mut self, function_name: &str, attribute: &str) {
+        self.extra_attributes
+            .entry(function_name.to_string())
+            .or_default()
+            .push(attribute.to_string());
+    }
+
+    /// Sets the options of the ``DllImport`` attribute of all imports. The character set is also
+    /// used by the ``StructLayout`` attribute of structs, which otherwise uses
+    /// ``CharSet.Unicode``. Source generated imports only support ``SetLastError``.
+    pub fn set_dllimport_defaults(&mut self, options: DllImportOptions) {
+        self.dllimport_defaults = options;
+    }
+
+    /// Sets the options of the ``DllImport`` attribute of the import of the Rust function with the
+    /// given name. The options that are set take precedence over those of
+    /// [`CSharpConfiguration::set_dllimport_defaults`].
+    pub fn set_dllimport_options_for(&mut self, function_name: &str, options: DllImportOptions) {
+        self.dllimport_options
+            .insert(function_name.to_string(), options);
+    }
+
+    /// The options of the import of a function, with its own options merged into the defaults.
+    pub(crate) fn get_dllimport_options(&self, function_name: &str) -> DllImportOptions {
+        let defaults = self.dllimport_defaults;
+        match self.dllimport_options.get(function_name) {
+            Some(options) => DllImportOptions {
+                set_last_error: options.set_last_error.or(defaults.set_last_error),
+                exact_spelling: options.exact_spelling.or(defaults.exact_spelling),
+                best_fit_mapping: options.best_fit_mapping.or(defaults.best_fit_mapping),
+                charset: options.charset.or(defaults.charset),
+            },
+            None => defaults,
+        }
+    }
+
+    /// Warns about functions that return a struct larger than the given number of bytes by value.
+    /// ABIs differ in how they return large structs, which often goes wrong when calling the
+    /// function from C#. The size is estimated from the fields of the struct, assuming the C
+    /// layout, and structs of which the size can't be estimated are ignored. The warnings are
+    /// returned by [`CSharpBuilder::warnings`].
+    pub fn warn_large_return_structs(&mut self, max_bytes: usize) {
+        self.max_return_struct_size = Some(max_bytes);
+    }
+
+    /// Makes returning structs larger than the size set with
+    /// [`CSharpConfiguration::warn_large_return_structs`] an error instead of a warning. Defaults
+    /// to false.
+    pub fn deny_large_return_structs(&mut self, deny: bool) {
+        self.deny_large_return_structs = deny;
+    }
+
+    /// The character set of the ``StructLayout`` attribute of structs.
+    pub(crate) fn struct_charset(&self) -> CharSet {
+        self.dllimport_defaults.charset.unwrap_or(CharSet::Unicode)
+    }
+
+    /// Registers concrete instantiations of a generic extern function, which can't be imported
+    /// itself. Each instantiation is imported as an overload, from a concrete function the Rust
+    /// library exports for it. The instantiations are given as pairs of a generic parameter and
+    /// its type, and a parameter that is given again starts the next instantiation, so
+    /// ``&[("T", "u8"), ("T", "Foo")]`` registers two instantiations. The entry points are named
+    /// ``{function}_{T}`` by default, such as ``get_u8``, which can be changed with
+    /// [`CSharpConfiguration::set_monomorphized_entry_point`].
+    pub fn monomorphize_function(&mut self, function_name: &str, instantiations: &[(&str, &str)]) {
+        let registered = self
+            .monomorphizations
+            .entry(function_name.to_string())
+            .or_default();
+        let mut instantiation: Vec<(String, String)> = Vec::new();
+        for (parameter, type_name) in instantiations {
+            if instantiation.iter().any(|(p, _)| p == parameter) {
+                registered.push(std::mem::take(&mut instantiation));
+            }
+            instantiation.push((parameter.to_string(), type_name.to_string()));
+        }
+        if !instantiation.is_empty() {
+            registered.push(instantiation);
+        }
+    }
+
+    /// Sets the pattern of the entry points of the instantiations of a generic function registered
+    /// with [`CSharpConfiguration::monomorphize_function`]. ``{function}`` is replaced with the
+    /// name of the function, and a generic parameter such as ``{T}`` with its type, in which
+    /// characters that can't be used in a symbol are replaced with an underscore.
+    pub fn set_monomorphized_entry_point(&mut self, function_name: &str, pattern: &str) {
+        self.monomorphized_entry_points
+            .insert(function_name.to_string(), pattern.to_string());
+    }
+
+    /// Marks a pointer parameter and a length parameter of a function as a slice. The function
+    /// gets an overload which takes a ``ReadOnlySpan<T>``, or a ``Span<T>`` for ``*mut T``
+    /// pointers, instead of the two parameters. The overload pins the span with ``fixed``, and
+    /// passes its address and length to the raw extern, which is renamed with a ``Native`` suffix
+    /// and hidden from IntelliSense. As the overload uses a pointer, it requires unsafe code to be
+    /// allowed. Requires C# 7.3.
+    pub fn mark_slice_params(&mut self, function_name: &str, pointer: &str, length: &str) {
+        self.slice_parameters
+            .entry(function_name.to_string())
+            .or_default()
+            .push((pointer.to_string(), length.to_string()));
+    }
+
+    /// Treats a pointer parameter followed by a ``usize`` parameter with the same name and a
+    /// ``_len`` suffix, such as ``data: *const u8, data_len: usize``, as a slice, like
+    /// [`CSharpConfiguration::mark_slice_params`]. Defaults to false.
+    pub fn detect_slice_params(&mut self, detect: bool) {
+        self.detect_slice_parameters = detect;
+    }
+
+    /// Enables the nullable annotation context with ``#nullable enable annotations``, and marks
+    /// the delegates of optional function pointers, such as ``Option<extern "C" fn(u32)>``, as
+    /// nullable. Without it, these are written as the delegate type, which can be null as well.
+    /// Requires C# 8. Defaults to false.
+    pub fn emit_nullable_annotations(&mut self, emit: bool) {
+        self.emit_nullable_annotations = emit;
+    }
+
+    /// Enables nullable reference types with ``#nullable enable``, so that the generated code can
+    /// be used in projects that treat nullable warnings as errors. Like
+    /// [`CSharpConfiguration::emit_nullable_annotations`], optional function pointers are marked as
+    /// nullable, as are the delegates of function pointer fields, which are null in a default
+    /// struct, and returned UTF-8 strings. Returns an error when targeting a version before C# 8.
+    /// Defaults to false.
+    pub fn emit_nullable_directive(&mut self, emit: bool) -> Result<(), Error> {
+        if emit && !self.supports_version(8, 0) {
+            return Err(Error::UnsupportedError(
+                "The nullable directive requires C# 8 or newer".to_string(),
+                proc_macro2::Span::call_site(),
+            ));
+        }
+        self.emit_nullable_directive = emit;
+        Ok(())
+    }
+
+    pub(crate) fn uses_nullable_annotations(&self) -> bool {
+        (self.emit_nullable_annotations || self.emit_nullable_directive)
+            && self.supports_version(8, 0)
+    }
+
+    pub(crate) fn uses_nullable_directive(&self) -> bool {
+        self.emit_nullable_directive && self.supports_version(8, 0)
+    }
+
+    /// Writes ``// <auto-generated/>`` as the first line of generated files, before the generated
+    /// warning. Analyzers use this marker to skip generated code. Defaults to false.
+    pub fn emit_auto_generated_marker(&mut self, emit: bool) {
+        self.emit_auto_generated_marker = emit;
+    }
+
+    /// Adds a ``GeneratedCode`` attribute with the name and version of this crate to the generated
+    /// class. Defaults to false.
+    pub fn emit_generated_code_attribute(&mut self, emit: bool) {
+        self.emit_generated_code_attribute = emit;
+    }
+
+    /// Sets the version used in the ``GeneratedCode`` attribute. Defaults to the version of this
+    /// crate.
+    pub fn set_generated_code_version(&mut self, version: &str) {
+        self.generated_code_version = version.to_string();
+    }
+
+    /// Adds a warning that is disabled with ``#pragma warning disable`` after the usings of
+    /// generated files, such as ``CA1707``.
+    pub fn add_pragma_suppression(&mut self, warning: &str) {
+        self.pragma_suppressions.push(warning.to_string());
+    }
+
+    /// Sets the order in which the items of the script are written. Defaults to
+    /// [`Ordering::SourceOrder`].
+    pub fn set_output_ordering(&mut self, ordering: Ordering) {
+        self.output_ordering = ordering;
+    }
+
+    /// Wraps each group of items in a ``#region``, such as ``#region Enums``, when the items are
+    /// written with [`Ordering::Grouped`]. Defaults to false.
+    pub fn emit_regions(&mut self, emit_regions: bool) {
+        self.emit_regions = emit_regions;
+    }
+
+    pub(crate) fn get_pointer_width(&self) -> PointerWidth {
+        match self.pointer_width {
+            Some(pointer_width) => pointer_width,
+            None if self.csharp_version >= 9 || self.is_unity() => PointerWidth::Native,
+            None => PointerWidth::W64,
+        }
+    }
+
+    pub(crate) fn uses_file_scoped_namespace(&self) -> bool {
+        self.use_file_scoped_namespace
+            .unwrap_or(self.csharp_version >= 10)
+    }
+
+    pub(crate) fn uses_library_import(&self) -> bool {
+        self.use_library_import
+            .unwrap_or(self.csharp_version >= 11 && !self.is_unity())
+    }
+
+    pub(crate) fn uses_init_accessors(&self) -> bool {
+        self.csharp_version >= 9 && !self.is_unity()
+    }
+
+    pub(crate) fn is_unity(&self) -> bool {
+        self.target_runtime == TargetRuntime::Unity
+    }
+
+    pub(crate) fn supports_version(&self, major: u8, minor: u8) -> bool {
+        (self.csharp_version, self.csharp_minor_version) >= (major, minor)
+    }
+
+    pub(crate) fn get_known_type(&self, rust_type_name: &str) -> Option<CSharpType> {
+        self.read_known_types().get(rust_type_name).cloned()
+    }
+
+    // A panic while holding the lock can't leave the registry in an inconsistent state, as it is
+    // only ever updated by single inserts, so a poisoned lock is ignored.
+    fn read_known_types(&self) -> RwLockReadGuard<'_, HashMap<String, CSharpType>> {
+        self.known_types
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write_known_types(&self) -> RwLockWriteGuard<'_, HashMap<String, CSharpType>> {
+        self.known_types
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
     }
 }
 
 /// The CSharpBuilder is used to load a Rust script string, and convert it into the appropriate C#
 /// script as a string.
 pub struct CSharpBuilder<'a> {
-    configuration: &'a mut CSharpConfiguration,
+    configuration: &'a CSharpConfiguration,
     dll_name: String,
+    dll_names: Option<DllNames>,
     usings: Vec<String>,
+    /// The name of the source of the script, for the generated warning.
+    source_name: Option<String>,
+    /// The names of the sources of scripts added with [`CSharpBuilder::add_named_script`], with a
+    /// span in each of them, which tells apart the sources of errors.
+    named_sources: Vec<(String, proc_macro2::Span)>,
+    /// The timestamp used in the generated warning, instead of the time of the build.
+    timestamp: Option<String>,
+    /// Code written verbatim at the start of the class.
+    class_preamble: Vec<String>,
+    /// Code written verbatim at the end of the class.
+    class_epilogue: Vec<String>,
     tokens: syn::File,
     namespace: Option<String>,
     type_name: Option<String>,
+    /// The delegates generated for function pointers in this build, mapped from their signature to
+    /// their name.
+    delegates: HashMap<String, String>,
+    /// The C# names of the members generated in this build.
+    member_names: HashSet<String>,
+    /// The Rust types this builder registered in the type registry, which it is allowed to
+    /// register again.
+    registered_types: HashSet<String>,
+    /// The types registered during the current build, which are added to the type registry once
+    /// the build succeeds.
+    staged_types: HashMap<String, (CSharpType, proc_macro2::Span)>,
+    /// The types registered during the current build, mapped from their path in the crate, such
+    /// as ``ffi::Config``, which is used to tell apart types with the same name.
+    qualified_types: HashMap<String, CSharpType>,
+    /// The path of the module whose items are being converted.
+    module_path: Vec<String>,
+    warnings: Vec<Warning>,
+    mode: BuildMode,
+    errors: Vec<Error>,
+    /// The files of enums, structs and unions, while building split files.
+    split_files: Option<Vec<GeneratedFile>>,
+    /// The written items with their group and name, while writing them with
+    /// [`Ordering::Grouped`].
+    grouped_items: Option<Vec<GroupedItem>>,
+    /// The converted items, while extracting them.
+    extraction: Option<ExtractedItems>,
+    /// The enums written inside the type in this build, whose extension methods are written at the
+    /// end of the type.
+    enum_extensions: Vec<EnumExtensions>,
+    /// The fields of structs that aren't blittable, found during the current build.
+    blittability_warnings: Vec<BlittabilityWarning>,
+    /// The structs that aren't blittable written in the current build, mapped from their Rust name
+    /// to their C# name.
+    non_blittable_structs: HashMap<String, String>,
+    /// The entry points of the functions imported in the current build, in the order they are
+    /// written.
+    entry_points: Vec<String>,
+    /// Whether the signature of a function is being converted. Pointers to opaque handles are only
+    /// converted into their SafeHandle class there, as fields keep plain pointers.
+    in_function_signature: bool,
 }
 
 impl<'a> CSharpBuilder<'a> {
@@ -197,28 +1274,247 @@ impl<'a> CSharpBuilder<'a> {
     pub fn new(
         script: &str,
         dll_name: &str,
-        configuration: &'a mut CSharpConfiguration,
+        configuration: &'a CSharpConfiguration,
     ) -> Result<CSharpBuilder<'a>, Error> {
         match parse_script(script) {
             Ok(tokens) => Ok(CSharpBuilder {
                 configuration,
                 dll_name: dll_name.to_string(),
+                dll_names: None,
                 // Load the default usings.
                 usings: vec![
                     "System".to_string(),
                     "System.Runtime.InteropServices".to_string(),
                 ],
+                source_name: None,
+                named_sources: Vec::new(),
+                timestamp: None,
+                class_preamble: Vec::new(),
+                class_epilogue: Vec::new(),
                 tokens,
                 namespace: None,
                 type_name: None,
+                delegates: HashMap::new(),
+                member_names: HashSet::new(),
+                registered_types: HashSet::new(),
+                staged_types: HashMap::new(),
+                qualified_types: HashMap::new(),
+                module_path: Vec::new(),
+                warnings: Vec::new(),
+                mode: BuildMode::Strict,
+                errors: Vec::new(),
+                split_files: None,
+                grouped_items: None,
+                extraction: None,
+                enum_extensions: Vec::new(),
+                blittability_warnings: Vec::new(),
+                non_blittable_structs: HashMap::new(),
+                entry_points: Vec::new(),
+                in_function_signature: false,
             }),
             Err(e) => Err(Error::from(e)),
         }
     }
 
+    /// Creates a new C# Builder from a list of Rust files, the name of the library C# is going to
+    /// make calls to (the .so/.dll file), and a configuration. Directories are searched recursively
+    /// for ``.rs`` files. The items of all files are generated into the same script, in the order
+    /// of the paths.
+    pub fn from_paths(
+        paths: &[&Path],
+        dll_name: &str,
+        configuration: &'a CSharpConfiguration,
+    ) -> Result<CSharpBuilder<'a>, Error> {
+        let mut builder = CSharpBuilder::new("", dll_name, configuration)?;
+        for path in paths {
+            builder.add_path(path)?;
+        }
+        Ok(builder)
+    }
+
+    /// Creates a new C# Builder from a crate with its macros expanded, given the path to its
+    /// ``Cargo.toml``. The library of the crate is expanded with ``cargo expand``, or if that is not
+    /// installed, with ``cargo rustc -- -Zunpretty=expanded``. If expanding fails, an
+    /// [`Error::ExpansionError`] is returned with the output of cargo, or the reason cargo could
+    /// not be run.
+    #[cfg(feature = "expand")]
+    pub fn from_expanded_crate(
+        manifest_path: &Path,
+        dll_name: &str,
+        configuration: &'a CSharpConfiguration,
+    ) -> Result<CSharpBuilder<'a>, Error> {
+        let script = expand_crate(manifest_path, run_command)?;
+        CSharpBuilder::new(&script, dll_name, configuration)
+    }
+
+    /// Parses an additional Rust script, and appends its items to the items that will be
+    /// generated. The ``//!`` docs of the script are used for the type if no script before it
+    /// had any.
+    pub fn add_script(&mut self, script: &str) -> Result<(), Error> {
+        let tokens = parse_script(script)?;
+        self.append_tokens(tokens);
+        Ok(())
+    }
+
+    /// Parses an additional Rust script like [`CSharpBuilder::add_script`], from a source such as
+    /// ``ffi.rs``. Errors in the script are reported with the name of its source as
+    /// [`Error::InSource`], so scripts from several files can be combined.
+    pub fn add_named_script(&mut self, source_name: &str, script: &str) -> Result<(), Error> {
+        let tokens = parse_script(script)
+            .map_err(|e| Error::InSource(source_name.to_string(), Box::new(e.into())))?;
+        if let Some(item) = tokens.items.first() {
+            self.named_sources
+                .push((source_name.to_string(), item.span()));
+        }
+        self.append_tokens(tokens);
+        Ok(())
+    }
+
+    fn append_tokens(&mut self, tokens: syn::File) {
+        self.tokens.items.extend(tokens.items);
+        if self.tokens.attrs.is_empty() {
+            self.tokens.attrs = tokens.attrs;
+        }
+    }
+
+    fn add_path(&mut self, path: &Path) -> Result<(), Error> {
+        if path.is_dir() {
+            let mut entries = std::fs::read_dir(path)?
+                .map(|entry| entry.map(|e| e.path()))
+                .collect::<Result<Vec<_>, _>>()?;
+            // Sort the entries, so the output doesn't depend on the order of the file system.
+            entries.sort();
+            for entry in entries {
+                if entry.is_dir() || entry.extension().is_some_and(|e| e == "rs") {
+                    self.add_path(&entry)?;
+                }
+            }
+            Ok(())
+        } else {
+            self.add_named_script(&path.display().to_string(), &std::fs::read_to_string(path)?)
+        }
+    }
+
+    /// Registers the types of the script in the type registry of the configuration, without
+    /// generating anything. This allows builders whose scripts use each other's types to be built
+    /// in any order, by first registering the types of all builders. Types are registered as
+    /// [`CSharpBuilder::build`] registers them, so with the namespace and type of the builder.
+    /// Transparent structs and type aliases of types that aren't known yet are not registered.
+    pub fn register_types_only(&mut self) -> Result<(), Error> {
+        register_types(self)
+    }
+
+    /// Converts the items of the script without generating a script, and returns the functions,
+    /// structs and enums as they would be generated. Items that can't be converted are returned
+    /// with the error they produce. This can be called before or after building, and registers
+    /// types like [`CSharpBuilder::build`] does.
+    pub fn extracted_items(&mut self) -> Result<ExtractedItems, Error> {
+        let warnings = std::mem::take(&mut self.warnings);
+        let mode = self.mode;
+        self.mode = BuildMode::Lenient;
+        self.extraction = Some(ExtractedItems::default());
+        let result = build_csharp(self, &mut String::new());
+        let extracted = self.extraction.take().unwrap_or_default();
+        self.mode = mode;
+        self.warnings = warnings;
+        result?;
+        Ok(extracted)
+    }
+
+    /// Generates tests for the layout of the generated structs. The C# file has a ``Verify`` method
+    /// which compares the size and field offsets of each struct with those of the Rust struct, and
+    /// throws if any of them differ. These are retrieved from the library through extern functions
+    /// starting with the given prefix, which are returned as Rust code to add to the library. Generic
+    /// structs are skipped.
+    pub fn build_layout_tests(&mut self, rust_size_fn_prefix: &str) -> Result<LayoutTests, Error> {
+        build_layout_tests(self, rust_size_fn_prefix)
+    }
+
     /// This function will return the C# script. Should be called after the C# Builder is setup.
+    /// The types of the script are only added to the type registry of the configuration if the
+    /// build succeeds.
     pub fn build(&mut self) -> Result<String, Error> {
-        build_csharp(self)
+        let mut script = String::new();
+        build_csharp(self, &mut script).map_err(|e| self.locate_error(e))?;
+        Ok(script)
+    }
+
+    /// Writes the C# script to a writer as it is generated, instead of returning it as a string.
+    /// Should be called after the C# Builder is setup.
+    pub fn build_to<W: std::io::Write>(&mut self, writer: &mut W) -> Result<(), Error> {
+        let mut adapter = IoWriteAdapter {
+            writer,
+            error: None,
+        };
+        match build_csharp(self, &mut adapter) {
+            // Formatting errors don't carry information, so the error of the writer is returned
+            // instead, if there is one.
+            Err(Error::FmtError(e)) => Err(adapter.error.map_or(Error::FmtError(e), Error::from)),
+            result => result.map_err(|e| self.locate_error(e)),
+        }
+    }
+
+    /// Builds the C# script, and compares it with an existing script, such as a previously
+    /// generated file that is checked in. Line endings are normalized before comparing, and if the
+    /// generated warning contains a ``{timestamp}`` placeholder, the header is left out of the
+    /// comparison. Returns a [`VerifyError::Mismatch`] with the differences if the scripts differ,
+    /// or a [`VerifyError::Error`] if the build fails.
+    pub fn verify_against(&mut self, existing: &str) -> Result<(), VerifyError> {
+        verify_script(self, existing)
+    }
+
+    /// Compares the built C# script with the contents of a file, like
+    /// [`CSharpBuilder::verify_against`].
+    pub fn verify_against_path(&mut self, path: &Path) -> Result<(), VerifyError> {
+        let existing = std::fs::read_to_string(path).map_err(Error::from)?;
+        verify_script(self, &existing)
+    }
+
+    /// Builds the C# script as separate files, with one file for each enum, struct and union, and
+    /// one file for the type with everything else, such as functions. Each file has the generated
+    /// warning, usings and namespace. As enums, structs and unions are written in their own file,
+    /// they are not wrapped in the type.
+    pub fn build_split(&mut self) -> Result<Vec<GeneratedFile>, Error> {
+        build_csharp_split(self).map_err(|e| self.locate_error(e))
+    }
+
+    /// Builds the C# script as separate files like [`CSharpBuilder::build_split`], and writes them
+    /// to a directory with their suggested names.
+    pub fn build_split_to_directory(&mut self, directory: &Path) -> Result<(), Error> {
+        std::fs::create_dir_all(directory)?;
+        for file in self.build_split()? {
+            std::fs::write(directory.join(&file.suggested_name), &file.contents)?;
+        }
+        Ok(())
+    }
+
+    /// Builds the C# script like [`CSharpBuilder::build`], but omits items that fail to convert
+    /// instead of failing the build. A warning is raised for each omitted item, which can be
+    /// retrieved with [`CSharpBuilder::warnings`].
+    pub fn build_lenient(&mut self) -> Result<String, Error> {
+        self.mode = BuildMode::Lenient;
+        let result = self.build();
+        self.mode = BuildMode::Strict;
+        result
+    }
+
+    /// Builds the C# script like [`CSharpBuilder::build`], but continues converting after an item
+    /// fails, and returns all errors that were encountered. Where possible, multiple errors within
+    /// a single item are reported as well, such as multiple unknown parameter types of a function.
+    pub fn build_collecting_errors(&mut self) -> Result<String, Vec<Error>> {
+        self.mode = BuildMode::CollectErrors;
+        self.errors.clear();
+        let result = self.build();
+        self.mode = BuildMode::Strict;
+        let mut errors = std::mem::take(&mut self.errors);
+        match result {
+            Ok(script) if errors.is_empty() => Ok(script),
+            Ok(_) => Err(errors),
+            Err(e) => {
+                errors.push(e);
+                Err(errors)
+            }
+        }
     }
 
     /// Sets the namespace the C# script should use to generate its functions in. If not set, no
@@ -233,18 +1529,885 @@ impl<'a> CSharpBuilder<'a> {
         self.type_name = Some(type_name.to_string());
     }
 
+    /// Sets the name of the library for each platform, for libraries whose file name differs
+    /// between platforms. The imports then use a ``LibraryName`` constant in the type, which is
+    /// chosen with preprocessor symbols, or by a resolver at runtime if
+    /// [`CSharpConfiguration::resolve_dll_names_at_runtime`] is set. In the latter case the constant
+    /// is the name given to the builder. This requires a type to be set.
+    pub fn set_dll_names(&mut self, dll_names: DllNames) {
+        self.dll_names = Some(dll_names);
+    }
+
+    /// Sets the name of the source of the script, such as ``ffi.rs``, which replaces the
+    /// ``{source}`` placeholder of the generated warning. Errors and warnings of the build are
+    /// reported with the name of the source as [`Error::InSource`], so their location refers to
+    /// the right file.
+    pub fn set_source_name(&mut self, source_name: &str) {
+        self.source_name = Some(source_name.to_string());
+    }
+
+    /// Sets the timestamp that replaces the ``{timestamp}`` placeholder of the generated warning,
+    /// instead of the time of the build. This keeps the generated script the same between builds.
+    pub fn set_timestamp(&mut self, timestamp: &str) {
+        self.timestamp = Some(timestamp.to_string());
+    }
+
+    /// Adds code to the start of the generated class, such as a static constructor or helper
+    /// methods. The code is written verbatim, but indented to the level of the class members. It is
+    /// only written when a type is set.
+    pub fn add_class_preamble(&mut self, code: &str) {
+        self.class_preamble.push(code.to_string());
+    }
+
+    /// Adds code to the end of the generated class, like [`CSharpBuilder::add_class_preamble`].
+    pub fn add_class_epilogue(&mut self, code: &str) {
+        self.class_epilogue.push(code.to_string());
+    }
+
     /// Adds a using to the top of the C# script.
     pub fn add_using(&mut self, using: &str) {
         self.usings.push(using.to_string());
     }
 
-    pub(crate) fn add_known_type(&mut self, rust_type_name: &str, csharp_type_name: &str) {
-        self.configuration.add_known_type(
+    /// Returns the warnings raised during the last build. These are raised for items that were
+    /// skipped because they could not be converted, but did not fail the build.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Returns the fields of structs that aren't blittable found during the last build. Fields of
+    /// nested structs are only known to be non-blittable if the nested struct is declared first.
+    pub fn blittability_warnings(&self) -> &[BlittabilityWarning] {
+        &self.blittability_warnings
+    }
+
+    /// Converts the items of the script like [`CSharpBuilder::extracted_items`], and returns the
+    /// fields of structs that aren't blittable.
+    pub fn check_blittable(&mut self) -> Result<Vec<BlittabilityWarning>, Error> {
+        self.extracted_items()?;
+        Ok(self.blittability_warnings.clone())
+    }
+
+    /// Converts the items of the script like [`CSharpBuilder::extracted_items`], and returns the
+    /// entry points of the functions the script imports from the library, in the order they are
+    /// imported.
+    pub fn entry_points(&mut self) -> Result<Vec<String>, Error> {
+        self.extracted_items()?;
+        Ok(self.entry_points.clone())
+    }
+
+    /// Checks the entry points of the script against the symbols exported by the library, such as
+    /// the symbols listed by ``nm`` or ``dumpbin``. Returns the entry points the library doesn't
+    /// export, and the entry points that are imported more than once.
+    pub fn verify_entry_points(
+        &mut self,
+        exports: &HashSet<String>,
+    ) -> Result<Vec<MissingSymbol>, Error> {
+        let mut imported = HashSet::new();
+        let mut missing = Vec::new();
+        for entry_point in self.entry_points()? {
+            if imported.contains(&entry_point) {
+                let duplicate = MissingSymbol::Duplicate(entry_point);
+                if !missing.contains(&duplicate) {
+                    missing.push(duplicate);
+                }
+                continue;
+            }
+            if !exports.contains(&entry_point) {
+                missing.push(MissingSymbol::NotExported(entry_point.clone()));
+            }
+            imported.insert(entry_point);
+        }
+        Ok(missing)
+    }
+
+    /// Attaches the name of the source to an error that has a location in the source. This is the
+    /// source of the named script the location is in, or else the source name if it is set.
+    pub(crate) fn locate_error(&self, error: Error) -> Error {
+        let span = match &error {
+            Error::UnsupportedError(_, span)
+            | Error::UnknownType(_, span)
+            | Error::DuplicateSymbol(_, span)
+            | Error::AmbiguousType(_, _, span) => *span,
+            _ => return error,
+        };
+        // Spans can only be joined with spans of the same source.
+        let source_name = self
+            .named_sources
+            .iter()
+            .find(|(_, source_span)| source_span.join(span).is_some())
+            .map(|(source_name, _)| source_name)
+            .or(self.source_name.as_ref());
+        match source_name {
+            Some(source_name) => Error::InSource(source_name.to_string(), Box::new(error)),
+            None => error,
+        }
+    }
+
+    /// Records an error that does not need to stop the conversion of the current item, so that
+    /// further errors in the item can be found. Only builds collecting errors record them, other
+    /// builds return the error instead.
+    pub(crate) fn record_error(&mut self, error: Error) -> Result<(), Error> {
+        let error = self.locate_error(error);
+        match self.mode {
+            BuildMode::CollectErrors => {
+                self.errors.push(error);
+                Ok(())
+            }
+            _ => Err(error),
+        }
+    }
+
+    pub(crate) fn add_warning(&mut self, item_name: &str, error: Error) {
+        let error = self.locate_error(error);
+        self.warnings.push(Warning {
+            item_name: item_name.to_string(),
+            error,
+        });
+    }
+
+    /// Claims a C# name for a member generated in this build. If the name is already used, either
+    /// a name with a numeric suffix is returned, or an error if that is not allowed.
+    pub(crate) fn claim_member_name(
+        &mut self,
+        name: &str,
+        span: proc_macro2::Span,
+    ) -> Result<String, Error> {
+        if self.member_names.insert(name.to_string()) {
+            return Ok(name.to_string());
+        }
+        if !self.configuration.disambiguate_duplicate_names {
+            return Err(Error::DuplicateSymbol(name.to_string(), span));
+        }
+        let mut index = 2;
+        while self.member_names.contains(&format!("{}{}", name, index)) {
+            index += 1;
+        }
+        let disambiguated = format!("{}{}", name, index);
+        self.member_names.insert(disambiguated.clone());
+        self.add_warning(name, Error::DuplicateSymbol(name.to_string(), span));
+        Ok(disambiguated)
+    }
+
+    pub(crate) fn add_known_type(
+        &mut self,
+        rust_type_name: &str,
+        csharp_type_name: &str,
+        span: proc_macro2::Span,
+    ) -> Result<(), Error> {
+        self.register_known_type(
             rust_type_name,
-            self.namespace.clone(),
-            self.type_name.clone(),
-            csharp_type_name.to_string(),
-        );
+            CSharpType {
+                namespace: self.namespace.clone(),
+                inside_type: self.type_name.clone(),
+                real_type_name: csharp_type_name.to_string(),
+                marshal_as: None,
+            },
+            span,
+        )
+    }
+
+    /// Registers a type for the current build. It is added to the type registry once the build
+    /// succeeds, so a failed build leaves the registry as it was. Types registered by another
+    /// builder are not overwritten with a different type.
+    pub(crate) fn register_known_type(
+        &mut self,
+        rust_type_name: &str,
+        t: CSharpType,
+        span: proc_macro2::Span,
+    ) -> Result<(), Error> {
+        if !self.registered_types.contains(rust_type_name) {
+            if let Some(existing) = self.configuration.get_known_type(rust_type_name) {
+                if existing != t {
+                    return Err(Error::DuplicateSymbol(rust_type_name.to_string(), span));
+                }
+            }
+        }
+        let mut path = self.module_path.clone();
+        path.push(rust_type_name.to_string());
+        self.qualified_types.insert(path.join("::"), t.clone());
+        self.staged_types
+            .insert(rust_type_name.to_string(), (t, span));
+        Ok(())
+    }
+
+    /// Gets a type from the types registered during the current build, or from the type registry.
+    pub(crate) fn get_known_type(&self, rust_type_name: &str) -> Option<CSharpType> {
+        match self.staged_types.get(rust_type_name) {
+            Some((t, _)) => Some(t.clone()),
+            None => self.configuration.get_known_type(rust_type_name),
+        }
+    }
+
+    /// Gets the names of all types that can be resolved during the current build.
+    pub(crate) fn known_type_names(&self) -> impl Iterator<Item = String> + '_ {
+        let staged = self
+            .staged_types
+            .keys()
+            .filter(|name| self.configuration.get_known_type(name).is_none())
+            .cloned()
+            .collect::<Vec<_>>();
+        self.configuration.known_type_names().chain(staged)
+    }
+
+    /// Adds the types registered during the current build to the type registry, if the build
+    /// succeeded. Otherwise they are discarded.
+    pub(crate) fn finish_known_types<T>(&mut self, result: Result<T, Error>) -> Result<T, Error> {
+        let staged_types = std::mem::take(&mut self.staged_types);
+        if result.is_err() || !self.errors.is_empty() {
+            return result;
+        }
+        // The check and the insert happen under the same lock, so builders on other threads can't
+        // register a different type in between.
+        let mut known_types = self.configuration.write_known_types();
+        for (rust_type_name, (t, span)) in &staged_types {
+            if !self.registered_types.contains(rust_type_name) {
+                if let Some(existing) = known_types.get(rust_type_name) {
+                    if existing != t {
+                        return Err(Error::DuplicateSymbol(rust_type_name.to_string(), *span));
+                    }
+                }
+            }
+        }
+        for (rust_type_name, (t, _)) in staged_types {
+            self.registered_types.insert(rust_type_name.clone());
+            known_types.insert(rust_type_name, t);
+        }
+        result
+    }
+}
+
+/// The types registered by [`CSharpConfiguration::register_dotnet_interop_defaults`].
+const DOTNET_INTEROP_DEFAULTS: &[(&str, &str)] = &[
+    ("HANDLE", "IntPtr"),
+    ("HWND", "IntPtr"),
+    ("HINSTANCE", "IntPtr"),
+    ("HMODULE", "IntPtr"),
+    ("HDC", "IntPtr"),
+    ("HKEY", "IntPtr"),
+    ("HMENU", "IntPtr"),
+    ("HICON", "IntPtr"),
+    ("PVOID", "IntPtr"),
+    ("LPVOID", "IntPtr"),
+    ("LPCVOID", "IntPtr"),
+    ("WPARAM", "UIntPtr"),
+    ("LPARAM", "IntPtr"),
+    ("LRESULT", "IntPtr"),
+    ("GCHandle", "IntPtr"),
+];
+
+/// Generates C# bindings for Rust files and writes them to a file, for use in a build script.
+/// When running under cargo, ``cargo:rerun-if-changed`` directives are printed for the inputs, so
+/// the bindings are only generated again when the inputs change.
+///
+/// Example:
+/// ```no_run
+/// use csharp_binder::{generate, GenerateOptions};
+///
+/// let report = generate(GenerateOptions {
+///     inputs: vec!["src/ffi.rs".into()],
+///     output: "bindings/NativeMethods.cs".into(),
+///     dll_name: "foo".to_string(),
+///     namespace: Some("Foo".to_string()),
+///     class: Some("NativeMethods".to_string()),
+///     csharp_version: 9,
+///     ..Default::default()
+/// })
+/// .unwrap();
+/// for warning in report.warnings {
+///     println!("cargo:warning=Skipped {}: {}", warning.item_name, warning.error);
+/// }
+/// ```
+pub fn generate(options: GenerateOptions) -> Result<Report, Error> {
+    let under_cargo = std::env::var_os("OUT_DIR").is_some();
+    generate_bindings(&options, under_cargo, &mut std::io::stdout())
+}
+
+pub(crate) fn generate_bindings(
+    options: &GenerateOptions,
+    rerun_hints: bool,
+    out: &mut dyn std::io::Write,
+) -> Result<Report, Error> {
+    let default_configuration;
+    let configuration = match options.configuration {
+        Some(configuration) => configuration,
+        None => {
+            default_configuration = CSharpConfiguration::new(options.csharp_version);
+            &default_configuration
+        }
+    };
+    if rerun_hints {
+        for input in &options.inputs {
+            writeln!(out, "cargo:rerun-if-changed={}", input.display())?;
+        }
+    }
+
+    let mut builder = CSharpBuilder::new("", &options.dll_name, configuration)?;
+    for input in &options.inputs {
+        builder.add_path(input)?;
+    }
+    // The name of a single input file is used in the generated warning.
+    if let [input] = options.inputs.as_slice() {
+        if input.is_file() {
+            builder.set_source_name(&input.display().to_string());
+        }
+    }
+    if let Some(namespace) = &options.namespace {
+        builder.set_namespace(namespace);
+    }
+    if let Some(class) = &options.class {
+        builder.set_type(class);
+    }
+
+    builder.extraction = Some(ExtractedItems::default());
+    let result = builder.build();
+    let extracted = builder.extraction.take().unwrap_or_default();
+    let script = result?;
+    if let Some(parent) = options.output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&options.output, script)?;
+    Ok(Report {
+        functions: extracted.functions.len(),
+        structs: extracted.structs.len(),
+        enums: extracted.enums.len(),
+        warnings: std::mem::take(&mut builder.warnings),
+    })
+}
+
+/// The options of [`generate`].
+#[derive(Default)]
+pub struct GenerateOptions<'a> {
+    /// The Rust files to generate bindings for. Directories are searched recursively for ``.rs``
+    /// files. The items of all files are generated into the same script, in the order of the
+    /// paths.
+    pub inputs: Vec<PathBuf>,
+    /// The file to write the C# script to. Its directory is created if it doesn't exist.
+    pub output: PathBuf,
+    /// The name of the library C# is going to make calls to.
+    pub dll_name: String,
+    /// The namespace to generate the script in.
+    pub namespace: Option<String>,
+    /// The type that is wrapped around the generated script.
+    pub class: Option<String>,
+    /// The C# version to generate code for. Not used if a configuration is given.
+    pub csharp_version: u8,
+    /// The configuration to build with, for options and known types beyond those above. If not
+    /// given, the default configuration for the C# version is used.
+    pub configuration: Option<&'a CSharpConfiguration>,
+}
+
+/// The result of [`generate`].
+#[derive(Debug)]
+pub struct Report {
+    /// The number of functions that were generated.
+    pub functions: usize,
+    /// The number of structs that were generated.
+    pub structs: usize,
+    /// The number of enums that were generated.
+    pub enums: usize,
+    /// The warnings raised during the build, for items that were skipped.
+    pub warnings: Vec<Warning>,
+}
+
+/// A type to register with [`CSharpConfiguration::add_known_types`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "cli",
+    derive(serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
+pub struct KnownTypeEntry {
+    /// The name of the Rust type.
+    pub rust_name: String,
+    /// The namespace of the C# type, if it's in a different namespace.
+    pub namespace: Option<String>,
+    /// The type the C# type is declared in, if any.
+    pub inside_type: Option<String>,
+    /// The name of the C# type.
+    pub csharp_name: String,
+}
+
+/// The result of [`CSharpConfiguration::add_known_type`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KnownTypeUpdate {
+    /// The type was not known yet.
+    Inserted,
+    /// The type was already known with the same C# type.
+    ReplacedIdentical,
+    /// The type was known with a different C# type, which was replaced.
+    ReplacedDifferent,
+}
+
+/// A function that converts a Rust name into a C# name, see
+/// [`CSharpConfiguration::set_naming_converter`].
+pub type NamingConverter = Box<dyn Fn(&str, NameKind) -> String + Send + Sync>;
+
+/// The kind of symbol a name belongs to, which is passed to the naming converter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameKind {
+    /// The name of a function.
+    Function,
+    /// The name of a parameter of a function or delegate.
+    Parameter,
+    /// The name of a field of a struct, union or enum variant.
+    Field,
+    /// The name of an enum, struct, union or delegate.
+    Type,
+}
+
+/// The indentation used in generated C# scripts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indentation {
+    /// Indent with a tab for each level.
+    Tabs,
+    /// Indent with the given number of spaces for each level.
+    Spaces(u8),
+}
+
+/// The newlines used in generated C# scripts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Newline {
+    /// Unix style newlines, ``\n``.
+    Lf,
+    /// Windows style newlines, ``\r\n``.
+    CrLf,
+}
+
+/// How structs are declared in C#.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructStyle {
+    /// A ``struct`` with a property or readonly field for each field, and a constructor.
+    Classic,
+    /// Like [`StructStyle::Classic`], but declared as a ``readonly struct``. Requires C# 7.2, and is
+    /// the same as [`StructStyle::Classic`] for older versions.
+    ReadonlyStruct,
+    /// A positional ``readonly record struct``, with a parameter for each field, which provides
+    /// equality and deconstruction. The fields are documented as parameters of the record, with
+    /// their Rust type. Requires C# 10, and is the same as [`StructStyle::ReadonlyStruct`] for
+    /// older versions, and for structs with skipped fields or fixed buffers.
+    RecordStruct,
+}
+
+/// When structs get a constructor that initializes their fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstructorMode {
+    /// Every struct gets a constructor, including structs without fields.
+    Always,
+    /// No struct gets a constructor.
+    Never,
+    /// Structs get a constructor if it has any parameters. Structs without fields, or with only
+    /// fields that are left out of the constructor, don't get one.
+    NonEmptyOnly,
+}
+
+/// The order in which the items of a script are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ordering {
+    /// Items are written in the order they are declared in the Rust script.
+    SourceOrder,
+    /// Enums are written first, then structs and unions, then functions, and then everything else,
+    /// such as constants and delegates. Each group is sorted by the generated name, so moving items
+    /// around in the Rust script doesn't change the generated script.
+    Grouped,
+}
+
+/// Where the Rust type of fields, parameters and return values is written in the XML documentation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeRemarks {
+    /// The Rust type is not written.
+    Off,
+    /// The Rust type of fields is written in their remarks, and the Rust type of parameters and
+    /// return values after their description.
+    Remarks,
+    /// The Rust type of fields is appended to their summary. Parameters and return values are
+    /// documented the same as with [`TypeRemarks::Remarks`].
+    AppendToSummary,
+}
+
+/// How the Rust ``char`` type is converted into C#.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CSharpCharMapping {
+    /// Convert it into a ``uint``, holding the Unicode scalar value.
+    UInt,
+    /// Fail with an [`Error::UnsupportedError`].
+    Error,
+}
+
+/// The pointer width used for ``usize`` and ``isize``.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerWidth {
+    /// 32 bit, converted into ``uint`` and ``int``.
+    W32,
+    /// 64 bit, converted into ``ulong`` and ``long``.
+    W64,
+    /// The native width, converted into ``nuint`` and ``nint``. This requires C# 9. For
+    /// [`TargetRuntime::Unity`], ``UIntPtr`` and ``IntPtr`` are used instead.
+    Native,
+}
+
+/// The access modifiers of the generated code, set with
+/// [`CSharpConfiguration::set_accessibility`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccessibilityOptions {
+    /// The access modifier of the class that the script is wrapped in.
+    pub class: Access,
+    /// The access modifier of the imported functions.
+    pub methods: Access,
+    /// The access modifier of the generated structs, enums, unions, delegates and handles.
+    pub types: Access,
+}
+
+impl Default for AccessibilityOptions {
+    fn default() -> Self {
+        AccessibilityOptions {
+            class: Access::Internal,
+            methods: Access::Internal,
+            types: Access::Public,
+        }
+    }
+}
+
+/// A C# access modifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// ``public``, accessible from other assemblies.
+    Public,
+    /// ``internal``, only accessible from the same assembly.
+    Internal,
+}
+
+impl Access {
+    pub(crate) fn csharp_name(&self) -> &'static str {
+        match self {
+            Access::Public => "public",
+            Access::Internal => "internal",
+        }
+    }
+}
+
+/// The runtime the generated script is used with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetRuntime {
+    /// .NET or .NET Framework, where the features used depend on the C# version.
+    DotNet,
+    /// Unity with IL2CPP, which doesn't support native sized integers, ``init`` accessors or
+    /// ``LibraryImport``. Struct fields are written as readonly fields, pointer sized integers as
+    /// ``UIntPtr`` and ``IntPtr``, and delegates document the ``MonoPInvokeCallback`` attribute
+    /// that IL2CPP requires for callbacks.
+    Unity,
+}
+
+/// The size of the C ``long`` type on the target platform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CLongSize {
+    /// 32 bit, as used on Windows and 32 bit platforms.
+    Bits32,
+    /// 64 bit, as used on 64 bit Linux and macOS.
+    Bits64,
+}
+
+/// The calling conventions that can be used to call native functions from C#.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallingConvention {
+    /// The C calling convention, used for ``extern "C"`` functions by default.
+    Cdecl,
+    /// The standard Win32 calling convention, used for ``extern "stdcall"`` functions.
+    StdCall,
+    /// The C++ member function calling convention, used for ``extern "thiscall"`` functions.
+    ThisCall,
+    /// The default calling convention of the platform, used for ``extern "system"`` and
+    /// ``extern "win64"`` functions.
+    Winapi,
+}
+
+impl CallingConvention {
+    pub(crate) fn csharp_name(&self) -> &'static str {
+        match self {
+            CallingConvention::Cdecl => "Cdecl",
+            CallingConvention::StdCall => "StdCall",
+            CallingConvention::ThisCall => "ThisCall",
+            CallingConvention::Winapi => "Winapi",
+        }
+    }
+
+    /// The type used to specify the calling convention for source generated imports. The platform
+    /// default has no type, and is used when none is specified.
+    pub(crate) fn call_conv_type(&self) -> Option<&'static str> {
+        match self {
+            CallingConvention::Cdecl => Some("CallConvCdecl"),
+            CallingConvention::StdCall => Some("CallConvStdcall"),
+            CallingConvention::ThisCall => Some("CallConvThiscall"),
+            CallingConvention::Winapi => None,
+        }
+    }
+}
+
+/// The options of a ``DllImport`` attribute. Options that aren't set are left out of the attribute,
+/// so the defaults of .NET are used for them. The options are written in the order of the fields.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DllImportOptions {
+    /// Whether the error of the last native call is kept, so that it can be read with
+    /// ``Marshal.GetLastWin32Error``.
+    pub set_last_error: Option<bool>,
+    /// Whether the runtime only looks for the entry point as it is written, instead of probing for
+    /// variants with an ``A`` or ``W`` suffix.
+    pub exact_spelling: Option<bool>,
+    /// Whether Unicode characters without an ANSI equivalent are mapped to the closest character.
+    pub best_fit_mapping: Option<bool>,
+    /// The character set strings and chars are marshalled with.
+    pub charset: Option<CharSet>,
+}
+
+/// The character sets that strings and chars can be marshalled with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharSet {
+    None,
+    Ansi,
+    Unicode,
+    Auto,
+}
+
+impl CharSet {
+    pub(crate) fn csharp_name(&self) -> &'static str {
+        match self {
+            CharSet::None => "None",
+            CharSet::Ansi => "Ansi",
+            CharSet::Unicode => "Unicode",
+            CharSet::Auto => "Auto",
+        }
+    }
+}
+
+fn compile_pattern(pattern: &str) -> Result<Regex, Error> {
+    let is_glob = pattern
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == '*' || c == '?');
+    let expression = if is_glob {
+        regex::escape(pattern)
+            .replace("\\*", ".*")
+            .replace("\\?", ".")
+    } else {
+        pattern.to_string()
+    };
+    Ok(Regex::new(&format!("^(?:{})$", expression))?)
+}
+
+/// The configuration that ``#[cfg(...)]`` attributes are evaluated against. Supported predicates
+/// are ``feature = "..."``, ``target_os = "..."``, ``test``, ``windows`` and ``unix``, combined
+/// with ``all``, ``any`` and ``not``.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CfgOptions {
+    /// The enabled features of the crate.
+    pub features: Vec<String>,
+    /// The operating system the library is built for, such as ``linux`` or ``windows``. If not set,
+    /// predicates on the operating system can't be evaluated.
+    pub target_os: Option<String>,
+    /// Whether the library is built for tests.
+    pub test: bool,
+}
+
+/// The file names of a library on each platform, set with [`CSharpBuilder::set_dll_names`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DllNames {
+    /// The file name on Windows, such as ``mylib.dll``.
+    pub windows: String,
+    /// The file name on Linux, such as ``libmylib.so``. This is also used on other platforms.
+    pub linux: String,
+    /// The file name on macOS, such as ``libmylib.dylib``.
+    pub macos: String,
+}
+
+/// A C# file generated by [`CSharpBuilder::build_split`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedFile {
+    /// The suggested file name, which is the name of the type in the file followed by ``.cs``.
+    pub suggested_name: String,
+    /// The contents of the file.
+    pub contents: String,
+}
+
+/// Allows writing formatted text to an ``io::Write``, keeping the error of the writer if it fails.
+struct IoWriteAdapter<'a, W: std::io::Write> {
+    writer: &'a mut W,
+    error: Option<std::io::Error>,
+}
+
+impl<W: std::io::Write> std::fmt::Write for IoWriteAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.writer.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            std::fmt::Error
+        })
+    }
+}
+
+/// The items of a Rust script as they are converted into C#, returned by
+/// [`CSharpBuilder::extracted_items`].
+#[derive(Debug, Default)]
+pub struct ExtractedItems {
+    pub functions: Vec<ExtractedFunction>,
+    pub structs: Vec<ExtractedStruct>,
+    pub enums: Vec<ExtractedEnum>,
+    /// The items that could not be converted, with the error they produce.
+    pub unsupported: Vec<Warning>,
+}
+
+/// A type, both as it is written in Rust, and as it is converted into C#.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedType {
+    pub rust_type: String,
+    pub csharp_type: String,
+}
+
+/// An extern function, converted into a C# method.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedFunction {
+    pub rust_name: String,
+    pub csharp_name: String,
+    /// The name of the symbol the function is exported as.
+    pub entry_point: String,
+    /// The parameters, with their C# name and type.
+    pub params: Vec<(String, ExtractedType)>,
+    pub return_type: ExtractedType,
+}
+
+/// A struct, converted into a C# struct.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedStruct {
+    pub rust_name: String,
+    pub csharp_name: String,
+    /// The names of the generic type parameters.
+    pub generics: Vec<String>,
+    pub fields: Vec<ExtractedField>,
+}
+
+/// A field of a struct. Fields that are split into multiple C# fields, such as 128 bit integers,
+/// have an entry for each C# field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedField {
+    /// The name of the field in Rust, or its index for tuple structs.
+    pub rust_name: String,
+    pub csharp_name: String,
+    pub field_type: ExtractedType,
+    /// Whether the field is written as an auto-property, rather than as a field.
+    pub is_property: bool,
+    /// The offset of this C# field within the Rust field, in bytes. This is only non-zero for the
+    /// high half of a split 128 bit integer.
+    pub offset_in_field: usize,
+}
+
+/// An enum, converted into a C# enum. Enums with fields are converted into a struct instead, and
+/// their variants are the values of its tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedEnum {
+    pub rust_name: String,
+    pub csharp_name: String,
+    pub underlying_type: ExtractedType,
+    pub variants: Vec<ExtractedVariant>,
+}
+
+/// A variant of an enum, with its discriminant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedVariant {
+    pub rust_name: String,
+    pub csharp_name: String,
+    pub discriminant: String,
+}
+
+/// The layout tests generated by [`CSharpBuilder::build_layout_tests`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutTests {
+    /// The C# file with the tests.
+    pub csharp: String,
+    /// The Rust functions the tests use, which need to be added to the library, in a module where
+    /// the structs are in scope.
+    pub rust: String,
+}
+
+/// How a build handles items that fail to convert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BuildMode {
+    /// The build fails on the first error.
+    Strict,
+    /// Failing items are omitted, and a warning is raised for them.
+    Lenient,
+    /// Failing items are omitted, and their errors are collected.
+    CollectErrors,
+}
+
+/// A warning raised during a build, for an item that was skipped as it could not be converted.
+#[derive(Debug)]
+pub struct Warning {
+    /// The Rust name of the item the warning was raised for.
+    pub item_name: String,
+    /// The reason the item was skipped.
+    pub error: Error,
+}
+
+/// A field of a generated struct that isn't blittable, which makes the struct be copied and
+/// converted when it is marshalled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlittabilityWarning {
+    /// The Rust name of the struct.
+    pub struct_name: String,
+    /// The Rust name of the field, or its index for tuple structs.
+    pub field_name: String,
+    /// Why the field isn't blittable.
+    pub reason: String,
+}
+
+/// An entry point found by [`CSharpBuilder::verify_entry_points`], which would fail or be ambiguous
+/// at runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MissingSymbol {
+    /// The entry point isn't exported by the library.
+    NotExported(String),
+    /// The entry point is imported by more than one method.
+    Duplicate(String),
+}
+
+/// The error returned by [`CSharpBuilder::verify_against`].
+#[derive(Debug)]
+pub enum VerifyError {
+    /// The script could not be built, or the existing script could not be read.
+    Error(Error),
+    /// The generated script differs from the existing script.
+    Mismatch(Mismatch),
+}
+
+/// The differences between a generated script and an existing script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// The number of the first line that differs, starting at 1.
+    pub line: usize,
+    /// The line in the existing script, or None if the existing script has fewer lines.
+    pub existing_line: Option<String>,
+    /// The line in the generated script, or None if the generated script has fewer lines.
+    pub generated_line: Option<String>,
+    /// A unified diff from the existing script to the generated script.
+    pub diff: String,
+}
+
+impl std::fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerifyError::Error(e) => e.fmt(f),
+            VerifyError::Mismatch(mismatch) => {
+                writeln!(
+                    f,
+                    "The generated script differs from the existing script at line {}.",
+                    mismatch.line
+                )?;
+                f.write_str(&mismatch.diff)
+            }
+        }
+    }
+}
+
+impl From<Error> for VerifyError {
+    fn from(error: Error) -> Self {
+        VerifyError::Error(error)
     }
 }
 
@@ -253,8 +2416,30 @@ pub enum Error {
     ParseError(syn::Error),
     IOError(std::io::Error),
     FmtError(std::fmt::Error),
+    PatternError(regex::Error),
     UnsupportedError(String, proc_macro2::Span),
     UnknownType(String, proc_macro2::Span),
+    DuplicateSymbol(String, proc_macro2::Span),
+    /// A type is referenced by a name that multiple types in different modules have, with the
+    /// paths of these types.
+    AmbiguousType(String, Vec<String>, proc_macro2::Span),
+    /// Expanding the macros of a crate failed, with the error output of cargo, or the reason cargo
+    /// could not be run.
+    ExpansionError(String),
+    /// An error in the source set with [`CSharpBuilder::set_source_name`], whose location is
+    /// reported with the name of the source.
+    InSource(String, Box<Error>),
+}
+
+impl Error {
+    /// Gets the error without the name of its source, which is useful to match on the kind of
+    /// error.
+    pub fn without_source(&self) -> &Error {
+        match self {
+            Error::InSource(_, error) => error,
+            error => error,
+        }
+    }
 }
 
 impl std::fmt::Display for Error {
@@ -263,6 +2448,8 @@ impl std::fmt::Display for Error {
             Error::ParseError(e) => e.fmt(f),
             Error::IOError(e) => e.fmt(f),
             Error::FmtError(e) => e.fmt(f),
+            Error::PatternError(e) => e.fmt(f),
+            Error::ExpansionError(e) => write!(f, "Failed to expand the crate: {}", e),
             Error::UnsupportedError(e, span) => {
                 f.write_str(e)?;
                 f.write_str(
@@ -278,13 +2465,58 @@ impl std::fmt::Display for Error {
                 f.write_str(e)?;
                 f.write_str(
                     format!(
-                        ". At At line {}, position {}",
+                        ". At line {}, position {}",
                         span.start().line,
                         span.start().column
                     )
                     .as_str(),
                 )
             }
+            Error::DuplicateSymbol(name, span) => f.write_str(
+                format!(
+                    "The name '{}' is already used. At line {}, position {}",
+                    name,
+                    span.start().line,
+                    span.start().column
+                )
+                .as_str(),
+            ),
+            Error::AmbiguousType(name, candidates, span) => write!(
+                f,
+                "The type '{}' is ambiguous, it could be any of {}. At line {}, position {}",
+                name,
+                candidates.join(", "),
+                span.start().line,
+                span.start().column
+            ),
+            Error::InSource(source_name, error) => {
+                let (message, span) = match error.as_ref() {
+                    Error::ParseError(e) => (e.to_string(), e.span()),
+                    Error::UnsupportedError(e, span) | Error::UnknownType(e, span) => {
+                        (e.to_string(), *span)
+                    }
+                    Error::DuplicateSymbol(name, span) => {
+                        (format!("The name '{}' is already used", name), *span)
+                    }
+                    Error::AmbiguousType(name, candidates, span) => (
+                        format!(
+                            "The type '{}' is ambiguous, it could be any of {}",
+                            name,
+                            candidates.join(", ")
+                        ),
+                        *span,
+                    ),
+                    e => return write!(f, "{}: {}", source_name, e),
+                };
+                write!(
+                    f,
+                    "{}. At {}: line {}, column {}",
+                    message,
+                    source_name,
+                    span.start().line,
+                    span.start().column
+                )
+            }
         }
     }
 }
@@ -304,3 +2536,9 @@ impl From<std::fmt::Error> for Error {
         Error::FmtError(error)
     }
 }
+
+impl From<regex::Error> for Error {
+    fn from(error: regex::Error) -> Self {
+        Error::PatternError(error)
+    }
+}
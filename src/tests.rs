@@ -1,14 +1,21 @@
-use crate::{CSharpBuilder, CSharpConfiguration};
+use crate::{
+    Access, AccessibilityOptions, BlittabilityWarning, CLongSize, CSharpBuilder, CSharpCharMapping,
+    CSharpConfiguration, CallingConvention, CfgOptions, CharSet, ConstructorMode, DllImportOptions,
+    DllNames, Error, ExtractedField, ExtractedType, ExtractedVariant, GenerateOptions, Indentation,
+    KnownTypeEntry, KnownTypeUpdate, Mismatch, MissingSymbol, NameKind, Newline, Ordering,
+    PointerWidth, StructStyle, TargetRuntime, TypeRemarks, VerifyError,
+};
+use regex::Regex;
 
 #[test]
 fn create_builder() {
-    let mut configuration = CSharpConfiguration::new(9);
-    CSharpBuilder::new(r#"pub fn foo(){}"#, "foo", &mut configuration).unwrap();
+    let configuration = CSharpConfiguration::new(9);
+    CSharpBuilder::new(r#"pub fn foo(){}"#, "foo", &configuration).unwrap();
 }
 #[test]
 fn build_empty_with_namespace() {
-    let mut configuration = CSharpConfiguration::new(9);
-    let mut builder = CSharpBuilder::new(r#""#, "foo", &mut configuration).unwrap();
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(r#""#, "foo", &configuration).unwrap();
     builder.set_namespace("foo");
     let script = builder.build().unwrap();
     assert_eq!(
@@ -26,8 +33,8 @@ namespace foo
 
 #[test]
 fn build_empty_with_type() {
-    let mut configuration = CSharpConfiguration::new(9);
-    let mut builder = CSharpBuilder::new(r#""#, "foo", &mut configuration).unwrap();
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(r#""#, "foo", &configuration).unwrap();
     builder.set_type("foo");
     let script = builder.build().unwrap();
     assert_eq!(
@@ -42,8 +49,8 @@ internal static class foo\n{\n}\n"
 
 #[test]
 fn build_empty_with_namespace_and_type() {
-    let mut configuration = CSharpConfiguration::new(9);
-    let mut builder = CSharpBuilder::new(r#""#, "foo", &mut configuration).unwrap();
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(r#""#, "foo", &configuration).unwrap();
     builder.set_namespace("foo");
     builder.set_type("bar");
     let script = builder.build().unwrap();
@@ -64,9 +71,9 @@ namespace foo
 
 #[test]
 fn build_with_void_function() {
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let mut builder =
-        CSharpBuilder::new(r#"pub extern "C" fn foo(){}"#, "foo", &mut configuration).unwrap();
+        CSharpBuilder::new(r#"pub extern "C" fn foo(){}"#, "foo", &configuration).unwrap();
     builder.set_namespace("foo");
     builder.set_type("bar");
     let script = builder.build().unwrap();
@@ -91,11 +98,11 @@ namespace foo
 
 #[test]
 fn build_with_longer_named_void_function() {
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let mut builder = CSharpBuilder::new(
         r#"pub extern "C" fn foo_bar_zet(foo_bar: u8){}"#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
@@ -123,11 +130,11 @@ namespace foo
 
 #[test]
 fn build_with_u8_function() {
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let mut builder = CSharpBuilder::new(
         r#"pub extern "C" fn foo() -> u8 { 0 }"#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
@@ -154,11 +161,11 @@ namespace foo
 
 #[test]
 fn build_with_u8_ptr_function() {
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let mut builder = CSharpBuilder::new(
         r#"pub extern "C" fn foo() -> *const u8 { 0 }"#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
@@ -174,7 +181,7 @@ namespace foo
 {
     internal static class bar
     {
-        /// <returns>u8*</returns>
+        /// <returns>*const u8</returns>
         [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"foo\")]
         internal static extern IntPtr Foo();
 
@@ -185,11 +192,11 @@ namespace foo
 
 #[test]
 fn build_with_void_function_with_parameters() {
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let mut builder = CSharpBuilder::new(
         r#"pub extern "C" fn foo(a: u8, b: u8) { }"#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
@@ -218,11 +225,11 @@ namespace foo
 
 #[test]
 fn build_with_void_function_with_pointer_parameters() {
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let mut builder = CSharpBuilder::new(
         r#"pub extern "C" fn foo(a: *const u8, b: *const u8) {  }"#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
@@ -238,8 +245,8 @@ namespace foo
 {
     internal static class bar
     {
-        /// <param name=\"a\">u8*</param>
-        /// <param name=\"b\">u8*</param>
+        /// <param name=\"a\">*const u8</param>
+        /// <param name=\"b\">*const u8</param>
         /// <returns>void</returns>
         [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"foo\")]
         internal static extern void Foo(IntPtr a, IntPtr b);
@@ -251,13 +258,13 @@ namespace foo
 
 #[test]
 fn build_with_void_function_with_outer_doc_documentation() {
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let mut builder = CSharpBuilder::new(
         r#"
             /// test documentation
             pub extern "C" fn foo(a: *const u8, b: *const u8) {  }"#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
@@ -276,8 +283,8 @@ namespace foo
         /// <summary>
         /// test documentation
         /// </summary>
-        /// <param name=\"a\">u8*</param>
-        /// <param name=\"b\">u8*</param>
+        /// <param name=\"a\">*const u8</param>
+        /// <param name=\"b\">*const u8</param>
         /// <returns>void</returns>
         [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"foo\")]
         internal static extern void Foo(IntPtr a, IntPtr b);
@@ -289,11 +296,11 @@ namespace foo
 
 #[test]
 fn build_void_function_inside_module() {
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let mut builder = CSharpBuilder::new(
         r#"mod foo_module { pub extern "C" fn foo(){} }"#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
@@ -320,11 +327,11 @@ namespace foo
 
 #[test]
 fn build_enum() {
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let mut builder = CSharpBuilder::new(
         r#"#[repr(u8)] enum Foo { One, Two, Three}"#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
@@ -354,11 +361,11 @@ namespace foo
 
 #[test]
 fn build_enum_with_values() {
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let mut builder = CSharpBuilder::new(
         r#"#[repr(u8)] enum Foo { One = 1, Two = 2, Five = 5}"#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
@@ -388,13 +395,13 @@ namespace foo
 
 #[test]
 fn build_enum_with_values_and_documentation() {
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let mut builder = CSharpBuilder::new(
         r#"#[repr(u8)] 
             /// testing documentation for enum
             enum Foo { One = 1, Two = 2, Five = 5}"#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
@@ -427,7 +434,7 @@ namespace foo
 
 #[test]
 fn build_enum_with_values_and_documentation_for_keys() {
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let mut builder = CSharpBuilder::new(
         r#"#[repr(u8)] 
             /// testing documentation for enum
@@ -440,7 +447,7 @@ fn build_enum_with_values_and_documentation_for_keys() {
                 Five = 5
             }"#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
@@ -482,7 +489,7 @@ namespace foo
 
 #[test]
 fn build_struct_csharp_8() {
-    let mut configuration = CSharpConfiguration::new(8);
+    let configuration = CSharpConfiguration::new(8);
     let mut builder = CSharpBuilder::new(
         r#"#[repr(C)] 
             struct Foo {
@@ -490,7 +497,7 @@ fn build_struct_csharp_8() {
                 field_b: u8,
             }"#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
@@ -528,7 +535,7 @@ namespace foo
 
 #[test]
 fn build_struct_with_documentation_csharp_8() {
-    let mut configuration = CSharpConfiguration::new(8);
+    let configuration = CSharpConfiguration::new(8);
     let mut builder = CSharpBuilder::new(
         r#"#[repr(C)] 
             /// test documentation struct
@@ -539,7 +546,7 @@ fn build_struct_with_documentation_csharp_8() {
                 field_b: u8,
             }"#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
@@ -572,6 +579,8 @@ namespace foo
             /// <remarks>u8</remarks>
             public readonly byte FieldB;
 
+            /// <param name=\"fieldA\">a field. Very important!</param>
+            /// <param name=\"fieldB\">b field. reserved or something</param>
             public Foo(byte fieldA, byte fieldB)
             {
                 FieldA = fieldA;
@@ -586,7 +595,7 @@ namespace foo
 
 #[test]
 fn build_struct_csharp_9() {
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let mut builder = CSharpBuilder::new(
         r#"#[repr(C)] 
             struct Foo {
@@ -594,7 +603,7 @@ fn build_struct_csharp_9() {
                 field_b: u8,
             }"#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
@@ -632,11 +641,11 @@ namespace foo
 
 #[test]
 fn build_function_with_unknown_return_type() {
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let mut builder = CSharpBuilder::new(
         r#"pub extern "C" fn foo() -> UnknownType {}"#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
@@ -647,7 +656,7 @@ fn build_function_with_unknown_return_type() {
 
 #[test]
 fn build_function_with_registered_enum_and_return_function_of_enum() {
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let mut builder = CSharpBuilder::new(
         r#"
 #[repr(u8)]
@@ -658,13 +667,13 @@ enum KnownEnum{
 pub extern "C" fn foo() -> KnownEnum {}
         "#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
     builder.set_type("bar");
     let script = builder.build();
-    assert!(!script.is_err());
+    assert!(script.is_ok());
     assert_eq!(
         script.unwrap(),
         "// Automatically generated, do not edit!
@@ -691,7 +700,7 @@ namespace foo
 
 #[test]
 fn build_function_with_registered_enum_from_earlier_build_without_type() {
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let mut enum_builder = CSharpBuilder::new(
         r#"
 #[repr(u8)]
@@ -700,7 +709,7 @@ enum KnownEnum{
 }
         "#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     enum_builder.set_namespace("foo");
@@ -711,13 +720,13 @@ enum KnownEnum{
 pub extern "C" fn foo() -> KnownEnum {}
         "#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
     builder.set_type("bar");
     let script = builder.build();
-    assert!(!script.is_err());
+    assert!(script.is_ok());
     assert_eq!(
         script.unwrap(),
         "// Automatically generated, do not edit!
@@ -739,7 +748,7 @@ namespace foo
 
 #[test]
 fn build_function_with_registered_enum_from_earlier_build_in_different_type() {
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let mut enum_builder = CSharpBuilder::new(
         r#"
 #[repr(u8)]
@@ -748,7 +757,7 @@ enum KnownEnum{
 }
         "#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     enum_builder.set_namespace("foo");
@@ -760,13 +769,13 @@ enum KnownEnum{
 pub extern "C" fn foo() -> KnownEnum {}
         "#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
     builder.set_type("bar");
     let script = builder.build();
-    assert!(!script.is_err());
+    assert!(script.is_ok());
     assert_eq!(
         script.unwrap(),
         "// Automatically generated, do not edit!
@@ -788,7 +797,7 @@ namespace foo
 
 #[test]
 fn build_function_with_registered_enum_from_earlier_build_in_different_type_and_namespace() {
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let mut enum_builder = CSharpBuilder::new(
         r#"
 #[repr(u8)]
@@ -797,7 +806,7 @@ enum KnownEnum{
 }
         "#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     enum_builder.set_namespace("DiffNameSpace.Test");
@@ -809,13 +818,13 @@ enum KnownEnum{
 pub extern "C" fn foo() -> KnownEnum {}
         "#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
     builder.set_type("bar");
     let script = builder.build();
-    assert!(!script.is_err());
+    assert!(script.is_ok());
     assert_eq!(
         script.unwrap(),
         "// Automatically generated, do not edit!
@@ -845,7 +854,7 @@ fn build_function_with_out_param() {
 pub extern "C" fn foo(p: Out<u8>) {}
         "#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
@@ -861,7 +870,7 @@ namespace foo
 {
     internal static class bar
     {
-        /// <param name=\"p\">Out</param>
+        /// <param name=\"p\">Out&lt;u8&gt;</param>
         /// <returns>void</returns>
         [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"foo\")]
         internal static extern void Foo(out byte p);
@@ -873,7 +882,7 @@ namespace foo
 
 #[test]
 fn build_function_with_type_def_of_enum() {
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let mut builder = CSharpBuilder::new(
         r#"
 #[repr(u8)]
@@ -886,13 +895,13 @@ type AnotherDefinition = KnownEnum;
 pub extern "C" fn foo() -> AnotherDefinition {}
         "#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
     builder.set_type("bar");
     let script = builder.build();
-    assert!(!script.is_err());
+    assert!(script.is_ok());
     assert_eq!(
         script.unwrap(),
         "// Automatically generated, do not edit!
@@ -919,7 +928,7 @@ namespace foo
 
 #[test]
 fn build_function_with_generic_struct() {
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let mut builder = CSharpBuilder::new(
         r#"
 #[repr(C)]
@@ -930,7 +939,7 @@ struct TestStruct<T> {
 pub extern "C" fn foo(par: TestStruct<u16>) -> TestStruct<u8> {}
         "#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
@@ -948,6 +957,7 @@ namespace foo
     {
         [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
         public struct TestStruct<T>
+            where T : unmanaged
         {
             /// <remarks>T</remarks>
             public T Value { get; init; }
@@ -958,8 +968,8 @@ namespace foo
             }
         }
 
-        /// <param name=\"par\">TestStruct</param>
-        /// <returns>TestStruct</returns>
+        /// <param name=\"par\">TestStruct&lt;u16&gt;</param>
+        /// <returns>TestStruct&lt;u8&gt;</returns>
         [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"foo\")]
         internal static extern TestStruct<byte> Foo(TestStruct<ushort> par);
 
@@ -970,7 +980,7 @@ namespace foo
 
 #[test]
 fn build_function_with_generic_struct_type_definition() {
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let mut builder = CSharpBuilder::new(
         r#"
 #[repr(C)]
@@ -983,7 +993,7 @@ type Redefinition = TestStruct<u8>;
 pub extern "C" fn foo() -> Redefinition {}
         "#,
         "foo",
-        &mut configuration,
+        &configuration,
     )
     .unwrap();
     builder.set_namespace("foo");
@@ -1001,6 +1011,7 @@ namespace foo
     {
         [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
         public struct TestStruct<T>
+            where T : unmanaged
         {
             /// <remarks>T</remarks>
             public T Value { get; init; }
@@ -1024,7 +1035,7 @@ namespace foo
 fn remove_top_warning() {
     let mut configuration = CSharpConfiguration::new(9);
     configuration.set_generated_warning("");
-    let mut builder = CSharpBuilder::new(r#""#, "foo", &mut configuration).unwrap();
+    let mut builder = CSharpBuilder::new(r#""#, "foo", &configuration).unwrap();
     builder.set_namespace("foo");
     let script = builder.build().unwrap();
     assert_eq!(
@@ -1043,7 +1054,7 @@ namespace foo
 fn sets_multiline_top_warning() {
     let mut configuration = CSharpConfiguration::new(9);
     configuration.set_generated_warning("This should set\na warning\nacross multiple\nlines");
-    let mut builder = CSharpBuilder::new(r#""#, "foo", &mut configuration).unwrap();
+    let mut builder = CSharpBuilder::new(r#""#, "foo", &configuration).unwrap();
     builder.set_namespace("foo");
     let script = builder.build().unwrap();
     assert_eq!(
@@ -1065,7 +1076,7 @@ namespace foo
 #[test]
 fn example_test() {
     // Create C# configuration with C# target version 9.
-    let mut configuration = CSharpConfiguration::new(9);
+    let configuration = CSharpConfiguration::new(9);
     let rust_file = r#"
         /// Just a random return enum
         #[repr(u8)]
@@ -1086,7 +1097,7 @@ fn example_test() {
         }
         "#;
     let mut builder =
-        CSharpBuilder::new(rust_file, "foo", &mut configuration).expect("Failed to parse file");
+        CSharpBuilder::new(rust_file, "foo", &configuration).expect("Failed to parse file");
     builder.set_namespace("MainNamespace");
     builder.set_type("InsideClass");
     let script = builder.build().expect("Failed to build");
@@ -1123,6 +1134,8 @@ namespace MainNamespace
             /// <remarks>f64</remarks>
             public double FieldB { get; init; }
 
+            /// <param name="fieldA">u16</param>
+            /// <param name="fieldB">This field is used for floats!</param>
             public InputStruct(ushort fieldA, double fieldB)
             {
                 FieldA = fieldA;
@@ -1140,3 +1153,9999 @@ namespace MainNamespace
 "#
     )
 }
+
+#[test]
+fn build_extern_function_abi_spellings() {
+    for script in &[
+        r#"pub extern "C" fn foo(){}"#,
+        r#"pub extern fn foo(){}"#,
+        r#"pub extern "C-unwind" fn foo(){}"#,
+    ] {
+        let configuration = CSharpConfiguration::new(9);
+        let mut builder = CSharpBuilder::new(script, "foo", &configuration).unwrap();
+        builder.set_namespace("foo");
+        builder.set_type("bar");
+        let script = builder.build().unwrap();
+        assert_eq!(
+            script,
+            "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"foo\")]
+        internal static extern void Foo();
+
+    }
+}\n"
+        )
+    }
+}
+
+#[test]
+fn build_function_with_bool_without_marshaling_fails() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"pub extern "C" fn foo(flag: bool) {}"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    assert!(builder.build().is_err());
+}
+
+#[test]
+fn build_function_with_bool_marshaling() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.allow_bool_marshaling(true);
+    let mut builder = CSharpBuilder::new(
+        r#"pub extern "C" fn foo(flag: bool, ptr: *const bool) -> bool {}"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        /// <param name=\"flag\">bool</param>
+        /// <param name=\"ptr\">*const bool</param>
+        /// <returns>bool</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"foo\")]
+        [return: MarshalAs(UnmanagedType.I1)]
+        internal static extern bool Foo([MarshalAs(UnmanagedType.I1)] bool flag, IntPtr ptr);
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_struct_with_bool_marshaling() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.allow_bool_marshaling(true);
+    let mut builder = CSharpBuilder::new(
+        r#"#[repr(C)]
+            struct Foo {
+                flag: bool,
+            }"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Foo
+        {
+            /// <remarks>bool</remarks>
+            [field: MarshalAs(UnmanagedType.I1)]
+            public bool Flag { get; init; }
+
+            public Foo(bool flag)
+            {
+                Flag = flag;
+            }
+        }
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_struct_with_bool_marshaling_csharp_8() {
+    let mut configuration = CSharpConfiguration::new(8);
+    configuration.allow_bool_marshaling(true);
+    let mut builder = CSharpBuilder::new(
+        r#"#[repr(C)]
+            struct Foo {
+                flag: bool,
+            }"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Foo
+        {
+            /// <remarks>bool</remarks>
+            [MarshalAs(UnmanagedType.I1)]
+            public readonly bool Flag;
+
+            public Foo(bool flag)
+            {
+                Flag = flag;
+            }
+        }
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_struct_with_array_field() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            struct Inner {
+                value: u8,
+            }
+
+            #[repr(C)]
+            struct Foo {
+                buf: [u8; 32],
+                inners: [Inner; 2],
+            }"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Inner
+        {
+            /// <remarks>u8</remarks>
+            public byte Value { get; init; }
+
+            public Inner(byte value)
+            {
+                Value = value;
+            }
+        }
+
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Foo
+        {
+            /// <remarks>[u8; 32]</remarks>
+            [field: MarshalAs(UnmanagedType.ByValArray, SizeConst = 32)]
+            public byte[] Buf { get; init; }
+            /// <remarks>[Inner; 2]</remarks>
+            [field: MarshalAs(UnmanagedType.ByValArray, SizeConst = 2)]
+            public Inner[] Inners { get; init; }
+
+            public Foo(byte[] buf, Inner[] inners)
+            {
+                Buf = buf;
+                Inners = inners;
+            }
+        }
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_struct_with_array_field_as_fixed_buffer() {
+    let mut configuration = CSharpConfiguration::new(8);
+    configuration.use_fixed_buffers(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            struct Foo {
+                len: u32,
+                buf: [u8; 32],
+            }"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Foo
+        {
+            /// <remarks>u32</remarks>
+            public readonly uint Len;
+            /// <remarks>[u8; 32]</remarks>
+            public unsafe fixed byte Buf[32];
+
+            public Foo(uint len)
+            {
+                Len = len;
+            }
+        }
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_struct_with_non_literal_array_length_fails() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            struct Foo {
+                buf: [u8; SIZE],
+            }"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    assert!(builder.build().is_err());
+}
+
+#[test]
+fn build_function_with_callback_parameter() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            /// Registers a callback.
+            pub extern "C" fn foo(cb: extern "C" fn(u8, data: *const u8) -> u32) {}
+            pub extern "C" fn bar(callback: extern "C" fn(u8, data: *const u8) -> u32) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate uint FooCbDelegate(byte arg0, IntPtr data);
+
+        /// <summary>
+        /// Registers a callback.
+        /// </summary>
+        /// <param name=\"cb\">fn(u8, *const u8) -&gt; u32</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"foo\")]
+        internal static extern void Foo(FooCbDelegate cb);
+
+        /// <param name=\"callback\">fn(u8, *const u8) -&gt; u32</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"bar\")]
+        internal static extern void Bar(FooCbDelegate callback);
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_function_with_rust_abi_callback_fails() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"pub extern "C" fn foo(cb: fn(u8)) {}"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    assert!(builder.build().is_err());
+}
+
+#[test]
+fn build_function_with_callback_type_alias() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            /// Called when a message gets logged.
+            type LogCallback = extern "C" fn(message: *const c_char);
+
+            #[repr(C)]
+            struct Logger {
+                callback: LogCallback,
+            }
+
+            pub extern "C" fn set_logger(cb: LogCallback) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        /// <summary>
+        /// Called when a message gets logged.
+        /// </summary>
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void LogCallback(IntPtr message);
+
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Logger
+        {
+            /// <remarks>LogCallback</remarks>
+            public LogCallback Callback { get; init; }
+
+            public Logger(LogCallback callback)
+            {
+                Callback = callback;
+            }
+        }
+
+        /// <param name=\"cb\">LogCallback</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"set_logger\")]
+        internal static extern void SetLogger(LogCallback cb);
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_constants() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            /// The maximum length of a name.
+            pub const MAX_NAME_LEN: usize = 64;
+            pub const VERSION: u32 = 0x0103;
+            pub const MIN_OFFSET: i32 = -10;
+            pub const SCALE: f32 = 1.5;
+            const PRIVATE: u32 = 5;
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        /// <summary>
+        /// The maximum length of a name.
+        /// </summary>
+        public const nuint MaxNameLen = 64;
+
+        public const uint Version = 0x0103;
+
+        public const int MinOffset = -10;
+
+        public const float Scale = 1.5f;
+
+    }
+}\n"
+    );
+    assert!(builder.warnings().is_empty());
+}
+
+#[test]
+fn build_constant_with_non_literal_value_warns() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub const SIZE: u32 = 4 * 8;
+            pub const OTHER: u32 = 4;
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+internal static class bar
+{
+    public const uint Other = 4;
+
+}
+"
+    );
+    assert_eq!(builder.warnings().len(), 1);
+    assert_eq!(builder.warnings()[0].item_name, "SIZE");
+}
+
+#[test]
+fn build_tuple_struct_csharp_8() {
+    let configuration = CSharpConfiguration::new(8);
+    let mut builder = CSharpBuilder::new(
+        r#"#[repr(C)] struct Color(u8, u8, u8);"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Color
+        {
+            /// <remarks>u8</remarks>
+            public readonly byte Item0;
+            /// <remarks>u8</remarks>
+            public readonly byte Item1;
+            /// <remarks>u8</remarks>
+            public readonly byte Item2;
+
+            public Color(byte item0, byte item1, byte item2)
+            {
+                Item0 = item0;
+                Item1 = item1;
+                Item2 = item2;
+            }
+        }
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_tuple_struct_csharp_9_with_prefix() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_tuple_field_prefix("Value");
+    let mut builder = CSharpBuilder::new(
+        r#"#[repr(C)] struct Pair(u16, f64);"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Pair
+        {
+            /// <remarks>u16</remarks>
+            public ushort Value0 { get; init; }
+            /// <remarks>f64</remarks>
+            public double Value1 { get; init; }
+
+            public Pair(ushort value0, double value1)
+            {
+                Value0 = value0;
+                Value1 = value1;
+            }
+        }
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_function_with_transparent_struct() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(transparent)]
+            pub struct Handle(pub *mut u8);
+
+            pub extern "C" fn foo(handle: Handle) -> Handle {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        /// <param name=\"handle\">Handle</param>
+        /// <returns>Handle</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"foo\")]
+        internal static extern IntPtr Foo(IntPtr handle);
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_function_with_transparent_struct_wrapper() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.emit_transparent_wrappers(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            /// A handle to a native object.
+            #[repr(transparent)]
+            pub struct Handle(pub *mut u8);
+
+            pub extern "C" fn foo(handle: Handle) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        /// <summary>
+        /// A handle to a native object.
+        /// </summary>
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Handle
+        {
+            /// <remarks>*mut u8</remarks>
+            public IntPtr Item0 { get; init; }
+
+            public Handle(IntPtr item0)
+            {
+                Item0 = item0;
+            }
+        }
+
+        /// <param name=\"handle\">Handle</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"foo\")]
+        internal static extern void Foo(Handle handle);
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_union() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            /// Either an integer or a float.
+            #[repr(C)]
+            union Value {
+                /// The integer value.
+                int: i32,
+                float: f32,
+            }
+
+            pub extern "C" fn foo(value: Value) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        /// <summary>
+        /// Either an integer or a float.
+        /// </summary>
+        [StructLayout(LayoutKind.Explicit)]
+        public struct Value
+        {
+            /// <summary>
+            /// The integer value.
+            /// </summary>
+            /// <remarks>i32</remarks>
+            [FieldOffset(0)]
+            public int Int;
+            /// <remarks>f32</remarks>
+            [FieldOffset(0)]
+            public float Float;
+        }
+
+        /// <param name=\"value\">Value</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"foo\")]
+        internal static extern void Foo(Value value);
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_union_with_non_blittable_field_fails() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.allow_bool_marshaling(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            union Value {
+                flag: bool,
+                int: i32,
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    assert!(builder.build().is_err());
+}
+
+#[test]
+fn build_packed_structs() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C, packed)]
+            struct Foo {
+                a: u8,
+                b: u32,
+            }
+
+            #[repr(C, packed(2))]
+            struct Bar {
+                a: u8,
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode, Pack = 1)]
+        public struct Foo
+        {
+            /// <remarks>u8</remarks>
+            public byte A { get; init; }
+            /// <remarks>u32</remarks>
+            public uint B { get; init; }
+
+            public Foo(byte a, uint b)
+            {
+                A = a;
+                B = b;
+            }
+        }
+
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode, Pack = 2)]
+        public struct Bar
+        {
+            /// <remarks>u8</remarks>
+            public byte A { get; init; }
+
+            public Bar(byte a)
+            {
+                A = a;
+            }
+        }
+
+    }
+}\n"
+    );
+    assert!(builder.warnings().is_empty());
+}
+
+#[test]
+fn build_aligned_struct_warns() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C, align(8))]
+            struct Foo {
+                a: u8,
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains("[StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]"));
+    assert_eq!(builder.warnings().len(), 1);
+    assert_eq!(builder.warnings()[0].item_name, "Foo");
+}
+
+#[test]
+fn build_struct_with_phantom_data() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            struct Foo<T> {
+                value: u32,
+                _marker: PhantomData<T>,
+                _pinned: std::marker::PhantomPinned,
+                other: u8,
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Foo<T>
+            where T : unmanaged
+        {
+            /// <remarks>u32</remarks>
+            public uint Value { get; init; }
+            /// <remarks>u8</remarks>
+            public byte Other { get; init; }
+
+            public Foo(uint value, byte other)
+            {
+                Value = value;
+                Other = other;
+            }
+        }
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_function_with_nested_pointers() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"pub extern "C" fn foo(a: *mut *mut u8, b: *const *mut u8) -> *mut u8 {}"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        /// <param name=\"a\">*mut *mut u8</param>
+        /// <param name=\"b\">*const *mut u8</param>
+        /// <returns>*mut u8</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"foo\")]
+        internal static extern IntPtr Foo(IntPtr a, IntPtr b);
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_function_with_unsafe_pointers() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.use_unsafe_pointers(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            struct Node {
+                data: *const u8,
+            }
+
+            pub extern "C" fn foo(a: *mut *mut u8, b: *const Node) -> *mut u8 {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static unsafe class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Node
+        {
+            /// <remarks>*const u8</remarks>
+            public byte* Data { get; init; }
+
+            public Node(byte* data)
+            {
+                Data = data;
+            }
+        }
+
+        /// <param name=\"a\">*mut *mut u8</param>
+        /// <param name=\"b\">*const Node</param>
+        /// <returns>*mut u8</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"foo\")]
+        internal static extern byte* Foo(byte** a, Node* b);
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_function_with_references() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            struct Config<T> {
+                value: T,
+            }
+
+            pub extern "C" fn foo(cfg: &Config<u16>, value: &u8, result: &mut u32) -> &Config<u8> {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains(
+        "        /// <param name=\"cfg\">&amp;Config&lt;u16&gt;</param>
+        /// <param name=\"value\">&amp;u8</param>
+        /// <param name=\"result\">&amp;mut u32</param>
+        /// <returns>&amp;Config&lt;u8&gt;</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"foo\")]
+        internal static extern IntPtr Foo(in Config<ushort> cfg, in byte value, ref uint result);
+"
+    ));
+}
+
+#[test]
+fn build_function_with_references_before_csharp_7_2() {
+    let mut configuration = CSharpConfiguration::new(7);
+    configuration.set_csharp_minor_version(1);
+    let mut builder = CSharpBuilder::new(
+        r#"pub extern "C" fn foo(value: &u8, result: &mut u32) {}"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains("internal static extern void Foo(ref byte value, ref uint result);"));
+}
+
+#[test]
+fn build_opaque_handle() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.add_opaque_handle("Foo", "destroy_foo");
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn create_foo() -> *mut Foo {}
+            pub extern "C" fn destroy_foo(foo: *mut Foo) {}
+            pub extern "C" fn use_foo(foo: *const Foo, other: *mut u8) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        /// <returns>*mut Foo</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"create_foo\")]
+        internal static extern FooHandle CreateFoo();
+
+        public class FooHandle : Microsoft.Win32.SafeHandles.SafeHandleZeroOrMinusOneIsInvalid
+        {
+            public FooHandle() : base(true)
+            {
+            }
+
+            protected override bool ReleaseHandle()
+            {
+                Destroy(handle);
+                return true;
+            }
+
+            [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"destroy_foo\")]
+            private static extern void Destroy(IntPtr handle);
+        }
+
+        /// <param name=\"foo\">*const Foo</param>
+        /// <param name=\"other\">*mut u8</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"use_foo\")]
+        internal static extern void UseFoo(FooHandle foo, IntPtr other);
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_library_import_function() {
+    let configuration = CSharpConfiguration::new(11);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn foo(a: u8) -> u32 {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+using System.Runtime.InteropServices.Marshalling;
+
+namespace foo;
+
+internal static partial class bar
+{
+    /// <param name=\"a\">u8</param>
+    /// <returns>u32</returns>
+    [LibraryImport(\"foo\", EntryPoint = \"foo\")]
+    [UnmanagedCallConv(CallConvs = new[] { typeof(System.Runtime.CompilerServices.CallConvCdecl) })]
+    internal static partial uint Foo(byte a);
+
+}\n"
+    )
+}
+
+#[test]
+fn build_library_import_opt_out() {
+    let mut configuration = CSharpConfiguration::new(11);
+    configuration.use_library_import(false);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn foo(a: u8) -> u32 {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains("internal static class bar"));
+    assert!(script.contains(
+        "[DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"foo\")]"
+    ));
+    assert!(script.contains("internal static extern uint Foo(byte a);"));
+    assert!(!script.contains("Marshalling"));
+}
+
+#[test]
+fn build_library_import_opaque_handle() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.use_library_import(true);
+    configuration.add_opaque_handle("Foo", "destroy_foo");
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn destroy_foo(foo: *mut Foo) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains(
+        "public partial class FooHandle : Microsoft.Win32.SafeHandles.SafeHandleZeroOrMinusOneIsInvalid"
+    ));
+    assert!(script.contains("[LibraryImport(\"foo\", EntryPoint = \"destroy_foo\")]"));
+    assert!(script.contains("private static partial void Destroy(IntPtr handle);"));
+}
+
+#[test]
+fn build_stdcall_function() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "stdcall" fn foo(callback: extern "system" fn(u8)) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [UnmanagedFunctionPointer(CallingConvention.Winapi)]
+        public delegate void FooCallbackDelegate(byte arg0);
+
+        /// <param name=\"callback\">fn(u8)</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.StdCall, EntryPoint=\"foo\")]
+        internal static extern void Foo(FooCallbackDelegate callback);
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_default_calling_convention() {
+    let mut configuration = CSharpConfiguration::new(11);
+    configuration.set_default_calling_convention(CallingConvention::StdCall);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn foo() {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains(
+        "[UnmanagedCallConv(CallConvs = new[] { typeof(System.Runtime.CompilerServices.CallConvStdcall) })]"
+    ));
+}
+
+#[test]
+fn build_unknown_abi() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "vectorcall" fn foo() {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build();
+    assert!(matches!(script, Err(Error::UnsupportedError(..))));
+}
+
+#[test]
+fn build_function_export_name() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[export_name = "mylib_foo_v2"]
+            pub extern "C" fn foo() {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"mylib_foo_v2\")]
+        internal static extern void Foo();
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_function_require_no_mangle() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.require_no_mangle(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[no_mangle]
+            pub extern "C" fn foo() {}
+            #[export_name = "bar_v2"]
+            pub extern "C" fn bar() {}
+            pub extern "C" fn baz() {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains("internal static extern void Foo();"));
+    assert!(script.contains("EntryPoint=\"bar_v2\""));
+    assert!(!script.contains("Baz"));
+    assert_eq!(builder.warnings().len(), 1);
+    assert_eq!(builder.warnings()[0].item_name, "baz");
+}
+
+#[test]
+fn build_lenient_skips_failing_function() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn foo(callback: extern "C" fn(u8), a: bool) {}
+            pub extern "C" fn bar(callback: extern "C" fn(u8)) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build_lenient().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void BarCallbackDelegate(byte arg0);
+
+        /// <param name=\"callback\">fn(u8)</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"bar\")]
+        internal static extern void Bar(BarCallbackDelegate callback);
+
+    }
+}\n"
+    );
+    assert_eq!(builder.warnings().len(), 1);
+    assert_eq!(builder.warnings()[0].item_name, "foo");
+    assert!(matches!(
+        builder.warnings()[0].error,
+        Error::UnsupportedError(..)
+    ));
+}
+
+#[test]
+fn build_strict_fails_on_failing_item() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            mod inner {
+                pub extern "C" fn foo(a: bool) {}
+            }
+            pub extern "C" fn bar() {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert!(builder.build().is_err());
+    let script = builder.build_lenient().unwrap();
+    assert!(script.contains("internal static extern void Bar();"));
+    assert!(!script.contains("Foo"));
+    assert_eq!(builder.warnings().len(), 1);
+    assert_eq!(builder.warnings()[0].item_name, "foo");
+}
+
+#[test]
+fn build_collecting_errors() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn foo(a: Unknown, b: u8, c: Missing) {}
+            pub extern "C" fn bar() {}
+            #[repr(C)]
+            pub struct Baz {
+                a: Other,
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let errors = builder.build_collecting_errors().unwrap_err();
+    assert_eq!(errors.len(), 3);
+    assert!(
+        matches!(&errors[0], Error::UnknownType(t, span) if t.contains("Unknown") && span.start().line == 2)
+    );
+    assert!(
+        matches!(&errors[1], Error::UnknownType(t, span) if t.contains("Missing") && span.start().line == 2)
+    );
+    assert!(
+        matches!(&errors[2], Error::UnknownType(t, span) if t.contains("Other") && span.start().line == 6)
+    );
+}
+
+#[test]
+fn build_collecting_errors_without_errors() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn bar() {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build_collecting_errors().unwrap();
+    assert_eq!(script, builder.build().unwrap());
+}
+
+#[test]
+fn build_with_ignore_patterns() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.add_ignore_pattern("__test_*").unwrap();
+    configuration.add_ignore_pattern("internal_.*").unwrap();
+    configuration.add_ignore_pattern("Hidden").unwrap();
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn __test_foo() {}
+            pub extern "C" fn internal_bar() {}
+            pub extern "C" fn my_internal_bar() {}
+            #[repr(C)]
+            pub struct Hidden {
+                a: u8,
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"my_internal_bar\")]
+        internal static extern void MyInternalBar();
+
+    }
+}\n"
+    );
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn foo(a: Hidden) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    assert!(matches!(builder.build(), Err(Error::UnknownType(..))));
+}
+
+#[test]
+fn build_with_include_patterns() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.add_include_pattern("mylib_?oo").unwrap();
+    let mut builder = CSharpBuilder::new(
+        r#"
+            mod inner {
+                pub extern "C" fn mylib_foo() {}
+            }
+            pub extern "C" fn mylib_bar() {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    let script = builder.build().unwrap();
+    assert!(script.contains("MylibFoo"));
+    assert!(!script.contains("MylibBar"));
+}
+
+#[test]
+fn add_invalid_ignore_pattern() {
+    let mut configuration = CSharpConfiguration::new(9);
+    assert!(matches!(
+        configuration.add_ignore_pattern("foo(.*"),
+        Err(Error::PatternError(..))
+    ));
+}
+
+#[test]
+fn build_skips_doc_hidden_items() {
+    let configuration = CSharpConfiguration::new(8);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[doc(hidden)]
+            pub extern "C" fn plumbing() {}
+            #[doc(hidden)]
+            #[repr(C)]
+            pub struct Hidden {
+                a: u8,
+            }
+            #[repr(u8)]
+            pub enum Foo {
+                A,
+                #[doc(hidden)]
+                B,
+            }
+            #[repr(C)]
+            pub struct Bar {
+                a: u8,
+                #[doc(hidden)]
+                reserved: u8,
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        public enum Foo : byte
+        {
+            A,
+        }
+
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Bar
+        {
+            /// <remarks>u8</remarks>
+            public readonly byte A;
+            private readonly byte Reserved;
+
+            public Bar(byte a)
+            {
+                A = a;
+                Reserved = default;
+            }
+        }
+
+    }
+}\n"
+    );
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn foo(a: Hidden) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    assert!(matches!(builder.build(), Err(Error::UnknownType(..))));
+}
+
+#[test]
+fn build_skips_items_with_skip_attribute() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_skip_attribute("binder_skip");
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[binder_skip]
+            pub extern "C" fn foo() {}
+            #[cfg_attr(feature = "binder", binder_skip)]
+            pub extern "C" fn bar() {}
+            #[cfg_attr(feature = "binder", inline)]
+            pub extern "C" fn baz() {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    let script = builder.build().unwrap();
+    assert!(!script.contains("Foo"));
+    assert!(!script.contains("Bar"));
+    assert!(script.contains("internal static extern void Baz();"));
+}
+
+#[test]
+fn build_deprecated_function() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            /// Does foo.
+            #[deprecated(since = "1.2.0", note = "use \"foo_v2\" \\ instead")]
+            pub extern "C" fn foo() {}
+            #[deprecated]
+            pub extern "C" fn bar() {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        /// <summary>
+        /// Does foo.
+        /// </summary>
+        /// <returns>void</returns>
+        [Obsolete(\"use \\\"foo_v2\\\" \\\\ instead\")]
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"foo\")]
+        internal static extern void Foo();
+
+        /// <returns>void</returns>
+        [Obsolete]
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"bar\")]
+        internal static extern void Bar();
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_deprecated_enum_and_struct() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(u8)]
+            #[deprecated = "use Bar"]
+            pub enum Foo {
+                A,
+                #[deprecated(since = "1.0.0")]
+                B,
+            }
+            #[repr(C)]
+            #[deprecated(note = "use Baz")]
+            pub struct Bar {
+                a: u8,
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains(
+        "        [Obsolete(\"use Bar\")]
+        public enum Foo : byte
+        {
+            A,
+            [Obsolete]
+            B,
+        }"
+    ));
+    assert!(script.contains(
+        "        [Obsolete(\"use Baz\")]
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Bar"
+    ));
+}
+
+#[test]
+fn build_enum_with_negative_values() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(i32)]
+            enum Status {
+                Error = -1,
+                Ok = 0,
+                Other = -(20),
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        public enum Status : int
+        {
+            Error = -1,
+            Ok = 0,
+            Other = -20,
+        }
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_enum_reprs() {
+    for (repr, expected) in [
+        ("i8", "sbyte"),
+        ("i16", "short"),
+        ("i32", "int"),
+        ("i64", "long"),
+        ("isize", "long"),
+        ("u8", "byte"),
+        ("u16", "ushort"),
+        ("u32", "uint"),
+        ("u64", "ulong"),
+        ("usize", "ulong"),
+    ] {
+        let configuration = CSharpConfiguration::new(9);
+        let script = format!("#[repr({})] enum Foo {{ A = 1 }}", repr);
+        let mut builder = CSharpBuilder::new(&script, "foo", &configuration).unwrap();
+        let script = builder.build().unwrap();
+        assert!(script.contains(&format!("public enum Foo : {}", expected)));
+    }
+}
+
+#[test]
+fn build_enum_with_negative_unsigned_value_fails() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(u32)]
+            enum Status {
+                Error = -1,
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    assert!(matches!(builder.build(), Err(Error::UnsupportedError(..))));
+}
+
+#[test]
+fn build_flags_enum() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.detect_flags_enums(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(u32)]
+            enum Caps {
+                None = 0,
+                Read = 1,
+                Write = 2,
+                Exec = 4,
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [Flags]
+        public enum Caps : uint
+        {
+            None = 0,
+            Read = 1,
+            Write = 2,
+            Exec = 4,
+        }
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_flags_enum_detection() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.detect_flags_enums(true);
+    configuration.mark_enum_as_flags("Explicit");
+    configuration.mark_enum_as_not_flags("NotFlags");
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(u8)]
+            enum Sequential { A = 0, B = 1, C = 2, D = 3 }
+            #[repr(u8)]
+            enum Implicit { A, B, C, D }
+            #[repr(u8)]
+            enum Duplicate { A = 1, B = 2, C = 2, D = 4 }
+            #[repr(u8)]
+            enum Explicit { A = 0, B = 1 }
+            #[repr(u8)]
+            enum NotFlags { A = 1, B = 2, C = 4 }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    let script = builder.build().unwrap();
+    assert_eq!(script.matches("[Flags]").count(), 1);
+    assert!(script.contains("[Flags]\npublic enum Explicit : byte"));
+}
+
+#[test]
+fn build_repr_c_enum_with_fields() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            /// A message.
+            #[repr(C, u8)]
+            enum Message {
+                /// Checks the connection.
+                Ping,
+                Payload { len: u32, ptr: *const u8 },
+                Code(i32),
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        /// <summary>
+        /// A message.
+        /// </summary>
+        [StructLayout(LayoutKind.Sequential)]
+        public struct Message
+        {
+            public enum Tag : byte
+            {
+                /// <summary>
+                /// Checks the connection.
+                /// </summary>
+                Ping,
+                Payload,
+                Code,
+            }
+
+            [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+            public struct PayloadData
+            {
+                /// <remarks>u32</remarks>
+                public uint Len;
+                /// <remarks>*const u8</remarks>
+                public IntPtr Ptr;
+            }
+
+            [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+            public struct CodeData
+            {
+                /// <remarks>i32</remarks>
+                public int Item0;
+            }
+
+            [StructLayout(LayoutKind.Explicit)]
+            public struct Variants
+            {
+                [FieldOffset(0)]
+                public PayloadData Payload;
+                [FieldOffset(0)]
+                public CodeData Code;
+            }
+
+            public Tag Kind;
+            public Variants Data;
+        }
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_primitive_repr_enum_with_fields() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(u16)]
+            enum Message {
+                Ping,
+                Code(i32),
+            }
+            pub extern "C" fn send(message: Message) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Explicit)]
+        public struct Message
+        {
+            public enum Tag : ushort
+            {
+                Ping,
+                Code,
+            }
+
+            [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+            public struct CodeData
+            {
+                public Tag Kind;
+                /// <remarks>i32</remarks>
+                public int Item0;
+            }
+
+            [FieldOffset(0)]
+            public Tag Kind;
+            [FieldOffset(0)]
+            public CodeData Code;
+        }
+
+        /// <param name=\"message\">Message</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"send\")]
+        internal static extern void Send(Message message);
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_enum_with_skipped_variant_keeps_values() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(u8)]
+            enum Foo {
+                A,
+                #[doc(hidden)]
+                B,
+                C,
+                D = 10,
+                E,
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    let script = builder.build().unwrap();
+    assert!(script.contains("    A,\n    C = 2,\n    D = 10,\n    E = 11,\n"));
+}
+
+#[test]
+fn build_with_added_script() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(u8)]
+            enum Foo { A, B }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder
+        .add_script(
+            r#"
+            pub extern "C" fn bar(foo: Foo) {}
+        "#,
+        )
+        .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        public enum Foo : byte
+        {
+            A,
+            B,
+        }
+
+        /// <param name=\"foo\">Foo</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"bar\")]
+        internal static extern void Bar(Foo foo);
+
+    }
+}\n"
+    );
+    assert!(matches!(
+        builder.add_script("fn {"),
+        Err(Error::ParseError(..))
+    ));
+}
+
+#[test]
+fn build_from_paths() {
+    let directory = std::env::temp_dir().join("csharp_binder_build_from_paths");
+    let ffi = directory.join("ffi");
+    std::fs::create_dir_all(&ffi).unwrap();
+    std::fs::write(ffi.join("structs.rs"), "#[repr(u8)] enum Foo { A, B }").unwrap();
+    std::fs::write(ffi.join("ignored.txt"), "fn {").unwrap();
+    let functions = directory.join("functions.rs");
+    std::fs::write(&functions, "pub extern \"C\" fn bar(foo: Foo) {}").unwrap();
+
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder =
+        CSharpBuilder::from_paths(&[ffi.as_path(), functions.as_path()], "foo", &configuration)
+            .unwrap();
+    let script = builder.build().unwrap();
+    assert!(script.find("public enum Foo : byte").unwrap() < script.find("Bar(Foo foo)").unwrap());
+
+    let missing = directory.join("missing.rs");
+    assert!(matches!(
+        CSharpBuilder::from_paths(&[missing.as_path()], "foo", &configuration),
+        Err(Error::IOError(..))
+    ));
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn build_to_writer() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(u8)]
+            enum Foo { A, B }
+            pub extern "C" fn bar(foo: Foo) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let mut output: Vec<u8> = Vec::new();
+    builder.build_to(&mut output).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), builder.build().unwrap());
+}
+
+#[test]
+fn build_to_failing_writer() {
+    struct FailingWriter;
+    impl std::io::Write for FailingWriter {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("disk full"))
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn bar() {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    assert!(matches!(
+        builder.build_to(&mut FailingWriter),
+        Err(Error::IOError(e)) if e.to_string() == "disk full"
+    ));
+}
+
+#[test]
+fn build_split_files() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(u8)]
+            enum Foo { A, B }
+            #[repr(C)]
+            struct Bar {
+                foo: Foo,
+            }
+            pub extern "C" fn baz(bar: Bar) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let files = builder.build_split().unwrap();
+    assert_eq!(files.len(), 3);
+    assert_eq!(files[0].suggested_name, "Foo.cs");
+    assert_eq!(
+        files[0].contents,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    public enum Foo : byte
+    {
+        A,
+        B,
+    }
+}\n"
+    );
+    assert_eq!(files[1].suggested_name, "Bar.cs");
+    assert_eq!(
+        files[1].contents,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    public struct Bar
+    {
+        /// <remarks>Foo</remarks>
+        public Foo Foo { get; init; }
+
+        public Bar(Foo foo)
+        {
+            Foo = foo;
+        }
+    }
+}\n"
+    );
+    assert_eq!(files[2].suggested_name, "bar.cs");
+    assert_eq!(
+        files[2].contents,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        /// <param name=\"bar\">Bar</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"baz\")]
+        internal static extern void Baz(Bar bar);
+
+    }
+}\n"
+    );
+}
+
+#[test]
+fn build_split_files_to_directory() {
+    let directory = std::env::temp_dir().join("csharp_binder_build_split_files_to_directory");
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(u8)]
+            enum Foo { A, B }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("bar");
+    builder.build_split_to_directory(&directory).unwrap();
+    let contents = std::fs::read_to_string(directory.join("Foo.cs")).unwrap();
+    assert!(contents.contains("public enum Foo : byte"));
+    // The type is not written, as it would be empty.
+    assert!(!directory.join("bar.cs").exists());
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+/// Converts the expected output of a script with a braced namespace into the expected output with
+/// a file scoped namespace.
+fn to_file_scoped_namespace(script: &str) -> String {
+    let (header, body) = script.split_at(script.find("namespace ").unwrap());
+    let mut lines = body.lines();
+    let namespace = lines.next().unwrap();
+    assert_eq!(lines.next(), Some("{"));
+    let mut result = format!("{}{};\n\n", header, namespace);
+    let body: Vec<&str> = lines.collect();
+    assert_eq!(body.last(), Some(&"}"));
+    for line in &body[..body.len() - 1] {
+        result += line.strip_prefix("    ").unwrap_or(line);
+        result += "\n";
+    }
+    result
+}
+
+#[test]
+fn build_file_scoped_namespace() {
+    let configuration = CSharpConfiguration::new(10);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(u8)]
+            enum Foo { A, B }
+            pub extern "C" fn bar(foo: Foo) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo;
+
+internal static class bar
+{
+    public enum Foo : byte
+    {
+        A,
+        B,
+    }
+
+    /// <param name=\"foo\">Foo</param>
+    /// <returns>void</returns>
+    [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"bar\")]
+    internal static extern void Bar(Foo foo);
+
+}\n"
+    )
+}
+
+#[test]
+fn build_file_scoped_namespace_matches_braced_namespace() {
+    let scripts = [
+        r#"
+            /// Docs for foo.
+            pub extern "C" fn foo(a: u8, callback: extern "C" fn(u16) -> u32) -> i64 {}
+        "#,
+        r#"
+            /// testing documentation for enum
+            #[repr(u8)]
+            enum Foo {
+                /// Enum value one
+                One = 1,
+                Two = 2,
+            }
+        "#,
+        r#"
+            #[repr(C)]
+            /// test documentation struct
+            struct Foo<T> {
+                /// a field. Very important!
+                field_a: u8,
+                field_b: T,
+            }
+        "#,
+        r#"
+            #[repr(C)]
+            union Foo {
+                a: u32,
+                b: f32,
+            }
+        "#,
+        r#"
+            #[repr(C, u8)]
+            enum Message {
+                Ping,
+                Payload { len: u32, ptr: *const u8 },
+            }
+        "#,
+        r#"
+            mod inner {
+                pub const SIZE: u32 = 0x10;
+                pub type Callback = extern "C" fn(u8);
+            }
+        "#,
+        r#"
+            pub extern "C" fn create_foo() -> *mut Foo {}
+            pub extern "C" fn destroy_foo(foo: *mut Foo) {}
+        "#,
+    ];
+    for version in [8, 9, 10] {
+        for script in &scripts {
+            let build = |file_scoped: bool| {
+                let mut configuration = CSharpConfiguration::new(version);
+                configuration.use_file_scoped_namespace(file_scoped);
+                configuration.add_opaque_handle("Foo", "destroy_foo");
+                let mut builder = CSharpBuilder::new(script, "foo", &configuration).unwrap();
+                builder.set_namespace("foo");
+                builder.set_type("bar");
+                builder.build().unwrap()
+            };
+            assert_eq!(build(true), to_file_scoped_namespace(&build(false)));
+        }
+    }
+}
+
+#[test]
+fn build_split_files_file_scoped_namespace() {
+    let configuration = CSharpConfiguration::new(10);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(u8)]
+            enum Foo { A, B }
+            pub extern "C" fn bar(foo: Foo) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let files = builder.build_split().unwrap();
+    assert_eq!(
+        files[0].contents,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo;
+
+public enum Foo : byte
+{
+    A,
+    B,
+}
+"
+    );
+    assert!(files[1].contents.contains(
+        "namespace foo;\n\ninternal static class bar\n{\n    /// <param name=\"foo\">Foo</param>\n"
+    ));
+}
+
+#[test]
+fn build_with_tabs_and_crlf() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_indentation(Indentation::Tabs);
+    configuration.set_newline(Newline::CrLf);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(u8)]
+            enum Foo { A, B }
+            pub extern "C" fn bar(foo: Foo, callback: extern "C" fn(u8)) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!\r\n\
+using System;\r\n\
+using System.Runtime.InteropServices;\r\n\
+\r\n\
+namespace foo\r\n\
+{\r\n\
+\tinternal static class bar\r\n\
+\t{\r\n\
+\t\tpublic enum Foo : byte\r\n\
+\t\t{\r\n\
+\t\t\tA,\r\n\
+\t\t\tB,\r\n\
+\t\t}\r\n\
+\r\n\
+\t\t[UnmanagedFunctionPointer(CallingConvention.Cdecl)]\r\n\
+\t\tpublic delegate void BarCallbackDelegate(byte arg0);\r\n\
+\r\n\
+\t\t/// <param name=\"foo\">Foo</param>\r\n\
+\t\t/// <param name=\"callback\">fn(u8)</param>\r\n\
+\t\t/// <returns>void</returns>\r\n\
+\t\t[DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"bar\")]\r\n\
+\t\tinternal static extern void Bar(Foo foo, BarCallbackDelegate callback);\r\n\
+\r\n\
+\t}\r\n\
+}\r\n"
+    );
+    let script = builder.build_lenient().unwrap();
+    assert!(!script.contains("\r\r"));
+}
+
+#[test]
+fn build_with_two_space_indentation() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_indentation(Indentation::Spaces(2));
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            struct Foo {
+                a: u8,
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+  internal static class bar
+  {
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    public struct Foo
+    {
+      /// <remarks>u8</remarks>
+      public byte A { get; init; }
+
+      public Foo(byte a)
+      {
+        A = a;
+      }
+    }
+
+  }
+}\n"
+    );
+}
+
+#[test]
+fn build_split_files_with_crlf() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_newline(Newline::CrLf);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(u8)]
+            enum Foo { A, B }
+            pub extern "C" fn bar(foo: Foo) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    for file in builder.build_split().unwrap() {
+        assert_eq!(
+            file.contents.matches('\n').count(),
+            file.contents.matches("\r\n").count()
+        );
+        assert!(!file.contents.contains("\r\r"));
+    }
+}
+
+#[test]
+fn build_keyword_parameters() {
+    let source = r#"
+#[no_mangle]
+pub extern "C" fn raise(event: u32, string: *const u8, r#params: i32) {}
+
+pub type Callback = extern "C" fn(object: u32);
+"#;
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("Bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class Bar
+    {
+        /// <param name=\"event\">u32</param>
+        /// <param name=\"string\">*const u8</param>
+        /// <param name=\"params\">i32</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"raise\")]
+        internal static extern void Raise(uint @event, IntPtr @string, int @params);
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void Callback(uint @object);
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_keyword_struct_fields() {
+    let source = r#"
+#[repr(C)]
+pub struct Message {
+    pub event: u32,
+    pub r#type: u8,
+    pub string: *const u8,
+}
+"#;
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    public struct Message
+    {
+        /// <remarks>u32</remarks>
+        public uint Event { get; init; }
+        /// <remarks>u8</remarks>
+        public byte Type { get; init; }
+        /// <remarks>*const u8</remarks>
+        public IntPtr String { get; init; }
+
+        public Message(uint @event, byte type, IntPtr @string)
+        {
+            Event = @event;
+            Type = type;
+            String = @string;
+        }
+    }
+
+}
+"
+    );
+}
+
+#[test]
+fn build_naming_converter() {
+    let source = r#"
+#[repr(C)]
+pub struct json_value {
+    pub value_id: u32,
+}
+
+pub type json_callback = extern "C" fn(event_id: u32);
+
+#[no_mangle]
+pub extern "C" fn parse_json_id(value_id: u32, callback: json_callback) -> json_value {}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_naming_converter(Box::new(|name, kind| {
+        let name = name
+            .split('_')
+            .map(|part| match part {
+                "id" => "ID".to_string(),
+                part => part[..1].to_uppercase() + &part[1..],
+            })
+            .collect::<String>();
+        match kind {
+            NameKind::Parameter => name[..1].to_lowercase() + &name[1..],
+            NameKind::Function | NameKind::Field | NameKind::Type => name,
+        }
+    }));
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("Bar");
+    assert_eq!(builder.build().unwrap(), "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class Bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct JsonValue
+        {
+            /// <remarks>u32</remarks>
+            public uint ValueID { get; init; }
+
+            public JsonValue(uint valueID)
+            {
+                ValueID = valueID;
+            }
+        }
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void JsonCallback(uint eventID);
+
+        /// <param name=\"valueID\">u32</param>
+        /// <param name=\"callback\">json_callback</param>
+        /// <returns>json_value</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"parse_json_id\")]
+        internal static extern JsonValue ParseJsonID(uint valueID, JsonCallback callback);
+
+    }
+}
+");
+}
+
+#[test]
+fn build_renamed_symbols() {
+    let source = r#"
+#[repr(C)]
+pub struct ctx {
+    pub ctx_flags: u32,
+}
+
+#[no_mangle]
+pub extern "C" fn create_ctx_v2(ctx_flags: u32) -> *mut ctx {}
+
+#[no_mangle]
+pub extern "C" fn destroy_ctx(handle: *mut ctx) {}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_naming_converter(Box::new(|name, _| name.to_uppercase()));
+    configuration.rename_symbol("create_ctx_v2", "CreateContext");
+    configuration.rename_symbol("ctx", "Context");
+    configuration.rename_symbol("ctx_flags", "Flags");
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("Bar");
+    assert_eq!(builder.build().unwrap(), "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class Bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Context
+        {
+            /// <remarks>u32</remarks>
+            public uint Flags { get; init; }
+
+            public Context(uint flags)
+            {
+                Flags = flags;
+            }
+        }
+
+        /// <param name=\"Flags\">u32</param>
+        /// <returns>*mut ctx</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"create_ctx_v2\")]
+        internal static extern IntPtr CreateContext(uint Flags);
+
+        /// <param name=\"HANDLE\">*mut ctx</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"destroy_ctx\")]
+        internal static extern void DESTROY_CTX(IntPtr HANDLE);
+
+    }
+}
+");
+}
+
+#[test]
+fn build_function_prefix_strip() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_function_prefix_strip("mylib_");
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn mylib_do_thing(callback: extern "C" fn(u8)) {}
+            pub extern "C" fn mylib_() {}
+            pub extern "C" fn mylib_2d() {}
+            pub extern "C" fn other() {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void DoThingCallbackDelegate(byte arg0);
+
+        /// <param name=\"callback\">fn(u8)</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"mylib_do_thing\")]
+        internal static extern void DoThing(DoThingCallbackDelegate callback);
+
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"mylib_\")]
+        internal static extern void Mylib();
+
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"mylib_2d\")]
+        internal static extern void Mylib2d();
+
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"other\")]
+        internal static extern void Other();
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_function_prefix_strip_collision() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_function_prefix_strip("mylib_");
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn mylib_create() {}
+            pub extern "C" fn create() {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    match builder.build() {
+        Err(Error::DuplicateSymbol(name, span)) => {
+            assert_eq!(name, "Create");
+            assert_eq!(span.start().line, 3);
+        }
+        _ => panic!("Expected a collision error"),
+    }
+}
+
+#[test]
+fn build_duplicate_function_names() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn get_value() -> u8 { 0 }
+            pub extern "C" fn getValue() -> u8 { 0 }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    match builder.build() {
+        Err(Error::DuplicateSymbol(name, span)) => {
+            assert_eq!(name, "GetValue");
+            assert_eq!(span.start().line, 3);
+        }
+        _ => panic!("Expected a duplicate symbol error"),
+    }
+}
+
+#[test]
+fn build_duplicate_names_disambiguated() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.disambiguate_duplicate_names(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn get_value() -> u8 { 0 }
+            pub extern "C" fn getValue() -> u8 { 0 }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    let script = builder.build().unwrap();
+    assert_eq!(builder.warnings().len(), 1);
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace Foo
+{
+    internal static class Bar
+    {
+        /// <returns>u8</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"get_value\")]
+        internal static extern byte GetValue();
+
+        /// <returns>u8</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"getValue\")]
+        internal static extern byte GetValue2();
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_known_type_conflict() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut first = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Point { x: i32 }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    first.set_namespace("First");
+    first.set_type("Bar");
+    first.build().unwrap();
+
+    let mut second = CSharpBuilder::new(
+        r#"
+
+            #[repr(C)]
+            pub struct Point { x: i32 }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    second.set_namespace("Second");
+    second.set_type("Bar");
+    match second.build() {
+        Err(Error::DuplicateSymbol(name, span)) => {
+            assert_eq!(name, "Point");
+            assert_eq!(span.start().line, 4);
+        }
+        _ => panic!("Expected a duplicate symbol error"),
+    }
+}
+
+#[test]
+fn build_function_doc_sections() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            /// Copies a buffer.
+            ///
+            /// # Arguments
+            ///
+            /// * `source` - The buffer to copy from.
+            /// * `length` - The number of bytes
+            ///   to copy.
+            /// * `unknown` - Not a parameter.
+            ///
+            /// # Returns
+            ///
+            /// Whether the copy succeeded.
+            ///
+            /// # Safety
+            ///
+            /// The source must be valid for `length` bytes.
+            pub unsafe extern "C" fn copy(source: *const u8, length: usize) -> u8 { 0 }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace Foo
+{
+    internal static class Bar
+    {
+        /// <summary>
+        /// Copies a buffer.
+        /// * <c>unknown</c> - Not a parameter.
+        /// </summary>
+        /// <param name=\"source\">The buffer to copy from. (*const u8)</param>
+        /// <param name=\"length\">The number of bytes to copy. (usize)</param>
+        /// <returns>Whether the copy succeeded. (u8)</returns>
+        /// <remarks>
+        /// Safety:
+        /// The source must be valid for <c>length</c> bytes.
+        /// </remarks>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"copy\")]
+        internal static extern byte Copy(IntPtr source, nuint length);
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_struct_doc_remarks() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            /// A raw view into a buffer.
+            ///
+            /// # Panics
+            ///
+            /// Panics when accessed after the buffer is freed.
+            ///
+            /// # Examples
+            ///
+            /// ```
+            /// # let buffer = [0u8; 4];
+            /// let view = View {
+            ///     ptr: buffer.as_ptr() as usize,
+            /// };
+            /// ```
+            #[repr(C)]
+            pub struct View {
+                ptr: usize,
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace Foo
+{
+    /// <summary>
+    /// <para>
+    /// A raw view into a buffer.
+    /// </para>
+    /// <para>
+    /// # Examples
+    /// </para>
+    /// <code>
+    /// let view = View {
+    ///     ptr: buffer.as_ptr() as usize,
+    /// };
+    /// </code>
+    /// </summary>
+    /// <remarks>
+    /// Panics:
+    /// Panics when accessed after the buffer is freed.
+    /// </remarks>
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    public struct View
+    {
+        /// <remarks>usize</remarks>
+        public nuint Ptr { get; init; }
+
+        public View(nuint ptr)
+        {
+            Ptr = ptr;
+        }
+    }
+
+}\n"
+    )
+}
+
+#[test]
+fn build_doc_links_and_code_spans() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Point {
+                x: i32,
+            }
+
+            /// Moves a [`Point`] by `x & y`, if a < b.
+            /// See [Vector] and [the docs](https://example.com).
+            pub extern "C" fn move_point(point: Point) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains(
+        "        /// <summary>
+        /// Moves a <see cref=\"Point\"/> by <c>x &amp; y</c>, if a &lt; b.
+        /// See <c>Vector</c> and [the docs](https://example.com).
+        /// </summary>
+"
+    ));
+}
+
+#[test]
+fn build_type_remarks_off() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_type_remarks(TypeRemarks::Off);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Point {
+                /// The horizontal position.
+                x: i32,
+                y: i32,
+            }
+
+            /// # Arguments
+            ///
+            /// * `point` - The point to move.
+            pub extern "C" fn move_point(point: Point, distance: i32) -> i32 { 0 }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace Foo
+{
+    internal static class Bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Point
+        {
+            /// <summary>
+            /// The horizontal position.
+            /// </summary>
+            public int X { get; init; }
+            public int Y { get; init; }
+
+            /// <param name=\"x\">The horizontal position.</param>
+            /// <param name=\"y\">i32</param>
+            public Point(int x, int y)
+            {
+                X = x;
+                Y = y;
+            }
+        }
+
+        /// <param name=\"point\">The point to move.</param>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"move_point\")]
+        internal static extern int MovePoint(Point point, int distance);
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_type_remarks_append_to_summary() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_type_remarks(TypeRemarks::AppendToSummary);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Point {
+                /// The horizontal position.
+                x: i32,
+                y: i32,
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    let script = builder.build().unwrap();
+    assert!(script.contains(
+        "        /// <summary>
+        /// The horizontal position.
+        /// (i32)
+        /// </summary>
+        public int X { get; init; }
+        /// <summary>
+        /// i32
+        /// </summary>
+        public int Y { get; init; }
+"
+    ));
+}
+
+#[test]
+fn build_type_remarks_merged_with_doc_remarks() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Buffer {
+                /// The data of the buffer.
+                ///
+                /// # Safety
+                ///
+                /// Only valid while the buffer is alive.
+                data: *const u8,
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    let script = builder.build().unwrap();
+    assert!(script.contains(
+        "        /// <summary>
+        /// The data of the buffer.
+        /// </summary>
+        /// <remarks>
+        /// Safety:
+        /// Only valid while the buffer is alive.
+        /// *const u8
+        /// </remarks>
+"
+    ));
+}
+
+#[test]
+fn build_safe_wrappers() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_out_type("Out");
+    configuration.allow_bool_marshaling(true);
+    configuration.generate_safe_wrappers(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            /// Gets the name of the library.
+            pub extern "C" fn get_name() -> *const c_char { }
+
+            /// Gets the size.
+            ///
+            /// # Arguments
+            ///
+            /// * `size` - The size of the buffer.
+            pub extern "C" fn get_size(size: Out<u32>) {}
+
+            pub extern "C" fn divide(a: i32, b: i32, remainder: Out<i32>) -> i32 { 0 }
+
+            pub extern "C" fn parse(input: *const c_char, value: Out<u32>) -> bool { true }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace Foo
+{
+    internal static class Bar
+    {
+        [System.ComponentModel.EditorBrowsable(System.ComponentModel.EditorBrowsableState.Never)]
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"get_name\")]
+        internal static extern IntPtr GetNameNative();
+
+        /// <summary>
+        /// Gets the name of the library.
+        /// </summary>
+        /// <returns>*const c_char</returns>
+        internal static string GetName()
+        {
+            return Marshal.PtrToStringUTF8(GetNameNative());
+        }
+
+        [System.ComponentModel.EditorBrowsable(System.ComponentModel.EditorBrowsableState.Never)]
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"get_size\")]
+        internal static extern void GetSizeNative(out uint size);
+
+        /// <summary>
+        /// Gets the size.
+        /// </summary>
+        /// <returns>The size of the buffer. (Out&lt;u32&gt;)</returns>
+        internal static uint GetSize()
+        {
+            uint size;
+            GetSizeNative(out size);
+            return size;
+        }
+
+        [System.ComponentModel.EditorBrowsable(System.ComponentModel.EditorBrowsableState.Never)]
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"divide\")]
+        internal static extern int DivideNative(int a, int b, out int remainder);
+
+        /// <param name=\"a\">i32</param>
+        /// <param name=\"b\">i32</param>
+        /// <returns>i32, Out&lt;i32&gt;</returns>
+        internal static (int Result, int Remainder) Divide(int a, int b)
+        {
+            int remainder;
+            var result = DivideNative(a, b, out remainder);
+            return (result, remainder);
+        }
+
+        [System.ComponentModel.EditorBrowsable(System.ComponentModel.EditorBrowsableState.Never)]
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"parse\")]
+        [return: MarshalAs(UnmanagedType.I1)]
+        internal static extern bool ParseNative(IntPtr input, out uint value);
+
+        /// <param name=\"input\">*const c_char</param>
+        /// <param name=\"value\">Out&lt;u32&gt;</param>
+        /// <returns>bool</returns>
+        internal static bool TryParse(IntPtr input, out uint value)
+        {
+            return ParseNative(input, out value);
+        }
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_safe_wrappers_pass_through() {
+    let mut configuration = CSharpConfiguration::new(6);
+    configuration.set_out_type("Out");
+    configuration.generate_safe_wrappers(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn increment(value: &mut u32, previous: Out<u32>) -> u32 { 0 }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains(
+        "        internal static uint Increment(ref uint value, out uint previous)
+        {
+            return IncrementNative(ref value, out previous);
+        }
+"
+    ));
+}
+
+#[test]
+fn build_utf8_strings() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.marshal_utf8_strings(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn greet(name: *const c_char) -> *const c_char { }
+            pub extern "C" fn hash(data: *const u8) -> u64 { 0 }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace Foo
+{
+    internal static class Bar
+    {
+        /// <summary>
+        /// Converts a UTF-8 string returned by a native function into a managed string.
+        /// </summary>
+        internal static string PtrToString(IntPtr ptr)
+        {
+            return Marshal.PtrToStringUTF8(ptr);
+        }
+
+        /// <param name=\"name\">*const c_char (UTF-8 string)</param>
+        /// <returns>*const c_char (UTF-8 string)</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"greet\")]
+        internal static extern IntPtr Greet([MarshalAs(UnmanagedType.LPUTF8Str)] string name);
+
+        /// <param name=\"data\">*const u8</param>
+        /// <returns>u64</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"hash\")]
+        internal static extern ulong Hash(IntPtr data);
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_utf8_strings_library_import() {
+    let mut configuration = CSharpConfiguration::new(11);
+    configuration.marshal_utf8_strings(true);
+    configuration.add_utf8_string_type("u8");
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn greet(name: *const u8) -> *const c_char { }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains(
+        "    /// <param name=\"name\">*const u8 (UTF-8 string)</param>
+    /// <returns>*const c_char (UTF-8 string)</returns>
+    [LibraryImport(\"foo\", EntryPoint = \"greet\")]
+    [UnmanagedCallConv(CallConvs = new[] { typeof(System.Runtime.CompilerServices.CallConvCdecl) })]
+    [return: MarshalAs(UnmanagedType.LPUTF8Str)]
+    internal static partial string Greet([MarshalAs(UnmanagedType.LPUTF8Str)] string name);
+"
+    ));
+}
+
+#[test]
+fn build_std_ffi_types() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_c_long_size(CLongSize::Bits32);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            use std::os::raw::{c_int, c_long, c_void};
+
+            pub extern "C" fn process(data: *mut c_void, count: c_int, size: core::ffi::c_ulong, scale: c_double) -> c_long { 0 }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains(
+        "        internal static extern int Process(IntPtr data, int count, uint size, double scale);\n"
+    ));
+}
+
+#[test]
+fn build_c_long_default_size() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn size(value: c_ulong) -> c_long { 0 }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    let script = builder.build().unwrap();
+    assert!(script.contains("internal static extern long Size(ulong value);\n"));
+}
+
+#[test]
+fn build_bare_c_void_unsupported() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn nothing() -> c_void { }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    match builder.build() {
+        Err(Error::UnsupportedError(message, _)) => assert!(message.contains("c_void")),
+        _ => panic!("Expected an unsupported error"),
+    }
+}
+
+#[test]
+fn build_rust_char_as_uint() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Glyph {
+                character: char,
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    let script = builder.build().unwrap();
+    assert!(script.contains(
+        "        /// <remarks>char</remarks>
+        public uint Character { get; init; }
+"
+    ));
+}
+
+#[test]
+fn build_rust_char_error() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.map_rust_char_to(CSharpCharMapping::Error);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn next_char() -> char { 'a' }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    match builder.build() {
+        Err(Error::UnsupportedError(message, span)) => {
+            assert!(message.contains("char type"));
+            assert_eq!(span.start().line, 2);
+        }
+        _ => panic!("Expected an unsupported error"),
+    }
+}
+
+#[test]
+fn build_c_char_signedness() {
+    let mut configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn first(value: c_char) -> c_char { value }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    let script = builder.build().unwrap();
+    assert!(script.contains("internal static extern sbyte First(sbyte value);\n"));
+
+    configuration.set_c_char_signed(false);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn first(value: c_char) -> c_char { value }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    let script = builder.build().unwrap();
+    assert!(script.contains("internal static extern byte First(byte value);\n"));
+}
+
+#[test]
+fn build_nullable_pointer_types() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Data {
+                value: u8,
+            }
+
+            #[repr(C)]
+            pub struct Node {
+                id: NonZeroU32,
+                next: Option<NonNull<Data>>,
+                parent: NonNull<Data>,
+            }
+
+            pub extern "C" fn visit(
+                node: Option<&Data>,
+                callback: Option<extern "C" fn(id: NonZeroU32)>,
+                data: Option<Box<u8>>,
+            ) -> Option<NonZero<u64>> { None }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace Foo
+{
+    internal static class Bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Data
+        {
+            /// <remarks>u8</remarks>
+            public byte Value { get; init; }
+
+            public Data(byte value)
+            {
+                Value = value;
+            }
+        }
+
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Node
+        {
+            /// <remarks>NonZeroU32</remarks>
+            public uint Id { get; init; }
+            /// <remarks>Option&lt;NonNull&lt;Data&gt;&gt;</remarks>
+            public IntPtr Next { get; init; }
+            /// <remarks>NonNull&lt;Data&gt;</remarks>
+            public IntPtr Parent { get; init; }
+
+            public Node(uint id, IntPtr next, IntPtr parent)
+            {
+                Id = id;
+                Next = next;
+                Parent = parent;
+            }
+        }
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void VisitCallbackDelegate(uint id);
+
+        /// <param name=\"node\">Option&lt;&amp;Data&gt;</param>
+        /// <param name=\"callback\">Option&lt;fn(NonZeroU32)&gt;, nullable</param>
+        /// <param name=\"data\">Option&lt;Box&lt;u8&gt;&gt;</param>
+        /// <returns>Option&lt;NonZero&lt;u64&gt;&gt;</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"visit\")]
+        internal static extern ulong Visit(IntPtr node, VisitCallbackDelegate callback, IntPtr data);
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_option_unsupported() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn get() -> Option<u32> { None }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    match builder.build() {
+        Err(Error::UnsupportedError(message, _)) => {
+            assert!(message.contains("guaranteed layout"))
+        }
+        _ => panic!("Expected an unsupported error"),
+    }
+}
+
+#[test]
+fn build_pointer_width_combinations() {
+    for (pointer_width, version, expected) in [
+        (None, 8, Some("long F(ulong a)")),
+        (None, 9, Some("nint F(nuint a)")),
+        (Some(PointerWidth::W32), 8, Some("int F(uint a)")),
+        (Some(PointerWidth::W32), 9, Some("int F(uint a)")),
+        (Some(PointerWidth::W64), 8, Some("long F(ulong a)")),
+        (Some(PointerWidth::W64), 9, Some("long F(ulong a)")),
+        (Some(PointerWidth::Native), 8, None),
+        (Some(PointerWidth::Native), 9, Some("nint F(nuint a)")),
+    ] {
+        let mut configuration = CSharpConfiguration::new(version);
+        if let Some(pointer_width) = pointer_width {
+            configuration.set_pointer_width(pointer_width);
+        }
+        let mut builder = CSharpBuilder::new(
+            r#"pub extern "C" fn f(a: usize) -> isize { 0 }"#,
+            "foo",
+            &configuration,
+        )
+        .unwrap();
+        match (builder.build(), expected) {
+            (Ok(script), Some(expected)) => assert!(script.contains(expected)),
+            (Err(Error::UnsupportedError(message, _)), None) => {
+                assert!(message.contains("C# 9"))
+            }
+            (result, _) => panic!(
+                "Unexpected result {:?} for {:?} on C# {}",
+                result, pointer_width, version
+            ),
+        }
+    }
+}
+
+#[test]
+fn build_pointer_width_in_remarks() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_pointer_width(PointerWidth::W32);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Buffer {
+                length: usize,
+            }
+
+            #[repr(usize)]
+            pub enum Kind {
+                A = 1,
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    let script = builder.build().unwrap();
+    assert!(script.contains(
+        "        /// <remarks>usize (32 bit)</remarks>
+        public uint Length { get; init; }
+"
+    ));
+    assert!(script.contains("    public enum Kind : uint\n"));
+}
+
+#[test]
+fn build_native_pointer_width_enum_unsupported() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_pointer_width(PointerWidth::Native);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(isize)]
+            pub enum Kind {
+                A = 1,
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    match builder.build() {
+        Err(Error::UnsupportedError(message, _)) => assert!(message.contains("enums")),
+        _ => panic!("Expected an unsupported error"),
+    }
+}
+
+#[test]
+fn build_128_bit_integer_unsupported() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn get_id() -> u128 { 0 }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    match builder.build() {
+        Err(Error::UnsupportedError(message, span)) => {
+            assert!(message.contains("128 bit integers"));
+            assert_eq!(span.start().line, 2);
+        }
+        _ => panic!("Expected an unsupported error"),
+    }
+}
+
+#[test]
+fn build_128_bit_integer_as_pair() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.map_u128_as_pair(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Id {
+                /// The value of the id.
+                value: i128,
+                kind: u8,
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace Foo
+{
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    public struct Id
+    {
+        /// <summary>
+        /// The value of the id.
+        /// </summary>
+        /// <remarks>i128, low 64 bits</remarks>
+        public ulong ValueLow { get; init; }
+        /// <summary>
+        /// The value of the id.
+        /// </summary>
+        /// <remarks>i128, high 64 bits</remarks>
+        public long ValueHigh { get; init; }
+        /// <remarks>u8</remarks>
+        public byte Kind { get; init; }
+
+        /// <param name=\"valueLow\">The value of the id.</param>
+        /// <param name=\"valueHigh\">The value of the id.</param>
+        /// <param name=\"kind\">u8</param>
+        public Id(ulong valueLow, long valueHigh, byte kind)
+        {
+            ValueLow = valueLow;
+            ValueHigh = valueHigh;
+            Kind = kind;
+        }
+    }
+
+}\n"
+    )
+}
+
+#[test]
+fn build_128_bit_integer_opt_ins() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.map_u128_as_big_integer(true);
+    let mut builder = CSharpBuilder::new(
+        r#"pub extern "C" fn get_id() -> u128 { 0 }"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    let script = builder.build().unwrap();
+    assert!(script.contains("internal static extern System.Numerics.BigInteger GetId();"));
+
+    let configuration = CSharpConfiguration::new(9);
+    configuration
+        .add_known_type("u128", None, None, "UInt128".to_string())
+        .unwrap();
+    let mut builder = CSharpBuilder::new(
+        r#"pub extern "C" fn get_id() -> u128 { 0 }"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    let script = builder.build().unwrap();
+    assert!(script.contains("internal static extern UInt128 GetId();"));
+}
+
+#[test]
+fn build_generic_parameter_order() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Entry<TKey, TValue, TExtra> {
+                key: TKey,
+                value: TValue,
+                extra: TExtra,
+            }
+
+            pub extern "C" fn insert(entry: Entry<u8, u32, i16>) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace Foo
+{
+    internal static class Bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Entry<TKey, TValue, TExtra>
+            where TKey : unmanaged
+            where TValue : unmanaged
+            where TExtra : unmanaged
+        {
+            /// <remarks>TKey</remarks>
+            public TKey Key { get; init; }
+            /// <remarks>TValue</remarks>
+            public TValue Value { get; init; }
+            /// <remarks>TExtra</remarks>
+            public TExtra Extra { get; init; }
+
+            public Entry(TKey key, TValue value, TExtra extra)
+            {
+                Key = key;
+                Value = value;
+                Extra = extra;
+            }
+        }
+
+        /// <param name=\"entry\">Entry&lt;u8, u32, i16&gt;</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"insert\")]
+        internal static extern void Insert(Entry<byte, uint, short> entry);
+
+    }
+}\n"
+    )
+}
+
+#[test]
+fn build_unmanaged_constraints_disabled() {
+    for (version, disable) in [(9, true), (7, false)] {
+        let mut configuration = CSharpConfiguration::new(version);
+        if disable {
+            configuration.emit_unmanaged_constraints(false);
+        }
+        let mut builder = CSharpBuilder::new(
+            r#"
+                #[repr(C)]
+                pub struct Wrapper<T> {
+                    value: T,
+                }
+            "#,
+            "foo",
+            &configuration,
+        )
+        .unwrap();
+        builder.set_namespace("Foo");
+        let script = builder.build().unwrap();
+        assert!(script.contains("    public struct Wrapper<T>\n    {\n"));
+        assert!(!script.contains("unmanaged"));
+    }
+}
+
+#[test]
+fn build_generic_arguments_in_docs() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.use_unsafe_pointers(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Wrapper<T> {
+                value: T,
+            }
+
+            #[repr(C)]
+            pub struct Holder {
+                nested: Wrapper<Wrapper<u8>>,
+                items: [Wrapper<u16>; 2],
+            }
+
+            pub extern "C" fn foo(ptr: *mut Wrapper<u32>) -> *const Wrapper<i8> {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains(
+        "            /// <remarks>Wrapper&lt;Wrapper&lt;u8&gt;&gt;</remarks>
+            public Wrapper<Wrapper<byte>> Nested { get; init; }
+"
+    ));
+    assert!(script.contains(
+        "            /// <remarks>[Wrapper&lt;u16&gt;; 2]</remarks>
+"
+    ));
+    assert!(script.contains(
+        "        /// <param name=\"ptr\">*mut Wrapper&lt;u32&gt;</param>
+        /// <returns>*const Wrapper&lt;i8&gt;</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"foo\")]
+        internal static extern Wrapper<sbyte>* Foo(Wrapper<uint>* ptr);
+"
+    ));
+}
+
+#[test]
+fn build_impl_block_functions() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Engine {
+                ticks: u64,
+            }
+
+            impl Engine {
+                /// Advances the engine by a single tick.
+                #[no_mangle]
+                pub extern "C" fn engine_tick(ptr: *mut Engine) {}
+
+                pub fn ticks(&self) -> u64 { self.ticks }
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains(
+        "        /// <summary>
+        /// Advances the engine by a single tick.
+        /// </summary>
+        /// <param name=\"ptr\">*mut Engine</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"engine_tick\")]
+        internal static extern void EngineTick(IntPtr ptr);
+"
+    ));
+    assert!(!script.contains("Ticks("));
+}
+
+#[test]
+fn build_impl_block_receivers() {
+    let script = r#"
+        #[repr(C)]
+        pub struct Engine {
+            ticks: u64,
+        }
+
+        impl Engine {
+            #[no_mangle]
+            pub extern "C" fn engine_ticks(&self) -> u64 { self.ticks }
+
+            #[no_mangle]
+            pub extern "C" fn engine_reset(&mut self, ticks: u64) {}
+        }
+    "#;
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(script, "foo", &configuration).unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    assert!(matches!(builder.build(), Err(Error::UnsupportedError(..))));
+
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.map_impl_receivers(true);
+    let mut builder = CSharpBuilder::new(script, "foo", &configuration).unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains(
+        "        /// <param name=\"self\">*const Engine</param>
+        /// <returns>u64</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"engine_ticks\")]
+        internal static extern ulong EngineTicks(IntPtr self);
+
+        /// <param name=\"self\">*mut Engine</param>
+        /// <param name=\"ticks\">u64</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"engine_reset\")]
+        internal static extern void EngineReset(IntPtr self, ulong ticks);
+"
+    ));
+}
+
+#[test]
+fn build_modules_as_classes() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.nest_modules_as_classes(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            mod audio {
+                #[repr(C)]
+                pub struct Sample {
+                    value: i16,
+                }
+
+                #[no_mangle]
+                pub extern "C" fn play(sample: Sample) {}
+
+                mod effects {
+                    #[no_mangle]
+                    pub extern "C" fn echo(sample: Sample) -> Sample {}
+                }
+            }
+
+            mod video {
+                #[no_mangle]
+                pub extern "C" fn play(sample: Sample) {}
+            }
+
+            mod empty {
+                pub fn internal() {}
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace Foo
+{
+    internal static class Bar
+    {
+        internal static class Audio
+        {
+            [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+            public struct Sample
+            {
+                /// <remarks>i16</remarks>
+                public short Value { get; init; }
+
+                public Sample(short value)
+                {
+                    Value = value;
+                }
+            }
+
+            /// <param name=\"sample\">Sample</param>
+            /// <returns>void</returns>
+            [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"play\")]
+            internal static extern void Play(Sample sample);
+
+            internal static class Effects
+            {
+                /// <param name=\"sample\">Sample</param>
+                /// <returns>Sample</returns>
+                [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"echo\")]
+                internal static extern Bar.Audio.Sample Echo(Bar.Audio.Sample sample);
+
+            }
+
+        }
+
+        internal static class Video
+        {
+            /// <param name=\"sample\">Sample</param>
+            /// <returns>void</returns>
+            [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"play\")]
+            internal static extern void Play(Bar.Audio.Sample sample);
+
+        }
+
+    }
+}
+"
+    );
+
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            mod audio {
+                #[no_mangle]
+                pub extern "C" fn play() {}
+            }
+
+            mod video {
+                #[no_mangle]
+                pub extern "C" fn play() {}
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("Bar");
+    assert!(matches!(builder.build(), Err(Error::DuplicateSymbol(..))));
+}
+
+#[test]
+fn build_cfg_evaluation() {
+    let script = r#"
+        #[cfg(test)]
+        #[no_mangle]
+        pub extern "C" fn test_only() {}
+
+        #[cfg(feature = "internal")]
+        #[no_mangle]
+        pub extern "C" fn internal() {}
+
+        #[cfg(all(unix, not(feature = "internal")))]
+        #[no_mangle]
+        pub extern "C" fn unix_release() {}
+
+        #[cfg(any(windows, target_os = "macos"))]
+        mod windows {
+            #[no_mangle]
+            pub extern "C" fn windows_only() {}
+        }
+
+        #[no_mangle]
+        pub extern "C" fn always() {}
+    "#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_cfg_evaluation(CfgOptions {
+        features: vec!["internal".to_string()],
+        target_os: Some("windows".to_string()),
+        test: false,
+    });
+    let mut builder = CSharpBuilder::new(script, "foo", &configuration).unwrap();
+    builder.set_type("Bar");
+    let output = builder.build().unwrap();
+    assert!(!output.contains("TestOnly"));
+    assert!(output.contains("Internal()"));
+    assert!(!output.contains("UnixRelease"));
+    assert!(output.contains("WindowsOnly()"));
+    assert!(output.contains("Always()"));
+    assert!(builder.warnings().is_empty());
+
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_cfg_evaluation(CfgOptions {
+        target_os: Some("linux".to_string()),
+        test: true,
+        ..Default::default()
+    });
+    let mut builder = CSharpBuilder::new(script, "foo", &configuration).unwrap();
+    builder.set_type("Bar");
+    let output = builder.build().unwrap();
+    assert!(output.contains("TestOnly()"));
+    assert!(!output.contains("Internal()"));
+    assert!(output.contains("UnixRelease()"));
+    assert!(!output.contains("WindowsOnly"));
+    assert!(output.contains("Always()"));
+
+    // Without cfg evaluation, everything is generated.
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(script, "foo", &configuration).unwrap();
+    builder.set_type("Bar");
+    let output = builder.build().unwrap();
+    assert!(output.contains("TestOnly()"));
+    assert!(output.contains("UnixRelease()"));
+}
+
+#[test]
+fn build_cfg_unknown_predicate() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_cfg_evaluation(CfgOptions::default());
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[cfg(target_arch = "x86_64")]
+            #[no_mangle]
+            pub extern "C" fn vectorised() {}
+
+            #[cfg(any(target_arch = "x86_64", not(test)))]
+            #[no_mangle]
+            pub extern "C" fn decided() {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("Bar");
+    let output = builder.build().unwrap();
+    assert!(output.contains("Vectorised()"));
+    assert!(output.contains("Decided()"));
+    assert_eq!(builder.warnings().len(), 1);
+    assert_eq!(builder.warnings()[0].item_name, "vectorised");
+}
+
+#[test]
+fn build_cfg_attr_repr() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_cfg_evaluation(CfgOptions {
+        features: vec!["ffi".to_string()],
+        ..Default::default()
+    });
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[cfg_attr(feature = "ffi", repr(C))]
+            pub struct Point {
+                x: i32,
+            }
+
+            #[cfg_attr(feature = "other", repr(C))]
+            pub struct Hidden {
+                x: i32,
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("Bar");
+    let output = builder.build().unwrap();
+    assert!(output.contains("public struct Point"));
+    assert!(!output.contains("Hidden"));
+}
+
+#[test]
+fn build_statics() {
+    let script = r#"
+        #[repr(C)]
+        pub struct Config {
+            threads: u32,
+        }
+
+        /// The version of the API.
+        #[no_mangle]
+        pub static API_VERSION: u32 = 3;
+
+        /// The default configuration.
+        #[no_mangle]
+        pub static DEFAULT_CONFIG: Config = Config { threads: 4 };
+
+        #[export_name = "log_level"]
+        pub static mut LOG_LEVEL: u8 = 2;
+
+        pub static NOT_EXPORTED: u32 = 1;
+    "#;
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(script, "foo", &configuration).unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    let output = builder.build().unwrap();
+    assert!(output.contains(
+        "        /// <summary>
+        /// The version of the API.
+        /// </summary>
+        public const uint ApiVersion = 3;
+"
+    ));
+    assert!(!output.contains("DefaultConfig"));
+    let warnings = builder
+        .warnings()
+        .iter()
+        .map(|warning| warning.item_name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(warnings, ["DEFAULT_CONFIG", "LOG_LEVEL", "NOT_EXPORTED"]);
+
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.read_statics_from_library(true);
+    let mut builder = CSharpBuilder::new(script, "foo", &configuration).unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    let output = builder.build().unwrap();
+    assert!(output.contains(
+        "        private static IntPtr DefaultConfigAddress;
+
+        /// <summary>
+        /// The default configuration.
+        /// </summary>
+        public static Config DefaultConfig
+        {
+            get
+            {
+                if (DefaultConfigAddress == IntPtr.Zero)
+                {
+                    DefaultConfigAddress = NativeLibrary.GetExport(NativeLibrary.Load(\"foo\"), \"DEFAULT_CONFIG\");
+                }
+                return Marshal.PtrToStructure<Config>(DefaultConfigAddress);
+            }
+        }
+
+        private static IntPtr LogLevelAddress;
+
+        public static byte LogLevel
+        {
+            get
+            {
+                if (LogLevelAddress == IntPtr.Zero)
+                {
+                    LogLevelAddress = NativeLibrary.GetExport(NativeLibrary.Load(\"foo\"), \"log_level\");
+                }
+                return Marshal.PtrToStructure<byte>(LogLevelAddress);
+            }
+        }
+"
+    ));
+    assert_eq!(builder.warnings().len(), 1);
+}
+
+#[test]
+fn build_static_requires_marshalling() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.allow_bool_marshaling(true);
+    configuration.read_statics_from_library(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[no_mangle]
+            pub static mut ENABLED: bool = true;
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("Bar");
+    let output = builder.build().unwrap();
+    assert!(!output.contains("Enabled"));
+    assert_eq!(builder.warnings()[0].item_name, "ENABLED");
+}
+
+#[test]
+fn configuration_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<CSharpConfiguration>();
+}
+
+#[test]
+fn build_concurrently_with_shared_configuration() {
+    let configuration = CSharpConfiguration::new(9);
+    configuration
+        .add_known_type("Handle", None, None, "IntPtr".to_string())
+        .unwrap();
+    let scripts = std::thread::scope(|scope| {
+        let threads = (0..8)
+            .map(|index| {
+                let configuration = &configuration;
+                scope.spawn(move || {
+                    let mut builder = CSharpBuilder::new(
+                        &format!(
+                            r#"
+                                #[repr(C)]
+                                pub struct Type{index} {{
+                                    handle: Handle,
+                                }}
+
+                                pub extern "C" fn create_{index}() -> Type{index} {{}}
+                            "#,
+                            index = index
+                        ),
+                        "foo",
+                        configuration,
+                    )
+                    .unwrap();
+                    builder.set_namespace("Foo");
+                    builder.set_type(&format!("Bar{}", index));
+                    builder.build().unwrap()
+                })
+            })
+            .collect::<Vec<_>>();
+        threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+    for (index, script) in scripts.iter().enumerate() {
+        assert!(script.contains(&format!(
+            "internal static extern Type{0} Create{0}();",
+            index
+        )));
+    }
+
+    // The types registered on the other threads can be used afterwards.
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn combine(a: Type3, b: Type5) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Combined");
+    let script = builder.build().unwrap();
+    assert!(script.contains("internal static extern void Combine(Bar3.Type3 a, Bar5.Type5 b);"));
+}
+
+#[test]
+fn build_with_registered_types_in_any_order() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut first = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn create_point() -> Point {}
+            pub extern "C" fn get_callback() -> Callback {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    first.set_namespace("Foo");
+    first.set_type("Functions");
+    let mut second = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Point {
+                x: i32,
+                shape: Shape,
+            }
+
+            #[repr(u8)]
+            pub enum Shape {
+                Square,
+            }
+
+            pub type Callback = extern "C" fn(Point);
+            pub type Origin = Point;
+
+            // Not generated, so not registered either.
+            pub struct Internal {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    second.set_namespace("Foo.Types");
+    second.set_type("Types");
+
+    assert!(matches!(first.build(), Err(Error::UnknownType(..))));
+    first.register_types_only().unwrap();
+    second.register_types_only().unwrap();
+    assert!(configuration.get_known_type("Internal").is_none());
+
+    let script = first.build().unwrap();
+    assert!(script.contains("        internal static extern Foo.Types.Types.Point CreatePoint();"));
+    assert!(
+        script.contains("        internal static extern Foo.Types.Types.Callback GetCallback();")
+    );
+    let origin = configuration.get_known_type("Origin").unwrap();
+    assert_eq!(origin.real_type_name, "Point");
+    assert_eq!(origin.inside_type.as_deref(), Some("Types"));
+    second.build().unwrap();
+}
+
+#[test]
+fn extract_items() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(u8)]
+            pub enum Mode {
+                Fast = 2,
+                Slow,
+            }
+
+            #[repr(C)]
+            pub struct Settings {
+                mode: Mode,
+                limits: [u16; 2],
+            }
+
+            #[repr(C)]
+            pub struct Pair(u32, i64);
+
+            #[export_name = "apply"]
+            pub extern "C" fn apply_settings(settings: *const Settings, scale: f32) -> u8 {}
+
+            pub extern "C" fn unsupported(value: &str) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("Bar");
+    let extracted = builder.extracted_items().unwrap();
+
+    assert_eq!(extracted.enums.len(), 1);
+    assert_eq!(extracted.enums[0].csharp_name, "Mode");
+    assert_eq!(extracted.enums[0].underlying_type.csharp_type, "byte");
+    assert_eq!(
+        extracted.enums[0].variants,
+        [
+            ExtractedVariant {
+                rust_name: "Fast".to_string(),
+                csharp_name: "Fast".to_string(),
+                discriminant: "2".to_string(),
+            },
+            ExtractedVariant {
+                rust_name: "Slow".to_string(),
+                csharp_name: "Slow".to_string(),
+                discriminant: "3".to_string(),
+            },
+        ]
+    );
+
+    assert_eq!(extracted.structs.len(), 2);
+    assert_eq!(
+        extracted.structs[0].fields,
+        [
+            ExtractedField {
+                rust_name: "mode".to_string(),
+                csharp_name: "Mode".to_string(),
+                field_type: ExtractedType {
+                    rust_type: "Mode".to_string(),
+                    csharp_type: "Mode".to_string(),
+                },
+                is_property: true,
+                offset_in_field: 0,
+            },
+            ExtractedField {
+                rust_name: "limits".to_string(),
+                csharp_name: "Limits".to_string(),
+                field_type: ExtractedType {
+                    rust_type: "[u16; 2]".to_string(),
+                    csharp_type: "ushort[]".to_string(),
+                },
+                is_property: true,
+                offset_in_field: 0,
+            },
+        ]
+    );
+    assert_eq!(extracted.structs[1].fields[1].rust_name, "1");
+    assert_eq!(extracted.structs[1].fields[1].csharp_name, "Item1");
+
+    assert_eq!(extracted.functions.len(), 1);
+    let function = &extracted.functions[0];
+    assert_eq!(function.rust_name, "apply_settings");
+    assert_eq!(function.csharp_name, "ApplySettings");
+    assert_eq!(function.entry_point, "apply");
+    assert_eq!(function.params[0].0, "settings");
+    assert_eq!(function.params[0].1.rust_type, "*const Settings");
+    assert_eq!(function.params[0].1.csharp_type, "IntPtr");
+    assert_eq!(function.params[1].1.csharp_type, "float");
+    assert_eq!(function.return_type.csharp_type, "byte");
+
+    assert_eq!(extracted.unsupported.len(), 1);
+    assert_eq!(extracted.unsupported[0].item_name, "unsupported");
+    assert!(matches!(
+        extracted.unsupported[0].error,
+        Error::UnsupportedError(..)
+    ));
+}
+
+#[test]
+fn extract_items_after_build() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(u16)]
+            pub enum Shape {
+                Circle(f32),
+                Square { side: f32 },
+            }
+
+            #[repr(C)]
+            pub struct Point {
+                x: i32,
+            }
+
+            pub extern "C" fn shape_of(point: Point) -> Shape {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("Bar");
+    let script = builder.build().unwrap();
+    let extracted = builder.extracted_items().unwrap();
+    assert_eq!(builder.build().unwrap(), script);
+    assert!(builder.warnings().is_empty());
+
+    assert_eq!(extracted.enums[0].csharp_name, "Shape");
+    assert_eq!(extracted.enums[0].underlying_type.rust_type, "u16");
+    assert_eq!(extracted.enums[0].variants[1].csharp_name, "Square");
+    assert_eq!(extracted.enums[0].variants[1].discriminant, "1");
+    assert_eq!(extracted.functions[0].params[0].1.csharp_type, "Point");
+    assert_eq!(extracted.functions[0].return_type.csharp_type, "Shape");
+    assert!(extracted.unsupported.is_empty());
+}
+
+#[test]
+fn build_layout_tests() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct DisplaySettings {
+                mode: u8,
+                scale: f32,
+            }
+
+            #[repr(C)]
+            pub struct Pair(u8, u64);
+
+            #[repr(C)]
+            pub struct Wrapper<T> {
+                value: T,
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    let tests = builder.build_layout_tests("layout_").unwrap();
+    assert_eq!(
+        tests.rust,
+        "// Automatically generated, do not edit!
+
+#[no_mangle]
+pub extern \"C\" fn layout_display_settings_size() -> usize {
+    std::mem::size_of::<DisplaySettings>()
+}
+
+#[no_mangle]
+pub extern \"C\" fn layout_display_settings_mode_offset() -> usize {
+    std::mem::offset_of!(DisplaySettings, mode)
+}
+
+#[no_mangle]
+pub extern \"C\" fn layout_display_settings_scale_offset() -> usize {
+    std::mem::offset_of!(DisplaySettings, scale)
+}
+
+#[no_mangle]
+pub extern \"C\" fn layout_pair_size() -> usize {
+    std::mem::size_of::<Pair>()
+}
+
+#[no_mangle]
+pub extern \"C\" fn layout_pair_0_offset() -> usize {
+    std::mem::offset_of!(Pair, 0)
+}
+
+#[no_mangle]
+pub extern \"C\" fn layout_pair_1_offset() -> usize {
+    std::mem::offset_of!(Pair, 1)
+}
+"
+    );
+    assert_eq!(tests.csharp, "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace Foo
+{
+    internal static class BarLayoutTests
+    {
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"layout_display_settings_size\")]
+        private static extern UIntPtr LayoutDisplaySettingsSize();
+
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"layout_display_settings_mode_offset\")]
+        private static extern UIntPtr LayoutDisplaySettingsModeOffset();
+
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"layout_display_settings_scale_offset\")]
+        private static extern UIntPtr LayoutDisplaySettingsScaleOffset();
+
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"layout_pair_size\")]
+        private static extern UIntPtr LayoutPairSize();
+
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"layout_pair_0_offset\")]
+        private static extern UIntPtr LayoutPair0Offset();
+
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"layout_pair_1_offset\")]
+        private static extern UIntPtr LayoutPair1Offset();
+
+        /// <summary>
+        /// Checks that the sizes and field offsets of the generated structs match those of the Rust
+        /// structs, and throws an <see cref=\"InvalidOperationException\"/> if any of them differ.
+        /// </summary>
+        public static void Verify()
+        {
+            Check(\"DisplaySettings\", Marshal.SizeOf<Bar.DisplaySettings>(), LayoutDisplaySettingsSize().ToUInt64());
+            Check(\"DisplaySettings.Mode\", Marshal.OffsetOf<Bar.DisplaySettings>(\"<Mode>k__BackingField\").ToInt64(), LayoutDisplaySettingsModeOffset().ToUInt64());
+            Check(\"DisplaySettings.Scale\", Marshal.OffsetOf<Bar.DisplaySettings>(\"<Scale>k__BackingField\").ToInt64(), LayoutDisplaySettingsScaleOffset().ToUInt64());
+            Check(\"Pair\", Marshal.SizeOf<Bar.Pair>(), LayoutPairSize().ToUInt64());
+            Check(\"Pair.Item0\", Marshal.OffsetOf<Bar.Pair>(\"<Item0>k__BackingField\").ToInt64(), LayoutPair0Offset().ToUInt64());
+            Check(\"Pair.Item1\", Marshal.OffsetOf<Bar.Pair>(\"<Item1>k__BackingField\").ToInt64(), LayoutPair1Offset().ToUInt64());
+        }
+
+        private static void Check(string name, long actual, ulong expected)
+        {
+            if ((ulong)actual != expected)
+            {
+                throw new InvalidOperationException(\"The layout of \" + name + \" differs, expected \" + expected + \" but was \" + actual + \".\");
+            }
+        }
+    }
+}
+");
+}
+
+#[test]
+fn build_layout_tests_fields() {
+    let mut configuration = CSharpConfiguration::new(8);
+    configuration.map_u128_as_pair(true);
+    configuration.use_fixed_buffers(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Value {
+                big: u128,
+                buffer: [u8; 4],
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    let tests = builder.build_layout_tests("test_").unwrap();
+    assert_eq!(tests.csharp, "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+internal static class LayoutTests
+{
+    [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"test_value_size\")]
+    private static extern UIntPtr TestValueSize();
+
+    [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"test_value_big_offset\")]
+    private static extern UIntPtr TestValueBigOffset();
+
+    [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"test_value_buffer_offset\")]
+    private static extern UIntPtr TestValueBufferOffset();
+
+    /// <summary>
+    /// Checks that the sizes and field offsets of the generated structs match those of the Rust
+    /// structs, and throws an <see cref=\"InvalidOperationException\"/> if any of them differ.
+    /// </summary>
+    public static void Verify()
+    {
+        Check(\"Value\", Marshal.SizeOf<Value>(), TestValueSize().ToUInt64());
+        Check(\"Value.BigLow\", Marshal.OffsetOf<Value>(\"BigLow\").ToInt64(), TestValueBigOffset().ToUInt64());
+        Check(\"Value.BigHigh\", Marshal.OffsetOf<Value>(\"BigHigh\").ToInt64(), TestValueBigOffset().ToUInt64() + 8);
+        Check(\"Value.Buffer\", Marshal.OffsetOf<Value>(\"Buffer\").ToInt64(), TestValueBufferOffset().ToUInt64());
+    }
+
+    private static void Check(string name, long actual, ulong expected)
+    {
+        if ((ulong)actual != expected)
+        {
+            throw new InvalidOperationException(\"The layout of \" + name + \" differs, expected \" + expected + \" but was \" + actual + \".\");
+        }
+    }
+}
+");
+}
+
+#[test]
+fn build_dll_names() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[no_mangle]
+            pub extern "C" fn init() {}
+        "#,
+        "mylib",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    builder.set_dll_names(DllNames {
+        windows: "mylib.dll".to_string(),
+        linux: "libmylib.so".to_string(),
+        macos: "libmylib.dylib".to_string(),
+    });
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace Foo
+{
+    internal static class Bar
+    {
+#if UNITY_STANDALONE_WIN || UNITY_EDITOR_WIN || WINDOWS
+        internal const string LibraryName = \"mylib.dll\";
+#elif UNITY_STANDALONE_OSX || UNITY_EDITOR_OSX || OSX || MACOS
+        internal const string LibraryName = \"libmylib.dylib\";
+#else
+        internal const string LibraryName = \"libmylib.so\";
+#endif
+
+        /// <returns>void</returns>
+        [DllImport(LibraryName, CallingConvention = CallingConvention.Cdecl, EntryPoint=\"init\")]
+        internal static extern void Init();
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_dll_names_resolved_at_runtime() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.resolve_dll_names_at_runtime(true);
+    configuration.read_statics_from_library(true);
+    configuration.nest_modules_as_classes(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[no_mangle]
+            pub static mut COUNTER: u32 = 0;
+
+            pub mod audio {
+                #[no_mangle]
+                pub extern "C" fn play() {}
+            }
+        "#,
+        "mylib",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("Bar");
+    builder.set_dll_names(DllNames {
+        windows: "mylib.dll".to_string(),
+        linux: "libmylib.so".to_string(),
+        macos: "libmylib.dylib".to_string(),
+    });
+    assert_eq!(builder.build().unwrap(), "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+internal static class Bar
+{
+    internal const string LibraryName = \"mylib\";
+
+    static Bar()
+    {
+        NativeLibrary.SetDllImportResolver(typeof(Bar).Assembly, (name, assembly, searchPath) =>
+        {
+            if (name != LibraryName)
+            {
+                return IntPtr.Zero;
+            }
+            if (RuntimeInformation.IsOSPlatform(OSPlatform.Windows))
+            {
+                return NativeLibrary.Load(\"mylib.dll\", assembly, searchPath);
+            }
+            if (RuntimeInformation.IsOSPlatform(OSPlatform.OSX))
+            {
+                return NativeLibrary.Load(\"libmylib.dylib\", assembly, searchPath);
+            }
+            return NativeLibrary.Load(\"libmylib.so\", assembly, searchPath);
+        });
+    }
+
+    private static IntPtr CounterAddress;
+
+    public static uint Counter
+    {
+        get
+        {
+            if (CounterAddress == IntPtr.Zero)
+            {
+                CounterAddress = NativeLibrary.GetExport(NativeLibrary.Load(LibraryName, typeof(Bar).Assembly, null), \"COUNTER\");
+            }
+            return Marshal.PtrToStructure<uint>(CounterAddress);
+        }
+    }
+
+    internal static class Audio
+    {
+        static Audio()
+        {
+            System.Runtime.CompilerServices.RuntimeHelpers.RunClassConstructor(typeof(Bar).TypeHandle);
+        }
+
+        /// <returns>void</returns>
+        [DllImport(LibraryName, CallingConvention = CallingConvention.Cdecl, EntryPoint=\"play\")]
+        internal static extern void Play();
+
+    }
+
+}
+");
+}
+
+#[test]
+fn build_unity_target_runtime() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_target_runtime(TargetRuntime::Unity);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Buffer {
+                data: *mut u8,
+                length: usize,
+            }
+
+            #[repr(u8)]
+            pub enum Status {
+                Ok,
+                Failed,
+            }
+
+            #[no_mangle]
+            pub extern "C" fn read(buffer: Buffer, offset: isize) -> usize {}
+
+            #[no_mangle]
+            pub extern "C" fn set_callback(callback: extern "C" fn(status: Status, read: usize) -> u8) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("Bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+internal static class Bar
+{
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    public struct Buffer
+    {
+        /// <remarks>*mut u8</remarks>
+        public readonly IntPtr Data;
+        /// <remarks>usize</remarks>
+        public readonly UIntPtr Length;
+
+        public Buffer(IntPtr data, UIntPtr length)
+        {
+            Data = data;
+            Length = length;
+        }
+    }
+
+    public enum Status : byte
+    {
+        Ok,
+        Failed,
+    }
+
+    /// <param name=\"buffer\">Buffer</param>
+    /// <param name=\"offset\">isize</param>
+    /// <returns>usize</returns>
+    [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"read\")]
+    internal static extern UIntPtr Read(Buffer buffer, IntPtr offset);
+
+    /// <example>
+    /// IL2CPP requires methods passed as this delegate to be static, and marked with
+    /// <c>MonoPInvokeCallback</c>:
+    /// <code>
+    /// [AOT.MonoPInvokeCallback(typeof(SetCallbackCallbackDelegate))]
+    /// private static byte OnSetCallbackCallback(Status status, UIntPtr read) { return default; }
+    /// </code>
+    /// </example>
+    [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+    public delegate byte SetCallbackCallbackDelegate(Status status, UIntPtr read);
+
+    /// <param name=\"callback\">fn(Status, usize) -&gt; u8</param>
+    /// <returns>void</returns>
+    [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"set_callback\")]
+    internal static extern void SetCallback(SetCallbackCallbackDelegate callback);
+
+}
+"
+    );
+}
+
+#[test]
+fn build_unity_target_runtime_ignores_version() {
+    let mut configuration = CSharpConfiguration::new(11);
+    configuration.set_target_runtime(TargetRuntime::Unity);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[no_mangle]
+            pub extern "C" fn length() -> usize {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("Bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+internal static class Bar
+{
+    /// <returns>usize</returns>
+    [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"length\")]
+    internal static extern UIntPtr Length();
+
+}
+"
+    );
+}
+
+#[test]
+fn build_accessibility() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_accessibility(AccessibilityOptions {
+        class: Access::Public,
+        methods: Access::Public,
+        types: Access::Internal,
+    });
+    configuration.emit_partial_class(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Point {
+                x: i32,
+            }
+
+            #[repr(u8)]
+            pub enum Status {
+                Ok,
+            }
+
+            #[no_mangle]
+            pub extern "C" fn draw(point: Point, callback: extern "C" fn(status: Status)) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("Bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+public static partial class Bar
+{
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    internal struct Point
+    {
+        /// <remarks>i32</remarks>
+        public int X { get; init; }
+
+        public Point(int x)
+        {
+            X = x;
+        }
+    }
+
+    internal enum Status : byte
+    {
+        Ok,
+    }
+
+    [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+    internal delegate void DrawCallbackDelegate(Status status);
+
+    /// <param name=\"point\">Point</param>
+    /// <param name=\"callback\">fn(Status)</param>
+    /// <returns>void</returns>
+    [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"draw\")]
+    public static extern void Draw(Point point, DrawCallbackDelegate callback);
+
+}
+"
+    );
+}
+
+#[test]
+fn build_accessibility_split() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_accessibility(AccessibilityOptions {
+        types: Access::Internal,
+        ..AccessibilityOptions::default()
+    });
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub union Value {
+                int: i32,
+                float: f32,
+            }
+
+            #[no_mangle]
+            pub extern "C" fn get() -> Value {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("Bar");
+    let files = builder.build_split().unwrap();
+    assert_eq!(
+        files[0].contents,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+[StructLayout(LayoutKind.Explicit)]
+internal struct Value
+{
+    /// <remarks>i32</remarks>
+    [FieldOffset(0)]
+    public int Int;
+    /// <remarks>f32</remarks>
+    [FieldOffset(0)]
+    public float Float;
+}
+"
+    );
+    assert_eq!(
+        files[1].contents,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+internal static class Bar
+{
+    /// <returns>Value</returns>
+    [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"get\")]
+    internal static extern Value Get();
+
+}
+"
+    );
+}
+
+#[test]
+fn build_class_preamble_and_epilogue() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_indentation(Indentation::Tabs);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[no_mangle]
+            pub extern "C" fn tick() {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bindings");
+    builder.add_class_preamble(
+        "
+        static Bindings()
+        {
+            NativeLoader.Init();
+        }
+        ",
+    );
+    builder.add_class_epilogue("public static void TickTwice()\n{\n    Tick();\n\n    Tick();\n}");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace Foo
+{
+\tinternal static class Bindings
+\t{
+\t\tstatic Bindings()
+\t\t{
+\t\t    NativeLoader.Init();
+\t\t}
+
+\t\t/// <returns>void</returns>
+\t\t[DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"tick\")]
+\t\tinternal static extern void Tick();
+
+\t\tpublic static void TickTwice()
+\t\t{
+\t\t    Tick();
+
+\t\t    Tick();
+\t\t}
+
+\t}
+}
+"
+    );
+}
+
+#[test]
+fn build_extra_attributes() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.add_extra_attribute("fast_tick", "[SuppressGCTransition]");
+    configuration.add_extra_attribute("fast_tick", "[Obsolete]");
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[no_mangle]
+            pub extern "C" fn fast_tick() {}
+
+            #[no_mangle]
+            pub extern "C" fn slow_tick() {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("Bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+internal static class Bar
+{
+    /// <returns>void</returns>
+    [SuppressGCTransition]
+    [Obsolete]
+    [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"fast_tick\")]
+    internal static extern void FastTick();
+
+    /// <returns>void</returns>
+    [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"slow_tick\")]
+    internal static extern void SlowTick();
+
+}
+"
+    );
+}
+
+#[test]
+fn build_analyzer_suppressions() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.emit_auto_generated_marker(true);
+    configuration.emit_generated_code_attribute(true);
+    configuration.set_generated_code_version("1.2.3");
+    configuration.add_pragma_suppression("CA1707");
+    configuration.add_pragma_suppression("IDE1006");
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[no_mangle]
+            pub extern "C" fn tick() {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// <auto-generated/>
+// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+#pragma warning disable CA1707, IDE1006
+
+namespace Foo
+{
+    [System.CodeDom.Compiler.GeneratedCode(\"csharp_binder\", \"1.2.3\")]
+    internal static class Bar
+    {
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"tick\")]
+        internal static extern void Tick();
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_generated_code_attribute_version() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.emit_generated_code_attribute(true);
+    configuration.set_generated_warning("");
+    configuration.emit_auto_generated_marker(true);
+    let mut builder = CSharpBuilder::new("", "foo", &configuration).unwrap();
+    builder.set_type("Bar");
+    let script = builder.build().unwrap();
+    assert!(script.starts_with("// <auto-generated/>\nusing System;"));
+    assert!(script.contains(&format!(
+        "[System.CodeDom.Compiler.GeneratedCode(\"csharp_binder\", \"{}\")]\ninternal static class Bar",
+        env!("CARGO_PKG_VERSION")
+    )));
+}
+
+#[test]
+fn build_generated_warning_placeholders() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_generated_warning(
+        "Generated from {source} for {dll_name} at {timestamp}.\nTool version {tool_version}, {unknown}.",
+    );
+    let mut builder = CSharpBuilder::new("", "foo", &configuration).unwrap();
+    builder.set_type("Bar");
+    builder.set_source_name("ffi.rs");
+    builder.set_timestamp("2024-01-31T12:00:00Z");
+    let script = builder.build().unwrap();
+    assert!(script.starts_with(&format!(
+        "// Generated from ffi.rs for foo at 2024-01-31T12:00:00Z.\n// Tool version {}, {{unknown}}.\nusing System;",
+        env!("CARGO_PKG_VERSION")
+    )));
+}
+
+#[test]
+fn build_generated_warning_current_timestamp() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_generated_warning("{timestamp} {source}");
+    let mut builder = CSharpBuilder::new("", "foo", &configuration).unwrap();
+    let script = builder.build().unwrap();
+    let header = script.lines().next().unwrap();
+    let pattern = Regex::new(r"^// \d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}Z \{source\}$").unwrap();
+    assert!(pattern.is_match(header), "{}", header);
+}
+
+#[test]
+fn verify_against_existing_script() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[no_mangle]
+            pub extern "C" fn tick() {}
+
+            #[no_mangle]
+            pub extern "C" fn get_count() -> u32 {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("Bar");
+    let script = builder.build().unwrap();
+    builder.verify_against(&script).unwrap();
+    builder
+        .verify_against(&script.replace('\n', "\r\n"))
+        .unwrap();
+
+    let existing = script
+        .replace("Count() -> u32", "Count() -> u16")
+        .replace("uint", "ushort");
+    match builder.verify_against(&existing) {
+        Err(VerifyError::Mismatch(mismatch)) => assert_eq!(
+            mismatch,
+            Mismatch {
+                line: 13,
+                existing_line: Some("    internal static extern ushort GetCount();".to_string()),
+                generated_line: Some("    internal static extern uint GetCount();".to_string()),
+                diff: "--- existing
++++ generated
+@@ -10,6 +10,6 @@
+ 
+     /// <returns>u32</returns>
+     [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"get_count\")]
+-    internal static extern ushort GetCount();
++    internal static extern uint GetCount();
+ 
+ }
+"
+                .to_string(),
+            }
+        ),
+        result => panic!("Expected a mismatch, but got {:?}", result),
+    }
+}
+
+#[test]
+fn verify_against_ignores_timestamp_header() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_generated_warning("Generated at {timestamp}");
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[no_mangle]
+            pub extern "C" fn tick() {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("Bar");
+    builder.set_timestamp("2024-01-31T12:00:00Z");
+    let script = builder.build().unwrap();
+    builder.set_timestamp("2024-02-01T12:00:00Z");
+    builder.verify_against(&script).unwrap();
+
+    let existing = script.replace("void Tick()", "void Tock()");
+    match builder.verify_against(&existing) {
+        Err(VerifyError::Mismatch(mismatch)) => {
+            assert_eq!(mismatch.line, 9);
+            assert_eq!(
+                mismatch.existing_line.as_deref(),
+                Some("    internal static extern void Tock();")
+            );
+        }
+        result => panic!("Expected a mismatch, but got {:?}", result),
+    }
+}
+
+#[test]
+fn verify_against_build_error() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[no_mangle]
+            pub extern "C" fn get() -> Unknown {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("Bar");
+    assert!(matches!(
+        builder.verify_against(""),
+        Err(VerifyError::Error(Error::UnknownType(..)))
+    ));
+}
+
+#[test]
+fn export_and_import_known_types() {
+    let configuration = CSharpConfiguration::new(9);
+    configuration
+        .add_known_type(
+            "Vec3",
+            Some("Math".to_string()),
+            None,
+            "Vector3".to_string(),
+        )
+        .unwrap();
+    configuration
+        .add_known_type(
+            "Handle",
+            None,
+            Some("Native".to_string()),
+            "IntPtr".to_string(),
+        )
+        .unwrap();
+    let exported = configuration.export_known_types();
+    assert_eq!(
+        exported,
+        "csharp_binder known types 1
+Handle\t\tNative\tIntPtr
+Vec3\tMath\t\tVector3
+"
+    );
+
+    let imported = CSharpConfiguration::new(9);
+    imported.import_known_types(&exported).unwrap();
+    assert_eq!(imported.export_known_types(), exported);
+    // Importing the same types again is not a conflict.
+    imported.import_known_types(&exported).unwrap();
+}
+
+#[test]
+fn import_known_types_from_other_crate() {
+    let crate_a = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Point {
+                x: i32,
+            }
+        "#,
+        "crate_a",
+        &crate_a,
+    )
+    .unwrap();
+    builder.set_namespace("CrateA");
+    builder.set_type("A");
+    builder.build().unwrap();
+    let exported = crate_a.export_known_types();
+
+    let crate_b = CSharpConfiguration::new(9);
+    crate_b.import_known_types(&exported).unwrap();
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[no_mangle]
+            pub extern "C" fn draw(point: Point) {}
+        "#,
+        "crate_b",
+        &crate_b,
+    )
+    .unwrap();
+    builder.set_namespace("CrateB");
+    builder.set_type("B");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace CrateB
+{
+    internal static class B
+    {
+        /// <param name=\"point\">Point</param>
+        /// <returns>void</returns>
+        [DllImport(\"crate_b\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"draw\")]
+        internal static extern void Draw(CrateA.A.Point point);
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn import_known_types_conflict() {
+    let configuration = CSharpConfiguration::new(9);
+    configuration
+        .add_known_type(
+            "Vec3",
+            Some("Math".to_string()),
+            None,
+            "Vector3".to_string(),
+        )
+        .unwrap();
+    let other = CSharpConfiguration::new(9);
+    other
+        .add_known_type("Vec3", None, None, "Vector3".to_string())
+        .unwrap();
+    other
+        .add_known_type("Vec2", None, None, "Vector2".to_string())
+        .unwrap();
+    assert!(matches!(
+        configuration.import_known_types(&other.export_known_types()),
+        Err(Error::DuplicateSymbol(name, _)) if name == "Vec3"
+    ));
+    assert!(!configuration.export_known_types().contains("Vec2"));
+    assert!(matches!(
+        configuration.import_known_types("Vec2\tVector2"),
+        Err(Error::IOError(_))
+    ));
+}
+
+#[test]
+fn manage_known_types() {
+    let configuration = CSharpConfiguration::new(9);
+    let add = |namespace: Option<&str>, name: &str| {
+        configuration.add_known_type(
+            "Vec3",
+            namespace.map(str::to_string),
+            None,
+            name.to_string(),
+        )
+    };
+    assert_eq!(
+        add(Some("Math"), "Vector3").unwrap(),
+        KnownTypeUpdate::Inserted
+    );
+    assert_eq!(
+        add(Some("Math"), "Vector3").unwrap(),
+        KnownTypeUpdate::ReplacedIdentical
+    );
+    assert_eq!(
+        add(None, "Vector3").unwrap(),
+        KnownTypeUpdate::ReplacedDifferent
+    );
+    configuration
+        .add_known_type(
+            "Handle",
+            None,
+            Some("Native".to_string()),
+            "IntPtr".to_string(),
+        )
+        .unwrap();
+
+    assert_eq!(
+        configuration.known_type_names().collect::<Vec<_>>(),
+        vec!["Handle", "Vec3"]
+    );
+    assert_eq!(
+        configuration.get_known_type_info("Handle"),
+        Some((None, Some("Native".to_string()), "IntPtr".to_string()))
+    );
+    assert!(configuration.remove_known_type("Handle"));
+    assert!(!configuration.remove_known_type("Handle"));
+    assert_eq!(configuration.get_known_type_info("Handle"), None);
+}
+
+#[test]
+fn deny_known_type_conflicts() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.deny_type_conflicts(true);
+    configuration
+        .add_known_type(
+            "Vec3",
+            Some("Math".to_string()),
+            None,
+            "Vector3".to_string(),
+        )
+        .unwrap();
+    assert_eq!(
+        configuration
+            .add_known_type(
+                "Vec3",
+                Some("Math".to_string()),
+                None,
+                "Vector3".to_string()
+            )
+            .unwrap(),
+        KnownTypeUpdate::ReplacedIdentical
+    );
+    assert!(matches!(
+        configuration.add_known_type("Vec3", Some("Physics".to_string()), None, "Vector3".to_string()),
+        Err(Error::DuplicateSymbol(name, _)) if name == "Vec3"
+    ));
+    assert_eq!(
+        configuration.get_known_type_info("Vec3"),
+        Some((Some("Math".to_string()), None, "Vector3".to_string()))
+    );
+}
+
+#[test]
+fn add_known_types_in_bulk() {
+    let configuration = CSharpConfiguration::new(9);
+    configuration
+        .add_known_types(vec![
+            KnownTypeEntry {
+                rust_name: "FfiSlice".to_string(),
+                namespace: Some("Interop".to_string()),
+                inside_type: None,
+                csharp_name: "FfiSlice".to_string(),
+            },
+            KnownTypeEntry {
+                rust_name: "RustString".to_string(),
+                namespace: Some("Interop".to_string()),
+                inside_type: Some("Strings".to_string()),
+                csharp_name: "RustString".to_string(),
+            },
+        ])
+        .unwrap();
+    assert_eq!(
+        configuration.known_type_names().collect::<Vec<_>>(),
+        vec!["FfiSlice", "RustString"]
+    );
+    assert_eq!(
+        configuration.get_known_type_info("RustString"),
+        Some((
+            Some("Interop".to_string()),
+            Some("Strings".to_string()),
+            "RustString".to_string()
+        ))
+    );
+}
+
+#[test]
+fn build_with_dotnet_interop_defaults() {
+    let configuration = CSharpConfiguration::new(9);
+    configuration.register_dotnet_interop_defaults().unwrap();
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[no_mangle]
+            pub extern "system" fn window_proc(
+                window: HWND,
+                message: u32,
+                w_param: WPARAM,
+                l_param: LPARAM,
+                state: GCHandle,
+                data: *mut c_void,
+            ) -> LRESULT {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("Bar");
+    assert_eq!(builder.build().unwrap(), "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+internal static class Bar
+{
+    /// <param name=\"window\">HWND</param>
+    /// <param name=\"message\">u32</param>
+    /// <param name=\"wParam\">WPARAM</param>
+    /// <param name=\"lParam\">LPARAM</param>
+    /// <param name=\"state\">GCHandle</param>
+    /// <param name=\"data\">*mut c_void</param>
+    /// <returns>LRESULT</returns>
+    [DllImport(\"foo\", CallingConvention = CallingConvention.Winapi, EntryPoint=\"window_proc\")]
+    internal static extern IntPtr WindowProc(IntPtr window, uint message, UIntPtr wParam, IntPtr lParam, IntPtr state, IntPtr data);
+
+}
+");
+}
+
+#[test]
+fn build_multiple_out_types() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_out_type("Out");
+    configuration.add_out_type("OutPtr");
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[no_mangle]
+            pub extern "C" fn get_size(width: Out<u32>, height: OutPtr<u32>) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("Bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+internal static class Bar
+{
+    /// <param name=\"width\">Out&lt;u32&gt;</param>
+    /// <param name=\"height\">OutPtr&lt;u32&gt;</param>
+    /// <returns>void</returns>
+    [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"get_size\")]
+    internal static extern void GetSize(out uint width, out uint height);
+
+}
+"
+    );
+}
+
+#[test]
+fn build_out_parameters_by_name() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.treat_mut_pointer_params_named_as_out(&["out_", "result_"]);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Size {
+                width: u32,
+            }
+
+            #[no_mangle]
+            pub extern "C" fn measure(
+                input: *mut u32,
+                out_width: *mut u32,
+                result_scale: &mut f32,
+                out_size: *mut Size,
+                out_count: *const u32,
+            ) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("Bar");
+    assert_eq!(builder.build().unwrap(), "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+internal static class Bar
+{
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    public struct Size
+    {
+        /// <remarks>u32</remarks>
+        public uint Width { get; init; }
+
+        public Size(uint width)
+        {
+            Width = width;
+        }
+    }
+
+    /// <param name=\"input\">*mut u32</param>
+    /// <param name=\"outWidth\">*mut u32</param>
+    /// <param name=\"resultScale\">&amp;mut f32</param>
+    /// <param name=\"outSize\">*mut Size</param>
+    /// <param name=\"outCount\">*const u32</param>
+    /// <returns>void</returns>
+    [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"measure\")]
+    internal static extern void Measure(IntPtr input, out uint outWidth, out float resultScale, IntPtr outSize, IntPtr outCount);
+
+}
+");
+}
+
+#[test]
+fn build_out_parameter_types() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_out_type("Out");
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Point {
+                x: i32,
+            }
+
+            #[repr(C)]
+            pub struct TestStruct<T> {
+                value: T,
+            }
+
+            #[repr(u8)]
+            pub enum Status {
+                Ok,
+            }
+
+            #[no_mangle]
+            pub extern "C" fn get_values(
+                point: Out<Point>,
+                status: Out<Status>,
+                points: Out<*mut Point>,
+                test: Out<TestStruct<u16>>,
+            ) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_type("Bar");
+    assert_eq!(builder.build().unwrap(), "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+internal static class Bar
+{
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    public struct Point
+    {
+        /// <remarks>i32</remarks>
+        public int X { get; init; }
+
+        public Point(int x)
+        {
+            X = x;
+        }
+    }
+
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    public struct TestStruct<T>
+        where T : unmanaged
+    {
+        /// <remarks>T</remarks>
+        public T Value { get; init; }
+
+        public TestStruct(T value)
+        {
+            Value = value;
+        }
+    }
+
+    public enum Status : byte
+    {
+        Ok,
+    }
+
+    /// <param name=\"point\">Out&lt;Point&gt;</param>
+    /// <param name=\"status\">Out&lt;Status&gt;</param>
+    /// <param name=\"points\">Out&lt;*mut Point&gt;</param>
+    /// <param name=\"test\">Out&lt;TestStruct&lt;u16&gt;&gt;</param>
+    /// <returns>void</returns>
+    [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"get_values\")]
+    internal static extern void GetValues(out Point point, out Status status, out IntPtr points, out TestStruct<ushort> test);
+
+}
+");
+}
+
+#[test]
+fn build_unknown_type_error_context() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+pub extern "C" fn engine_start(cfg: Missing) -> Other {}
+#[repr(C)]
+pub struct Engine {
+    speed: Absent,
+}
+"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let errors = builder.build_collecting_errors().unwrap_err();
+    let messages = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        vec![
+            "Type with name 'Other' was not found, in the return type of function 'engine_start'. At line 2, position 48",
+            "Type with name 'Missing' was not found, in parameter 'cfg' of function 'engine_start'. At line 2, position 36",
+            "Type with name 'Absent' was not found, in field 'speed' of struct 'Engine'. At line 5, position 11",
+        ]
+    );
+}
+
+#[test]
+fn build_unknown_type_suggestions() {
+    let configuration = CSharpConfiguration::new(9);
+    configuration
+        .add_known_type("EngineConfig", None, None, "EngineConfig".to_string())
+        .unwrap();
+    let mut builder = CSharpBuilder::new(
+        r#"
+pub extern "C" fn engine_start(cfg: EngineConfg, count: bol, other: Unrelated) {}
+"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let errors = builder.build_collecting_errors().unwrap_err();
+    let messages = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        vec![
+            "Type with name 'EngineConfg' was not found (did you mean 'EngineConfig'?), in parameter 'cfg' of function 'engine_start'. At line 2, position 36",
+            "Type with name 'bol' was not found (did you mean 'bool'?), in parameter 'count' of function 'engine_start'. At line 2, position 56",
+            "Type with name 'Unrelated' was not found, in parameter 'other' of function 'engine_start'. At line 2, position 68",
+        ]
+    );
+}
+
+#[test]
+fn build_errors_with_source_name() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+pub extern "C" fn play(sound: Sound) {}
+pub extern "C" fn stop() {}
+#[no_mangle]
+pub extern "C" fn stop() {}
+"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    builder.set_source_name("ffi/audio.rs");
+    let errors = builder.build_collecting_errors().unwrap_err();
+    let messages = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        vec![
+            "Type with name 'Sound' was not found, in parameter 'sound' of function 'play'. At ffi/audio.rs: line 2, column 30",
+            "The name 'Stop' is already used. At ffi/audio.rs: line 5, column 18",
+        ]
+    );
+    assert!(matches!(errors[0].without_source(), Error::UnknownType(..)));
+}
+
+#[test]
+fn build_errors_without_source_name() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+pub extern "C" fn play(sound: Sound) {}
+"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let error = builder.build().unwrap_err();
+    assert!(matches!(error, Error::UnknownType(..)));
+    assert!(matches!(error.without_source(), Error::UnknownType(..)));
+    assert_eq!(
+        error.to_string(),
+        "Type with name 'Sound' was not found, in parameter 'sound' of function 'play'. At line 2, position 30"
+    );
+}
+
+#[test]
+fn failed_build_leaves_known_types_unchanged() {
+    let configuration = CSharpConfiguration::new(9);
+    configuration
+        .add_known_type("Existing", None, None, "Existing".to_string())
+        .unwrap();
+    let before = configuration.export_known_types();
+    let source = r#"
+#[repr(u8)]
+pub enum Mode {
+    A,
+}
+#[repr(C)]
+pub struct Settings {
+    mode: Mode,
+}
+pub extern "C" fn apply(settings: Settings, other: Missing) {}
+"#;
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert!(builder.build().is_err());
+    assert!(builder.build_collecting_errors().is_err());
+    assert!(builder.build_split().is_err());
+    assert_eq!(configuration.export_known_types(), before);
+
+    // Another builder doesn't resolve types from the failed build.
+    let mut other = CSharpBuilder::new(
+        r#"pub extern "C" fn get_mode() -> Mode {}"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    other.set_type("baz");
+    assert!(matches!(other.build(), Err(Error::UnknownType(..))));
+
+    // A build that succeeds after fixing the source registers its types.
+    let mut fixed = CSharpBuilder::new(
+        &source.replace("other: Missing", "other: u8"),
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    fixed.set_namespace("foo");
+    fixed.set_type("bar");
+    fixed.build().unwrap();
+    assert_eq!(
+        configuration.known_type_names().collect::<Vec<_>>(),
+        vec!["Existing", "Mode", "Settings"]
+    );
+    other.build().unwrap();
+}
+
+#[test]
+fn build_grouped_ordering() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_output_ordering(Ordering::Grouped);
+    let mut builder = CSharpBuilder::new(
+        r#"
+#[no_mangle]
+pub extern "C" fn stop(mixer: Mixer) {}
+#[no_mangle]
+pub extern "C" fn play(sound: Sound) {}
+#[repr(C)]
+pub struct Sound {
+    volume: Volume,
+}
+#[repr(u8)]
+pub enum Volume {
+    Low,
+}
+#[repr(C)]
+pub struct Mixer {
+    id: u32,
+}
+#[repr(u8)]
+pub enum Channel {
+    Left,
+}
+pub const MAX_SOUNDS: u32 = 16;
+mod effects {
+    #[no_mangle]
+    pub extern "C" fn echo() {}
+}
+"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.register_types_only().unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        public enum Channel : byte
+        {
+            Left,
+        }
+
+        public enum Volume : byte
+        {
+            Low,
+        }
+
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Mixer
+        {
+            /// <remarks>u32</remarks>
+            public uint Id { get; init; }
+
+            public Mixer(uint id)
+            {
+                Id = id;
+            }
+        }
+
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Sound
+        {
+            /// <remarks>Volume</remarks>
+            public Volume Volume { get; init; }
+
+            public Sound(Volume volume)
+            {
+                Volume = volume;
+            }
+        }
+
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"echo\")]
+        internal static extern void Echo();
+
+        /// <param name=\"sound\">Sound</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"play\")]
+        internal static extern void Play(Sound sound);
+
+        /// <param name=\"mixer\">Mixer</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"stop\")]
+        internal static extern void Stop(Mixer mixer);
+
+        public const uint MaxSounds = 16;
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_grouped_ordering_with_regions() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_output_ordering(Ordering::Grouped);
+    configuration.emit_regions(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+#[no_mangle]
+pub extern "C" fn play(volume: Volume) {}
+#[repr(u8)]
+pub enum Volume {
+    Low,
+}
+"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.register_types_only().unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        #region Enums
+
+        public enum Volume : byte
+        {
+            Low,
+        }
+
+        #endregion
+
+        #region Functions
+
+        /// <param name=\"volume\">Volume</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"play\")]
+        internal static extern void Play(Volume volume);
+
+        #endregion
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_parenthesized_types() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+#[repr(C)]
+pub struct Sample {
+    value: (u32),
+    data: *const (u8),
+}
+#[no_mangle]
+pub extern "C" fn update(sample: (&mut Sample), count: (u16)) -> (i32) {}
+"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Sample
+        {
+            /// <remarks>u32</remarks>
+            public uint Value { get; init; }
+            /// <remarks>*const u8</remarks>
+            public IntPtr Data { get; init; }
+
+            public Sample(uint value, IntPtr data)
+            {
+                Value = value;
+                Data = data;
+            }
+        }
+
+        /// <param name=\"sample\">&amp;mut Sample</param>
+        /// <param name=\"count\">u16</param>
+        /// <returns>i32</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"update\")]
+        internal static extern int Update(ref Sample sample, ushort count);
+
+    }
+}
+"
+    );
+}
+
+/// Replaces ``group(...)`` in a script with an invisible group, like the ones left by macro
+/// expansion.
+fn replace_invisible_groups(tokens: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    use proc_macro2::{Delimiter, Group, TokenTree};
+    let mut output = Vec::new();
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Ident(ident) if ident == "group" => match tokens.next() {
+                Some(TokenTree::Group(group)) => output.push(TokenTree::Group(Group::new(
+                    Delimiter::None,
+                    replace_invisible_groups(group.stream()),
+                ))),
+                _ => panic!("Expected the contents of the group"),
+            },
+            TokenTree::Group(group) => output.push(TokenTree::Group(Group::new(
+                group.delimiter(),
+                replace_invisible_groups(group.stream()),
+            ))),
+            token => output.push(token),
+        }
+    }
+    output.into_iter().collect()
+}
+
+#[test]
+fn build_invisible_group_types() {
+    let configuration = CSharpConfiguration::new(9);
+    let tokens: proc_macro2::TokenStream = r#"
+#[repr(C)]
+pub struct Sample {
+    value: group(u32),
+    data: *const group(u8),
+}
+#[no_mangle]
+pub extern "C" fn update(sample: group(*mut Sample), count: group(u16)) -> group(i32) {}
+"#
+    .parse()
+    .unwrap();
+    let mut builder = CSharpBuilder::new("", "foo", &configuration).unwrap();
+    builder.tokens = syn::parse2(replace_invisible_groups(tokens)).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Sample
+        {
+            /// <remarks>u32</remarks>
+            public uint Value { get; init; }
+            /// <remarks>*const u8</remarks>
+            public IntPtr Data { get; init; }
+
+            public Sample(uint value, IntPtr data)
+            {
+                Value = value;
+                Data = data;
+            }
+        }
+
+        /// <param name=\"sample\">*mut Sample</param>
+        /// <param name=\"count\">u16</param>
+        /// <returns>i32</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"update\")]
+        internal static extern int Update(IntPtr sample, ushort count);
+
+    }
+}
+"
+    );
+}
+
+#[cfg(feature = "expand")]
+#[test]
+fn build_from_expanded_crate() {
+    use crate::builder::{expand_crate, CommandOutput};
+    use std::cell::RefCell;
+    let commands = RefCell::new(Vec::new());
+    let script = expand_crate(std::path::Path::new("audio/Cargo.toml"), |command| {
+        let to_string = |s: &std::ffi::OsStr| s.to_string_lossy().to_string();
+        commands.borrow_mut().push((
+            to_string(command.get_program()),
+            command.get_args().map(to_string).collect::<Vec<_>>(),
+            command
+                .get_envs()
+                .map(|(key, value)| (to_string(key), value.map(to_string)))
+                .collect::<Vec<_>>(),
+        ));
+        Ok(match commands.borrow().len() {
+            1 => CommandOutput {
+                success: false,
+                stdout: String::new(),
+                stderr: "error: no such command: `expand`".to_string(),
+            },
+            _ => CommandOutput {
+                success: true,
+                stdout: r#"#![feature(prelude_import)]
+#[prelude_import]
+use std::prelude::rust_2018::*;
+#[macro_use]
+extern crate std;
+#[no_mangle]
+pub extern "C" fn play() {}
+"#
+                .to_string(),
+                stderr: String::new(),
+            },
+        })
+    })
+    .unwrap();
+    assert_eq!(
+        commands.into_inner(),
+        vec![
+            (
+                "cargo".to_string(),
+                vec!["expand", "--lib", "--manifest-path", "audio/Cargo.toml"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+                vec![],
+            ),
+            (
+                "cargo".to_string(),
+                vec![
+                    "rustc",
+                    "--lib",
+                    "--manifest-path",
+                    "audio/Cargo.toml",
+                    "--",
+                    "-Zunpretty=expanded",
+                ]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+                vec![("RUSTC_BOOTSTRAP".to_string(), Some("1".to_string()))],
+            ),
+        ]
+    );
+    assert_eq!(script, "#[no_mangle]\npub extern \"C\" fn play() {}");
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(&script, "audio", &configuration).unwrap();
+    assert!(builder
+        .build()
+        .unwrap()
+        .contains("internal static extern void Play();"));
+}
+
+#[cfg(feature = "expand")]
+#[test]
+fn build_from_expanded_crate_failure() {
+    use crate::builder::{expand_crate, CommandOutput};
+    let result = expand_crate(std::path::Path::new("audio/Cargo.toml"), |_| {
+        Ok(CommandOutput {
+            success: false,
+            stdout: String::new(),
+            stderr: "error: could not find `Cargo.toml`\n".to_string(),
+        })
+    });
+    let error = result.unwrap_err();
+    assert!(
+        matches!(&error, Error::ExpansionError(e) if e == "error: could not find `Cargo.toml`")
+    );
+    assert_eq!(
+        error.to_string(),
+        "Failed to expand the crate: error: could not find `Cargo.toml`"
+    );
+}
+
+#[cfg(feature = "expand")]
+#[test]
+fn build_from_expanded_crate_without_cargo() {
+    use crate::builder::expand_crate;
+    let result = expand_crate(std::path::Path::new("audio/Cargo.toml"), |_| {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "program not found",
+        ))
+    });
+    assert!(matches!(
+        result.unwrap_err(),
+        Error::ExpansionError(e) if e == "Failed to run cargo: program not found"
+    ));
+}
+
+#[test]
+fn build_variadic_function_unsupported() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+#[no_mangle]
+pub unsafe extern "C" fn log(fmt: *const c_char, ...) {}
+"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let error = builder.build().unwrap_err();
+    assert_eq!(error.to_string(), "Variadic function 'log' is not supported, as C# can only call it with __arglist, or through native shims with a fixed number of arguments. __arglist can be enabled with allow_arglist_variadics. At line 3, position 49");
+}
+
+#[test]
+fn build_variadic_function_with_arglist() {
+    let mut configuration = CSharpConfiguration::new(11);
+    configuration.allow_arglist_variadics(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+#[no_mangle]
+pub unsafe extern "C" fn log(level: u8, fmt: *const c_char, ...) -> i32 {}
+#[no_mangle]
+pub unsafe extern "C" fn trace(...) {}
+#[no_mangle]
+pub unsafe extern "stdcall" fn log_stdcall(fmt: *const c_char, ...) {}
+"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build_lenient().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+using System.Runtime.InteropServices.Marshalling;
+
+namespace foo;
+
+internal static partial class bar
+{
+    /// <param name=\"level\">u8</param>
+    /// <param name=\"fmt\">*const c_char</param>
+    /// <returns>i32</returns>
+    [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"log\")]
+    internal static extern int Log(byte level, IntPtr fmt, __arglist);
+
+    /// <returns>void</returns>
+    [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"trace\")]
+    internal static extern void Trace(__arglist);
+
+}
+"
+    );
+    assert_eq!(builder.warnings().len(), 1);
+    assert_eq!(builder.warnings()[0].item_name, "log_stdcall");
+}
+
+#[test]
+fn build_generic_function_unsupported() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+pub extern "C" fn get<T>(ptr: *const T) -> T {}
+"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let error = builder.build().unwrap_err();
+    assert_eq!(error.to_string(), "Generic function 'get' can't be imported, as only concrete instantiations of it can be exported. Export a function for each type it is used with, and register them with monomorphize_function. At line 2, position 21");
+}
+
+#[test]
+fn build_monomorphized_functions() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.monomorphize_function("get", &[("T", "u8"), ("T", "Foo")]);
+    configuration.monomorphize_function("convert", &[("A", "u8"), ("B", "u16")]);
+    configuration.set_monomorphized_entry_point("convert", "convert_{A}_to_{B}");
+    let mut builder = CSharpBuilder::new(
+        r#"
+#[repr(C)]
+pub struct Foo {
+    value: u32,
+}
+/// Gets a value.
+/// # Arguments
+/// * `ptr` - The pointer to read from.
+pub extern "C" fn get<'a, T>(ptr: &'a T, count: u8) -> T {}
+pub extern "C" fn convert<A, B>(value: A) -> B where A: Copy {}
+"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(script, "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Foo
+        {
+            /// <remarks>u32</remarks>
+            public uint Value { get; init; }
+
+            public Foo(uint value)
+            {
+                Value = value;
+            }
+        }
+
+        /// <summary>
+        /// Gets a value.
+        /// </summary>
+        /// <param name=\"ptr\">The pointer to read from. (&amp;u8)</param>
+        /// <param name=\"count\">u8</param>
+        /// <returns>u8</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"get_u8\")]
+        internal static extern byte Get(in byte ptr, byte count);
+
+        /// <summary>
+        /// Gets a value.
+        /// </summary>
+        /// <param name=\"ptr\">The pointer to read from. (&amp;Foo)</param>
+        /// <param name=\"count\">u8</param>
+        /// <returns>Foo</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"get_Foo\")]
+        internal static extern Foo Get(in Foo ptr, byte count);
+
+        /// <param name=\"value\">u8</param>
+        /// <returns>u16</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"convert_u8_to_u16\")]
+        internal static extern ushort Convert(byte value);
+
+    }
+}
+");
+}
+
+#[test]
+fn build_struct_constructor_modes() {
+    let source = r#"
+#[repr(C)]
+pub struct Marker {}
+#[repr(C)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Marker
+        {
+        }
+
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Point
+        {
+            /// <remarks>i32</remarks>
+            public int X { get; init; }
+            /// <remarks>i32</remarks>
+            public int Y { get; init; }
+
+            public Point(int x, int y)
+            {
+                X = x;
+                Y = y;
+            }
+        }
+
+    }
+}
+"
+    );
+
+    configuration.set_struct_constructor(ConstructorMode::Never);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Marker
+        {
+        }
+
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Point
+        {
+            /// <remarks>i32</remarks>
+            public int X { get; init; }
+            /// <remarks>i32</remarks>
+            public int Y { get; init; }
+        }
+
+    }
+}
+"
+    );
+
+    configuration.set_struct_constructor(ConstructorMode::Always);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Marker
+        {
+
+            public Marker()
+            {
+            }
+        }
+
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Point
+        {
+            /// <remarks>i32</remarks>
+            public int X { get; init; }
+            /// <remarks>i32</remarks>
+            public int Y { get; init; }
+
+            public Point(int x, int y)
+            {
+                X = x;
+                Y = y;
+            }
+        }
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_struct_constructor_default_parameters() {
+    let mut configuration = CSharpConfiguration::new(10);
+    configuration.emit_default_constructor_parameters(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+#[repr(C)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo;
+
+internal static class bar
+{
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    public struct Point
+    {
+        /// <remarks>i32</remarks>
+        public int X { get; init; }
+        /// <remarks>i32</remarks>
+        public int Y { get; init; }
+
+        public Point(int x = default, int y = default)
+        {
+            X = x;
+            Y = y;
+        }
+    }
+
+}
+"
+    );
+}
+
+#[test]
+fn build_struct_equality() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.generate_struct_equality(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+#[repr(C)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+#[repr(C)]
+pub struct Sample {
+    position: Point,
+    data: *const u8,
+    a: u8,
+    b: u8,
+    c: u8,
+    d: u8,
+    e: u8,
+    f: u8,
+    g: f32,
+}
+#[repr(C)]
+pub struct Marker {}
+"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(script, "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Point : IEquatable<Point>
+        {
+            /// <remarks>i32</remarks>
+            public int X { get; init; }
+            /// <remarks>i32</remarks>
+            public int Y { get; init; }
+
+            public Point(int x, int y)
+            {
+                X = x;
+                Y = y;
+            }
+
+            public bool Equals(Point other)
+            {
+                return X.Equals(other.X)
+                    && Y.Equals(other.Y);
+            }
+
+            public override bool Equals(object obj)
+            {
+                return obj is Point other && Equals(other);
+            }
+
+            public override int GetHashCode()
+            {
+                return HashCode.Combine(X, Y);
+            }
+
+            public static bool operator ==(Point left, Point right)
+            {
+                return left.Equals(right);
+            }
+
+            public static bool operator !=(Point left, Point right)
+            {
+                return !left.Equals(right);
+            }
+        }
+
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Sample : IEquatable<Sample>
+        {
+            /// <remarks>Point</remarks>
+            public Point Position { get; init; }
+            /// <remarks>*const u8</remarks>
+            public IntPtr Data { get; init; }
+            /// <remarks>u8</remarks>
+            public byte A { get; init; }
+            /// <remarks>u8</remarks>
+            public byte B { get; init; }
+            /// <remarks>u8</remarks>
+            public byte C { get; init; }
+            /// <remarks>u8</remarks>
+            public byte D { get; init; }
+            /// <remarks>u8</remarks>
+            public byte E { get; init; }
+            /// <remarks>u8</remarks>
+            public byte F { get; init; }
+            /// <remarks>f32</remarks>
+            public float G { get; init; }
+
+            public Sample(Point position, IntPtr data, byte a, byte b, byte c, byte d, byte e, byte f, float g)
+            {
+                Position = position;
+                Data = data;
+                A = a;
+                B = b;
+                C = c;
+                D = d;
+                E = e;
+                F = f;
+                G = g;
+            }
+
+            public bool Equals(Sample other)
+            {
+                return Position.Equals(other.Position)
+                    && Data == other.Data
+                    && A.Equals(other.A)
+                    && B.Equals(other.B)
+                    && C.Equals(other.C)
+                    && D.Equals(other.D)
+                    && E.Equals(other.E)
+                    && F.Equals(other.F)
+                    && G.Equals(other.G);
+            }
+
+            public override bool Equals(object obj)
+            {
+                return obj is Sample other && Equals(other);
+            }
+
+            public override int GetHashCode()
+            {
+                return HashCode.Combine(HashCode.Combine(Position, Data, A, B, C, D, E, F), HashCode.Combine(G));
+            }
+
+            public static bool operator ==(Sample left, Sample right)
+            {
+                return left.Equals(right);
+            }
+
+            public static bool operator !=(Sample left, Sample right)
+            {
+                return !left.Equals(right);
+            }
+        }
+
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Marker : IEquatable<Marker>
+        {
+            public bool Equals(Marker other)
+            {
+                return true;
+            }
+
+            public override bool Equals(object obj)
+            {
+                return obj is Marker other && Equals(other);
+            }
+
+            public override int GetHashCode()
+            {
+                return 0;
+            }
+
+            public static bool operator ==(Marker left, Marker right)
+            {
+                return left.Equals(right);
+            }
+
+            public static bool operator !=(Marker left, Marker right)
+            {
+                return !left.Equals(right);
+            }
+        }
+
+    }
+}
+");
+}
+
+#[test]
+fn build_generic_struct_equality() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.generate_struct_equality(true);
+    configuration.use_unsafe_pointers(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+#[repr(C)]
+pub struct Pair<T> {
+    value: T,
+    next: *mut u8,
+}
+"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(script, "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static unsafe class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Pair<T> : IEquatable<Pair<T>>
+            where T : unmanaged
+        {
+            /// <remarks>T</remarks>
+            public T Value { get; init; }
+            /// <remarks>*mut u8</remarks>
+            public byte* Next { get; init; }
+
+            public Pair(T value, byte* next)
+            {
+                Value = value;
+                Next = next;
+            }
+
+            public bool Equals(Pair<T> other)
+            {
+                return System.Collections.Generic.EqualityComparer<T>.Default.Equals(Value, other.Value)
+                    && Next == other.Next;
+            }
+
+            public override bool Equals(object obj)
+            {
+                return obj is Pair<T> other && Equals(other);
+            }
+
+            public override int GetHashCode()
+            {
+                return HashCode.Combine(Value, (IntPtr)Next);
+            }
+
+            public static bool operator ==(Pair<T> left, Pair<T> right)
+            {
+                return left.Equals(right);
+            }
+
+            public static bool operator !=(Pair<T> left, Pair<T> right)
+            {
+                return !left.Equals(right);
+            }
+        }
+
+    }
+}
+");
+}
+
+#[test]
+fn build_readonly_struct_style() {
+    let source = r#"
+#[repr(C)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_struct_style(StructStyle::ReadonlyStruct);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public readonly struct Point
+        {
+            /// <remarks>i32</remarks>
+            public int X { get; init; }
+            /// <remarks>i32</remarks>
+            public int Y { get; init; }
+
+            public Point(int x, int y)
+            {
+                X = x;
+                Y = y;
+            }
+        }
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_record_struct_style() {
+    let source = r#"
+/// A point.
+#[repr(C)]
+pub struct Point {
+    /// The horizontal position.
+    x: u16,
+    y: f64,
+    visible: bool,
+}
+#[repr(C)]
+pub struct Wrapper<T> {
+    value: T,
+}
+#[repr(C)]
+pub struct Marker {}
+#[repr(C)]
+pub struct Partial {
+    x: i32,
+    #[doc(hidden)]
+    reserved: i32,
+}
+"#;
+    let mut configuration = CSharpConfiguration::new(10);
+    configuration.set_struct_style(StructStyle::RecordStruct);
+    configuration.emit_unmanaged_constraints(true);
+    configuration.allow_bool_marshaling(true);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(builder.build().unwrap(), "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo;
+
+internal static class bar
+{
+    /// <summary>
+    /// A point.
+    /// </summary>
+    /// <param name=\"X\">The horizontal position. (u16)</param>
+    /// <param name=\"Y\">f64</param>
+    /// <param name=\"Visible\">bool</param>
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    public readonly record struct Point(ushort X, double Y, [field: MarshalAs(UnmanagedType.I1)] bool Visible);
+
+    /// <param name=\"Value\">T</param>
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    public readonly record struct Wrapper<T>(T Value)
+        where T : unmanaged;
+
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    public readonly record struct Marker;
+
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    public readonly struct Partial
+    {
+        /// <remarks>i32</remarks>
+        public int X { get; init; }
+        private int Reserved { get; init; }
+
+        public Partial(int x)
+        {
+            X = x;
+            Reserved = default;
+        }
+    }
+
+}
+");
+
+    // Older versions of C# don't support record structs.
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_struct_style(StructStyle::RecordStruct);
+    let mut builder = CSharpBuilder::new(
+        "#[repr(C)] pub struct Point { x: u16 }",
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public readonly struct Point
+        {
+            /// <remarks>u16</remarks>
+            public ushort X { get; init; }
+
+            public Point(ushort x)
+            {
+                X = x;
+            }
+        }
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_struct_to_string() {
+    let source = r#"
+#[repr(C)]
+pub struct Marker {}
+#[repr(C)]
+pub struct Buffer {
+    data: *mut u8,
+    handle: isize,
+    length: usize,
+    #[doc(hidden)]
+    reserved: u32,
+}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.use_unsafe_pointers(true);
+    configuration.generate_to_string(true);
+    configuration.set_struct_constructor(ConstructorMode::Never);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(builder.build().unwrap(), "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static unsafe class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Marker
+        {
+            public override string ToString()
+            {
+                return \"Marker { }\";
+            }
+        }
+
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Buffer
+        {
+            /// <remarks>*mut u8</remarks>
+            public byte* Data { get; init; }
+            /// <remarks>isize</remarks>
+            public nint Handle { get; init; }
+            /// <remarks>usize</remarks>
+            public nuint Length { get; init; }
+            private uint Reserved { get; init; }
+
+            public override string ToString()
+            {
+                return $\"Buffer {{ Data = 0x{(ulong)Data:X}, Handle = {Handle}, Length = {Length} }}\";
+            }
+        }
+
+    }
+}
+");
+}
+
+#[test]
+fn build_enum_to_string() {
+    let source = r#"
+#[repr(u8)]
+pub enum Access {
+    Read = 1,
+    Write = 2,
+}
+pub mod io {
+    #[repr(i32)]
+    pub enum Mode {
+        Sync,
+        Async,
+    }
+}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.generate_to_string(true);
+    configuration.nest_modules_as_classes(true);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        public enum Access : byte
+        {
+            Read = 1,
+            Write = 2,
+        }
+
+        internal static class Io
+        {
+            public enum Mode : int
+            {
+                Sync,
+                Async,
+            }
+
+        }
+
+        public static string ToFriendlyString(this Access value)
+        {
+            return $\"{value} ({(byte)value})\";
+        }
+
+        public static string ToFriendlyString(this Io.Mode value)
+        {
+            return $\"{value} ({(int)value})\";
+        }
+
+    }
+}
+"
+    );
+
+    // Without a type, the extension methods are written in a class next to the enum.
+    let mut builder = CSharpBuilder::new(
+        "#[repr(u8)] pub enum Permission { Read = 1, Write = 2 }",
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    public enum Permission : byte
+    {
+        Read = 1,
+        Write = 2,
+    }
+
+    public static class PermissionExtensions
+    {
+        public static string ToFriendlyString(this Permission value)
+        {
+            return $\"{value} ({(byte)value})\";
+        }
+    }
+
+}
+"
+    );
+}
+
+#[test]
+fn build_hidden_struct_fields() {
+    let source = r#"
+#[repr(C)]
+pub struct Device {
+    id: u32,
+    /// Only used by the library.
+    internal_state: u64,
+    flags: u8,
+    _reserved: [u8; 3],
+}
+"#;
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Device
+        {
+            /// <remarks>u32</remarks>
+            public uint Id { get; init; }
+            /// <summary>
+            /// Only used by the library.
+            /// </summary>
+            /// <remarks>u64</remarks>
+            public ulong InternalState { get; init; }
+            /// <remarks>u8</remarks>
+            public byte Flags { get; init; }
+            /// <remarks>[u8; 3]</remarks>
+            [field: MarshalAs(UnmanagedType.ByValArray, SizeConst = 3)]
+            public byte[] Reserved { get; init; }
+
+            /// <param name=\"id\">u32</param>
+            /// <param name=\"internalState\">Only used by the library.</param>
+            /// <param name=\"flags\">u8</param>
+            /// <param name=\"reserved\">[u8; 3]</param>
+            public Device(uint id, ulong internalState, byte flags, byte[] reserved)
+            {
+                Id = id;
+                InternalState = internalState;
+                Flags = flags;
+                Reserved = reserved;
+            }
+        }
+
+    }
+}
+"
+    );
+
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.hide_struct_field("Device", "internal_state");
+    configuration.hide_struct_field("Device", "_reserved");
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Device
+        {
+            /// <remarks>u32</remarks>
+            public uint Id { get; init; }
+            private ulong InternalState { get; init; }
+            /// <remarks>u8</remarks>
+            public byte Flags { get; init; }
+            [field: MarshalAs(UnmanagedType.ByValArray, SizeConst = 3)]
+            private byte[] Reserved { get; init; }
+
+            public Device(uint id, byte flags)
+            {
+                Id = id;
+                Flags = flags;
+                InternalState = default;
+                Reserved = default;
+            }
+        }
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_hidden_tuple_struct_fields() {
+    let mut configuration = CSharpConfiguration::new(8);
+    configuration.hide_struct_field("Pair", "1");
+    let mut builder = CSharpBuilder::new(
+        "#[repr(C)] pub struct Pair(u16, u16, u32);",
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Pair
+        {
+            /// <remarks>u16</remarks>
+            public readonly ushort Item0;
+            private readonly ushort Item1;
+            /// <remarks>u32</remarks>
+            public readonly uint Item2;
+
+            public Pair(ushort item0, uint item2)
+            {
+                Item0 = item0;
+                Item2 = item2;
+                Item1 = default;
+            }
+        }
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_self_referential_struct() {
+    let source = r#"
+#[repr(C)]
+pub struct Node {
+    value: i32,
+    next: *mut Node,
+}
+"#;
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Node
+        {
+            /// <remarks>i32</remarks>
+            public int Value { get; init; }
+            /// <remarks>*mut Node</remarks>
+            public IntPtr Next { get; init; }
+
+            public Node(int value, IntPtr next)
+            {
+                Value = value;
+                Next = next;
+            }
+        }
+
+    }
+}
+"
+    );
+
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.use_unsafe_pointers(true);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static unsafe class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Node
+        {
+            /// <remarks>i32</remarks>
+            public int Value { get; init; }
+            /// <remarks>*mut Node</remarks>
+            public Node* Next { get; init; }
+
+            public Node(int value, Node* next)
+            {
+                Value = value;
+                Next = next;
+            }
+        }
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_generic_pointer_struct() {
+    let source = r#"
+#[repr(C)]
+pub struct Slice<T> {
+    data: *mut T,
+    first: *const *const T,
+    len: usize,
+}
+"#;
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Slice<T>
+            where T : unmanaged
+        {
+            /// <remarks>*mut T</remarks>
+            public IntPtr Data { get; init; }
+            /// <remarks>*const *const T</remarks>
+            public IntPtr First { get; init; }
+            /// <remarks>usize</remarks>
+            public nuint Len { get; init; }
+
+            public Slice(IntPtr data, IntPtr first, nuint len)
+            {
+                Data = data;
+                First = first;
+                Len = len;
+            }
+        }
+
+    }
+}
+"
+    );
+
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.use_unsafe_pointers(true);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static unsafe class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Slice<T>
+            where T : unmanaged
+        {
+            /// <remarks>*mut T</remarks>
+            public T* Data { get; init; }
+            /// <remarks>*const *const T</remarks>
+            public T** First { get; init; }
+            /// <remarks>usize</remarks>
+            public nuint Len { get; init; }
+
+            public Slice(T* data, T** first, nuint len)
+            {
+                Data = data;
+                First = first;
+                Len = len;
+            }
+        }
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_non_blittable_warnings() {
+    let source = r#"
+#[repr(C)]
+pub struct Inner {
+    enabled: bool,
+    count: u32,
+}
+#[repr(C)]
+pub struct Outer {
+    inner: Inner,
+    name: [u16; 4],
+    id: u64,
+}
+#[repr(C)]
+pub struct Plain {
+    x: f32,
+}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.allow_bool_marshaling(true);
+    configuration.warn_non_blittable(true);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Inner
+        {
+            /// <remarks>
+            /// bool
+            /// WARNING: non-blittable, bool fields are converted when marshalled.
+            /// </remarks>
+            [field: MarshalAs(UnmanagedType.I1)]
+            public bool Enabled { get; init; }
+            /// <remarks>u32</remarks>
+            public uint Count { get; init; }
+
+            public Inner(bool enabled, uint count)
+            {
+                Enabled = enabled;
+                Count = count;
+            }
+        }
+
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Outer
+        {
+            /// <remarks>
+            /// Inner
+            /// WARNING: non-blittable, Inner contains non-blittable fields.
+            /// </remarks>
+            public Inner Inner { get; init; }
+            /// <remarks>
+            /// [u16; 4]
+            /// WARNING: non-blittable, arrays are copied when marshalled.
+            /// </remarks>
+            [field: MarshalAs(UnmanagedType.ByValArray, SizeConst = 4)]
+            public ushort[] Name { get; init; }
+            /// <remarks>u64</remarks>
+            public ulong Id { get; init; }
+
+            public Outer(Inner inner, ushort[] name, ulong id)
+            {
+                Inner = inner;
+                Name = name;
+                Id = id;
+            }
+        }
+
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Plain
+        {
+            /// <remarks>f32</remarks>
+            public float X { get; init; }
+
+            public Plain(float x)
+            {
+                X = x;
+            }
+        }
+
+    }
+}
+"
+    );
+    assert_eq!(
+        builder
+            .blittability_warnings()
+            .iter()
+            .map(|w| format!("{}.{}: {}", w.struct_name, w.field_name, w.reason))
+            .collect::<Vec<_>>(),
+        vec![
+            "Inner.enabled: bool fields are converted when marshalled",
+            "Outer.inner: Inner contains non-blittable fields",
+            "Outer.name: arrays are copied when marshalled",
+        ]
+    );
+}
+
+#[test]
+fn check_blittable() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.allow_bool_marshaling(true);
+    configuration.use_unsafe_pointers(true);
+    configuration.use_fixed_buffers(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+#[repr(C)]
+pub struct Flags(bool, [u8; 4]);
+"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    assert_eq!(
+        builder.check_blittable().unwrap(),
+        vec![BlittabilityWarning {
+            struct_name: "Flags".to_string(),
+            field_name: "0".to_string(),
+            reason: "bool fields are converted when marshalled".to_string(),
+        }]
+    );
+    // Without the configuration, the generated script isn't annotated.
+    assert!(!builder.build().unwrap().contains("WARNING"));
+}
+
+#[test]
+fn build_span_overloads() {
+    let source = r#"
+/// Writes data to the stream.
+/// # Arguments
+/// * `data` - The data to write.
+/// * `len` - The length of the data.
+#[no_mangle]
+pub extern "C" fn write_data(stream: u32, data: *const u8, len: usize) -> i32 {}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.mark_slice_params("write_data", "data", "len");
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [System.ComponentModel.EditorBrowsable(System.ComponentModel.EditorBrowsableState.Never)]
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"write_data\")]
+        internal static extern int WriteDataNative(uint stream, IntPtr data, nuint len);
+
+        /// <summary>
+        /// Writes data to the stream.
+        /// </summary>
+        /// <param name=\"stream\">u32</param>
+        /// <param name=\"data\">The data to write. (*const u8)</param>
+        /// <returns>i32</returns>
+        internal static unsafe int WriteData(uint stream, ReadOnlySpan<byte> data)
+        {
+            fixed (byte* dataPointer = data)
+            {
+                return WriteDataNative(stream, (IntPtr)dataPointer, (nuint)data.Length);
+            }
+        }
+
+    }
+}
+"
+    );
+
+    // Spans can only be pinned since C# 7.3.
+    let mut configuration = CSharpConfiguration::new(7);
+    configuration.mark_slice_params("write_data", "data", "len");
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert!(!builder.build().unwrap().contains("Span"));
+}
+
+#[test]
+fn build_detected_span_overloads() {
+    let source = r#"
+#[repr(C)]
+pub struct Point {
+    x: f32,
+    y: f32,
+}
+#[no_mangle]
+pub extern "C" fn fill_points(points: *mut Point, points_len: usize, value: f32) {}
+#[no_mangle]
+pub extern "C" fn free(data: *mut u8, size: usize) {}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.use_unsafe_pointers(true);
+    configuration.detect_slice_params(true);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(builder.build().unwrap(), "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static unsafe class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Point
+        {
+            /// <remarks>f32</remarks>
+            public float X { get; init; }
+            /// <remarks>f32</remarks>
+            public float Y { get; init; }
+
+            public Point(float x, float y)
+            {
+                X = x;
+                Y = y;
+            }
+        }
+
+        [System.ComponentModel.EditorBrowsable(System.ComponentModel.EditorBrowsableState.Never)]
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"fill_points\")]
+        internal static extern void FillPointsNative(Point* points, nuint pointsLen, float value);
+
+        /// <param name=\"points\">*mut Point</param>
+        /// <param name=\"value\">f32</param>
+        /// <returns>void</returns>
+        internal static void FillPoints(Span<Point> points, float value)
+        {
+            fixed (Point* pointsPointer = points)
+            {
+                FillPointsNative(pointsPointer, (nuint)points.Length, value);
+            }
+        }
+
+        /// <param name=\"data\">*mut u8</param>
+        /// <param name=\"size\">usize</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"free\")]
+        internal static extern void Free(byte* data, nuint size);
+
+    }
+}
+");
+}
+
+#[test]
+fn build_slice_params_not_a_pointer() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.mark_slice_params("write_data", "data", "len");
+    let mut builder = CSharpBuilder::new(
+        r#"#[no_mangle] pub extern "C" fn write_data(data: u64, len: usize) {}"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    assert_eq!(builder.build().unwrap_err().to_string(), "Parameter 'data' of function 'write_data' can't be passed as a slice, as it isn't a typed pointer. At line 1, position 48");
+}
+
+#[test]
+fn build_entry_points() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.monomorphize_function("get", &[("T", "u8"), ("T", "u16")]);
+    let mut builder = CSharpBuilder::new(
+        r#"
+#[no_mangle]
+pub extern "C" fn open() {}
+#[export_name = "lib_close"]
+pub extern "C" fn close() {}
+#[no_mangle]
+pub extern "C" fn get<T>() -> T {}
+"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    assert_eq!(
+        builder.entry_points().unwrap(),
+        vec!["open", "lib_close", "get_u8", "get_u16"]
+    );
+}
+
+#[test]
+fn verify_entry_points() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+#[no_mangle]
+pub extern "C" fn open() {}
+#[export_name = "close"]
+pub extern "C" fn close_v1() {}
+#[no_mangle]
+pub extern "C" fn close() {}
+#[no_mangle]
+pub extern "C" fn read() {}
+"#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    let exports = ["open", "close", "write"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    assert_eq!(
+        builder.verify_entry_points(&exports).unwrap(),
+        vec![
+            MissingSymbol::Duplicate("close".to_string()),
+            MissingSymbol::NotExported("read".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn build_optional_callback_parameters() {
+    let source = r#"
+#[no_mangle]
+pub extern "C" fn set_handler(cb: Option<extern "C" fn(u32)>) {}
+"#;
+    let configuration = CSharpConfiguration::new(7);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(builder.build().unwrap(), "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void SetHandlerCbDelegate(uint arg0);
+
+        /// <param name=\"cb\">Option&lt;fn(u32)&gt;, nullable</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"set_handler\")]
+        internal static extern void SetHandler(SetHandlerCbDelegate cb);
+
+    }
+}
+");
+
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.emit_nullable_annotations(true);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(builder.build().unwrap(), "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+#nullable enable annotations
+
+namespace foo
+{
+    internal static class bar
+    {
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void SetHandlerCbDelegate(uint arg0);
+
+        /// <param name=\"cb\">Option&lt;fn(u32)&gt;, nullable</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"set_handler\")]
+        internal static extern void SetHandler(SetHandlerCbDelegate? cb);
+
+    }
+}
+");
+}
+
+#[test]
+fn build_callback_struct_fields() {
+    let source = r#"
+#[repr(C)]
+pub struct Handlers {
+    /// Called for each event.
+    on_event: Option<extern "C" fn(u32) -> u8>,
+    on_close: extern "C" fn(),
+}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.emit_nullable_annotations(true);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+#nullable enable annotations
+
+namespace foo
+{
+    internal static class bar
+    {
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate byte HandlersOnEventDelegate(uint arg0);
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void HandlersOnCloseDelegate();
+
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Handlers
+        {
+            /// <summary>
+            /// Called for each event.
+            /// </summary>
+            /// <remarks>Option&lt;fn(u32) -&gt; u8&gt;, nullable</remarks>
+            [field: MarshalAs(UnmanagedType.FunctionPtr)]
+            public HandlersOnEventDelegate? OnEvent { get; init; }
+            /// <remarks>fn()</remarks>
+            [field: MarshalAs(UnmanagedType.FunctionPtr)]
+            public HandlersOnCloseDelegate OnClose { get; init; }
+
+            /// <param name=\"onEvent\">Called for each event.</param>
+            /// <param name=\"onClose\">fn()</param>
+            public Handlers(HandlersOnEventDelegate? onEvent, HandlersOnCloseDelegate onClose)
+            {
+                OnEvent = onEvent;
+                OnClose = onClose;
+            }
+        }
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_enum_helpers() {
+    let source = r#"
+/// The state of a connection.
+#[repr(u8)]
+#[non_exhaustive]
+pub enum State {
+    Idle,
+    Busy = 4,
+}
+pub mod io {
+    #[repr(u32)]
+    pub enum Access {
+        None = 0,
+        Read = 1,
+        Write = 2,
+        Execute = 4,
+    }
+}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.generate_enum_helpers(true);
+    configuration.generate_enum_try_from_raw(true);
+    configuration.nest_modules_as_classes(true);
+    configuration.detect_flags_enums(true);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(builder.build().unwrap(), "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        /// <summary>
+        /// The state of a connection.
+        /// </summary>
+        /// <remarks>
+        /// This enum is non-exhaustive, native code may return values that are not declared here. Use IsDefinedFast to check for these.
+        /// </remarks>
+        public enum State : byte
+        {
+            Idle,
+            Busy = 4,
+        }
+
+        internal static class Io
+        {
+            [Flags]
+            public enum Access : uint
+            {
+                None = 0,
+                Read = 1,
+                Write = 2,
+                Execute = 4,
+            }
+
+        }
+
+        public static bool IsDefinedFast(this State value)
+        {
+            switch (value)
+            {
+                case State.Idle:
+                case State.Busy:
+                    return true;
+                default:
+                    return false;
+            }
+        }
+
+        public static bool TryFromRaw(byte raw, out State value)
+        {
+            value = (State)raw;
+            return value.IsDefinedFast();
+        }
+
+        public static bool IsDefinedFast(this Io.Access value)
+        {
+            return (value & ~(Io.Access.None | Io.Access.Read | Io.Access.Write | Io.Access.Execute)) == 0;
+        }
+
+        public static bool TryFromRaw(uint raw, out Io.Access value)
+        {
+            value = (Io.Access)raw;
+            return value.IsDefinedFast();
+        }
+
+    }
+}
+");
+}
+
+#[test]
+fn build_enum_helpers_with_to_string() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.generate_to_string(true);
+    configuration.generate_enum_helpers(true);
+    let mut builder = CSharpBuilder::new(
+        "#[repr(i16)] pub enum Level { Low = -1, High = 1 }",
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    public enum Level : short
+    {
+        Low = -1,
+        High = 1,
+    }
+
+    public static class LevelExtensions
+    {
+        public static string ToFriendlyString(this Level value)
+        {
+            return $\"{value} ({(short)value})\";
+        }
+
+        public static bool IsDefinedFast(this Level value)
+        {
+            switch (value)
+            {
+                case Level.Low:
+                case Level.High:
+                    return true;
+                default:
+                    return false;
+            }
+        }
+    }
+
+}
+"
+    );
+}
+
+#[test]
+fn build_enum_discriminant_literals() {
+    let source = r#"
+#[repr(u32)]
+pub enum Mask {
+    Low = 0x0000_00FF,
+    High = 0x00FF_0000u32,
+    Bits = 0b1010_0000,
+    Octal = 0o17,
+    Large = 1_000_000,
+    Parenthesized = (8u32),
+}
+"#;
+    let configuration = CSharpConfiguration::new(7);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        public enum Mask : uint
+        {
+            Low = 0x0000_00FF,
+            High = 0x00FF_0000,
+            Bits = 0b1010_0000,
+            Octal = 15,
+            Large = 1_000_000,
+            Parenthesized = 8,
+        }
+
+    }
+}
+"
+    );
+
+    // C# 6 has neither digit separators nor binary literals.
+    let configuration = CSharpConfiguration::new(6);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        public enum Mask : uint
+        {
+            Low = 0x000000FF,
+            High = 0x00FF0000,
+            Bits = 160,
+            Octal = 15,
+            Large = 1000000,
+            Parenthesized = 8,
+        }
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_constant_literals() {
+    let source = r#"
+pub const FLAGS: u64 = 0x_FFFF_0000_u64;
+pub const BITS: u8 = 0b0000_0101;
+pub const MODE: u32 = 0o755;
+pub const LIMIT: i32 = -1_000i32;
+"#;
+    let configuration = CSharpConfiguration::new(7);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        public const ulong Flags = 0xFFFF_0000;
+
+        public const byte Bits = 0b0000_0101;
+
+        public const uint Mode = 493;
+
+        public const int Limit = -1_000;
+
+    }
+}
+"
+    );
+
+    let configuration = CSharpConfiguration::new(6);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        public const ulong Flags = 0xFFFF0000;
+
+        public const byte Bits = 5;
+
+        public const uint Mode = 493;
+
+        public const int Limit = -1000;
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_converted_enum_variant_names() {
+    let source = r#"
+#[repr(i32)]
+pub enum Status {
+    /// The request succeeded.
+    STATUS_OK = 0,
+    HTTP_404 = 404,
+    _500 = 500,
+    PartialContent = 206,
+    Teapot = 418,
+}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.convert_enum_variant_names(true);
+    configuration.rename_enum_variant("Status", "Teapot", "ImATeapot");
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        public enum Status : int
+        {
+            /// <summary>
+            /// The request succeeded.
+            /// Rust name: STATUS_OK
+            /// </summary>
+            StatusOk = 0,
+            /// <summary>
+            /// Rust name: HTTP_404
+            /// </summary>
+            Http404 = 404,
+            _500 = 500,
+            PartialContent = 206,
+            /// <summary>
+            /// Rust name: Teapot
+            /// </summary>
+            ImATeapot = 418,
+        }
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_renamed_enum_variants_without_conversion() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.rename_enum_variant("Shape", "CIRCLE", "Round");
+    let mut builder = CSharpBuilder::new(
+        "#[repr(u8)] pub enum Shape { CIRCLE(f32), SQUARE_BOX(f32) }",
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Explicit)]
+        public struct Shape
+        {
+            public enum Tag : byte
+            {
+                /// <summary>
+                /// Rust name: CIRCLE
+                /// </summary>
+                Round,
+                SQUARE_BOX,
+            }
+
+            [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+            public struct RoundData
+            {
+                public Tag Kind;
+                /// <remarks>f32</remarks>
+                public float Item0;
+            }
+
+            [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+            public struct SQUARE_BOXData
+            {
+                public Tag Kind;
+                /// <remarks>f32</remarks>
+                public float Item0;
+            }
+
+            [FieldOffset(0)]
+            public Tag Kind;
+            [FieldOffset(0)]
+            public RoundData Round;
+            [FieldOffset(0)]
+            public SQUARE_BOXData SQUARE_BOX;
+        }
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_nullable_directive() {
+    let source = r#"
+#[repr(C)]
+pub struct Callbacks {
+    on_close: extern "C" fn(),
+}
+
+#[no_mangle]
+pub extern "C" fn get_name(id: u32) -> *const c_char {}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.emit_nullable_directive(true).unwrap();
+    configuration.marshal_utf8_strings(true);
+    configuration.generate_safe_wrappers(true);
+    configuration.generate_struct_equality(true);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+#nullable enable
+
+namespace foo
+{
+    internal static class bar
+    {
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void CallbacksOnCloseDelegate();
+
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Callbacks : IEquatable<Callbacks>
+        {
+            /// <remarks>fn()</remarks>
+            [field: MarshalAs(UnmanagedType.FunctionPtr)]
+            public CallbacksOnCloseDelegate? OnClose { get; init; }
+
+            public Callbacks(CallbacksOnCloseDelegate? onClose)
+            {
+                OnClose = onClose;
+            }
+
+            public bool Equals(Callbacks other)
+            {
+                return OnClose == other.OnClose;
+            }
+
+            public override bool Equals(object? obj)
+            {
+                return obj is Callbacks other && Equals(other);
+            }
+
+            public override int GetHashCode()
+            {
+                return HashCode.Combine(OnClose);
+            }
+
+            public static bool operator ==(Callbacks left, Callbacks right)
+            {
+                return left.Equals(right);
+            }
+
+            public static bool operator !=(Callbacks left, Callbacks right)
+            {
+                return !left.Equals(right);
+            }
+        }
+
+        /// <summary>
+        /// Converts a UTF-8 string returned by a native function into a managed string.
+        /// </summary>
+        internal static string? PtrToString(IntPtr ptr)
+        {
+            return Marshal.PtrToStringUTF8(ptr);
+        }
+
+        [System.ComponentModel.EditorBrowsable(System.ComponentModel.EditorBrowsableState.Never)]
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"get_name\")]
+        internal static extern IntPtr GetNameNative(uint id);
+
+        /// <param name=\"id\">u32</param>
+        /// <returns>*const c_char (UTF-8 string)</returns>
+        internal static string? GetName(uint id)
+        {
+            return Marshal.PtrToStringUTF8(GetNameNative(id));
+        }
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_nullable_directive_library_import() {
+    let mut configuration = CSharpConfiguration::new(11);
+    configuration.emit_nullable_directive(true).unwrap();
+    configuration.marshal_utf8_strings(true);
+    configuration.use_library_import(true);
+    let mut builder = CSharpBuilder::new(
+        "#[no_mangle] pub extern \"C\" fn get_name(id: u32) -> *const c_char {}",
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+using System.Runtime.InteropServices.Marshalling;
+#nullable enable
+
+namespace foo;
+
+internal static partial class bar
+{
+    /// <param name=\"id\">u32</param>
+    /// <returns>*const c_char (UTF-8 string)</returns>
+    [LibraryImport(\"foo\", EntryPoint = \"get_name\")]
+    [UnmanagedCallConv(CallConvs = new[] { typeof(System.Runtime.CompilerServices.CallConvCdecl) })]
+    [return: MarshalAs(UnmanagedType.LPUTF8Str)]
+    internal static partial string? GetName(uint id);
+
+}
+"
+    );
+}
+
+#[test]
+fn nullable_directive_requires_csharp_8() {
+    let mut configuration = CSharpConfiguration::new(7);
+    configuration.set_csharp_minor_version(3);
+    let error = configuration.emit_nullable_directive(true).unwrap_err();
+    assert!(matches!(error, Error::UnsupportedError(..)));
+    assert!(configuration.emit_nullable_directive(false).is_ok());
+}
+
+#[test]
+fn build_dllimport_options() {
+    let source = r#"
+#[repr(C)]
+pub struct Path {
+    len: u32,
+}
+
+#[no_mangle]
+pub extern "C" fn open_file(path: Path) -> u32 {}
+
+#[no_mangle]
+pub extern "C" fn close_file(handle: u32) {}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_dllimport_defaults(DllImportOptions {
+        exact_spelling: Some(true),
+        charset: Some(CharSet::Ansi),
+        ..Default::default()
+    });
+    configuration.set_dllimport_options_for(
+        "open_file",
+        DllImportOptions {
+            set_last_error: Some(true),
+            best_fit_mapping: Some(false),
+            charset: Some(CharSet::Unicode),
+            ..Default::default()
+        },
+    );
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(builder.build().unwrap(), "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Ansi)]
+        public struct Path
+        {
+            /// <remarks>u32</remarks>
+            public uint Len { get; init; }
+
+            public Path(uint len)
+            {
+                Len = len;
+            }
+        }
+
+        /// <param name=\"path\">Path</param>
+        /// <returns>u32</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"open_file\", SetLastError = true, ExactSpelling = true, BestFitMapping = false, CharSet = CharSet.Unicode)]
+        internal static extern uint OpenFile(Path path);
+
+        /// <param name=\"handle\">u32</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"close_file\", ExactSpelling = true, CharSet = CharSet.Ansi)]
+        internal static extern void CloseFile(uint handle);
+
+    }
+}
+");
+}
+
+#[test]
+fn build_dllimport_options_library_import() {
+    let mut configuration = CSharpConfiguration::new(11);
+    configuration.use_library_import(true);
+    configuration.set_dllimport_options_for(
+        "open_file",
+        DllImportOptions {
+            set_last_error: Some(true),
+            exact_spelling: Some(true),
+            ..Default::default()
+        },
+    );
+    let mut builder = CSharpBuilder::new(
+        "#[no_mangle] pub extern \"C\" fn open_file(id: u32) -> u32 {}",
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+using System.Runtime.InteropServices.Marshalling;
+
+namespace foo;
+
+internal static partial class bar
+{
+    /// <param name=\"id\">u32</param>
+    /// <returns>u32</returns>
+    [LibraryImport(\"foo\", EntryPoint = \"open_file\", SetLastError = true)]
+    [UnmanagedCallConv(CallConvs = new[] { typeof(System.Runtime.CompilerServices.CallConvCdecl) })]
+    internal static partial uint OpenFile(uint id);
+
+}
+"
+    );
+}
+
+#[test]
+fn build_large_return_struct_warnings() {
+    let source = r#"
+#[repr(C)]
+pub struct Small {
+    a: u32,
+    b: u32,
+}
+
+#[repr(C)]
+pub struct Large {
+    flag: u8,
+    inner: Small,
+    value: u64,
+    data: *const u8,
+    bytes: [u8; 3],
+}
+
+#[no_mangle]
+pub extern "C" fn get_small() -> Small {}
+
+#[no_mangle]
+pub extern "C" fn get_large() -> Large {}
+
+#[no_mangle]
+pub extern "C" fn get_large_pointer() -> *const Large {}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.warn_large_return_structs(16);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    builder.build().unwrap();
+    let warnings = builder.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].item_name, "get_large");
+    assert_eq!(warnings[0].error.to_string(), "Function 'get_large' returns the struct 'Large' of an estimated 40 bytes by value, which is larger than 16 bytes. ABIs differ in how large structs are returned, return it through a pointer instead. At line 21, position 33");
+}
+
+#[test]
+fn build_deny_large_return_structs() {
+    let source = r#"
+#[repr(C, packed)]
+pub struct Packed {
+    a: u8,
+    b: u64,
+    c: u64,
+}
+
+#[no_mangle]
+pub extern "C" fn get_packed() -> Packed {}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.warn_large_return_structs(16);
+    configuration.deny_large_return_structs(true);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let error = builder.build().unwrap_err();
+    assert_eq!(error.to_string(), "Function 'get_packed' returns the struct 'Packed' of an estimated 17 bytes by value, which is larger than 16 bytes. ABIs differ in how large structs are returned, return it through a pointer instead. At line 10, position 34");
+}
+
+#[test]
+fn build_primitive_type_aliases() {
+    let source = r#"
+pub type Id = NodeId;
+pub type NodeId = u64;
+pub type RawHandle = *mut c_void;
+
+#[no_mangle]
+pub extern "C" fn connect(from: Id, to: NodeId, handle: RawHandle) -> RawHandle {}
+"#;
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        /// <param name=\"from\">Id</param>
+        /// <param name=\"to\">NodeId</param>
+        /// <param name=\"handle\">RawHandle</param>
+        /// <returns>RawHandle</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"connect\")]
+        internal static extern IntPtr Connect(ulong from, ulong to, IntPtr handle);
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_cyclic_type_aliases() {
+    let source = r#"
+pub type A = B;
+pub type B = C;
+pub type C = A;
+"#;
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let error = builder.build().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Type alias 'A' refers to itself: A -> B -> C -> A. At line 2, position 9"
+    );
+}
+
+#[test]
+fn build_module_path_types() {
+    let source = r#"
+mod a {
+    #[repr(C)]
+    pub struct Config {
+        pub width: u32,
+    }
+
+    #[no_mangle]
+    pub extern "C" fn get_a(config: Config) {}
+}
+
+mod b {
+    #[repr(C)]
+    pub struct Config {
+        pub enabled: u8,
+    }
+}
+
+mod c {
+    use crate::b::Config;
+
+    #[no_mangle]
+    pub extern "C" fn get_b(config: Config) {}
+}
+
+#[no_mangle]
+pub extern "C" fn get_both(first: a::Config, second: self::b::Config) {}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.nest_modules_as_classes(true);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        internal static class A
+        {
+            [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+            public struct Config
+            {
+                /// <remarks>u32</remarks>
+                public uint Width { get; init; }
+
+                public Config(uint width)
+                {
+                    Width = width;
+                }
+            }
+
+            /// <param name=\"config\">Config</param>
+            /// <returns>void</returns>
+            [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"get_a\")]
+            internal static extern void GetA(Config config);
+
+        }
+
+        internal static class B
+        {
+            [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+            public struct Config
+            {
+                /// <remarks>u8</remarks>
+                public byte Enabled { get; init; }
+
+                public Config(byte enabled)
+                {
+                    Enabled = enabled;
+                }
+            }
+
+        }
+
+        internal static class C
+        {
+            /// <param name=\"config\">Config</param>
+            /// <returns>void</returns>
+            [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"get_b\")]
+            internal static extern void GetB(bar.B.Config config);
+
+        }
+
+        /// <param name=\"first\">Config</param>
+        /// <param name=\"second\">Config</param>
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"get_both\")]
+        internal static extern void GetBoth(bar.A.Config first, bar.B.Config second);
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_ambiguous_module_path_types() {
+    let source = r#"
+mod a {
+    #[repr(C)]
+    pub struct Config {
+        pub width: u32,
+    }
+}
+
+mod b {
+    #[repr(C)]
+    pub struct Config {
+        pub enabled: u8,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn get_config(config: *const Config) {}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.nest_modules_as_classes(true);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let error = builder.build().unwrap_err();
+    assert!(matches!(error, Error::AmbiguousType(..)));
+    assert_eq!(error.to_string(), "The type 'Config' is ambiguous, it could be any of a::Config, b::Config. At line 17, position 44");
+}
+
+#[test]
+fn generate_bindings_with_report() {
+    let directory = tempfile::tempdir().unwrap();
+    let directory = directory.path();
+    let ffi = directory.join("ffi");
+    std::fs::create_dir_all(&ffi).unwrap();
+    std::fs::write(
+        ffi.join("types.rs"),
+        "#[repr(u8)] pub enum Foo { A, B } #[repr(C)] pub struct Bar { pub foo: Foo }",
+    )
+    .unwrap();
+    let functions = directory.join("functions.rs");
+    std::fs::write(
+        &functions,
+        "#[no_mangle] pub extern \"C\" fn get_bar(foo: Foo) -> Bar {} #[no_mangle] pub extern \"C\" fn get_handle() -> Handle {}",
+    )
+    .unwrap();
+    let output = directory.join("bindings").join("NativeMethods.cs");
+
+    let configuration = CSharpConfiguration::new(9);
+    configuration
+        .add_known_type("Handle", None, None, "SafeFooHandle".to_string())
+        .unwrap();
+    let mut hints = Vec::new();
+    let report = crate::generate_bindings(
+        &GenerateOptions {
+            inputs: vec![ffi.clone(), functions.clone()],
+            output: output.clone(),
+            dll_name: "foo".to_string(),
+            namespace: Some("Foo".to_string()),
+            class: Some("NativeMethods".to_string()),
+            configuration: Some(&configuration),
+            ..Default::default()
+        },
+        true,
+        &mut hints,
+    )
+    .unwrap();
+    assert_eq!((report.functions, report.structs, report.enums), (2, 1, 1));
+    assert!(report.warnings.is_empty());
+    assert_eq!(
+        String::from_utf8(hints).unwrap(),
+        format!(
+            "cargo:rerun-if-changed={}\ncargo:rerun-if-changed={}\n",
+            ffi.display(),
+            functions.display()
+        )
+    );
+    let script = std::fs::read_to_string(&output).unwrap();
+    assert!(script.contains("internal static class NativeMethods"));
+    assert!(script.contains("internal static extern SafeFooHandle GetHandle();"));
+}
+
+#[test]
+fn generate_bindings_reports_input_errors() {
+    let directory = tempfile::tempdir().unwrap();
+    let directory = directory.path();
+    let ffi = directory.join("ffi.rs");
+    std::fs::write(
+        &ffi,
+        "\n#[no_mangle]\npub extern \"C\" fn foo(a: Missing) {}\n",
+    )
+    .unwrap();
+    let options = GenerateOptions {
+        inputs: vec![ffi.clone()],
+        output: directory.join("NativeMethods.cs"),
+        dll_name: "foo".to_string(),
+        csharp_version: 9,
+        ..Default::default()
+    };
+
+    let mut hints = Vec::new();
+    let error = crate::generate_bindings(&options, false, &mut hints).unwrap_err();
+    assert!(hints.is_empty());
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "Type with name 'Missing' was not found, in parameter 'a' of function 'foo'. At {}: line 3, column 25",
+            ffi.display()
+        )
+    );
+    assert!(!options.output.exists());
+
+    std::fs::write(&ffi, "pub struct Foo {\n    x: i32 y: i32,\n}\n").unwrap();
+    let error = crate::generate_bindings(&options, false, &mut hints).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!("expected `,`. At {}: line 2, column 11", ffi.display())
+    );
+}
+
+#[test]
+fn build_crate_docs_as_class_summary() {
+    let source = r#"
+//! Bindings for the `foo` library.
+//! Handles are a `Box<Foo>` & must be freed.
+//!
+//! # Safety
+//! Functions are not thread safe.
+#![doc = include_str!("../README.md")]
+
+#[no_mangle]
+pub extern "C" fn foo() {}
+"#;
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    /// <summary>
+    /// Bindings for the <c>foo</c> library.
+    /// Handles are a <c>Box&lt;Foo&gt;</c> &amp; must be freed.
+    /// </summary>
+    /// <remarks>
+    /// Safety:
+    /// Functions are not thread safe.
+    /// </remarks>
+    internal static class bar
+    {
+        /// <returns>void</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"foo\")]
+        internal static extern void Foo();
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_module_docs_as_nested_class_summary() {
+    let source = r#"
+/// Audio playback.
+mod audio {
+    //! Volumes range from 0 to 100.
+
+    #[no_mangle]
+    pub extern "C" fn set_volume(volume: u8) {}
+}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.nest_modules_as_classes(true);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(builder.build().unwrap(), "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        /// <summary>
+        /// Audio playback.
+        /// Volumes range from 0 to 100.
+        /// </summary>
+        internal static class Audio
+        {
+            /// <param name=\"volume\">u8</param>
+            /// <returns>void</returns>
+            [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"set_volume\")]
+            internal static extern void SetVolume(byte volume);
+
+        }
+
+    }
+}
+");
+}
+
+#[test]
+fn build_enum_remarks() {
+    let source = r#"
+/// The state of a connection.
+///
+/// # Safety
+/// Only read the state from the thread that owns the connection.
+#[repr(u8)]
+pub enum State {
+    Closed,
+    Opening,
+    Open,
+}
+
+#[repr(i32)]
+pub enum Code {
+    Ok = 0,
+    NotFound = 404,
+    Timeout,
+    #[doc(hidden)]
+    Internal,
+    Unknown,
+}
+
+#[repr(u16)]
+pub enum Port {
+    Http = 80,
+    Https = 443,
+}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.emit_enum_remarks(true);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    /// <summary>
+    /// The state of a connection.
+    /// </summary>
+    /// <remarks>
+    /// Safety:
+    /// Only read the state from the thread that owns the connection.
+    /// Rust representation: <c>repr(u8)</c>.
+    /// <list type=\"table\">
+    /// <listheader><term>Variant</term><description>Value</description></listheader>
+    /// <item><term>Closed</term><description>0</description></item>
+    /// <item><term>Opening</term><description>1</description></item>
+    /// <item><term>Open</term><description>2</description></item>
+    /// </list>
+    /// </remarks>
+    public enum State : byte
+    {
+        Closed,
+        Opening,
+        Open,
+    }
+
+    /// <remarks>
+    /// Rust representation: <c>repr(i32)</c>.
+    /// <list type=\"table\">
+    /// <listheader><term>Variant</term><description>Value</description></listheader>
+    /// <item><term>Timeout</term><description>405</description></item>
+    /// </list>
+    /// </remarks>
+    public enum Code : int
+    {
+        Ok = 0,
+        NotFound = 404,
+        Timeout,
+        Unknown = 407,
+    }
+
+    /// <remarks>
+    /// Rust representation: <c>repr(u16)</c>.
+    /// </remarks>
+    public enum Port : ushort
+    {
+        Http = 80,
+        Https = 443,
+    }
+
+}
+"
+    );
+}
+
+#[test]
+fn build_enum_remarks_with_converted_names() {
+    let source = r#"
+#[repr(u8)]
+#[non_exhaustive]
+pub enum LogLevel {
+    LOG_ERROR,
+    LOG_WARNING,
+}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.emit_enum_remarks(true);
+    configuration.convert_enum_variant_names(true);
+    configuration.generate_enum_helpers(true);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    assert_eq!(builder.build().unwrap(), "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    /// <remarks>
+    /// This enum is non-exhaustive, native code may return values that are not declared here. Use IsDefinedFast to check for these.
+    /// Rust representation: <c>repr(u8)</c>.
+    /// <list type=\"table\">
+    /// <listheader><term>Variant</term><description>Value</description></listheader>
+    /// <item><term>LogError</term><description>0</description></item>
+    /// <item><term>LogWarning</term><description>1</description></item>
+    /// </list>
+    /// </remarks>
+    public enum LogLevel : byte
+    {
+        /// <summary>
+        /// Rust name: LOG_ERROR
+        /// </summary>
+        LogError,
+        /// <summary>
+        /// Rust name: LOG_WARNING
+        /// </summary>
+        LogWarning,
+    }
+
+    public static class LogLevelExtensions
+    {
+        public static bool IsDefinedFast(this LogLevel value)
+        {
+            switch (value)
+            {
+                case LogLevel.LogError:
+                case LogLevel.LogWarning:
+                    return true;
+                default:
+                    return false;
+            }
+        }
+    }
+
+}
+");
+}
+
+#[test]
+fn build_pointer_sized_enum_reprs() {
+    let source = r#"
+#[repr(usize)]
+pub enum Kind {
+    A,
+    B,
+}
+
+#[repr(isize)]
+pub enum Offset {
+    Before = -1,
+    After = 1,
+}
+"#;
+    for version in [8, 9] {
+        let configuration = CSharpConfiguration::new(version);
+        let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+        builder.set_namespace("foo");
+        assert_eq!(builder.build().unwrap(), "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    /// <remarks>
+    /// The Rust representation <c>repr(usize)</c> is pointer sized, which C# enums can not use. It is written as <c>ulong</c>, which assumes a 64 bit process.
+    /// </remarks>
+    public enum Kind : ulong
+    {
+        A,
+        B,
+    }
+
+    /// <remarks>
+    /// The Rust representation <c>repr(isize)</c> is pointer sized, which C# enums can not use. It is written as <c>long</c>, which assumes a 64 bit process.
+    /// </remarks>
+    public enum Offset : long
+    {
+        Before = -1,
+        After = 1,
+    }
+
+}
+");
+    }
+}
+
+#[test]
+fn build_pointer_sized_enum_repr_widths() {
+    let source = r#"
+#[repr(isize)]
+pub enum Offset {
+    Before = -1,
+    After = 1,
+}
+"#;
+    let mut configuration = CSharpConfiguration::new(8);
+    configuration.set_pointer_width(PointerWidth::W32);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    let script = builder.build().unwrap();
+    assert!(script.contains("    /// The Rust representation <c>repr(isize)</c> is pointer sized, which C# enums can not use. It is written as <c>int</c>, which assumes a 32 bit process.\n"));
+    assert!(script.contains("    public enum Offset : int\n"));
+
+    configuration.set_pointer_width(PointerWidth::Native);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    assert_eq!(builder.build().unwrap_err().to_string(), "C# enums can not use native sized integers, so repr(isize) is not supported. Use set_pointer_width to use a fixed width, or use a fixed size repr such as repr(i64). At line 2, position 7");
+}
+
+#[test]
+fn build_returned_references() {
+    let source = r#"
+#[repr(C)]
+pub struct Foo {
+    pub value: u32,
+}
+
+#[no_mangle]
+pub extern "C" fn get_byte<'a>(data: &'a Foo) -> &'a u8 {}
+
+#[no_mangle]
+pub extern "C" fn get_foo(value: Option<&'static u8>) -> &mut Foo {}
+
+#[no_mangle]
+pub extern "C" fn get_name() -> &'static c_char {}
+
+pub type Callback = extern "C" fn() -> &'static Foo;
+"#;
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Foo
+        {
+            /// <remarks>u32</remarks>
+            public uint Value { get; init; }
+
+            public Foo(uint value)
+            {
+                Value = value;
+            }
+        }
+
+        /// <param name=\"data\">&amp;Foo</param>
+        /// <returns>&amp;u8</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"get_byte\")]
+        internal static extern IntPtr GetByte(in Foo data);
+
+        /// <param name=\"value\">Option&lt;&amp;u8&gt;</param>
+        /// <returns>&amp;mut Foo</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"get_foo\")]
+        internal static extern IntPtr GetFoo(IntPtr value);
+
+        /// <returns>&amp;c_char</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"get_name\")]
+        internal static extern IntPtr GetName();
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate IntPtr Callback();
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_returned_references_as_unsafe_pointers() {
+    let source = r#"
+#[repr(C)]
+pub struct Foo {
+    pub value: u32,
+}
+
+#[no_mangle]
+pub extern "C" fn get_byte() -> &'static u8 {}
+
+#[no_mangle]
+pub extern "C" fn get_foo() -> &mut Foo {}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.use_unsafe_pointers(true);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static unsafe class bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct Foo
+        {
+            /// <remarks>u32</remarks>
+            public uint Value { get; init; }
+
+            public Foo(uint value)
+            {
+                Value = value;
+            }
+        }
+
+        /// <returns>&amp;u8</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"get_byte\")]
+        internal static extern byte* GetByte();
+
+        /// <returns>&amp;mut Foo</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"get_foo\")]
+        internal static extern Foo* GetFoo();
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_constructor_parameter_docs() {
+    let source = r#"
+#[repr(C)]
+pub struct Vector {
+    /// The `x` coordinate, in pixels.
+    ///
+    /// Positive values are to the right.
+    pub x_position: i32,
+    pub y_position: *const Vector,
+}
+
+#[repr(C)]
+pub struct Size {
+    pub width: u32,
+    pub height: u32,
+}
+"#;
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    public struct Vector
+    {
+        /// <summary>
+        /// <para>
+        /// The <c>x</c> coordinate, in pixels.
+        /// </para>
+        /// <para>
+        /// Positive values are to the right.
+        /// </para>
+        /// </summary>
+        /// <remarks>i32</remarks>
+        public int XPosition { get; init; }
+        /// <remarks>*const Vector</remarks>
+        public IntPtr YPosition { get; init; }
+
+        /// <param name=\"xPosition\">The <c>x</c> coordinate, in pixels.</param>
+        /// <param name=\"yPosition\">*const Vector</param>
+        public Vector(int xPosition, IntPtr yPosition)
+        {
+            XPosition = xPosition;
+            YPosition = yPosition;
+        }
+    }
+
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    public struct Size
+    {
+        /// <remarks>u32</remarks>
+        public uint Width { get; init; }
+        /// <remarks>u32</remarks>
+        public uint Height { get; init; }
+
+        public Size(uint width, uint height)
+        {
+            Width = width;
+            Height = height;
+        }
+    }
+
+}
+"
+    );
+}
+
+#[test]
+fn build_safe_wrapper_out_parameter_named_result() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_out_type("Out");
+    configuration.generate_safe_wrappers(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            pub extern "C" fn compute(result: Out<i32>) -> u32 { 0 }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("Foo");
+    builder.set_type("Bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains(
+        "        internal static (uint Result_, int Result) Compute()
+        {
+            int result;
+            var result_ = ComputeNative(out result);
+            return (result_, result);
+        }
+"
+    ));
+}
+
+#[test]
+fn build_raw_identifiers() {
+    let source = r#"
+#[repr(C)]
+pub struct r#ref {
+    pub r#type: u8,
+}
+#[no_mangle]
+pub extern "C" fn r#match(r#type: u8) -> r#ref {}
+"#;
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("Bar");
+    assert_eq!(
+        builder.build().unwrap(),
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class Bar
+    {
+        [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+        public struct @ref
+        {
+            /// <remarks>u8</remarks>
+            public byte Type { get; init; }
+
+            public @ref(byte type)
+            {
+                Type = type;
+            }
+        }
+
+        /// <param name=\"type\">u8</param>
+        /// <returns>ref</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"match\")]
+        internal static extern @ref Match(byte type);
+
+    }
+}
+"
+    );
+}
+
+#[test]
+fn build_transparent_struct_with_phantom_data() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(transparent)]
+            pub struct Id {
+                v: u32,
+                _m: PhantomData<*const u8>,
+            }
+
+            pub extern "C" fn next(id: Id) -> Id {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains("internal static extern uint Next(uint id);"));
+}
+
+#[test]
+fn build_transparent_struct_keeps_marshalling() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.allow_bool_marshaling(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(transparent)]
+            pub struct Flag(pub bool);
+
+            pub extern "C" fn toggle(flag: Flag) -> Flag {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert_eq!(
+        script,
+        "// Automatically generated, do not edit!
+using System;
+using System.Runtime.InteropServices;
+
+namespace foo
+{
+    internal static class bar
+    {
+        /// <param name=\"flag\">Flag</param>
+        /// <returns>Flag</returns>
+        [DllImport(\"foo\", CallingConvention = CallingConvention.Cdecl, EntryPoint=\"toggle\")]
+        [return: MarshalAs(UnmanagedType.I1)]
+        internal static extern bool Toggle([MarshalAs(UnmanagedType.I1)] bool flag);
+
+    }
+}
+"
+    );
+    assert!(configuration
+        .export_known_types()
+        .contains("Flag\t\t\tbool\tUnmanagedType.I1\n"));
+}
+
+#[test]
+fn build_opaque_handle_in_struct_field() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.add_opaque_handle("Foo", "destroy_foo");
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Entry {
+                foo: *mut Foo,
+            }
+
+            pub type Visit = extern "C" fn(foo: *const Foo);
+
+            pub extern "C" fn destroy_foo(foo: *mut Foo) {}
+            pub extern "C" fn get_entry(foo: *const Foo) -> Entry {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains("public IntPtr Foo { get; init; }"));
+    assert!(script.contains("public delegate void Visit(IntPtr foo);"));
+    assert!(script.contains("internal static extern Entry GetEntry(FooHandle foo);"));
+}
+
+#[test]
+fn build_nested_lines_with_tabs() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_indentation(Indentation::Tabs);
+    configuration.add_opaque_handle("Foo", "destroy_foo");
+    configuration.generate_struct_equality(true);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Point { x: i32, y: i32 }
+            pub extern "C" fn destroy_foo(foo: *mut Foo) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains(
+        "\t\t\tprotected override bool ReleaseHandle()\n\t\t\t{\n\t\t\t\tDestroy(handle);\n\t\t\t\treturn true;\n\t\t\t}\n"
+    ));
+    assert!(script.contains("\t\t\t\treturn X.Equals(other.X)\n\t\t\t\t\t&& Y.Equals(other.Y);\n"));
+    assert!(!script.contains("    "));
+    let tests = builder.build_layout_tests("layout_").unwrap();
+    assert!(tests
+        .csharp
+        .contains("\t\t\tif ((ulong)actual != expected)\n\t\t\t{\n\t\t\t\tthrow new"));
+    assert!(!tests.csharp.contains("    "));
+}
+
+#[test]
+fn build_constructor_parameter_named_as_field() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.set_naming_converter(Box::new(|name, _| name.to_string()));
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct point { pub xpos: i32, pub Ypos: i32 }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains(
+        "            public point(int xpos, int ypos)
+            {
+                this.xpos = xpos;
+                Ypos = ypos;
+            }
+"
+    ));
+}
+
+#[test]
+fn build_tuple_fields_after_phantom_data() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct T(u8, PhantomData<u8>, u16);
+            #[repr(C, u8)]
+            enum Message {
+                Code(PhantomData<u8>, i32),
+            }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains("public byte Item0 { get; init; }"));
+    assert!(script.contains("public ushort Item1 { get; init; }"));
+    assert!(script.contains("public int Item0;"));
+    assert!(!script.contains("Item2"));
+}
+
+#[test]
+fn build_enum_with_shifted_discriminants() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(u32)]
+            enum Access { Read = 1 << 0, Write = 1 << 1, All = (1 << 0) | 0x2 | 1 << 4 }
+            #[repr(u8)]
+            enum Unsupported { A = 1 + 1 }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build_lenient().unwrap();
+    assert!(script.contains("public enum Access : uint\n        {\n            Read = 1,\n            Write = 2,\n            All = 19,\n        }"));
+    assert!(matches!(
+        builder.build().unwrap_err(),
+        Error::UnsupportedError(message, _) if message.contains("shifts and bitwise ors")
+    ));
+}
+
+#[test]
+fn build_flags_enum_not_detected_by_default() {
+    let configuration = CSharpConfiguration::new(9);
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(u32)]
+            enum Caps { None = 0, Read = 1, Write = 2, Exec = 4 }
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    let script = builder.build().unwrap();
+    assert!(!script.contains("[Flags]"));
+}
+
+#[test]
+fn build_safe_wrappers_with_internal_types() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.generate_safe_wrappers(true);
+    configuration.set_accessibility(AccessibilityOptions {
+        class: Access::Public,
+        methods: Access::Internal,
+        types: Access::Internal,
+    });
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[repr(C)]
+            pub struct Point { x: i32, y: i32 }
+            #[no_mangle]
+            pub extern "C" fn get_point() -> Point {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains("public static class bar"));
+    assert!(script.contains("internal struct Point"));
+    assert!(script.contains("internal static Point GetPoint()"));
+}
+
+#[test]
+fn build_span_overloads_with_public_methods() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.mark_slice_params("write_data", "data", "len");
+    configuration.set_accessibility(AccessibilityOptions {
+        methods: Access::Public,
+        ..AccessibilityOptions::default()
+    });
+    let mut builder = CSharpBuilder::new(
+        r#"
+            #[no_mangle]
+            pub extern "C" fn write_data(data: *const u8, len: usize) {}
+        "#,
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    builder.set_namespace("foo");
+    builder.set_type("bar");
+    let script = builder.build().unwrap();
+    assert!(script.contains("public static unsafe void WriteData(ReadOnlySpan<byte> data)"));
+}
+
+#[test]
+fn extract_split_128_bit_field() {
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.map_u128_as_pair(true);
+    let mut builder = CSharpBuilder::new(
+        "#[repr(C)] pub struct Value { pub big: i128 }",
+        "foo",
+        &configuration,
+    )
+    .unwrap();
+    let extracted = builder.extracted_items().unwrap();
+    let fields = &extracted.structs[0].fields;
+    assert_eq!(
+        fields
+            .iter()
+            .map(|f| (f.csharp_name.as_str(), f.offset_in_field))
+            .collect::<Vec<_>>(),
+        [("BigLow", 0), ("BigHigh", 8)]
+    );
+}
+
+#[test]
+fn build_large_return_struct_named_like_primitive() {
+    let source = r#"
+#[repr(C)]
+pub struct Char {
+    a: u64,
+    b: u64,
+    c: u64,
+}
+
+#[repr(C)]
+pub struct Box {
+    value: NonZeroU64,
+    other: NonZeroU64,
+    last: u8,
+}
+
+#[no_mangle]
+pub extern "C" fn get_char() -> Char {}
+
+#[no_mangle]
+pub extern "C" fn get_box() -> Box {}
+"#;
+    let mut configuration = CSharpConfiguration::new(9);
+    configuration.warn_large_return_structs(16);
+    let mut builder = CSharpBuilder::new(source, "foo", &configuration).unwrap();
+    builder.build().unwrap();
+    let warnings = builder
+        .warnings()
+        .iter()
+        .map(|w| w.error.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings[0].contains("returns the struct 'Char' of an estimated 24 bytes"));
+    assert!(warnings[1].contains("returns the struct 'Box' of an estimated 24 bytes"));
+}
@@ -0,0 +1,224 @@
+#![cfg(feature = "cli")]
+
+use csharp_binder::cli::{run, CliError, Config};
+use csharp_binder::KnownTypeEntry;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+const FFI: &str = r#"
+#[repr(C)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[no_mangle]
+pub extern "C" fn move_point(point: Point, handle: Handle, result: Out<i32>) -> Point {}
+
+#[no_mangle]
+pub extern "C" fn __test_helper() {}
+"#;
+
+const CONFIG: &str = r#"
+inputs = ["src/*.rs"]
+dll_name = "foo"
+namespace = "Foo"
+class_name = "NativeMethods"
+csharp_version = 9
+out_types = ["Out"]
+ignore = ["__test_*"]
+output = "bindings/NativeMethods.cs"
+
+[[known_types]]
+rust_name = "Handle"
+namespace = "Foo.Interop"
+csharp_name = "SafeFooHandle"
+"#;
+
+fn write_project(directory: &Path, config: &str) {
+    fs::create_dir_all(directory.join("src")).unwrap();
+    fs::write(directory.join("src/ffi.rs"), FFI).unwrap();
+    fs::write(directory.join("csharp_binder.toml"), config).unwrap();
+}
+
+#[test]
+fn parse_config() {
+    let config = Config::parse(CONFIG).unwrap();
+    assert_eq!(
+        config,
+        Config {
+            inputs: vec!["src/*.rs".to_string()],
+            dll_name: "foo".to_string(),
+            namespace: Some("Foo".to_string()),
+            class_name: Some("NativeMethods".to_string()),
+            csharp_version: 9,
+            known_types: vec![KnownTypeEntry {
+                rust_name: "Handle".to_string(),
+                namespace: Some("Foo.Interop".to_string()),
+                inside_type: None,
+                csharp_name: "SafeFooHandle".to_string(),
+            }],
+            out_types: vec!["Out".to_string()],
+            ignore: vec!["__test_*".to_string()],
+            output: Some("bindings/NativeMethods.cs".into()),
+            output_directory: None,
+        }
+    );
+}
+
+#[test]
+fn parse_invalid_config() {
+    let error =
+        Config::parse("inputs = [\"ffi.rs\"]\ndll_name = \"foo\"\ncsharp_version = 9").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Either 'output' or 'output_directory' has to be set"
+    );
+    let error = Config::parse(
+        "inputs = [\"ffi.rs\"]\ndll_name = \"foo\"\ncsharp_version = 9\noutput = \"a.cs\"\nnamespaces = \"Foo\"",
+    )
+    .unwrap_err();
+    assert!(matches!(error, CliError::Config(..)));
+    assert!(error.to_string().contains("unknown field `namespaces`"));
+}
+
+#[test]
+fn generate_and_check_script() {
+    let directory = tempfile::tempdir().unwrap();
+    write_project(directory.path(), CONFIG);
+    let config_path = directory.path().join("csharp_binder.toml");
+
+    let warnings = run(&config_path, false).unwrap();
+    assert!(warnings.is_empty());
+    let output = directory.path().join("bindings/NativeMethods.cs");
+    let script = fs::read_to_string(&output).unwrap();
+    assert!(script.contains("namespace Foo\n"));
+    assert!(script.contains("internal static class NativeMethods\n"));
+    assert!(script.contains("[DllImport(\"foo\""));
+    assert!(script.contains(
+        "internal static extern Point MovePoint(Point point, Foo.Interop.SafeFooHandle handle, out int result);"
+    ));
+    assert!(!script.contains("__test_helper"));
+
+    run(&config_path, true).unwrap();
+    fs::write(&output, script.replace("MovePoint", "Move")).unwrap();
+    let error = run(&config_path, true).unwrap_err();
+    assert!(matches!(error, CliError::Mismatch(..)));
+}
+
+#[test]
+fn generate_split_files() {
+    let directory = tempfile::tempdir().unwrap();
+    let config = CONFIG.replace(
+        "output = \"bindings/NativeMethods.cs\"",
+        "output_directory = \"bindings\"",
+    );
+    write_project(directory.path(), &config);
+    let config_path = directory.path().join("csharp_binder.toml");
+
+    run(&config_path, false).unwrap();
+    let bindings = directory.path().join("bindings");
+    assert!(bindings.join("Point.cs").is_file());
+    assert!(bindings.join("NativeMethods.cs").is_file());
+    run(&config_path, true).unwrap();
+
+    fs::remove_file(bindings.join("Point.cs")).unwrap();
+    let error = run(&config_path, true).unwrap_err();
+    assert!(error.to_string().ends_with(": Point.cs"));
+}
+
+#[test]
+fn binary_reports_errors_with_file_names() {
+    let directory = tempfile::tempdir().unwrap();
+    write_project(
+        directory.path(),
+        &CONFIG.replace("out_types = [\"Out\"]\n", ""),
+    );
+    let output = Command::new(env!("CARGO_BIN_EXE_csharp_binder"))
+        .arg("--config")
+        .arg(directory.path().join("csharp_binder.toml"))
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let ffi = directory.path().join("src").join("ffi.rs");
+    assert_eq!(
+        stderr,
+        format!(
+            "error: Type with name 'Out' was not found, in parameter 'result' of function 'move_point'. At {}: line 9, column 67\n",
+            ffi.display()
+        )
+    );
+}
+
+#[test]
+fn parse_errors_name_the_input() {
+    let directory = tempfile::tempdir().unwrap();
+    write_project(directory.path(), CONFIG);
+    let broken = directory.path().join("src").join("broken.rs");
+    fs::write(&broken, "\npub struct Point {\n    x: i32 y: i32,\n}\n").unwrap();
+    let error = run(&directory.path().join("csharp_binder.toml"), false).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!("expected `,`. At {}: line 3, column 11", broken.display())
+    );
+}
+
+#[test]
+fn binary_check_fails_on_missing_bindings() {
+    let directory = tempfile::tempdir().unwrap();
+    write_project(directory.path(), CONFIG);
+    let output = Command::new(env!("CARGO_BIN_EXE_csharp_binder"))
+        .arg("--check")
+        .current_dir(directory.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(!directory.path().join("bindings").exists());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_csharp_binder"))
+        .current_dir(directory.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let output = Command::new(env!("CARGO_BIN_EXE_csharp_binder"))
+        .arg("--check")
+        .current_dir(directory.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+}
+
+#[test]
+fn errors_name_the_input_of_several() {
+    let directory = tempfile::tempdir().unwrap();
+    write_project(directory.path(), CONFIG);
+    let extra = directory.path().join("src").join("extra.rs");
+    fs::write(&extra, "\npub extern \"C\" fn stop(engine: Engine) {}\n").unwrap();
+    let error = run(&directory.path().join("csharp_binder.toml"), false).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "Type with name 'Engine' was not found, in parameter 'engine' of function 'stop'. At {}: line 2, column 31",
+            extra.display()
+        )
+    );
+}
+
+#[test]
+fn check_split_files_with_other_newlines() {
+    let directory = tempfile::tempdir().unwrap();
+    let config = CONFIG.replace(
+        "output = \"bindings/NativeMethods.cs\"",
+        "output_directory = \"bindings\"",
+    );
+    write_project(directory.path(), &config);
+    let config_path = directory.path().join("csharp_binder.toml");
+
+    run(&config_path, false).unwrap();
+    let point = directory.path().join("bindings").join("Point.cs");
+    let script = fs::read_to_string(&point).unwrap();
+    fs::write(&point, script.replace('\n', "\r\n")).unwrap();
+    run(&config_path, true).unwrap();
+}